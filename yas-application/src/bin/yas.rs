@@ -1,17 +1,13 @@
-use clap::{command, Command};
-use yas::utils::press_any_key_to_continue;
-use yas_genshin::application::ArtifactScannerApplication;
-use yas_starrail::application::RelicScannerApplication;
-
-fn get_genshin_command() -> Command {
-    let cmd = ArtifactScannerApplication::build_command();
-    cmd.name("genshin")
-}
+use std::io::IsTerminal;
+use std::path::Path;
 
-fn get_starrail_command() -> Command {
-    let cmd = RelicScannerApplication::build_command();
-    cmd.name("starrail")
-}
+use anyhow::{anyhow, Result};
+use clap::{command, value_parser, Arg, ArgMatches, Command};
+use log::info;
+use yas::config_file::{apply_config_file, init_config_file, DEFAULT_CONFIG_FILE};
+use yas::ocr::{benchmark, comparison_table, BenchmarkReport, LabeledDataset, YasOCRModel};
+use yas::utils::press_any_key_to_continue;
+use yas_application::Game;
 
 fn init() {
     env_logger::Builder::new()
@@ -19,34 +15,192 @@ fn init() {
         .init();
 }
 
-pub fn main() {
-    init();
-    let cmd = command!()
-        .subcommand(get_genshin_command())
-        .subcommand(get_starrail_command());
-    let arg_matches = cmd.get_matches();
-
-    let res = if let Some((subcommand_name, matches)) = arg_matches.subcommand() {
-        if subcommand_name == "genshin" {
-            let application = ArtifactScannerApplication::new(matches.clone());
-            application.run()
-        } else if subcommand_name == "starrail" {
-            let application = RelicScannerApplication::new(matches.clone());
-            application.run()
-        } else {
-            Ok(())
-        }
-    } else {
-        Ok(())
+fn get_config_command() -> Command {
+    Command::new("config")
+        .about("管理配置文件")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("init")
+                .about("生成带注释的默认配置文件")
+                .arg(Arg::new("path").help("配置文件路径").default_value(DEFAULT_CONFIG_FILE))
+        )
+}
+
+fn run_config(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some(("init", matches)) => init_config_file(matches.get_one::<String>("path").unwrap()),
+        _ => Ok(()),
+    }
+}
+
+fn get_bench_ocr_command() -> Command {
+    Command::new("bench-ocr")
+        .about("在 --dump-dataset 保存的数据集上测试模型的识别准确率")
+        .arg(Arg::new("dataset-dir").help("数据集目录").required(true))
+        .arg(
+            Arg::new("game")
+                .long("game")
+                .help("使用此游戏的内置模型")
+                .value_parser(Game::ALL.map(|game| game.name()))
+                .default_value(Game::Genshin.name())
+        )
+        .arg(Arg::new("model-path").long("model-path").help("使用指定的 onnx 模型代替内置模型").requires("dict-path"))
+        .arg(Arg::new("dict-path").long("dict-path").help("指定模型对应的字典 json").requires("model-path"))
+        .arg(
+            Arg::new("compare-model-path")
+                .long("compare-model-path")
+                .help("与此 onnx 模型对比")
+                .requires("compare-dict-path")
+        )
+        .arg(
+            Arg::new("compare-dict-path")
+                .long("compare-dict-path")
+                .help("对比模型的字典 json")
+                .requires("compare-model-path")
+        )
+        .arg(
+            Arg::new("max-regression")
+                .long("max-regression")
+                .help("对比模型的完全匹配率或字符错误率比当前模型差超过此百分比时失败")
+                .value_parser(value_parser!(f64))
+                .default_value("0")
+        )
+        .arg(
+            Arg::new("show-mismatches")
+                .long("show-mismatches")
+                .help("显示的识别错误样本数量")
+                .value_parser(value_parser!(usize))
+                .default_value("10")
+        )
+}
+
+fn get_model(model_path: Option<&String>, dict_path: Option<&String>) -> Option<Result<YasOCRModel>> {
+    match (model_path, dict_path) {
+        (Some(model_path), Some(dict_path)) => Some(YasOCRModel::from_paths(Path::new(model_path), Path::new(dict_path))),
+        _ => None,
+    }
+}
+
+fn show_mismatches(name: &str, report: &BenchmarkReport, count: usize) {
+    if count == 0 || report.mismatches.is_empty() {
+        return;
+    }
+    info!("{} 识别错误的样本（共 {} 个）：", name, report.mismatches.len());
+    for mismatch in report.mismatches.iter().take(count) {
+        info!("{}: 应为 \"{}\"，识别为 \"{}\"", mismatch.image, mismatch.expected, mismatch.actual);
+    }
+}
+
+fn run_bench_ocr(matches: &ArgMatches) -> Result<()> {
+    let dataset_dir = matches.get_one::<String>("dataset-dir").unwrap();
+    let dataset = LabeledDataset::load(dataset_dir)?;
+    if dataset.is_empty() {
+        return Err(anyhow!("数据集 {} 中没有样本", dataset_dir));
+    }
+    info!("数据集共 {} 个样本", dataset.len());
+
+    let game_name = matches.get_one::<String>("game").unwrap();
+    let model = match get_model(matches.get_one("model-path"), matches.get_one("dict-path")) {
+        Some(model) => model?,
+        None => Game::ALL.iter().find(|game| game.name() == game_name).unwrap().ocr_model()?,
     };
+    let show_count = *matches.get_one::<usize>("show-mismatches").unwrap();
 
-    match res {
-        Ok(_) => {
-            press_any_key_to_continue();
-        },
-        Err(e) => {
-            log::error!("error: {}", e);
-            press_any_key_to_continue();
+    let report = benchmark(&model, &dataset)?;
+    let compare = match get_model(matches.get_one("compare-model-path"), matches.get_one("compare-dict-path")) {
+        Some(compare_model) => Some(benchmark(&compare_model?, &dataset)?),
+        None => None,
+    };
+
+    show_mismatches("当前模型", &report, show_count);
+    let mut columns = vec![("当前模型", &report)];
+    if let Some(compare) = &compare {
+        show_mismatches("对比模型", compare, show_count);
+        columns.push(("对比模型", compare));
+    }
+    for line in comparison_table(&columns).lines() {
+        info!("{}", line);
+    }
+
+    if let Some(compare) = &compare {
+        let tolerance = *matches.get_one::<f64>("max-regression").unwrap() / 100.0;
+        let regressions = compare.regressions(&report, tolerance);
+        if !regressions.is_empty() {
+            return Err(anyhow!("对比模型准确率下降：{}", regressions.join("，")));
         }
+        info!("对比模型没有准确率下降");
+    }
+
+    Ok(())
+}
+
+fn run() -> Result<()> {
+    let mut args: Vec<String> = std::env::args().collect();
+    // the options of the config file are given to the game after its subcommand
+    if let Some(game) = args.get(1).and_then(|name| Game::ALL.iter().find(|game| game.name() == name)) {
+        args = apply_config_file(args, 2, &game.command())?;
     }
-}
\ No newline at end of file
+
+    let cmd = Game::ALL.iter()
+        .fold(command!(), |cmd, game| cmd.subcommand(game.command()))
+        .subcommand(get_config_command())
+        .subcommand(get_bench_ocr_command());
+    let arg_matches = cmd.get_matches_from(args);
+
+    match arg_matches.subcommand() {
+        Some(("config", matches)) => run_config(matches),
+        Some(("bench-ocr", matches)) => run_bench_ocr(matches),
+        Some((subcommand_name, matches)) => match Game::ALL.iter().find(|game| game.name() == subcommand_name) {
+            Some(game) => game.run(matches.clone()),
+            None => Ok(()),
+        },
+        None => Ok(()),
+    }
+}
+
+/// the window of a scan stays open until a key is pressed, unless it's a command line tool,
+/// or nobody is there to press a key
+fn waits_for_key(subcommand: Option<&str>, is_terminal: bool) -> bool {
+    is_terminal && !matches!(subcommand, Some("bench-ocr" | "config"))
+}
+
+pub fn main() {
+    init();
+
+    let subcommand = std::env::args().nth(1);
+    let waits_for_key = waits_for_key(subcommand.as_deref(), std::io::stdin().is_terminal());
+
+    let result = run();
+    if let Err(e) = &result {
+        log::error!("error: {}", e);
+    }
+    if waits_for_key {
+        press_any_key_to_continue();
+    }
+    if result.is_err() {
+        // e.g. for `bench-ocr` to fail a regression check
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_scan_waits_for_a_key() {
+        assert!(waits_for_key(Some("genshin"), true));
+        assert!(waits_for_key(None, true));
+    }
+
+    #[test]
+    fn the_command_line_tools_do_not_wait() {
+        assert!(!waits_for_key(Some("bench-ocr"), true));
+        assert!(!waits_for_key(Some("config"), true));
+    }
+
+    #[test]
+    fn nothing_waits_without_a_terminal() {
+        assert!(!waits_for_key(Some("genshin"), false));
+    }
+}
@@ -1,5 +1,6 @@
+use yas::config_file::apply_config_file;
 use yas::utils::press_any_key_to_continue;
-use yas_genshin::application::ArtifactScannerApplication;
+use yas_application::Game;
 use log::error;
 
 pub fn main() {
@@ -7,11 +8,18 @@ pub fn main() {
         .filter_level(log::LevelFilter::Info)
         .init();
 
-    let command = ArtifactScannerApplication::build_command();
-    let matches = command.get_matches();
+    let command = Game::Genshin.command();
+    let args = match apply_config_file(std::env::args().collect(), 1, &command) {
+        Ok(args) => args,
+        Err(e) => {
+            error!("error: {}", e);
+            press_any_key_to_continue();
+            return;
+        },
+    };
+    let matches = command.get_matches_from(args);
 
-    let application = ArtifactScannerApplication::new(matches);
-    match application.run() {
+    match Game::Genshin.run(matches) {
         Err(e) => {
             error!("error: {}", e);
             press_any_key_to_continue();
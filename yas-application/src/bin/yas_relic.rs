@@ -1,15 +1,24 @@
+use yas::config_file::apply_config_file;
 use yas::utils::press_any_key_to_continue;
-use yas_starrail::application::RelicScannerApplication;
+use yas_application::Game;
 use log::error;
 
 pub fn main() {
     env_logger::Builder::new()
         .filter_level(log::LevelFilter::Info)
         .init();
-    let matches = RelicScannerApplication::build_command().get_matches();
+    let command = Game::StarRail.command();
+    let args = match apply_config_file(std::env::args().collect(), 1, &command) {
+        Ok(args) => args,
+        Err(e) => {
+            error!("error: {}", e);
+            press_any_key_to_continue();
+            return;
+        },
+    };
+    let matches = command.get_matches_from(args);
 
-    let application = RelicScannerApplication::new(matches);
-    match application.run() {
+    match Game::StarRail.run(matches) {
         Err(e) => {
             error!("error: {}", e);
             press_any_key_to_continue();
@@ -1,3 +1,4 @@
+use yas::config_file::apply_config_file;
 use yas::utils::press_any_key_to_continue;
 use log::error;
 use yas_wutheringwaves::application::WWEchoScannerApplication;
@@ -6,7 +7,17 @@ pub fn main() {
     env_logger::Builder::new()
         .filter_level(log::LevelFilter::Info)
         .init();
-    let matches = WWEchoScannerApplication::build_command().get_matches();
+    // named for its table of the config file
+    let command = WWEchoScannerApplication::build_command().name("ww_echo");
+    let args = match apply_config_file(std::env::args().collect(), 1, &command) {
+        Ok(args) => args,
+        Err(e) => {
+            error!("error: {}", e);
+            press_any_key_to_continue();
+            return;
+        },
+    };
+    let matches = command.get_matches_from(args);
 
     let application = WWEchoScannerApplication::new(matches);
     match application.run() {
@@ -0,0 +1,16 @@
+//! scanning as a library, what the binaries are built on
+//! ```no_run
+//! use yas_application::{Game, ScannerBuilder};
+//!
+//! let mut scanner = ScannerBuilder::new()
+//!     .game(Game::Genshin)
+//!     .args(["--min-star", "5"])
+//!     .on_item(|item| println!("{:?}", item))
+//!     .build()?;
+//! let items = scanner.scan()?;
+//! # anyhow::Ok(())
+//! ```
+
+pub use scanner_builder::{Artifact, Game, Relic, ScannedItem, Scanner, ScannerBuilder};
+
+mod scanner_builder;
@@ -0,0 +1,277 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use clap::ArgMatches;
+use yas::events::{ScanEvent, ScanObserver};
+use yas::game_info::GameInfo;
+use yas::ocr::YasOCRModel;
+
+use yas_genshin::application::ArtifactScannerApplication;
+use yas_genshin::artifact::GenshinArtifact;
+use yas_genshin::scanner::{get_embedded_artifact_model, GenshinArtifactScanResult};
+use yas_starrail::application::RelicScannerApplication;
+use yas_starrail::relic::StarRailRelic;
+use yas_starrail::scanner::relic_scanner::{get_embedded_relic_model, StarRailRelicScanResult};
+
+pub type Artifact = GenshinArtifact;
+pub type Relic = StarRailRelic;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Game {
+    Genshin,
+    StarRail,
+}
+
+impl Game {
+    pub const ALL: [Game; 2] = [Game::Genshin, Game::StarRail];
+
+    /// the subcommand of the game in `yas`
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Game::Genshin => "genshin",
+            Game::StarRail => "starrail",
+        }
+    }
+
+    /// the options of the game, the same for the binaries and `ScannerBuilder::args`
+    pub fn command(&self) -> clap::Command {
+        let cmd = match *self {
+            Game::Genshin => ArtifactScannerApplication::build_command(),
+            Game::StarRail => RelicScannerApplication::build_command(),
+        };
+        cmd.name(self.name())
+    }
+
+    /// scan and export the results as the binaries do, by the options parsed by `command`
+    pub fn run(&self, matches: ArgMatches) -> Result<()> {
+        ScannerBuilder::new()
+            .game(*self)
+            .matches(matches)
+            .build()?
+            .run()
+            .map(|_| ())
+    }
+
+    /// the model built into the scanner of the game
+    pub fn ocr_model(&self) -> Result<YasOCRModel> {
+        match *self {
+            Game::Genshin => get_embedded_artifact_model(),
+            Game::StarRail => get_embedded_relic_model(),
+        }
+    }
+
+    /// the typed item of a result of `ScanEvent::Item`
+    fn parse_item(&self, item: &serde_json::Value) -> Option<ScannedItem> {
+        match *self {
+            Game::Genshin => {
+                let result: GenshinArtifactScanResult = serde_json::from_value(item.clone()).ok()?;
+                GenshinArtifact::try_from(&result).ok().map(ScannedItem::Artifact)
+            },
+            Game::StarRail => {
+                let result: StarRailRelicScanResult = serde_json::from_value(item.clone()).ok()?;
+                StarRailRelic::try_from(&result).ok().map(ScannedItem::Relic)
+            },
+        }
+    }
+}
+
+/// an item recognized by `Scanner`, of its game
+#[derive(Clone, Debug)]
+pub enum ScannedItem {
+    Artifact(Artifact),
+    Relic(Relic),
+}
+
+type ItemCallback = Box<dyn FnMut(&ScannedItem) + Send>;
+
+/// calls back with the items of the scan as the recognition threads produce them
+struct ItemObserver {
+    game: Game,
+    on_item: Mutex<ItemCallback>,
+}
+
+impl ScanObserver for ItemObserver {
+    fn on_event(&self, event: &ScanEvent) {
+        let ScanEvent::Item { item, .. } = event else {
+            return;
+        };
+        // e.g. an artifact without a set, which was not to be exported either
+        let Some(item) = self.game.parse_item(item) else {
+            return;
+        };
+
+        (self.on_item.lock().unwrap())(&item);
+    }
+}
+
+#[derive(Default)]
+pub struct ScannerBuilder {
+    game: Option<Game>,
+    window: Option<GameInfo>,
+    args: Vec<String>,
+    matches: Option<ArgMatches>,
+    on_item: Option<ItemCallback>,
+}
+
+impl ScannerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn game(mut self, game: Game) -> Self {
+        self.game = Some(game);
+        self
+    }
+
+    /// the window of the game, found by its title when the scan starts if not given
+    pub fn window(mut self, game_info: GameInfo) -> Self {
+        self.window = Some(game_info);
+        self
+    }
+
+    /// the options as they are given to the binaries, e.g. `["--min-star", "5"]`
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where I: IntoIterator<Item = S>, S: Into<String> {
+        self.args.extend(args.into_iter().map(|arg| arg.into()));
+        self
+    }
+
+    /// the options already parsed by `Game::command`, e.g. by a binary, in place of `args`
+    pub fn matches(mut self, matches: ArgMatches) -> Self {
+        self.matches = Some(matches);
+        self
+    }
+
+    /// called with every item as soon as it's recognized, in the order they are scanned, from a recognition thread.
+    /// duplicates and items which can't be parsed are left out, as they are from the results
+    pub fn on_item<F>(mut self, f: F) -> Self
+    where F: FnMut(&ScannedItem) + Send + 'static {
+        self.on_item = Some(Box::new(f));
+        self
+    }
+
+    pub fn build(self) -> Result<Scanner> {
+        let game = self.game.ok_or(anyhow!("game is not set"))?;
+        let matches = match self.matches {
+            Some(matches) => matches,
+            None => game.command()
+                .try_get_matches_from(std::iter::once(game.name().to_string()).chain(self.args))?,
+        };
+
+        Ok(Scanner {
+            game,
+            game_info: self.window,
+            matches,
+            observer: self.on_item.map(|on_item| Arc::new(ItemObserver { game, on_item: Mutex::new(on_item) }) as Arc<dyn ScanObserver>),
+        })
+    }
+}
+
+/// scans the repository of a game
+pub struct Scanner {
+    game: Game,
+    game_info: Option<GameInfo>,
+    matches: ArgMatches,
+    /// calls `ScannerBuilder::on_item`
+    observer: Option<Arc<dyn ScanObserver>>,
+}
+
+impl Scanner {
+    pub fn game(&self) -> Game {
+        self.game
+    }
+
+    /// the window given to the builder
+    pub fn game_info(&self) -> Option<&GameInfo> {
+        self.game_info.as_ref()
+    }
+
+    /// the items which can be parsed into typed results, the others are left out, nothing is exported.
+    /// a resumed scan has those of the checkpoint first, which `on_item` is not called with
+    pub fn scan(&mut self) -> Result<Vec<ScannedItem>> {
+        self.scan_and_export(false)
+    }
+
+    /// scan, and export the results as the binaries do
+    pub fn run(&mut self) -> Result<Vec<ScannedItem>> {
+        self.scan_and_export(true)
+    }
+
+    fn scan_and_export(&mut self, export: bool) -> Result<Vec<ScannedItem>> {
+        let observer = self.observer.clone();
+
+        match self.game {
+            Game::Genshin => {
+                let application = ArtifactScannerApplication::new(self.matches.clone());
+                let output = application.scan(self.game_info.clone(), observer)?;
+                let items = output.artifacts.iter().cloned().map(ScannedItem::Artifact).collect();
+                if export {
+                    application.export(output)?;
+                }
+                Ok(items)
+            },
+            Game::StarRail => {
+                let application = RelicScannerApplication::new(self.matches.clone());
+                let relics = application.scan(self.game_info.clone(), observer)?;
+                if export {
+                    application.export(&relics)?;
+                }
+                Ok(relics.into_iter().map(ScannedItem::Relic).collect())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact_event(name: &str) -> ScanEvent {
+        let result = GenshinArtifactScanResult {
+            name: String::from(name),
+            main_stat_name: String::from("生命值"),
+            main_stat_value: String::from("4,780"),
+            sub_stat: [
+                String::from("暴击率+10.5%"),
+                String::from("暴击伤害+21.0%"),
+                String::from("攻击力+19"),
+                String::from("元素精通+23"),
+            ],
+            equip: String::new(),
+            level: 20,
+            star: 5,
+            lock: false,
+        };
+        ScanEvent::Item { index: 0, confidence: 1.0, item: serde_json::to_value(&result).unwrap() }
+    }
+
+    #[test]
+    fn on_item_is_called_with_the_typed_items() {
+        let items = Arc::new(Mutex::new(Vec::new()));
+        let received = items.clone();
+        let scanner = ScannerBuilder::new()
+            .game(Game::Genshin)
+            .on_item(move |item| received.lock().unwrap().push(item.clone()))
+            .build()
+            .unwrap();
+        let observer = scanner.observer.unwrap();
+
+        observer.on_event(&artifact_event("角斗士的留恋"));
+        observer.on_event(&artifact_event("不是圣遗物"));
+        observer.on_event(&ScanEvent::Start { total: None });
+
+        let items = items.lock().unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(matches!(&items[0], ScannedItem::Artifact(artifact) if artifact.level == 20));
+    }
+
+    #[test]
+    fn the_options_are_checked_by_the_builder() {
+        assert!(ScannerBuilder::default().build().is_err());
+        assert!(ScannerBuilder::new().game(Game::StarRail).args(["--no-such-option"]).build().is_err());
+
+        let scanner = ScannerBuilder::new().game(Game::Genshin).build().unwrap();
+        assert!(scanner.observer.is_none());
+        assert!(scanner.game_info().is_none());
+    }
+}
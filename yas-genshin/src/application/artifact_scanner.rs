@@ -1,16 +1,39 @@
 use anyhow::Result;
+#[cfg(target_os = "windows")]
 use anyhow::anyhow;
-use clap::{command, ArgMatches, Args};
-use log::info;
+use std::sync::Arc;
 
+use clap::{command, ArgMatches, Args, FromArgMatches};
+use log::{info, warn};
+
+use yas::config_file::{with_flag_values, ConfigFileArgs};
+use yas::events::{EventFormat, JsonLinesObserver, ScanObserver, ScanObservers};
 use yas::export::{AssetEmitter, ExportAssets};
-use yas::game_info::{GameInfo, GameInfoBuilder};
+use yas::frame_source::open_frame_source;
+use yas::game_info::{GameInfo, GameInfoBuilder, Platform, UI};
 use yas::window_info::{load_window_info_repo, WindowInfoRepository};
 
 use crate::artifact::GenshinArtifact;
-use crate::export::artifact::{ExportArtifactConfig, GenshinArtifactExporter};
-use crate::scanner::{GenshinArtifactScanner, GenshinArtifactScannerConfig};
+use crate::calibration::{calibrate_window_info, GenshinCalibrationConfig};
+use crate::character::GenshinCharacter;
+use crate::export::artifact::{
+    ArtifactExporterRegistry,
+    ALL_FORMATS,
+    ExportArtifactConfig,
+    GOODExporter,
+    GenshinArtifactExporter,
+    PreviousExport,
+};
+use crate::scanner::{
+    GenshinArtifactScanner,
+    GenshinArtifactScannerConfig,
+    GenshinCharacterScanner,
+    GenshinCharacterScannerConfig,
+    GenshinWeaponScanner,
+    GenshinWeaponScannerConfig,
+};
 use crate::scanner_controller::repository_layout::GenshinRepositoryScannerLogicConfig;
+use crate::weapon::GenshinWeapon;
 
 pub struct ArtifactScannerApplication {
     arg_matches: ArgMatches,
@@ -27,11 +50,16 @@ impl ArtifactScannerApplication {
         let mut cmd = command!();
         cmd = <ExportArtifactConfig as Args>::augment_args_for_update(cmd);
         cmd = <GenshinArtifactScannerConfig as Args>::augment_args_for_update(cmd);
+        cmd = <GenshinWeaponScannerConfig as Args>::augment_args_for_update(cmd);
+        cmd = <GenshinCharacterScannerConfig as Args>::augment_args_for_update(cmd);
         cmd = <GenshinRepositoryScannerLogicConfig as Args>::augment_args_for_update(cmd);
-        cmd
+        cmd = <GenshinCalibrationConfig as Args>::augment_args_for_update(cmd);
+        cmd = <ConfigFileArgs as Args>::augment_args_for_update(cmd);
+        // the options of the config file come first, and are overridden by those of the command line
+        with_flag_values(cmd).args_override_self(true)
     }
 
-    fn get_window_info_repository() -> WindowInfoRepository {
+    pub fn get_window_info_repository() -> WindowInfoRepository {
         load_window_info_repo!(
             "../../window_info/windows1600x900.json",
             "../../window_info/windows1280x960.json",
@@ -47,7 +75,7 @@ impl ArtifactScannerApplication {
     //         .init();
     // }
 
-    fn get_game_info() -> Result<GameInfo> {
+    pub fn get_game_info() -> Result<GameInfo> {
         let game_info = GameInfoBuilder::new()
             .add_local_window_name("原神")
             .add_local_window_name("Genshin Impact")
@@ -57,11 +85,41 @@ impl ArtifactScannerApplication {
     }
 }
 
+/// what `ArtifactScannerApplication::scan` recognized, for `export`
+pub struct GenshinScanOutput {
+    pub artifacts: Vec<GenshinArtifact>,
+    pub weapons: Vec<GenshinWeapon>,
+    pub characters: Vec<GenshinCharacter>,
+    /// the export of `--incremental` the artifacts are merged into
+    previous: Option<(String, Arc<PreviousExport>)>,
+    /// the items recognized, including those which are not valid artifacts
+    scanned_count: usize,
+}
+
 impl ArtifactScannerApplication {
+    /// scan and export
     pub fn run(&self) -> Result<()> {
+        let output = self.scan(None, None)?;
+        self.export(output)
+    }
+
+    /// scan the window of `game_info`, or the window found by its title, or the recorded frames of `--offline`.
+    /// the events go to `observer`, as well as where the options say
+    pub fn scan(&self, game_info: Option<GameInfo>, observer: Option<Arc<dyn ScanObserver>>) -> Result<GenshinScanOutput> {
         let arg_matches = &self.arg_matches;
-        let window_info_repository = Self::get_window_info_repository();
-        let game_info = Self::get_game_info()?;
+        let mut window_info_repository = Self::get_window_info_repository();
+
+        // offline, the recorded frames take the place of the window
+        let offline = match &GenshinArtifactScannerConfig::from_arg_matches(arg_matches)?.offline {
+            Some(path) => Some(open_frame_source(path)?),
+            None => None,
+        };
+        let game_info = match (&offline, game_info) {
+            (Some(source), _) => GameInfo::from_frame_size(source.frame_size()?, UI::Desktop, Platform::current())?,
+            (None, Some(game_info)) => game_info,
+            (None, None) => Self::get_game_info()?,
+        };
+        let is_offline = offline.is_some();
 
         info!("window: {:?}", game_info.window);
         info!("ui: {:?}", game_info.ui);
@@ -69,28 +127,129 @@ impl ArtifactScannerApplication {
         info!("resolution family: {:?}", game_info.resolution_family);
 
         #[cfg(target_os = "windows")]
-        {
+        if !is_offline {
             // assure admin
             if !yas::utils::is_admin() {
                 return Err(anyhow!("请使用管理员运行"));
             }
         }
 
+        let calibration_config = GenshinCalibrationConfig::from_arg_matches(arg_matches)?;
+        if is_offline && calibration_config.calibrate {
+            warn!("离线识别不支持校准，使用默认位置");
+        } else {
+            calibrate_window_info(&mut window_info_repository, &calibration_config, &game_info)?;
+        }
+
         let mut scanner = GenshinArtifactScanner::from_arg_matches(
             &window_info_repository,
             arg_matches,
             game_info.clone()
         )?;
 
-        let result = scanner.scan()?;
+        let scanner_config = GenshinArtifactScannerConfig::from_arg_matches(arg_matches)?;
+        let mut observers = ScanObservers(observer.into_iter().collect());
+        if let Some(EventFormat::Jsonl) = scanner_config.output_events {
+            let observer = match &scanner_config.events_file {
+                Some(path) => JsonLinesObserver::create(path)?,
+                None => JsonLinesObserver::stdout(),
+            };
+            observers.0.push(Arc::new(observer));
+        }
+        if let Some(observer) = observers.into_observer() {
+            scanner.set_observer(observer);
+        }
+
+        let export_config = ExportArtifactConfig::from_arg_matches(arg_matches)?;
+        // an unknown format fails before the scan rather than after it
+        ArtifactExporterRegistry::new().select(&export_config.format)?;
+        let previous = match &export_config.incremental {
+            Some(path) => {
+                let previous = Arc::new(PreviousExport::load(path)?);
+                info!("增量扫描，已有 {} 件圣遗物", previous.len());
+                let is_known = previous.clone();
+                scanner.set_known_filter(Box::new(move |artifact| is_known.contains(artifact)));
+                Some((path.clone(), previous))
+            },
+            None => None,
+        };
+
+        let result = match offline {
+            Some(source) => scanner.scan_offline(source)?,
+            None => scanner.scan()?,
+        };
         let artifacts = result
             .iter()
             .flat_map(GenshinArtifact::try_from)
             .collect::<Vec<_>>();
 
-        let exporter = GenshinArtifactExporter::new(arg_matches, &artifacts)?;
+        // the weapon tab is in the same repository, and the character screen is opened from there
+        let scan_weapons = GenshinWeaponScannerConfig::from_arg_matches(arg_matches)?.scan_weapons;
+        let scan_characters = GenshinCharacterScannerConfig::from_arg_matches(arg_matches)?.scan_characters;
+        if is_offline && (scan_weapons || scan_characters) {
+            warn!("离线识别只识别圣遗物，武器和角色不会扫描");
+        }
+        let weapons = if scan_weapons && !is_offline {
+            GenshinWeaponScanner::from_arg_matches(&window_info_repository, arg_matches, game_info.clone())?.scan()?
+        } else {
+            Vec::new()
+        };
+        let characters = if scan_characters && !is_offline {
+            GenshinCharacterScanner::from_arg_matches(&window_info_repository, arg_matches, game_info.clone())?.scan()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(GenshinScanOutput {
+            artifacts,
+            weapons,
+            characters,
+            previous,
+            scanned_count: result.len(),
+        })
+    }
+
+    /// save the results in the formats of the options
+    pub fn export(&self, output: GenshinScanOutput) -> Result<()> {
+        let arg_matches = &self.arg_matches;
+        let export_config = ExportArtifactConfig::from_arg_matches(arg_matches)?;
+        let GenshinScanOutput { artifacts, weapons, characters, previous, scanned_count } = output;
+        let has_inventory = !weapons.is_empty() || !characters.is_empty();
+
         let mut export_assets = ExportAssets::new();
-        exporter.emit(&mut export_assets);
+        match previous {
+            Some((path, previous)) => {
+                if has_inventory {
+                    warn!("增量扫描只合并圣遗物，角色和武器不会导出");
+                }
+                let new_count = artifacts.iter().filter(|artifact| !previous.contains(artifact)).count();
+                info!("新增 {} 件圣遗物", new_count);
+
+                export_assets.add_asset(
+                    Some(String::from("incremental")),
+                    path.into(),
+                    previous.merge(&artifacts)?.into_bytes(),
+                    Some(String::from("增量合并的圣遗物")),
+                );
+            },
+            None => {
+                let mut registry = ArtifactExporterRegistry::new();
+                if has_inventory {
+                    if export_config.format != "good" && export_config.format != ALL_FORMATS {
+                        warn!("角色和武器仅导出到 GOOD 格式，请使用 --format good");
+                    }
+                    registry.register(Box::new(
+                        GOODExporter::default()
+                            .with_characters(characters)
+                            .with_weapons(weapons)
+                    ));
+                }
+
+                let exporter = GenshinArtifactExporter::new(arg_matches, &artifacts)?
+                    .with_registry(registry);
+                exporter.emit(&mut export_assets);
+            },
+        }
 
         let stats = export_assets.save();
         info!("保存结果：");
@@ -99,7 +258,7 @@ impl ArtifactScannerApplication {
         for line in table.lines() {
             info!("{}", line);
         }
-        info!("Yas 识别结束，共识别到 {} 件圣遗物。", result.len());
+        info!("Yas 识别结束，共识别到 {} 件圣遗物。", scanned_count);
 
         Ok(())
     }
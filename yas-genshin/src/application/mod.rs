@@ -1,4 +1,4 @@
-pub use artifact_scanner::ArtifactScannerApplication;
+pub use artifact_scanner::{ArtifactScannerApplication, GenshinScanOutput};
 
 mod artifact_scanner;
 
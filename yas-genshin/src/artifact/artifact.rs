@@ -4,7 +4,7 @@ use log::error;
 use regex::Regex;
 use strum_macros::Display;
 
-use crate::character::CHARACTER_NAMES;
+use crate::language::language_pack;
 use crate::scanner::GenshinArtifactScanResult;
 
 #[derive(Debug, Hash, Clone, PartialEq, Eq, Display)]
@@ -174,10 +174,7 @@ impl ArtifactStat {
         }
 
         let is_percentage = temp[1].contains('%');
-        let stat_name = match ArtifactStatName::from_zh_cn(temp[0], is_percentage) {
-            Some(v) => v,
-            None => return None,
-        };
+        let stat_name = ArtifactStatName::from_zh_cn(temp[0], is_percentage)?;
 
         let re = Regex::new("[%,]").unwrap();
         let mut value = match re.replace_all(temp[1], "").parse::<f64>() {
@@ -202,33 +199,23 @@ impl TryFrom<&GenshinArtifactScanResult> for GenshinArtifact {
     type Error = ();
 
     fn try_from(value: &GenshinArtifactScanResult) -> Result<Self, Self::Error> {
-        let set_name = ArtifactSetName::from_zh_cn(&value.name).ok_or(())?;
-        let slot = ArtifactSlot::from_zh_cn(&value.name).ok_or(())?;
+        // the text is in the language of the client
+        let language = language_pack();
+        let (set_name, slot) = language.set_and_slot(&value.name).ok_or(())?;
         let star = value.star;
         let lock = value.lock;
 
-        let main_stat = ArtifactStat::from_zh_cn_raw(
+        let main_stat = language.stat(
             (value.main_stat_name.clone() + "+" + value.main_stat_value.as_str()).as_str(),
         )
         .ok_or(())?;
 
-        let sub1 = ArtifactStat::from_zh_cn_raw(&value.sub_stat[0]);
-        let sub2 = ArtifactStat::from_zh_cn_raw(&value.sub_stat[1]);
-        let sub3 = ArtifactStat::from_zh_cn_raw(&value.sub_stat[2]);
-        let sub4 = ArtifactStat::from_zh_cn_raw(&value.sub_stat[3]);
-
-        let equip = if value.equip.ends_with("已装备") {
-            let chars = value.equip.chars().collect::<Vec<_>>();
-            let equip_name = chars[..chars.len() - 3].iter().collect::<String>();
+        let sub1 = language.stat(&value.sub_stat[0]);
+        let sub2 = language.stat(&value.sub_stat[1]);
+        let sub3 = language.stat(&value.sub_stat[2]);
+        let sub4 = language.stat(&value.sub_stat[3]);
 
-            if CHARACTER_NAMES.contains(equip_name.as_str()) {
-                Some(equip_name)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        let equip = language.equip(&value.equip);
 
         Ok(GenshinArtifact {
             set_name,
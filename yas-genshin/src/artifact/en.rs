@@ -0,0 +1,46 @@
+use crate::artifact::ArtifactStatName;
+
+/// the stat names of the english client, for the lexicon
+pub(crate) const STAT_NAMES_EN: [&str; 16] = [
+    "HP",
+    "ATK",
+    "DEF",
+    "Elemental Mastery",
+    "Energy Recharge",
+    "CRIT Rate",
+    "CRIT DMG",
+    "Healing Bonus",
+    "Pyro DMG Bonus",
+    "Hydro DMG Bonus",
+    "Electro DMG Bonus",
+    "Cryo DMG Bonus",
+    "Anemo DMG Bonus",
+    "Geo DMG Bonus",
+    "Dendro DMG Bonus",
+    "Physical DMG Bonus",
+];
+
+impl ArtifactStatName {
+    #[rustfmt::skip]
+    pub fn from_en(name: &str, is_percentage: bool) -> Option<ArtifactStatName> {
+        match name {
+            "Healing Bonus" => Some(ArtifactStatName::HealingBonus),
+            "CRIT DMG" => Some(ArtifactStatName::CriticalDamage),
+            "CRIT Rate" => Some(ArtifactStatName::Critical),
+            "ATK" => if is_percentage { Some(ArtifactStatName::AtkPercentage) } else { Some(ArtifactStatName::Atk) },
+            "Elemental Mastery" => Some(ArtifactStatName::ElementalMastery),
+            "Energy Recharge" => Some(ArtifactStatName::Recharge),
+            "HP" => if is_percentage { Some(ArtifactStatName::HpPercentage) } else { Some(ArtifactStatName::Hp) },
+            "DEF" => if is_percentage { Some(ArtifactStatName::DefPercentage) } else { Some(ArtifactStatName::Def) },
+            "Electro DMG Bonus" => Some(ArtifactStatName::ElectroBonus),
+            "Pyro DMG Bonus" => Some(ArtifactStatName::PyroBonus),
+            "Hydro DMG Bonus" => Some(ArtifactStatName::HydroBonus),
+            "Cryo DMG Bonus" => Some(ArtifactStatName::CryoBonus),
+            "Anemo DMG Bonus" => Some(ArtifactStatName::AnemoBonus),
+            "Geo DMG Bonus" => Some(ArtifactStatName::GeoBonus),
+            "Dendro DMG Bonus" => Some(ArtifactStatName::DendroBonus),
+            "Physical DMG Bonus" => Some(ArtifactStatName::PhysicalBonus),
+            _ => None,
+        }
+    }
+}
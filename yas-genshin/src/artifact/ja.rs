@@ -0,0 +1,46 @@
+use crate::artifact::ArtifactStatName;
+
+/// the stat names of the japanese client, for the lexicon
+pub(crate) const STAT_NAMES_JA: [&str; 16] = [
+    "HP",
+    "攻撃力",
+    "防御力",
+    "元素熟知",
+    "元素チャージ効率",
+    "会心率",
+    "会心ダメージ",
+    "与える治癒効果",
+    "炎元素ダメージ",
+    "水元素ダメージ",
+    "雷元素ダメージ",
+    "氷元素ダメージ",
+    "風元素ダメージ",
+    "岩元素ダメージ",
+    "草元素ダメージ",
+    "物理ダメージ",
+];
+
+impl ArtifactStatName {
+    #[rustfmt::skip]
+    pub fn from_ja(name: &str, is_percentage: bool) -> Option<ArtifactStatName> {
+        match name {
+            "与える治癒効果" => Some(ArtifactStatName::HealingBonus),
+            "会心ダメージ" => Some(ArtifactStatName::CriticalDamage),
+            "会心率" => Some(ArtifactStatName::Critical),
+            "攻撃力" => if is_percentage { Some(ArtifactStatName::AtkPercentage) } else { Some(ArtifactStatName::Atk) },
+            "元素熟知" => Some(ArtifactStatName::ElementalMastery),
+            "元素チャージ効率" => Some(ArtifactStatName::Recharge),
+            "HP" => if is_percentage { Some(ArtifactStatName::HpPercentage) } else { Some(ArtifactStatName::Hp) },
+            "防御力" => if is_percentage { Some(ArtifactStatName::DefPercentage) } else { Some(ArtifactStatName::Def) },
+            "雷元素ダメージ" => Some(ArtifactStatName::ElectroBonus),
+            "炎元素ダメージ" => Some(ArtifactStatName::PyroBonus),
+            "水元素ダメージ" => Some(ArtifactStatName::HydroBonus),
+            "氷元素ダメージ" => Some(ArtifactStatName::CryoBonus),
+            "風元素ダメージ" => Some(ArtifactStatName::AnemoBonus),
+            "岩元素ダメージ" => Some(ArtifactStatName::GeoBonus),
+            "草元素ダメージ" => Some(ArtifactStatName::DendroBonus),
+            "物理ダメージ" => Some(ArtifactStatName::PhysicalBonus),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use lazy_static::lazy_static;
+
+use yas::ocr::Lexicon;
+
+use crate::character::CHARACTER_NAMES;
+
+/// the names of all artifacts, keep in sync with `ArtifactSlot::from_zh_cn`
+pub(crate) const ARTIFACT_NAMES_ZH_CN: [&str; 251] = [
+    "磐陀裂生之花",
+    "嵯峨群峰之翼",
+    "星罗圭壁之晷",
+    "星罗圭璧之晷",
+    "巉岩琢塑之樽",
+    "不动玄石之相",
+    "历经风雪的思念",
+    "摧冰而行的执望",
+    "冰雪故园的终期",
+    "遍结寒霜的傲骨",
+    "破冰踏雪的回音",
+    "染血的铁之心",
+    "染血的黑之羽",
+    "骑士染血之时",
+    "染血骑士之杯",
+    "染血的铁假面",
+    "魔女的炎之花",
+    "魔女常燃之羽",
+    "魔女破灭之时",
+    "魔女的心之火",
+    "焦灼的魔女帽",
+    "角斗士的留恋",
+    "角斗士的归宿",
+    "角斗士的希冀",
+    "角斗士的酣醉",
+    "角斗士的凯旋",
+    "饰金胸花",
+    "追忆之风",
+    "坚铜罗盘",
+    "沉波之盏",
+    "酒渍船帽",
+    "渡火者的决绝",
+    "渡火者的解脱",
+    "渡火者的煎熬",
+    "渡火者的醒悟",
+    "渡火者的智慧",
+    "远方的少女之心",
+    "少女飘摇的思念",
+    "少女苦短的良辰",
+    "少女片刻的闲暇",
+    "少女易逝的芳颜",
+    "宗室之花",
+    "宗室之翎",
+    "宗室时计",
+    "宗室银瓮",
+    "宗室面具",
+    "夏祭之花",
+    "夏祭终末",
+    "夏祭之刻",
+    "夏祭水玉",
+    "夏祭之面",
+    "平雷之心",
+    "平雷之羽",
+    "平雷之刻",
+    "平雷之器",
+    "平雷之冠",
+    "雷鸟的怜悯",
+    "雷灾的孑遗",
+    "雷霆的时计",
+    "降雷的凶兆",
+    "唤雷的头冠",
+    "野花记忆的绿野",
+    "猎人青翠的箭羽",
+    "翠绿猎人的笃定",
+    "翠绿猎人的容器",
+    "翠绿的猎人之冠",
+    "乐团的晨光",
+    "琴师的箭羽",
+    "终幕的时计",
+    "终末的时计",
+    "吟游者之壶",
+    "指挥的礼帽",
+    "战狂的蔷薇",
+    "战狂的翎羽",
+    "战狂的时计",
+    "战狂的骨杯",
+    "战狂的鬼面",
+    "勇士的勋章",
+    "勇士的期许",
+    "勇士的坚毅",
+    "勇士的壮行",
+    "勇士的冠冕",
+    "守护之花",
+    "守护徽印",
+    "守护座钟",
+    "守护之皿",
+    "守护束带",
+    "流放者之花",
+    "流放者之羽",
+    "流放者怀表",
+    "流放者之杯",
+    "流放者头冠",
+    "赌徒的胸花",
+    "赌徒的羽饰",
+    "赌徒的怀表",
+    "赌徒的骰盅",
+    "赌徒的耳环",
+    "教官的胸花",
+    "教官的羽饰",
+    "教官的怀表",
+    "教官的茶杯",
+    "教官的帽子",
+    "武人的红花",
+    "武人的羽饰",
+    "武人的水漏",
+    "武人的酒杯",
+    "武人的头巾",
+    "祭水礼冠",
+    "祭火礼冠",
+    "祭雷礼冠",
+    "祭冰礼冠",
+    "故人之心",
+    "归乡之羽",
+    "逐光之石",
+    "异国之盏",
+    "感别之冠",
+    "学士的书签",
+    "学士的羽笔",
+    "学士的时钟",
+    "学士的墨杯",
+    "学士的镜片",
+    "奇迹之花",
+    "奇迹之羽",
+    "奇迹之沙",
+    "奇迹之杯",
+    "奇迹耳坠",
+    "冒险家之花",
+    "冒险家尾羽",
+    "冒险家怀表",
+    "冒险家金杯",
+    "冒险家头带",
+    "幸运儿绿花",
+    "幸运儿鹰羽",
+    "幸运儿沙漏",
+    "幸运儿之杯",
+    "幸运儿银冠",
+    "游医的银莲",
+    "游医的枭羽",
+    "游医的怀钟",
+    "游医的药壶",
+    "游医的方巾",
+    "勋绩之花",
+    "昭武翎羽",
+    "金铜时晷",
+    "盟誓金爵",
+    "将帅兜鍪",
+    "无垢之花",
+    "贤医之羽",
+    "停摆之刻",
+    "超越之盏",
+    "嗤笑之面",
+    "明威之镡",
+    "切落之羽",
+    "雷云之笼",
+    "绯花之壶",
+    "华饰之兜",
+    "羁缠之花",
+    "思忆之矢",
+    "朝露之时",
+    "祈望之心",
+    "无常之面",
+    "荣花之期",
+    "华馆之羽",
+    "众生之谣",
+    "梦醒之瓢",
+    "形骸之笠",
+    "海染之花",
+    "渊宫之羽",
+    "离别之贝",
+    "真珠之笼",
+    "海祇之冠",
+    "生灵之华",
+    "阳辔之遗",
+    "潜光片羽",
+    "结契之刻",
+    "虺雷之姿",
+    "魂香之花",
+    "祝祀之凭",
+    "垂玉之叶",
+    "涌泉之盏",
+    "浮溯之珏",
+    "迷宫的游人",
+    "翠蔓的智者",
+    "贤智的定期",
+    "迷误者之灯",
+    "月桂的宝冠",
+    "梦中的铁花",
+    "裁断的翎羽",
+    "沉金的岁月",
+    "如蜜的终宴",
+    "沙王的投影",
+    "月女的华彩",
+    "谢落的筵席",
+    "凝结的时刻",
+    "守秘的魔瓶",
+    "紫晶的花冠",
+    "众王之都的开端",
+    "黄金邦国的结末",
+    "失落迷途的机芯",
+    "迷醉长梦的守护",
+    "流沙贵嗣的遗宝",
+    "旅途中的鲜花",
+    "坏巫师的羽杖",
+    "水仙的时时刻刻",
+    "勇者们的茶会",
+    "恶龙的单片镜",
+    "灵光源起之蕊",
+    "琦色灵彩之羽",
+    "久远花落之时",
+    "无边酣乐之筵",
+    "灵光明烁之心",
+    "猎人的胸花",
+    "杰作的序曲",
+    "裁判的时刻",
+    "遗忘的容器",
+    "老兵的容颜",
+    "黄金乐曲的变奏",
+    "黄金飞鸟的落羽",
+    "黄金时代的先声",
+    "黄金之夜的喧嚣",
+    "黄金剧团的奖赏",
+    "昔时遗落之誓",
+    "昔时浮想之思",
+    "昔时回映之音",
+    "昔时应许之梦",
+    "昔时传奏之诗",
+    "无私的妆饰花",
+    "诚恳的蘸水笔",
+    "忠实的砂时计",
+    "慷慨的墨水瓶",
+    "慈爱的淑女帽",
+    "异想零落的圆舞",
+    "古海玄幽的夜想",
+    "谐律交响的前奏",
+    "命途轮转的谐谑",
+    "灵露倾洒的狂诗",
+    "失冕的宝冠",
+    "褪光的翠尾",
+    "暗结的明花",
+    "举业的识刻",
+    "筹谋的共樽",
+    "魔战士的羽面",
+    "巡山客的信标",
+    "驯兽师的护符",
+    "秘术家的金盘",
+    "游学者的爪杯",
+    "诸圣的礼冠",
+    "灵髓的根脉",
+    "异种的期许",
+    "夜域的迷思",
+    "纷争的前宴",
+];
+
+/// the names of all stats, keep in sync with `ArtifactStatName::from_zh_cn`
+pub(crate) const STAT_NAMES_ZH_CN: [&str; 16] = [
+    "治疗加成",
+    "暴击伤害",
+    "暴击率",
+    "攻击力",
+    "元素精通",
+    "元素充能效率",
+    "生命值",
+    "防御力",
+    "雷元素伤害加成",
+    "火元素伤害加成",
+    "水元素伤害加成",
+    "冰元素伤害加成",
+    "风元素伤害加成",
+    "岩元素伤害加成",
+    "草元素伤害加成",
+    "物理伤害加成",
+];
+
+lazy_static! {
+    pub static ref ARTIFACT_NAME_LEXICON: Lexicon = Lexicon::new(ARTIFACT_NAMES_ZH_CN);
+    pub static ref STAT_NAME_LEXICON: Lexicon = Lexicon::new(STAT_NAMES_ZH_CN);
+    pub static ref CHARACTER_NAME_LEXICON: Lexicon = Lexicon::new(CHARACTER_NAMES.iter().copied());
+}
@@ -0,0 +1,91 @@
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::artifact::{ArtifactStatName, GenshinArtifact};
+
+/// which artifacts to lock, loaded from toml or json, e.g.
+/// ```toml
+/// unlock_others = false
+///
+/// [[rules]]
+/// min_star = 5
+/// min_crit_value = 30.0
+///
+/// [[rules]]
+/// sets = ["EmblemOfSeveredFate"]
+/// min_sub_stats = 4
+/// ```
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct LockRules {
+    /// an artifact is locked if it matches any of the rules
+    pub rules: Vec<LockRule>,
+    /// unlock the artifacts matching none of the rules, otherwise they are left as is
+    pub unlock_others: bool,
+}
+
+/// an artifact matches if it meets all the conditions given
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct LockRule {
+    pub min_star: Option<i32>,
+    pub min_level: Option<i32>,
+    pub min_sub_stats: Option<usize>,
+    /// twice the crit rate plus the crit damage of the sub stats, in percent
+    pub min_crit_value: Option<f64>,
+    /// the set keys in GOOD, e.g. "EmblemOfSeveredFate", any set if empty
+    pub sets: Vec<String>,
+}
+
+fn crit_value(artifact: &GenshinArtifact) -> f64 {
+    [&artifact.sub_stat_1, &artifact.sub_stat_2, &artifact.sub_stat_3, &artifact.sub_stat_4]
+        .into_iter()
+        .flatten()
+        .map(|stat| match stat.name {
+            ArtifactStatName::Critical => stat.value * 200.0,
+            ArtifactStatName::CriticalDamage => stat.value * 100.0,
+            _ => 0.0,
+        })
+        .sum()
+}
+
+impl LockRule {
+    pub fn matches(&self, artifact: &GenshinArtifact) -> bool {
+        let sub_stats = [&artifact.sub_stat_1, &artifact.sub_stat_2, &artifact.sub_stat_3, &artifact.sub_stat_4]
+            .iter()
+            .filter(|stat| stat.is_some())
+            .count();
+
+        self.min_star.is_none_or(|v| artifact.star >= v)
+            && self.min_level.is_none_or(|v| artifact.level >= v)
+            && self.min_sub_stats.is_none_or(|v| sub_stats >= v)
+            && self.min_crit_value.is_none_or(|v| crit_value(artifact) >= v)
+            && (self.sets.is_empty() || self.sets.iter().any(|set| set == artifact.set_name.to_good()))
+    }
+}
+
+impl LockRules {
+    /// toml if the file ends with ".toml", otherwise json
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<LockRules> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            Ok(toml::from_str(&content)?)
+        } else {
+            Ok(serde_json::from_str(&content)?)
+        }
+    }
+
+    /// whether the artifact should be locked, `None` if it should be left as is
+    pub fn evaluate(&self, artifact: &GenshinArtifact) -> Option<bool> {
+        if self.rules.iter().any(|rule| rule.matches(artifact)) {
+            Some(true)
+        } else if self.unlock_others {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
@@ -3,6 +3,17 @@ pub use artifact::ArtifactSlot;
 pub use artifact::ArtifactStat;
 pub use artifact::ArtifactStatName;
 pub use artifact::GenshinArtifact;
+pub use scanned_artifact::{ScannedArtifact, ScannedArtifactBuilder};
+pub use lock_rules::{LockRule, LockRules};
+pub use lexicon::{ARTIFACT_NAME_LEXICON, CHARACTER_NAME_LEXICON, STAT_NAME_LEXICON};
+pub(crate) use lexicon::{ARTIFACT_NAMES_ZH_CN, STAT_NAMES_ZH_CN};
+pub(crate) use en::STAT_NAMES_EN;
+pub(crate) use ja::STAT_NAMES_JA;
 
 mod artifact;
 mod zh_cn;
+mod en;
+mod ja;
+mod scanned_artifact;
+mod lexicon;
+mod lock_rules;
@@ -0,0 +1,270 @@
+use anyhow::{anyhow, Result};
+use serde::ser::{SerializeMap, SerializeSeq, Serializer};
+use serde::Serialize;
+
+use super::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStat, GenshinArtifact};
+use crate::language::language_pack;
+
+/// an artifact assembled from the recognized strings of its fields
+/// fields which parse but don't agree with each other (e.g. a level above the max of the rarity) are kept, and
+/// described in `issues`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedArtifact {
+    pub name: String,
+    pub set_name: ArtifactSetName,
+    pub slot: ArtifactSlot,
+    pub level: i32,
+    pub rarity: i32,
+    pub main_stat: ArtifactStat,
+    pub sub_stats: Vec<ArtifactStat>,
+    pub equip: Option<String>,
+    pub lock: bool,
+    pub issues: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct ScannedArtifactBuilder {
+    name: Option<String>,
+    main_stat_name: Option<String>,
+    main_stat_value: Option<String>,
+    sub_stats: Vec<String>,
+    level: Option<String>,
+    rarity: Option<i32>,
+    equip: Option<String>,
+    lock: bool,
+}
+
+impl ScannedArtifactBuilder {
+    pub fn new() -> ScannedArtifactBuilder {
+        Default::default()
+    }
+
+    pub fn name(&mut self, name: &str) -> &mut Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    // e.g. "暴击率", "31.1%"
+    pub fn main_stat(&mut self, name: &str, value: &str) -> &mut Self {
+        self.main_stat_name = Some(name.to_string());
+        self.main_stat_value = Some(value.to_string());
+        self
+    }
+
+    /// e.g. "暴击率+3.9%", an empty string (no sub stat in that row) is ignored
+    pub fn sub_stat(&mut self, sub_stat: &str) -> &mut Self {
+        if !sub_stat.is_empty() {
+            self.sub_stats.push(sub_stat.to_string());
+        }
+        self
+    }
+
+    // e.g. "+20"
+    pub fn level(&mut self, level: &str) -> &mut Self {
+        self.level = Some(level.to_string());
+        self
+    }
+
+    pub fn rarity(&mut self, rarity: i32) -> &mut Self {
+        self.rarity = Some(rarity);
+        self
+    }
+
+    // e.g. "胡桃已装备"
+    pub fn equip(&mut self, equip: &str) -> &mut Self {
+        self.equip = Some(equip.to_string());
+        self
+    }
+
+    pub fn lock(&mut self, lock: bool) -> &mut Self {
+        self.lock = lock;
+        self
+    }
+
+    pub fn build(&self) -> Result<ScannedArtifact> {
+        let name = self.name.clone().ok_or_else(|| anyhow!("name is not set"))?;
+        let language = language_pack();
+        let (set_name, slot) = language.set_and_slot(&name).ok_or_else(|| anyhow!("unknown artifact: {}", name))?;
+
+        let main_stat_raw = format!(
+            "{}+{}",
+            self.main_stat_name.as_deref().ok_or_else(|| anyhow!("main stat is not set"))?,
+            self.main_stat_value.as_deref().unwrap_or_default()
+        );
+        let main_stat = language.stat(&main_stat_raw)
+            .ok_or_else(|| anyhow!("cannot parse main stat: {}", main_stat_raw))?;
+
+        if self.sub_stats.len() < 3 || self.sub_stats.len() > 4 {
+            return Err(anyhow!("expect 3 or 4 sub stats, got {}", self.sub_stats.len()));
+        }
+        let mut sub_stats = Vec::new();
+        for raw in self.sub_stats.iter() {
+            let stat = language.stat(raw).ok_or_else(|| anyhow!("cannot parse sub stat: {}", raw))?;
+            sub_stats.push(stat);
+        }
+
+        let level_raw = self.level.as_deref().ok_or_else(|| anyhow!("level is not set"))?;
+        let level = level_raw.chars()
+            .filter(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse::<i32>()
+            .map_err(|_| anyhow!("cannot parse level: {}", level_raw))?;
+        let rarity = self.rarity.ok_or_else(|| anyhow!("rarity is not set"))?;
+
+        let equip = self.equip.as_deref().and_then(|equip| language.equip(equip));
+
+        let mut issues = Vec::new();
+        if level > rarity * 4 {
+            issues.push(format!("level {} exceeds the max level of a {} star artifact", level, rarity));
+        }
+        if level >= 4 && sub_stats.len() < 4 {
+            issues.push(format!("a level {} artifact should have 4 sub stats", level));
+        }
+        for (i, stat) in sub_stats.iter().enumerate() {
+            if stat.name == main_stat.name {
+                issues.push(format!("sub stat {} duplicates the main stat", stat.name));
+            }
+            if sub_stats[..i].iter().any(|other| other.name == stat.name) {
+                issues.push(format!("sub stat {} appears more than once", stat.name));
+            }
+        }
+
+        Ok(ScannedArtifact {
+            name,
+            set_name,
+            slot,
+            level,
+            rarity,
+            main_stat,
+            sub_stats,
+            equip,
+            lock: self.lock,
+            issues,
+        })
+    }
+}
+
+impl ScannedArtifact {
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl From<&ScannedArtifact> for GenshinArtifact {
+    fn from(value: &ScannedArtifact) -> Self {
+        GenshinArtifact {
+            set_name: value.set_name.clone(),
+            slot: value.slot.clone(),
+            star: value.rarity,
+            lock: value.lock,
+            level: value.level,
+            main_stat: value.main_stat.clone(),
+            sub_stat_1: value.sub_stats.first().cloned(),
+            sub_stat_2: value.sub_stats.get(1).cloned(),
+            sub_stat_3: value.sub_stats.get(2).cloned(),
+            sub_stat_4: value.sub_stats.get(3).cloned(),
+            equip: value.equip.clone(),
+        }
+    }
+}
+
+struct StatSer<'a>(&'a ArtifactStat);
+
+impl<'a> Serialize for StatSer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("name", &self.0.name.to_string())?;
+        map.serialize_entry("value", &self.0.value)?;
+        map.end()
+    }
+}
+
+struct StatsSer<'a>(&'a [ArtifactStat]);
+
+impl<'a> Serialize for StatsSer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for stat in self.0 {
+            seq.serialize_element(&StatSer(stat))?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for ScannedArtifact {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut root = serializer.serialize_map(Some(10))?;
+        root.serialize_entry("name", &self.name)?;
+        root.serialize_entry("setName", &self.set_name.to_string())?;
+        root.serialize_entry("slot", &self.slot.to_string())?;
+        root.serialize_entry("level", &self.level)?;
+        root.serialize_entry("rarity", &self.rarity)?;
+        root.serialize_entry("mainStat", &StatSer(&self.main_stat))?;
+        root.serialize_entry("subStats", &StatsSer(&self.sub_stats))?;
+        root.serialize_entry("equip", &self.equip)?;
+        root.serialize_entry("lock", &self.lock)?;
+        root.serialize_entry("issues", &self.issues)?;
+        root.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::ArtifactStatName;
+
+    fn builder() -> ScannedArtifactBuilder {
+        let mut builder = ScannedArtifactBuilder::new();
+        builder.name("角斗士的留恋")
+            .main_stat("生命值", "4,780")
+            .sub_stat("暴击率+3.9%")
+            .sub_stat("暴击伤害+7.8%")
+            .sub_stat("攻击力+19")
+            .sub_stat("")
+            .level("+0")
+            .rarity(5)
+            .equip("胡桃已装备")
+            .lock(true);
+        builder
+    }
+
+    #[test]
+    fn build_from_recognized_strings() {
+        let artifact = builder().build().unwrap();
+        assert_eq!(artifact.set_name, ArtifactSetName::GladiatorFinale);
+        assert_eq!(artifact.slot, ArtifactSlot::Flower);
+        assert_eq!(artifact.main_stat, ArtifactStat { name: ArtifactStatName::Hp, value: 4780.0 });
+        assert_eq!(artifact.sub_stats.len(), 3);
+        assert!(artifact.is_consistent());
+    }
+
+    #[test]
+    fn serialize_scanned_artifact() {
+        let json = serde_json::to_value(builder().build().unwrap()).unwrap();
+        assert_eq!(json, serde_json::json!({
+            "name": "角斗士的留恋",
+            "setName": "GladiatorFinale",
+            "slot": "Flower",
+            "level": 0,
+            "rarity": 5,
+            "mainStat": { "name": "Hp", "value": 4780.0 },
+            "subStats": [
+                { "name": "Critical", "value": 0.039 },
+                { "name": "CriticalDamage", "value": 0.078 },
+                { "name": "Atk", "value": 19.0 },
+            ],
+            "equip": "胡桃",
+            "lock": true,
+            "issues": [],
+        }));
+    }
+}
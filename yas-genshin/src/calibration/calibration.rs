@@ -0,0 +1,80 @@
+use anyhow::Result;
+use log::{info, warn};
+
+use yas::calibration::{AnchorDetector, Calibration, Calibrator};
+use yas::capture::{Capturer, GenericCapturer};
+use yas::game_info::GameInfo;
+use yas::window_info::WindowInfoRepository;
+
+use super::GenshinCalibrationConfig;
+
+/// the keys inside the item panel of the repository, the panels of artifacts and weapons share the layout
+const PANEL_KEYS: [&str; 18] = [
+    "genshin_artifact_title_rect",
+    "genshin_artifact_main_stat_name_rect",
+    "genshin_artifact_main_stat_value_rect",
+    "genshin_artifact_sub_stat1_rect",
+    "genshin_artifact_sub_stat2_rect",
+    "genshin_artifact_sub_stat3_rect",
+    "genshin_artifact_sub_stat4_rect",
+    "genshin_artifact_level_rect",
+    "genshin_artifact_item_equip_rect",
+    "genshin_artifact_star_pos",
+    "genshin_artifact_lock_pos",
+    "genshin_artifact_star_rect",
+    "genshin_artifact_lock_rect",
+    "genshin_artifact_equip_icon_rect",
+    "genshin_weapon_level_rect",
+    "genshin_weapon_refinement_rect",
+    "genshin_weapon_lock_pos",
+    "genshin_repository_panel_rect",
+];
+
+/// the anchors of the repository: the borders of the item panel, and the item count text
+pub fn genshin_calibrator() -> Calibrator {
+    let mut calibrator = Calibrator::new();
+    calibrator
+        .anchor("genshin_repository_panel_rect", AnchorDetector::Edges, &PANEL_KEYS)
+        .anchor("genshin_artifact_item_count_rect", AnchorDetector::Text, &[]);
+    calibrator
+}
+
+/// inject the calibration of the current window into `repo`, from the cache if it's of the same window,
+/// otherwise calibrate with a screenshot of the repository and cache it. the static positions are kept if it fails
+pub fn calibrate_window_info(
+    repo: &mut WindowInfoRepository,
+    config: &GenshinCalibrationConfig,
+    game_info: &GameInfo,
+) -> Result<()> {
+    if !config.calibrate {
+        return Ok(());
+    }
+
+    let window_size = game_info.window.to_rect_usize().size();
+    let path = &config.calibration_file;
+    if !config.recalibrate {
+        if let Ok(calibration) = Calibration::load(path) {
+            if calibration.is_for(window_size, game_info.ui, game_info.platform) {
+                info!("使用已缓存的校准结果: {}", path);
+                calibration.inject_into(repo);
+                return Ok(());
+            }
+        }
+    }
+
+    info!("校准界面布局");
+    let capturer = GenericCapturer::new()?;
+    let screenshot = capturer.capture_rect(game_info.window)?;
+    let calibration = genshin_calibrator().calibrate(&screenshot, repo, game_info.ui, game_info.platform);
+    if calibration.is_empty() {
+        warn!("校准失败，使用默认位置");
+        return Ok(());
+    }
+
+    calibration.inject_into(repo);
+    match calibration.save(path) {
+        Ok(_) => info!("校准结果已保存到 {}", path),
+        Err(e) => warn!("保存校准结果失败：{}", e),
+    }
+    Ok(())
+}
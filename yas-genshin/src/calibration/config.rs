@@ -0,0 +1,13 @@
+#[derive(Clone, clap::Args)]
+pub struct GenshinCalibrationConfig {
+    /// locate the panel and the texts of the repository at scan start, instead of only using the static positions
+    #[arg(id = "calibrate", long = "calibrate", help = "扫描开始时自动校准界面布局")]
+    pub calibrate: bool,
+
+    /// calibrate again even if the cached calibration is of the current window
+    #[arg(id = "recalibrate", long = "recalibrate", help = "忽略已缓存的校准结果，重新校准", requires = "calibrate")]
+    pub recalibrate: bool,
+
+    #[arg(id = "calibration-file", long = "calibration-file", help = "校准结果的缓存文件", value_name = "CALIBRATION_FILE", default_value = "calibration.json")]
+    pub calibration_file: String,
+}
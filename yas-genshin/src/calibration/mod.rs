@@ -0,0 +1,5 @@
+pub use calibration::{calibrate_window_info, genshin_calibrator};
+pub use config::GenshinCalibrationConfig;
+
+mod calibration;
+mod config;
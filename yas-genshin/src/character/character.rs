@@ -0,0 +1,13 @@
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+pub struct GenshinCharacter {
+    /// the chinese name, as shown in game
+    pub name: String,
+    pub level: i32,
+    pub ascension: i32,
+    /// 0 to 6
+    pub constellation: i32,
+    /// the talent levels as shown in game, including the bonus of constellations
+    pub talent_auto: i32,
+    pub talent_skill: i32,
+    pub talent_burst: i32,
+}
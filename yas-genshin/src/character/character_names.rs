@@ -95,3 +95,189 @@ lazy_static! {
         "希诺宁",
     ]);
 }
+
+/// the names in the english client, with the names in `CHARACTER_NAMES`
+pub const CHARACTER_NAMES_EN: [(&str, &str); 89] = [
+    ("Diluc", "迪卢克"),
+    ("Klee", "可莉"),
+    ("Hu Tao", "胡桃"),
+    ("Yoimiya", "宵宫"),
+    ("Amber", "安柏"),
+    ("Bennett", "班尼特"),
+    ("Xiangling", "香菱"),
+    ("Xinyan", "辛焱"),
+    ("Yanfei", "烟绯"),
+    ("Thoma", "托马"),
+    ("Mona", "莫娜"),
+    ("Tartaglia", "达达利亚"),
+    ("Sangonomiya Kokomi", "珊瑚宫心海"),
+    ("Kamisato Ayato", "神里绫人"),
+    ("Yelan", "夜兰"),
+    ("Nilou", "妮露"),
+    ("Barbara", "芭芭拉"),
+    ("Xingqiu", "行秋"),
+    ("Candace", "坎蒂丝"),
+    ("Jean", "琴"),
+    ("Venti", "温迪"),
+    ("Xiao", "魈"),
+    ("Traveler", "旅行者"),
+    ("Kaedehara Kazuha", "枫原万叶"),
+    ("Wanderer", "流浪者"),
+    ("Sucrose", "砂糖"),
+    ("Sayu", "早柚"),
+    ("Shikanoin Heizou", "鹿野院平藏"),
+    ("Faruzan", "珐露珊"),
+    ("Keqing", "刻晴"),
+    ("Raiden Shogun", "雷电将军"),
+    ("Yae Miko", "八重神子"),
+    ("Cyno", "赛诺"),
+    ("Beidou", "北斗"),
+    ("Lisa", "丽莎"),
+    ("Razor", "雷泽"),
+    ("Fischl", "菲谢尔"),
+    ("Kujou Sara", "九条裟罗"),
+    ("Kuki Shinobu", "久岐忍"),
+    ("Dori", "多莉"),
+    ("Qiqi", "七七"),
+    ("Ganyu", "甘雨"),
+    ("Kamisato Ayaka", "神里绫华"),
+    ("Eula", "优菈"),
+    ("Aloy", "埃洛伊"),
+    ("Shenhe", "申鹤"),
+    ("Kaeya", "凯亚"),
+    ("Chongyun", "重云"),
+    ("Diona", "迪奥娜"),
+    ("Rosaria", "罗莎莉亚"),
+    ("Layla", "莱依拉"),
+    ("Zhongli", "钟离"),
+    ("Albedo", "阿贝多"),
+    ("Arataki Itto", "荒泷一斗"),
+    ("Noelle", "诺艾尔"),
+    ("Ningguang", "凝光"),
+    ("Yun Jin", "云堇"),
+    ("Gorou", "五郎"),
+    ("Tighnari", "提纳里"),
+    ("Nahida", "纳西妲"),
+    ("Collei", "柯莱"),
+    ("Baizhu", "白术"),
+    ("Kaveh", "卡维"),
+    ("Yaoyao", "瑶瑶"),
+    ("Alhaitham", "艾尔海森"),
+    ("Dehya", "迪希雅"),
+    ("Mika", "米卡"),
+    ("Lynette", "琳妮特"),
+    ("Lyney", "林尼"),
+    ("Freminet", "菲米尼"),
+    ("Furina", "芙宁娜"),
+    ("Neuvillette", "那维莱特"),
+    ("Navia", "娜维娅"),
+    ("Kirara", "绮良良"),
+    ("Wriothesley", "莱欧斯利"),
+    ("Charlotte", "夏洛蒂"),
+    ("Chevreuse", "夏沃蕾"),
+    ("Gaming", "嘉明"),
+    ("Xianyun", "闲云"),
+    ("Chiori", "千织"),
+    ("Arlecchino", "阿蕾奇诺"),
+    ("Sethos", "赛索斯"),
+    ("Clorinde", "克洛琳德"),
+    ("Sigewinne", "希格雯"),
+    ("Emilie", "艾梅丽埃"),
+    ("Kachina", "卡齐娜"),
+    ("Mualani", "玛拉妮"),
+    ("Kinich", "基尼奇"),
+    ("Xilonen", "希诺宁"),
+];
+
+/// the names in the japanese client, with the names in `CHARACTER_NAMES`
+pub const CHARACTER_NAMES_JA: [(&str, &str); 89] = [
+    ("ディルック", "迪卢克"),
+    ("クレー", "可莉"),
+    ("胡桃", "胡桃"),
+    ("宵宮", "宵宫"),
+    ("アンバー", "安柏"),
+    ("ベネット", "班尼特"),
+    ("香菱", "香菱"),
+    ("辛炎", "辛焱"),
+    ("煙緋", "烟绯"),
+    ("トーマ", "托马"),
+    ("モナ", "莫娜"),
+    ("タルタリヤ", "达达利亚"),
+    ("珊瑚宮心海", "珊瑚宫心海"),
+    ("神里綾人", "神里绫人"),
+    ("夜蘭", "夜兰"),
+    ("ニィロウ", "妮露"),
+    ("バーバラ", "芭芭拉"),
+    ("行秋", "行秋"),
+    ("キャンディス", "坎蒂丝"),
+    ("ジン", "琴"),
+    ("ウェンティ", "温迪"),
+    ("魈", "魈"),
+    ("旅人", "旅行者"),
+    ("楓原万葉", "枫原万叶"),
+    ("放浪者", "流浪者"),
+    ("スクロース", "砂糖"),
+    ("早柚", "早柚"),
+    ("鹿野院平蔵", "鹿野院平藏"),
+    ("ファルザン", "珐露珊"),
+    ("刻晴", "刻晴"),
+    ("雷電将軍", "雷电将军"),
+    ("八重神子", "八重神子"),
+    ("セノ", "赛诺"),
+    ("北斗", "北斗"),
+    ("リサ", "丽莎"),
+    ("レザー", "雷泽"),
+    ("フィッシュル", "菲谢尔"),
+    ("九条裟羅", "九条裟罗"),
+    ("久岐忍", "久岐忍"),
+    ("ドリー", "多莉"),
+    ("七七", "七七"),
+    ("甘雨", "甘雨"),
+    ("神里綾華", "神里绫华"),
+    ("エウルア", "优菈"),
+    ("アーロイ", "埃洛伊"),
+    ("申鶴", "申鹤"),
+    ("ガイア", "凯亚"),
+    ("重雲", "重云"),
+    ("ディオナ", "迪奥娜"),
+    ("ロサリア", "罗莎莉亚"),
+    ("レイラ", "莱依拉"),
+    ("鍾離", "钟离"),
+    ("アルベド", "阿贝多"),
+    ("荒瀧一斗", "荒泷一斗"),
+    ("ノエル", "诺艾尔"),
+    ("凝光", "凝光"),
+    ("雲菫", "云堇"),
+    ("ゴロー", "五郎"),
+    ("ティナリ", "提纳里"),
+    ("ナヒーダ", "纳西妲"),
+    ("コレイ", "柯莱"),
+    ("白朮", "白术"),
+    ("カーヴェ", "卡维"),
+    ("ヨォーヨ", "瑶瑶"),
+    ("アルハイゼン", "艾尔海森"),
+    ("ディシア", "迪希雅"),
+    ("ミカ", "米卡"),
+    ("リネット", "琳妮特"),
+    ("リネ", "林尼"),
+    ("フレミネ", "菲米尼"),
+    ("フリーナ", "芙宁娜"),
+    ("ヌヴィレット", "那维莱特"),
+    ("ナヴィア", "娜维娅"),
+    ("綺良々", "绮良良"),
+    ("リオセスリ", "莱欧斯利"),
+    ("シャルロット", "夏洛蒂"),
+    ("シュヴルーズ", "夏沃蕾"),
+    ("嘉明", "嘉明"),
+    ("閑雲", "闲云"),
+    ("千織", "千织"),
+    ("アルレッキーノ", "阿蕾奇诺"),
+    ("セトス", "赛索斯"),
+    ("クロリンデ", "克洛琳德"),
+    ("シグウィン", "希格雯"),
+    ("エミリエ", "艾梅丽埃"),
+    ("カチーナ", "卡齐娜"),
+    ("ムアラニ", "玛拉妮"),
+    ("キィニチ", "基尼奇"),
+    ("シロネン", "希诺宁"),
+];
@@ -1,4 +1,5 @@
-pub use character_names::CHARACTER_NAMES;
+pub use character::GenshinCharacter;
+pub use character_names::{CHARACTER_NAMES, CHARACTER_NAMES_EN, CHARACTER_NAMES_JA};
 
+mod character;
 mod character_names;
-
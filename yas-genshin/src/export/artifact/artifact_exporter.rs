@@ -0,0 +1,300 @@
+use anyhow::{anyhow, Result};
+
+use crate::artifact::GenshinArtifact;
+use crate::character::GenshinCharacter;
+use crate::weapon::GenshinWeapon;
+
+use super::csv::GenshinArtifactCSVFormat;
+use super::good::GOODFormat;
+use super::hutao::HutaoFormat;
+use super::mingyu_lab::MingyuLabFormat;
+use super::mona_uranai::MonaFormat;
+
+/// the `--format` which exports every registered format
+pub const ALL_FORMATS: &str = "all";
+
+/// an export format of artifacts, register custom ones to an `ArtifactExporterRegistry`
+pub trait ArtifactExporter: Send + Sync {
+    /// the unique name of the format, e.g. "good"
+    fn name(&self) -> &str;
+
+    fn extension(&self) -> &str;
+
+    /// shown in the export summary
+    fn description(&self) -> &str;
+
+    /// the name of the exported file in the output dir
+    fn file_name(&self) -> String {
+        format!("{}.{}", self.name(), self.extension())
+    }
+
+    fn export(&self, artifacts: &[GenshinArtifact]) -> Result<Vec<u8>>;
+}
+
+struct MonaExporter;
+
+impl ArtifactExporter for MonaExporter {
+    fn name(&self) -> &str {
+        "mona"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn description(&self) -> &str {
+        "莫娜圣遗物格式"
+    }
+
+    fn export(&self, artifacts: &[GenshinArtifact]) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&MonaFormat::new(artifacts))?)
+    }
+}
+
+struct MingyuLabExporter;
+
+impl ArtifactExporter for MingyuLabExporter {
+    fn name(&self) -> &str {
+        "mingyulab"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn description(&self) -> &str {
+        "原魔计算器圣遗物格式"
+    }
+
+    fn export(&self, artifacts: &[GenshinArtifact]) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&MingyuLabFormat::new(artifacts))?)
+    }
+}
+
+/// GOOD, which may also hold characters and weapons
+#[derive(Default)]
+pub struct GOODExporter {
+    characters: Vec<GenshinCharacter>,
+    weapons: Vec<GenshinWeapon>,
+}
+
+impl GOODExporter {
+    pub fn with_characters(mut self, characters: Vec<GenshinCharacter>) -> Self {
+        self.characters = characters;
+        self
+    }
+
+    pub fn with_weapons(mut self, weapons: Vec<GenshinWeapon>) -> Self {
+        self.weapons = weapons;
+        self
+    }
+}
+
+impl ArtifactExporter for GOODExporter {
+    fn name(&self) -> &str {
+        "good"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn description(&self) -> &str {
+        "GOOD圣遗物格式"
+    }
+
+    fn export(&self, artifacts: &[GenshinArtifact]) -> Result<Vec<u8>> {
+        let mut format = GOODFormat::new(artifacts);
+        if !self.characters.is_empty() {
+            format = format.with_characters(&self.characters);
+        }
+        if !self.weapons.is_empty() {
+            format = format.with_weapons(&self.weapons);
+        }
+        Ok(serde_json::to_vec(&format)?)
+    }
+}
+
+struct CSVExporter;
+
+impl ArtifactExporter for CSVExporter {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn extension(&self) -> &str {
+        "csv"
+    }
+
+    fn description(&self) -> &str {
+        "CSV格式圣遗物"
+    }
+
+    fn file_name(&self) -> String {
+        String::from("artifacts.csv")
+    }
+
+    fn export(&self, artifacts: &[GenshinArtifact]) -> Result<Vec<u8>> {
+        // the BOM makes Excel read the file as UTF-8 rather than in the encoding of the locale
+        let contents = String::from("\u{feff}") + &GenshinArtifactCSVFormat::new(artifacts).to_csv_string();
+        Ok(contents.into_bytes())
+    }
+}
+
+struct HutaoExporter;
+
+impl ArtifactExporter for HutaoExporter {
+    fn name(&self) -> &str {
+        "hutao"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn description(&self) -> &str {
+        "胡桃工具箱圣遗物格式"
+    }
+
+    fn export(&self, artifacts: &[GenshinArtifact]) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&HutaoFormat::new(artifacts))?)
+    }
+}
+
+/// the export formats by name, `--format all` exports all of them
+pub struct ArtifactExporterRegistry {
+    exporters: Vec<Box<dyn ArtifactExporter>>,
+}
+
+impl ArtifactExporterRegistry {
+    /// a registry without any format
+    pub fn empty() -> Self {
+        ArtifactExporterRegistry {
+            exporters: Vec::new(),
+        }
+    }
+
+    /// a registry of the built-in formats: mona, mingyulab, good, csv and hutao
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(MonaExporter));
+        registry.register(Box::new(MingyuLabExporter));
+        registry.register(Box::new(GOODExporter::default()));
+        registry.register(Box::new(CSVExporter));
+        registry.register(Box::new(HutaoExporter));
+        registry
+    }
+
+    /// a format of the same name is replaced
+    pub fn register(&mut self, exporter: Box<dyn ArtifactExporter>) -> &mut Self {
+        self.exporters.retain(|e| e.name() != exporter.name());
+        self.exporters.push(exporter);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn ArtifactExporter> {
+        self.exporters.iter().find(|e| e.name() == name).map(|e| e.as_ref())
+    }
+
+    /// the formats selected by `--format`: the one of the name, or all of them for "all"
+    pub fn select(&self, name: &str) -> Result<Vec<&dyn ArtifactExporter>> {
+        if name == ALL_FORMATS {
+            return Ok(self.iter().collect());
+        }
+
+        match self.get(name) {
+            Some(exporter) => Ok(vec![exporter]),
+            None => {
+                let names: Vec<&str> = self.iter().map(|e| e.name()).collect();
+                Err(anyhow!("未知的导出格式：{}，可用格式：{}, {}", name, names.join(", "), ALL_FORMATS))
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ArtifactExporter> {
+        self.exporters.iter().map(|e| e.as_ref())
+    }
+}
+
+impl Default for ArtifactExporterRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName};
+
+    struct CountExporter;
+
+    impl ArtifactExporter for CountExporter {
+        fn name(&self) -> &str {
+            "count"
+        }
+
+        fn extension(&self) -> &str {
+            "txt"
+        }
+
+        fn description(&self) -> &str {
+            "count"
+        }
+
+        fn export(&self, artifacts: &[GenshinArtifact]) -> Result<Vec<u8>> {
+            Ok(artifacts.len().to_string().into_bytes())
+        }
+    }
+
+    fn names(exporters: &[&dyn ArtifactExporter]) -> Vec<String> {
+        exporters.iter().map(|e| e.name().to_string()).collect()
+    }
+
+    #[test]
+    fn select_formats_by_name() {
+        let mut registry = ArtifactExporterRegistry::new();
+        registry.register(Box::new(CountExporter));
+
+        assert_eq!(names(&registry.select("hutao").unwrap()), vec!["hutao"]);
+        assert_eq!(names(&registry.select("count").unwrap()), vec!["count"]);
+        assert_eq!(
+            names(&registry.select(ALL_FORMATS).unwrap()),
+            vec!["mona", "mingyulab", "good", "csv", "hutao", "count"]
+        );
+        assert!(registry.select("unknown").is_err());
+
+        let file_name = registry.get("count").unwrap().file_name();
+        assert_eq!(file_name, "count.txt");
+    }
+
+    #[test]
+    fn register_replaces_a_format_of_the_same_name() {
+        let mut registry = ArtifactExporterRegistry::new();
+        registry.register(Box::new(GOODExporter::default()));
+        assert_eq!(registry.iter().filter(|e| e.name() == "good").count(), 1);
+        assert_eq!(registry.iter().count(), 5);
+    }
+
+    #[test]
+    fn hutao_exporter_writes_the_list() {
+        let artifact = GenshinArtifact {
+            set_name: ArtifactSetName::GladiatorFinale,
+            slot: ArtifactSlot::Flower,
+            star: 5,
+            lock: true,
+            level: 20,
+            main_stat: ArtifactStat { name: ArtifactStatName::Hp, value: 4780.0 },
+            sub_stat_1: Some(ArtifactStat { name: ArtifactStatName::Critical, value: 0.039 }),
+            sub_stat_2: None,
+            sub_stat_3: None,
+            sub_stat_4: None,
+            equip: None,
+        };
+
+        let contents = ArtifactExporterRegistry::new().get("hutao").unwrap().export(&[artifact]).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&contents).unwrap();
+        assert_eq!(json["list"][0]["rankLevel"], 5);
+        assert_eq!(json["list"][0]["appendProps"], serde_json::json!([{ "propType": 20, "value": 0.039 }]));
+    }
+}
@@ -1,11 +1,13 @@
-use crate::export::artifact::GenshinArtifactExportFormat;
-
 #[derive(clap::Args)]
 pub struct ExportArtifactConfig {
-    #[arg(id = "format", long = "format", short = 'f', default_value_t = GenshinArtifactExportFormat::Mona, help = "输出格式")]
-    #[arg(value_enum)]
-    pub format: GenshinArtifactExportFormat,
+    /// the name of a format of `ArtifactExporterRegistry`, or "all"
+    #[arg(id = "format", long = "format", short = 'f', default_value_t = String::from("mona"), help = "输出格式：mona、mingyulab、good、csv、hutao，或 all 导出所有格式")]
+    pub format: String,
 
     #[arg(id = "output-dir", long = "output-dir", short, default_value_t = String::from("."), help = "输出目录")]
     pub output_dir: String,
+
+    /// skip the artifacts already in this GOOD or mona export, and merge the new ones into it instead of exporting
+    #[arg(id = "incremental", long = "incremental", value_name = "PATH", help = "增量扫描：跳过此前导出的 GOOD 或莫娜格式文件中已有的圣遗物，并将新圣遗物合并到该文件")]
+    pub incremental: Option<String>,
 }
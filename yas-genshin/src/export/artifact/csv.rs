@@ -5,47 +5,39 @@ pub struct GenshinArtifactCSVFormat<'a> {
     artifacts: &'a [GenshinArtifact],
 }
 
-/// CSV format:
-/// set name, slot, star, level, main stat name, main stat value, [sub state name, sub state value]*4, equip
-fn single_artifact_to_string(artifact: &GenshinArtifact) -> String {
-    let mut s = String::new();
-    s = s + &artifact.set_name.to_string();
-    s = s + "," + &artifact.slot.to_string();
-    s = s + "," + &format!("{}", artifact.star);
-    s = s + "," + &format!("{}", artifact.level);
-    s = s + "," + &artifact.main_stat.name.to_string();
-    s = s + "," + &format!("{}", artifact.main_stat.value);
-    if let Some(sub) = &artifact.sub_stat_1 {
-        s = s + "," + &sub.name.to_string();
-        s = s + "," + &format!("{}", sub.value);
-    } else {
-        s += ",,";
-    }
-    if let Some(sub) = &artifact.sub_stat_2 {
-        s = s + "," + &sub.name.to_string();
-        s = s + "," + &format!("{}", sub.value);
-    } else {
-        s += ",,";
-    }
-    if let Some(sub) = &artifact.sub_stat_3 {
-        s = s + "," + &sub.name.to_string();
-        s = s + "," + &format!("{}", sub.value);
+/// quote a field if it has a comma, a quote or a line break in it
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
     } else {
-        s += ",,";
+        field.to_string()
     }
-    if let Some(sub) = &artifact.sub_stat_4 {
-        s = s + "," + &sub.name.to_string();
-        s = s + "," + &format!("{}", sub.value);
-    } else {
-        s += ",,";
-    }
-    if let Some(e) = &artifact.equip {
-        s = s + "," + e;
-    } else {
-        s += ","
+}
+
+/// CSV format:
+/// set name, slot, star, level, main stat name, main stat value, [sub state name, sub state value]*4, lock, equip
+fn single_artifact_to_string(artifact: &GenshinArtifact) -> String {
+    let mut fields = vec![
+        artifact.set_name.to_string(),
+        artifact.slot.to_string(),
+        format!("{}", artifact.star),
+        format!("{}", artifact.level),
+        artifact.main_stat.name.to_string(),
+        format!("{}", artifact.main_stat.value),
+    ];
+    for sub in [&artifact.sub_stat_1, &artifact.sub_stat_2, &artifact.sub_stat_3, &artifact.sub_stat_4] {
+        match sub {
+            Some(sub) => {
+                fields.push(sub.name.to_string());
+                fields.push(format!("{}", sub.value));
+            },
+            None => fields.extend([String::new(), String::new()]),
+        }
     }
+    fields.push(String::from(if artifact.lock { "是" } else { "否" }));
+    fields.push(artifact.equip.clone().unwrap_or_default());
 
-    s
+    fields.iter().map(|field| escape_field(field)).collect::<Vec<_>>().join(",")
 }
 
 impl<'a> GenshinArtifactCSVFormat<'a> {
@@ -56,7 +48,7 @@ impl<'a> GenshinArtifactCSVFormat<'a> {
     }
 
     pub fn to_csv_string(&self) -> String {
-        let header = "套装,部位,星级,等级,主词条名,主词条值,副词条名1,副词条值1,副词条名2,副词条值2,副词条名3,副词条值3,副词条名4,副词条值4,装备";
+        let header = "套装,部位,星级,等级,主词条名,主词条值,副词条名1,副词条值1,副词条名2,副词条值2,副词条名3,副词条值3,副词条名4,副词条值4,锁定,装备";
         let mut result = String::from(header) + "\n";
 
         for artifact in self.artifacts.iter() {
@@ -74,3 +66,47 @@ impl<'a> Serialize for GenshinArtifactCSVFormat<'a> {
         serializer.serialize_str(&s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName};
+
+    fn artifact() -> GenshinArtifact {
+        GenshinArtifact {
+            set_name: ArtifactSetName::GladiatorFinale,
+            slot: ArtifactSlot::Flower,
+            star: 5,
+            lock: true,
+            level: 20,
+            main_stat: ArtifactStat { name: ArtifactStatName::Hp, value: 4780.0 },
+            sub_stat_1: Some(ArtifactStat { name: ArtifactStatName::Critical, value: 0.039 }),
+            sub_stat_2: Some(ArtifactStat { name: ArtifactStatName::Atk, value: 19.0 }),
+            sub_stat_3: None,
+            sub_stat_4: None,
+            equip: None,
+        }
+    }
+
+    #[test]
+    fn one_row_per_artifact() {
+        let csv = GenshinArtifactCSVFormat::new(&[artifact()]).to_csv_string();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].split(',').count(), 16);
+        assert_eq!(lines[1].split(',').count(), 16);
+        assert!(lines[1].ends_with(",是,"));
+    }
+
+    #[test]
+    fn every_field_is_escaped() {
+        assert_eq!(escape_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_field("plain"), "plain");
+
+        let mut equipped = artifact();
+        equipped.equip = Some(String::from("a,\"b\""));
+        let line = single_artifact_to_string(&equipped);
+        assert!(line.ends_with(",是,\"a,\"\"b\"\"\""));
+    }
+}
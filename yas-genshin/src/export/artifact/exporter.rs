@@ -2,21 +2,19 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::FromArgMatches;
+use log::error;
 
 use yas::export::{AssetEmitter, ExportAssets};
 
 use crate::artifact::GenshinArtifact;
-use crate::export::artifact::{ExportArtifactConfig, GenshinArtifactExportFormat};
-use crate::export::artifact::csv::GenshinArtifactCSVFormat;
-
-use super::good::GOODFormat;
-use super::mingyu_lab::MingyuLabFormat;
-use super::mona_uranai::MonaFormat;
+use crate::export::artifact::{ArtifactExporterRegistry, ExportArtifactConfig};
 
 pub struct GenshinArtifactExporter<'a> {
-    pub format: GenshinArtifactExportFormat,
+    /// the name of the format in `registry`, or `ALL_FORMATS`
+    pub format: String,
     pub results: Option<&'a [GenshinArtifact]>,
     pub output_dir: PathBuf,
+    pub registry: ArtifactExporterRegistry,
 }
 
 impl <'a> GenshinArtifactExporter<'a> {
@@ -25,9 +23,17 @@ impl <'a> GenshinArtifactExporter<'a> {
         Ok(Self {
             format: config.format,
             results: Some(results),
-            output_dir: PathBuf::from(&config.output_dir)
+            output_dir: PathBuf::from(&config.output_dir),
+            registry: ArtifactExporterRegistry::new(),
         })
     }
+
+    /// export with the formats of `registry` instead of the built-in ones,
+    /// `ALL_FORMATS` exports every format of it
+    pub fn with_registry(mut self, registry: ArtifactExporterRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
 }
 
 impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
@@ -38,101 +44,28 @@ impl<'a> AssetEmitter for GenshinArtifactExporter<'a> {
 
         let results = self.results.unwrap();
 
-        match self.format {
-            GenshinArtifactExportFormat::Mona => {
-                let path = self.output_dir.join("mona.json");
-                let value = MonaFormat::new(results);
-                let contents = serde_json::to_string(&value).unwrap();
-
-                export_assets.add_asset(
-                    Some(String::from("artifacts")),
-                    path,
-                    contents.into_bytes(),
-                    Some(String::from("莫娜圣遗物格式")));
-            },
-            GenshinArtifactExportFormat::MingyuLab => {
-                let path = self.output_dir.join("mingyulab.json");
-                let value = MingyuLabFormat::new(results);
-                let contents = serde_json::to_string(&value).unwrap();
-
-                export_assets.add_asset(
-                    Some(String::from("artifacts")),
-                    path,
-                    contents.into_bytes(),
-                    Some(String::from("原魔计算器圣遗物格式")));
-            },
-            GenshinArtifactExportFormat::Good => {
-                let path = self.output_dir.join("good.json");
-                let value = GOODFormat::new(results);
-                let contents = serde_json::to_string(&value).unwrap();
-
-                export_assets.add_asset(
-                    Some(String::from("artifacts")),
-                    path,
-                    contents.into_bytes(),
-                    Some(String::from("GOOD圣遗物格式")));
-            },
-            GenshinArtifactExportFormat::CSV => {
-                let path = self.output_dir.join("artifacts.csv");
-                let value = GenshinArtifactCSVFormat::new(results);
-                let contents = value.to_csv_string();
-                export_assets.add_asset(
-                    Some(String::from("artifacts csv format")),
-                    path,
-                    contents.into_bytes(),
-                    Some(String::from("CSV格式圣遗物"))
-                );
-            },
-            GenshinArtifactExportFormat::All => {
-                // mona
-                {
-                    let path = self.output_dir.join("mona.json");
-                    let value = MonaFormat::new(results);
-                    let contents = serde_json::to_string(&value).unwrap();
-
-                    export_assets.add_asset(
-                        Some(String::from("mona")),
-                        path,
-                        contents.into_bytes(),
-                        Some(String::from("莫娜圣遗物格式")));
-                }
-                // mingyulab
-                {
-                    let path = self.output_dir.join("mingyulab.json");
-                    let value = MingyuLabFormat::new(results);
-                    let contents = serde_json::to_string(&value).unwrap();
+        let exporters = match self.registry.select(&self.format) {
+            Ok(exporters) => exporters,
+            Err(e) => {
+                error!("{}", e);
+                return;
+            }
+        };
 
-                    export_assets.add_asset(
-                        Some(String::from("mingyulab")),
-                        path,
-                        contents.into_bytes(),
-                        Some(String::from("原魔计算器圣遗物格式")));
+        for exporter in exporters {
+            let contents = match exporter.export(results) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    error!("导出{}失败：{}", exporter.description(), e);
+                    continue;
                 }
-                // good
-                {
-                    let path = self.output_dir.join("good.json");
-                    let value = GOODFormat::new(results);
-                    let contents = serde_json::to_string(&value).unwrap();
+            };
 
-                    export_assets.add_asset(
-                        Some(String::from("GOOD")),
-                        path,
-                        contents.into_bytes(),
-                        Some(String::from("GOOD圣遗物格式")));
-                }
-                // csv
-                {
-                    let path = self.output_dir.join("artifacts.csv");
-                    let value = GenshinArtifactCSVFormat::new(results);
-                    let contents = value.to_csv_string();
-                    export_assets.add_asset(
-                        Some(String::from("csv")),
-                        path,
-                        contents.into_bytes(),
-                        Some(String::from("CSV格式圣遗物"))
-                    );
-                }
-            }
-        };
+            export_assets.add_asset(
+                Some(exporter.name().to_string()),
+                self.output_dir.join(exporter.file_name()),
+                contents,
+                Some(exporter.description().to_string()));
+        }
     }
 }
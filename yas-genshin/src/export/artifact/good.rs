@@ -1,9 +1,12 @@
 use serde::ser::{SerializeMap, Serializer};
 use serde::Serialize;
+use log::warn;
 
 use crate::artifact::{
     ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName, GenshinArtifact,
 };
+use crate::character::GenshinCharacter;
+use crate::weapon::{weapon_name_to_good, GenshinWeapon};
 
 struct GOODArtifact<'a> {
     artifact: &'a GenshinArtifact,
@@ -57,7 +60,7 @@ struct GOODStat<'a> {
 }
 
 impl<'a> GOODStat<'a> {
-    fn new(stat: &ArtifactStat) -> GOODStat {
+    fn new(stat: &ArtifactStat) -> GOODStat<'_> {
         GOODStat {
             key: stat.name.to_good(),
             value: match stat.name {
@@ -260,16 +263,123 @@ fn equip_from_zh_cn(equip: Option<&str>) -> &'static str {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Talent {
+    Auto,
+    Skill,
+    Burst,
+}
+
+/// the talents raised by 3 levels at the 3rd and the 5th constellation, by the GOOD key
+/// most characters raise the skill first, so does the traveler here whatever the element
+fn constellation_talents(key: &str) -> (Talent, Talent) {
+    match key {
+        "Amber" | "Barbara" | "Charlotte" | "Chongyun" | "Cyno" | "Diona" | "Dori" | "Eula" | "Furina" | "Ganyu"
+        | "Jean" | "KamisatoAyaka" | "Keqing" | "KujouSara" | "Lynette" | "Mika" | "Mona" | "Navia" | "Nilou"
+        | "Ningguang" | "Qiqi" | "RaidenShogun" | "Razor" | "SangonomiyaKokomi" | "Tighnari" | "Venti"
+        | "Xiangling" | "Xingqiu" | "Yelan" | "YunJin" => (Talent::Burst, Talent::Skill),
+        "Arlecchino" | "Lyney" | "Neuvillette" | "Sethos" | "Wriothesley" => (Talent::Auto, Talent::Burst),
+        _ => (Talent::Skill, Talent::Burst),
+    }
+}
+
+/// the level of a talent without the bonus of constellations (and of the passive of Tartaglia), as GOOD expects
+fn base_talent_level(key: &str, constellation: i32, talent: Talent, level: i32) -> i32 {
+    let (c3, c5) = constellation_talents(key);
+    let mut bonus = 0;
+    if constellation >= 3 && c3 == talent {
+        bonus += 3;
+    }
+    if constellation >= 5 && c5 == talent {
+        bonus += 3;
+    }
+    if key == "Tartaglia" && talent == Talent::Auto {
+        bonus += 1;
+    }
+
+    (level - bonus).max(1)
+}
+
+#[derive(Serialize)]
+struct GOODTalent {
+    auto: i32,
+    skill: i32,
+    burst: i32,
+}
+
+#[derive(Serialize)]
+struct GOODCharacter<'a> {
+    key: &'a str,
+    level: i32,
+    constellation: i32,
+    ascension: i32,
+    talent: GOODTalent,
+}
+
+impl<'a> GOODCharacter<'a> {
+    fn new(character: &GenshinCharacter) -> Option<GOODCharacter<'_>> {
+        let key = equip_from_zh_cn(Some(&character.name));
+        if key.is_empty() {
+            warn!("未知角色 {}，不导出到 GOOD", character.name);
+            return None;
+        }
+
+        Some(GOODCharacter {
+            key,
+            level: character.level,
+            constellation: character.constellation,
+            ascension: character.ascension,
+            talent: GOODTalent {
+                auto: base_talent_level(key, character.constellation, Talent::Auto, character.talent_auto),
+                skill: base_talent_level(key, character.constellation, Talent::Skill, character.talent_skill),
+                burst: base_talent_level(key, character.constellation, Talent::Burst, character.talent_burst),
+            },
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct GOODWeapon<'a> {
+    key: &'a str,
+    level: i32,
+    ascension: i32,
+    refinement: i32,
+    location: &'a str,
+    lock: bool,
+}
+
+impl<'a> GOODWeapon<'a> {
+    fn new(weapon: &GenshinWeapon) -> Option<GOODWeapon<'_>> {
+        let Some(key) = weapon_name_to_good(&weapon.name) else {
+            warn!("未知武器 {}，不导出到 GOOD", weapon.name);
+            return None;
+        };
+
+        Some(GOODWeapon {
+            key,
+            level: weapon.level,
+            ascension: weapon.ascension,
+            refinement: weapon.refinement,
+            location: equip_from_zh_cn(weapon.equip.as_deref()),
+            lock: weapon.lock,
+        })
+    }
+}
+
 #[derive(Serialize)]
 pub struct GOODFormat<'a> {
     format: &'a str,
     version: u32,
     source: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    characters: Option<Vec<GOODCharacter<'a>>>,
     artifacts: Vec<GOODArtifact<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weapons: Option<Vec<GOODWeapon<'a>>>,
 }
 
 impl<'a> GOODFormat<'a> {
-    pub fn new(results: &'a [GenshinArtifact]) -> GOODFormat {
+    pub fn new(results: &'a [GenshinArtifact]) -> GOODFormat<'a> {
         let artifacts: Vec<GOODArtifact<'a>> = results
             .iter()
             .map(|artifact| GOODArtifact { artifact })
@@ -278,7 +388,65 @@ impl<'a> GOODFormat<'a> {
             format: "GOOD",
             version: 1,
             source: "yas",
+            characters: None,
             artifacts,
+            weapons: None,
+        }
+    }
+
+    /// the characters not known to GOOD are left out
+    pub fn with_characters(mut self, characters: &'a [GenshinCharacter]) -> Self {
+        self.characters = Some(characters.iter().flat_map(GOODCharacter::new).collect());
+        self
+    }
+
+    /// the weapons not known to GOOD are left out
+    pub fn with_weapons(mut self, weapons: &'a [GenshinWeapon]) -> Self {
+        self.weapons = Some(weapons.iter().flat_map(GOODWeapon::new).collect());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn character(name: &str, constellation: i32, talents: (i32, i32, i32)) -> GenshinCharacter {
+        GenshinCharacter {
+            name: name.to_string(),
+            level: 90,
+            ascension: 6,
+            constellation,
+            talent_auto: talents.0,
+            talent_skill: talents.1,
+            talent_burst: talents.2,
         }
     }
+
+    fn talents(character: &GenshinCharacter) -> (i32, i32, i32) {
+        let talent = GOODCharacter::new(character).unwrap().talent;
+        (talent.auto, talent.skill, talent.burst)
+    }
+
+    #[test]
+    fn constellation_bonus_is_subtracted() {
+        // Hu Tao raises the skill at C3 and the burst at C5
+        assert_eq!(talents(&character("胡桃", 2, (10, 10, 10))), (10, 10, 10));
+        assert_eq!(talents(&character("胡桃", 3, (10, 13, 10))), (10, 10, 10));
+        assert_eq!(talents(&character("胡桃", 6, (10, 13, 13))), (10, 10, 10));
+        // Ganyu raises the burst first
+        assert_eq!(talents(&character("甘雨", 3, (10, 9, 12))), (10, 9, 9));
+        // Neuvillette raises the normal attack
+        assert_eq!(talents(&character("那维莱特", 5, (13, 10, 13))), (10, 10, 10));
+    }
+
+    #[test]
+    fn tartaglia_passive_is_subtracted() {
+        assert_eq!(talents(&character("达达利亚", 0, (11, 10, 10))), (10, 10, 10));
+    }
+
+    #[test]
+    fn base_level_is_at_least_one() {
+        assert_eq!(base_talent_level("HuTao", 3, Talent::Skill, 2), 1);
+    }
 }
@@ -0,0 +1,202 @@
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::artifact::{ArtifactSlot, ArtifactStat, ArtifactStatName, GenshinArtifact, ScannedArtifact};
+
+impl ArtifactStatName {
+    /// the `FightPropType` id used by HuTao
+    pub fn to_hutao(&self) -> i32 {
+        match self {
+            ArtifactStatName::Hp => 2,
+            ArtifactStatName::HpPercentage => 3,
+            ArtifactStatName::Atk => 5,
+            ArtifactStatName::AtkPercentage => 6,
+            ArtifactStatName::Def => 8,
+            ArtifactStatName::DefPercentage => 9,
+            ArtifactStatName::Critical => 20,
+            ArtifactStatName::CriticalDamage => 22,
+            ArtifactStatName::Recharge => 23,
+            ArtifactStatName::HealingBonus => 26,
+            ArtifactStatName::ElementalMastery => 28,
+            ArtifactStatName::PhysicalBonus => 30,
+            ArtifactStatName::PyroBonus => 40,
+            ArtifactStatName::ElectroBonus => 41,
+            ArtifactStatName::HydroBonus => 42,
+            ArtifactStatName::DendroBonus => 43,
+            ArtifactStatName::AnemoBonus => 44,
+            ArtifactStatName::GeoBonus => 45,
+            ArtifactStatName::CryoBonus => 46,
+        }
+    }
+}
+
+impl ArtifactSlot {
+    /// the `EquipType` id used by HuTao
+    pub fn to_hutao(&self) -> i32 {
+        match self {
+            ArtifactSlot::Flower => 1,
+            ArtifactSlot::Feather => 2,
+            ArtifactSlot::Sand => 3,
+            ArtifactSlot::Goblet => 4,
+            ArtifactSlot::Head => 5,
+        }
+    }
+}
+
+struct HutaoStat<'a>(&'a ArtifactStat);
+
+impl<'a> Serialize for HutaoStat<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("propType", &self.0.name.to_hutao())?;
+        map.serialize_entry("value", &self.0.value)?;
+        map.end()
+    }
+}
+
+struct HutaoStats<'a>(Vec<&'a ArtifactStat>);
+
+impl<'a> Serialize for HutaoStats<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for stat in self.0.iter() {
+            seq.serialize_element(&HutaoStat(stat))?;
+        }
+        seq.end()
+    }
+}
+
+struct HutaoArtifact<'a>(&'a GenshinArtifact);
+
+impl<'a> Serialize for HutaoArtifact<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let artifact = self.0;
+
+        let mut root = serializer.serialize_map(Some(8))?;
+        root.serialize_entry("setKey", &artifact.set_name.to_string())?;
+        root.serialize_entry("equipType", &artifact.slot.to_hutao())?;
+        root.serialize_entry("rankLevel", &artifact.star)?;
+        root.serialize_entry("level", &artifact.level)?;
+        root.serialize_entry("mainProp", &HutaoStat(&artifact.main_stat))?;
+        let sub_stats = [&artifact.sub_stat_1, &artifact.sub_stat_2, &artifact.sub_stat_3, &artifact.sub_stat_4]
+            .into_iter()
+            .flatten()
+            .collect();
+        root.serialize_entry("appendProps", &HutaoStats(sub_stats))?;
+        root.serialize_entry("location", artifact.equip.as_deref().unwrap_or(""))?;
+        root.serialize_entry("lock", &artifact.lock)?;
+        root.end()
+    }
+}
+
+pub struct HutaoFormat<'a> {
+    artifacts: &'a [GenshinArtifact],
+}
+
+impl<'a> HutaoFormat<'a> {
+    pub fn new(artifacts: &'a [GenshinArtifact]) -> HutaoFormat<'a> {
+        HutaoFormat { artifacts }
+    }
+}
+
+impl<'a> Serialize for HutaoFormat<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let list = self.artifacts.iter().map(HutaoArtifact).collect::<Vec<_>>();
+
+        let mut root = serializer.serialize_map(Some(2))?;
+        root.serialize_entry("info", &serde_json::json!({
+            "export_app": "yas",
+            "export_app_version": env!("CARGO_PKG_VERSION"),
+        }))?;
+        root.serialize_entry("list", &list)?;
+        root.end()
+    }
+}
+
+/// serialize to the artifact json of the HuTao toolbox
+/// stats and slots are encoded as the game's numeric ids, percentage values are fractions (e.g. 0.311)
+pub fn to_hutao_json(artifacts: &[ScannedArtifact]) -> String {
+    let artifacts: Vec<GenshinArtifact> = artifacts.iter().map(GenshinArtifact::from).collect();
+    serde_json::to_string(&HutaoFormat::new(&artifacts)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::ArtifactSetName;
+
+    fn artifact(slot: ArtifactSlot, rarity: i32, main_stat: ArtifactStat) -> ScannedArtifact {
+        ScannedArtifact {
+            name: String::new(),
+            set_name: ArtifactSetName::GladiatorFinale,
+            slot,
+            level: 20,
+            rarity,
+            main_stat,
+            sub_stats: vec![
+                ArtifactStat { name: ArtifactStatName::Critical, value: 0.039 },
+                ArtifactStat { name: ArtifactStatName::ElementalMastery, value: 23.0 },
+            ],
+            equip: None,
+            lock: false,
+            issues: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn serialize_to_hutao_fixture() {
+        let mut flower = artifact(ArtifactSlot::Flower, 5, ArtifactStat { name: ArtifactStatName::Hp, value: 4780.0 });
+        flower.equip = Some("胡桃".to_string());
+        flower.lock = true;
+        let goblet = artifact(ArtifactSlot::Goblet, 4, ArtifactStat { name: ArtifactStatName::PyroBonus, value: 0.348 });
+
+        let json: serde_json::Value = serde_json::from_str(&to_hutao_json(&[flower, goblet])).unwrap();
+        assert_eq!(json["info"]["export_app"], "yas");
+        assert_eq!(json["list"], serde_json::json!([
+            {
+                "setKey": "GladiatorFinale",
+                "equipType": 1,
+                "rankLevel": 5,
+                "level": 20,
+                "mainProp": { "propType": 2, "value": 4780.0 },
+                "appendProps": [
+                    { "propType": 20, "value": 0.039 },
+                    { "propType": 28, "value": 23.0 },
+                ],
+                "location": "胡桃",
+                "lock": true,
+            },
+            {
+                "setKey": "GladiatorFinale",
+                "equipType": 4,
+                "rankLevel": 4,
+                "level": 20,
+                "mainProp": { "propType": 40, "value": 0.348 },
+                "appendProps": [
+                    { "propType": 20, "value": 0.039 },
+                    { "propType": 28, "value": 23.0 },
+                ],
+                "location": "",
+                "lock": false,
+            },
+        ]));
+    }
+
+    #[test]
+    fn slot_ids() {
+        let slots = [ArtifactSlot::Flower, ArtifactSlot::Feather, ArtifactSlot::Sand, ArtifactSlot::Goblet, ArtifactSlot::Head];
+        let ids: Vec<i32> = slots.iter().map(|slot| slot.to_hutao()).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+    }
+}
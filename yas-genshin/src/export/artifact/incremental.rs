@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::artifact::GenshinArtifact;
+
+use super::good::GOODFormat;
+use super::mona_uranai::MonaFormat;
+
+const MONA_SLOTS: [&str; 5] = ["flower", "feather", "sand", "cup", "head"];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PreviousExportFormat {
+    Good,
+    Mona,
+}
+
+/// an earlier GOOD or mona export, to skip the artifacts already in it and merge the new ones into it
+pub struct PreviousExport {
+    format: PreviousExportFormat,
+    document: Value,
+    fingerprints: HashSet<String>,
+}
+
+/// values are compared at this precision, so that exports of other tools with slightly different rounding still match
+fn round_value(value: &Value) -> String {
+    format!("{:.4}", value.as_f64().unwrap_or(0.0))
+}
+
+impl PreviousExport {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<PreviousExport> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_json(&content)
+    }
+
+    pub fn from_json(content: &str) -> Result<PreviousExport> {
+        let document = serde_json::from_str::<Value>(content)?;
+
+        let format = if document.get("format").and_then(Value::as_str) == Some("GOOD") {
+            PreviousExportFormat::Good
+        } else if MONA_SLOTS.iter().any(|slot| document.get(slot).is_some()) {
+            PreviousExportFormat::Mona
+        } else {
+            return Err(anyhow!("不是 GOOD 或莫娜格式的圣遗物文件"));
+        };
+
+        let fingerprints = Self::items(format, &document)
+            .map(|item| Self::fingerprint(format, item))
+            .collect();
+
+        Ok(PreviousExport {
+            format,
+            document,
+            fingerprints,
+        })
+    }
+
+    pub fn format(&self) -> PreviousExportFormat {
+        self.format
+    }
+
+    /// how many distinct artifacts the export has
+    pub fn len(&self) -> usize {
+        self.fingerprints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fingerprints.is_empty()
+    }
+
+    fn items(format: PreviousExportFormat, document: &Value) -> impl Iterator<Item = &Value> {
+        let lists: Vec<&Value> = match format {
+            PreviousExportFormat::Good => document.get("artifacts").into_iter().collect(),
+            PreviousExportFormat::Mona => MONA_SLOTS.iter().filter_map(|slot| document.get(slot)).collect(),
+        };
+        lists.into_iter()
+            .filter_map(Value::as_array)
+            .flatten()
+    }
+
+    /// set, slot, rarity, level, main stat and sub stats, but not the lock or the equip, which change over time
+    fn fingerprint(format: PreviousExportFormat, item: &Value) -> String {
+        let (keys, sub_stats, stat_key, main_stat) = match format {
+            PreviousExportFormat::Good => (["setKey", "slotKey", "rarity", "level"], "substats", "key", &item["mainStatKey"]),
+            PreviousExportFormat::Mona => (["setName", "position", "star", "level"], "normalTags", "name", &item["mainTag"]["name"]),
+        };
+
+        let mut ans: Vec<String> = keys.iter().map(|key| item[key].to_string()).collect();
+        ans.push(main_stat.to_string());
+        for stat in item[sub_stats].as_array().into_iter().flatten() {
+            ans.push(format!("{}={}", stat[stat_key], round_value(&stat["value"])));
+        }
+        ans.join("|")
+    }
+
+    /// the artifacts serialized in the format of the export
+    fn to_items(&self, artifacts: &[GenshinArtifact]) -> Result<Value> {
+        Ok(match self.format {
+            PreviousExportFormat::Good => serde_json::to_value(GOODFormat::new(artifacts))?,
+            PreviousExportFormat::Mona => serde_json::to_value(MonaFormat::new(artifacts))?,
+        })
+    }
+
+    pub fn contains(&self, artifact: &GenshinArtifact) -> bool {
+        let Ok(document) = self.to_items(std::slice::from_ref(artifact)) else {
+            return false;
+        };
+        let mut items = Self::items(self.format, &document);
+        items.any(|item| self.fingerprints.contains(&Self::fingerprint(self.format, item)))
+    }
+
+    /// the export with the artifacts not already in it appended, in the same format
+    pub fn merge(&self, artifacts: &[GenshinArtifact]) -> Result<String> {
+        let new_document = self.to_items(artifacts)?;
+        let mut document = self.document.clone();
+
+        let mut seen = self.fingerprints.clone();
+        let lists: Vec<&str> = match self.format {
+            PreviousExportFormat::Good => vec!["artifacts"],
+            PreviousExportFormat::Mona => MONA_SLOTS.to_vec(),
+        };
+        for list in lists {
+            let Some(new_items) = new_document.get(list).and_then(Value::as_array) else {
+                continue;
+            };
+
+            if !document.get(list).is_some_and(Value::is_array) {
+                document[list] = Value::Array(Vec::new());
+            }
+            let items = document[list].as_array_mut().unwrap();
+            for item in new_items {
+                if seen.insert(Self::fingerprint(self.format, item)) {
+                    items.push(item.clone());
+                }
+            }
+        }
+
+        Ok(serde_json::to_string(&document)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStat, ArtifactStatName};
+
+    fn artifact(level: i32, crit: f64) -> GenshinArtifact {
+        GenshinArtifact {
+            set_name: ArtifactSetName::GladiatorFinale,
+            slot: ArtifactSlot::Flower,
+            star: 5,
+            lock: false,
+            level,
+            main_stat: ArtifactStat { name: ArtifactStatName::Hp, value: 4780.0 },
+            sub_stat_1: Some(ArtifactStat { name: ArtifactStatName::Critical, value: crit }),
+            sub_stat_2: Some(ArtifactStat { name: ArtifactStatName::Atk, value: 19.0 }),
+            sub_stat_3: None,
+            sub_stat_4: None,
+            equip: None,
+        }
+    }
+
+    #[test]
+    fn skip_and_merge_into_a_good_export() {
+        let old = artifact(20, 0.039);
+        let export = PreviousExport::from_json(&serde_json::to_string(&GOODFormat::new(std::slice::from_ref(&old))).unwrap()).unwrap();
+        assert_eq!(export.format(), PreviousExportFormat::Good);
+        assert_eq!(export.len(), 1);
+
+        // the lock and the equip change over time
+        let mut relocked = old.clone();
+        relocked.lock = true;
+        relocked.equip = Some("Hutao".to_string());
+        assert!(export.contains(&relocked));
+        let new = artifact(20, 0.07);
+        assert!(!export.contains(&new));
+
+        let merged: Value = serde_json::from_str(&export.merge(&[relocked, new.clone()]).unwrap()).unwrap();
+        let merged = PreviousExport::from_json(&merged.to_string()).unwrap();
+        assert_eq!(merged.len(), 2);
+        assert!(merged.contains(&new));
+    }
+
+    #[test]
+    fn skip_and_merge_into_a_mona_export() {
+        let export = PreviousExport::from_json(&serde_json::to_string(&MonaFormat::new(&[artifact(16, 0.039)])).unwrap()).unwrap();
+        assert_eq!(export.format(), PreviousExportFormat::Mona);
+        assert!(export.contains(&artifact(16, 0.039)));
+        assert!(!export.contains(&artifact(20, 0.039)));
+
+        let merged = PreviousExport::from_json(&export.merge(&[artifact(20, 0.039)]).unwrap()).unwrap();
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn other_documents_are_rejected() {
+        assert!(PreviousExport::from_json(r#"{"format": "other", "artifacts": []}"#).is_err());
+        assert!(PreviousExport::from_json("[]").is_err());
+    }
+}
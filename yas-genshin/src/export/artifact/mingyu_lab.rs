@@ -153,7 +153,7 @@ pub struct MingyuLabFormat<'a> {
 }
 
 impl<'a> MingyuLabFormat<'a> {
-    pub fn new(results: &'a [GenshinArtifact]) -> MingyuLabFormat {
+    pub fn new(results: &'a [GenshinArtifact]) -> MingyuLabFormat<'a> {
         let artifacts: Vec<MingyuLabArtifact<'a>> = results
             .iter()
             .filter(|artifact| {
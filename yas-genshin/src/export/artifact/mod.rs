@@ -1,11 +1,15 @@
+pub use artifact_exporter::{ArtifactExporter, ArtifactExporterRegistry, GOODExporter, ALL_FORMATS};
 pub use config::ExportArtifactConfig;
-pub use export_format::GenshinArtifactExportFormat;
 pub use exporter::GenshinArtifactExporter;
+pub use hutao::{to_hutao_json, HutaoFormat};
+pub use incremental::{PreviousExport, PreviousExportFormat};
 
 mod good;
 mod mingyu_lab;
 mod mona_uranai;
 mod exporter;
-mod export_format;
 mod config;
 mod csv;
+mod hutao;
+mod incremental;
+mod artifact_exporter;
@@ -179,7 +179,7 @@ impl<'a> Serialize for MonaFormat<'a> {
 }
 
 impl<'a> MonaFormat<'a> {
-    pub fn new(results: &[GenshinArtifact]) -> MonaFormat {
+    pub fn new(results: &[GenshinArtifact]) -> MonaFormat<'_> {
         let mut flower: Vec<&MonaArtifact> = Vec::new();
         let mut feather: Vec<&MonaArtifact> = Vec::new();
         let mut cup: Vec<&MonaArtifact> = Vec::new();
@@ -0,0 +1,84 @@
+use crate::artifact::{ArtifactStatName, STAT_NAMES_EN, STAT_NAMES_JA, STAT_NAMES_ZH_CN};
+use crate::character::{CHARACTER_NAMES, CHARACTER_NAMES_EN, CHARACTER_NAMES_JA};
+
+/// the language of the game client, which the text is recognized in
+/// the results are parsed into the same keys whatever the language, so the exports don't change
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GameLanguage {
+    /// simplified chinese, the embedded model
+    #[default]
+    Zh,
+    En,
+    Ja,
+}
+
+impl GameLanguage {
+    pub fn code(&self) -> &'static str {
+        match *self {
+            GameLanguage::Zh => "zh",
+            GameLanguage::En => "en",
+            GameLanguage::Ja => "ja",
+        }
+    }
+
+    pub fn stat_name(&self, name: &str, is_percentage: bool) -> Option<ArtifactStatName> {
+        match *self {
+            GameLanguage::Zh => ArtifactStatName::from_zh_cn(name, is_percentage),
+            GameLanguage::En => ArtifactStatName::from_en(name, is_percentage),
+            GameLanguage::Ja => ArtifactStatName::from_ja(name, is_percentage),
+        }
+    }
+
+    pub fn stat_names(&self) -> &'static [&'static str] {
+        match *self {
+            GameLanguage::Zh => &STAT_NAMES_ZH_CN,
+            GameLanguage::En => &STAT_NAMES_EN,
+            GameLanguage::Ja => &STAT_NAMES_JA,
+        }
+    }
+
+    /// the names of the characters, with their names in `CHARACTER_NAMES`
+    pub fn character_names(&self) -> Vec<(&'static str, &'static str)> {
+        match *self {
+            GameLanguage::Zh => CHARACTER_NAMES.iter().map(|name| (*name, *name)).collect(),
+            GameLanguage::En => CHARACTER_NAMES_EN.to_vec(),
+            GameLanguage::Ja => CHARACTER_NAMES_JA.to_vec(),
+        }
+    }
+
+    /// the character of an equip status, e.g. "胡桃已装备", "Equipped: Hu Tao", "胡桃装備中"
+    pub fn equip_name<'a>(&self, raw: &'a str) -> Option<&'a str> {
+        match *self {
+            GameLanguage::Zh => raw.strip_suffix("已装备"),
+            GameLanguage::En => raw.strip_prefix("Equipped:").map(|name| name.trim_start()),
+            GameLanguage::Ja => raw.strip_suffix("装備中"),
+        }
+    }
+
+    /// the equip status of a character, the inverse of `equip_name`
+    pub fn equip_status(&self, name: &str) -> String {
+        match *self {
+            GameLanguage::Zh => format!("{}已装备", name),
+            GameLanguage::En => format!("Equipped: {}", name),
+            GameLanguage::Ja => format!("{}装備中", name),
+        }
+    }
+
+    /// what the artifact count of the repository starts with, e.g. "圣遗物 1234/2100"
+    pub fn artifact_count_name(&self) -> &'static str {
+        match *self {
+            GameLanguage::Zh => "圣遗物",
+            GameLanguage::En => "Artifacts",
+            GameLanguage::Ja => "聖遺物",
+        }
+    }
+
+    /// what the weapon count of the repository starts with, e.g. "武器 123/2000"
+    pub fn weapon_count_name(&self) -> &'static str {
+        match *self {
+            GameLanguage::Zh => "武器",
+            GameLanguage::En => "Weapons",
+            GameLanguage::Ja => "武器",
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use regex::Regex;
+
+use yas::ocr::Lexicon;
+
+use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStat, ARTIFACT_NAMES_ZH_CN};
+use crate::language::GameLanguage;
+use crate::weapon::{weapon_name_to_good, WEAPON_NAMES_ZH_CN};
+
+/// the tables to correct and parse the text of a game language
+pub struct LanguagePack {
+    pub language: GameLanguage,
+    /// the artifact names of the language, to the names of the chinese client
+    artifact_names: HashMap<String, String>,
+    /// the weapon names of the language, to the names of the chinese client
+    weapon_names: HashMap<String, String>,
+    /// the character names of the language, to the names in `CHARACTER_NAMES`
+    character_names: HashMap<&'static str, &'static str>,
+    pub artifact_name_lexicon: Lexicon,
+    pub stat_name_lexicon: Lexicon,
+    pub character_name_lexicon: Lexicon,
+    pub weapon_name_lexicon: Lexicon,
+}
+
+lazy_static! {
+    static ref LANGUAGE_PACK: RwLock<Arc<LanguagePack>> = RwLock::new(Arc::new(LanguagePack::zh_cn()));
+}
+
+/// the language the results are parsed in, chinese unless `set_language_pack` is called
+pub fn language_pack() -> Arc<LanguagePack> {
+    LANGUAGE_PACK.read().unwrap().clone()
+}
+
+pub fn set_language_pack(pack: LanguagePack) {
+    *LANGUAGE_PACK.write().unwrap() = Arc::new(pack);
+}
+
+impl LanguagePack {
+    fn with_names(
+        language: GameLanguage,
+        artifact_names: HashMap<String, String>,
+        weapon_names: HashMap<String, String>,
+    ) -> LanguagePack {
+        let character_names: HashMap<_, _> = language.character_names().into_iter().collect();
+        LanguagePack {
+            language,
+            artifact_name_lexicon: Lexicon::new(artifact_names.keys().cloned()),
+            stat_name_lexicon: Lexicon::new(language.stat_names().iter().copied()),
+            character_name_lexicon: Lexicon::new(character_names.keys().copied()),
+            weapon_name_lexicon: Lexicon::new(weapon_names.keys().cloned()),
+            artifact_names,
+            weapon_names,
+            character_names,
+        }
+    }
+
+    pub fn zh_cn() -> LanguagePack {
+        let artifact_names = ARTIFACT_NAMES_ZH_CN.iter().map(|name| (name.to_string(), name.to_string())).collect();
+        let weapon_names = WEAPON_NAMES_ZH_CN.iter().map(|(name, _)| (name.to_string(), name.to_string())).collect();
+        Self::with_names(GameLanguage::Zh, artifact_names, weapon_names)
+    }
+
+    /// the weapon names of `weapon_names.json` of `dir`, like the artifact names.
+    /// only the weapon scanner needs them, so they may be missing, then the weapon names are not corrected
+    fn load_weapon_names(language: GameLanguage, dir: &str) -> Result<HashMap<String, String>> {
+        let path = Self::dir(language, dir).join("weapon_names.json");
+        if !path.exists() {
+            warn!("未找到武器名称表 {}，武器名称将不会被校正", path.display());
+            return Ok(HashMap::new());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("无法读取武器名称表 {}: {}", path.display(), e))?;
+        let weapon_names: HashMap<String, String> = serde_json::from_str(&content)?;
+        if let Some((name, zh_cn)) = weapon_names.iter().find(|(_, zh_cn)| weapon_name_to_good(zh_cn).is_none()) {
+            return Err(anyhow!("武器名称表中的未知武器: {} -> {}", name, zh_cn));
+        }
+        info!("已加载 {} 个武器名称，语言: {}", weapon_names.len(), language.code());
+
+        Ok(weapon_names)
+    }
+
+    /// where the model and the tables of a language are, i.e. `{dir}/{code}`
+    pub fn dir(language: GameLanguage, dir: &str) -> PathBuf {
+        Path::new(dir).join(language.code())
+    }
+
+    /// the artifact names of languages other than chinese are read from `artifact_names.json` of `dir`,
+    /// a map from the names of the language to the names of the chinese client, and so are the weapon names
+    pub fn load(language: GameLanguage, dir: &str) -> Result<LanguagePack> {
+        if language == GameLanguage::Zh {
+            return Ok(Self::zh_cn());
+        }
+
+        let path = Self::dir(language, dir).join("artifact_names.json");
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("无法读取圣遗物名称表 {}: {}", path.display(), e))?;
+        let artifact_names: HashMap<String, String> = serde_json::from_str(&content)?;
+        if let Some((name, zh_cn)) = artifact_names.iter().find(|(_, zh_cn)| ArtifactSetName::from_zh_cn(zh_cn).is_none()) {
+            return Err(anyhow!("圣遗物名称表中的未知圣遗物: {} -> {}", name, zh_cn));
+        }
+        info!("已加载 {} 个圣遗物名称，语言: {}", artifact_names.len(), language.code());
+
+        let weapon_names = Self::load_weapon_names(language, dir)?;
+
+        Ok(Self::with_names(language, artifact_names, weapon_names))
+    }
+
+    pub fn set_and_slot(&self, name: &str) -> Option<(ArtifactSetName, ArtifactSlot)> {
+        let zh_cn = self.artifact_names.get(name)?;
+        Some((ArtifactSetName::from_zh_cn(zh_cn)?, ArtifactSlot::from_zh_cn(zh_cn)?))
+    }
+
+    // e.g "生命值+4,123", "CRIT Rate+10%"
+    pub fn stat(&self, s: &str) -> Option<ArtifactStat> {
+        let (name, value) = s.rsplit_once('+')?;
+
+        let is_percentage = value.contains('%');
+        let name = self.language.stat_name(name, is_percentage)?;
+
+        let re = Regex::new("[%,]").unwrap();
+        let mut value = re.replace_all(value, "").parse::<f64>().ok()?;
+        if is_percentage {
+            value /= 100.0;
+        }
+
+        Some(ArtifactStat { name, value })
+    }
+
+    /// the chinese name of a recognized weapon name, corrected by the names of the language
+    pub fn weapon_name(&self, raw: &str) -> String {
+        let name = self.weapon_name_lexicon.correct_or_raw(raw).text;
+        self.weapon_names.get(&name).cloned().unwrap_or(name)
+    }
+
+    /// the name in `CHARACTER_NAMES` of a recognized character name, corrected by the names of the language
+    pub fn character_name(&self, raw: &str) -> String {
+        let name = self.character_name_lexicon.correct_or_raw(raw).text;
+        self.character_names.get(name.as_str()).map(|name| name.to_string()).unwrap_or(name)
+    }
+
+    /// the character equipping the artifact, by its name in `CHARACTER_NAMES`
+    pub fn equip(&self, s: &str) -> Option<String> {
+        let name = self.language.equip_name(s)?;
+        self.character_names.get(name).map(|name| name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weapon_and_character_names_of_chinese() {
+        let pack = LanguagePack::zh_cn();
+        assert_eq!(pack.weapon_name("风鹰剑"), "风鹰剑");
+        assert_eq!(pack.weapon_name("斫峰之刀"), "斫峰之刃");
+        assert_eq!(pack.character_name("胡桃"), "胡桃");
+        assert_eq!(pack.character_name("不知道"), "不知道");
+    }
+
+    #[test]
+    fn weapon_and_character_names_of_english() {
+        let dir = std::env::temp_dir().join(format!("yas_language_pack_{}", std::process::id()));
+        let en = LanguagePack::dir(GameLanguage::En, dir.to_str().unwrap());
+        std::fs::create_dir_all(&en).unwrap();
+        std::fs::write(en.join("artifact_names.json"), "{}").unwrap();
+        std::fs::write(en.join("weapon_names.json"), r#"{"Aquila Favonia": "风鹰剑"}"#).unwrap();
+
+        let pack = LanguagePack::load(GameLanguage::En, dir.to_str().unwrap()).unwrap();
+        assert_eq!(pack.weapon_name("Aquila Favonia"), "风鹰剑");
+        assert_eq!(pack.weapon_name("Aquila Favonla"), "风鹰剑");
+        assert_eq!(pack.character_name("Hu Tao"), "胡桃");
+        assert_eq!(pack.language.equip_name("Equipped: Hu Tao").map(|name| pack.character_name(name)), Some("胡桃".to_string()));
+
+        // the weapon names are optional, but must be known
+        std::fs::remove_file(en.join("weapon_names.json")).unwrap();
+        assert_eq!(LanguagePack::load(GameLanguage::En, dir.to_str().unwrap()).unwrap().weapon_name("Aquila Favonia"), "Aquila Favonia");
+        std::fs::write(en.join("weapon_names.json"), r#"{"Aquila Favonia": "风鹰枪"}"#).unwrap();
+        assert!(LanguagePack::load(GameLanguage::En, dir.to_str().unwrap()).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,5 @@
+pub use game_language::GameLanguage;
+pub use language_pack::{language_pack, set_language_pack, LanguagePack};
+
+mod game_language;
+mod language_pack;
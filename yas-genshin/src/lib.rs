@@ -1,10 +1,14 @@
 #![feature(coroutines, coroutine_trait)]
-#![feature(fn_traits)]
 #![feature(stmt_expr_attributes)]
+// the modules are named after their main file, e.g. `foo/foo.rs`
+#![allow(clippy::module_inception)]
 
 pub mod scanner_controller;
 pub mod export;
 pub mod scanner;
 pub mod artifact;
 pub mod character;
+pub mod weapon;
 pub mod application;
+pub mod calibration;
+pub mod language;
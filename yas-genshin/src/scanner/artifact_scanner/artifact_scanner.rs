@@ -1,19 +1,27 @@
-use std::{cell::RefCell, ops::{Coroutine, CoroutineState}, pin::Pin, rc::Rc, sync::mpsc::{self, Sender}, time::SystemTime};
+use std::{cell::RefCell, ops::{Coroutine, CoroutineState}, pin::Pin, rc::Rc, sync::{mpsc::{self, Receiver, SyncSender}, Arc}, time::SystemTime};
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use clap::FromArgMatches;
 use image::RgbImage;
-use log::{error, info};
+use log::{error, info, warn};
 
 use yas::capture::{Capturer, GenericCapturer};
+use yas::events::{ScanEvent, ScanObserver};
+use yas::frame_source::{DistinctFrames, FrameCapturer, FrameSource};
 use yas::game_info::GameInfo;
-use yas::ocr::{ImageToText, yas_ocr_model};
+use yas::ocr::{DatasetDumper, ImageToText};
 use yas::positioning::Pos;
+use yas::scan_control::{Checkpoint, ScanControl};
+use yas::system_control::SystemControl;
+use yas::utils;
 use yas::window_info::FromWindowInfoRepository;
 use yas::window_info::WindowInfoRepository;
 
-use crate::{scanner::artifact_scanner::artifact_scanner_worker::ArtifactScannerWorker};
-use crate::scanner::artifact_scanner::message_items::SendItem;
+use crate::artifact::LockRules;
+use crate::language::{set_language_pack, LanguagePack};
+use crate::scanner::artifact_scanner::artifact_scanner_worker::{get_dataset_dumper, get_image_to_text, ArtifactScannerWorker, KnownFilter};
+use crate::scanner::artifact_scanner::message_items::{SendItem, Verdict};
 use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
 use crate::scanner_controller::repository_layout::{
     GenshinRepositoryScanController,
@@ -24,6 +32,15 @@ use crate::scanner_controller::repository_layout::{
 use super::artifact_scanner_config::GenshinArtifactScannerConfig;
 use super::ArtifactScannerWindowInfo;
 
+/// captured panels waiting for recognition, capturing blocks when the workers are this far behind
+const CAPTURE_QUEUE_SIZE: usize = 32;
+
+/// the colors of the lock button of the panel, locked and unlocked
+const LOCK_COLORS: [image::Rgb<u8>; 2] = [
+    image::Rgb([74, 83, 102]),
+    image::Rgb([243, 239, 234]),
+];
+
 fn color_distance(c1: &image::Rgb<u8>, c2: &image::Rgb<u8>) -> usize {
     let x = c1.0[0] as i32 - c2.0[0] as i32;
     let y = c1.0[1] as i32 - c2.0[1] as i32;
@@ -38,28 +55,46 @@ pub struct GenshinArtifactScanner {
     image_to_text: Box<dyn ImageToText<RgbImage> + Send>,
     controller: Rc<RefCell<GenshinRepositoryScanController>>,
     capturer: Rc<dyn Capturer<RgbImage>>,
+    is_known: Option<KnownFilter>,
+    /// with `lock_rules`, evaluated by the workers
+    lock_rules: Option<LockRules>,
+    system_control: SystemControl,
+    observer: Option<Arc<dyn ScanObserver>>,
+    /// with `dump_dataset`
+    dataset_dumper: Option<Arc<DatasetDumper>>,
+    /// whether the last scan stopped before the end, by the user or an error
+    interrupted: bool,
 }
 
 // constructor
 impl GenshinArtifactScanner {
-    fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-        let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(
-            yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")?
-        );
-        Ok(model)
-    }
-
     fn get_capturer() -> Result<Rc<dyn Capturer<RgbImage>>> {
         Ok(Rc::new(GenericCapturer::new()?))
     }
 
+    fn get_lock_rules(config: &GenshinArtifactScannerConfig) -> Result<Option<LockRules>> {
+        match &config.lock_rules {
+            Some(path) => {
+                let rules = LockRules::load(path)
+                    .map_err(|e| anyhow::anyhow!("无法读取锁定规则 {}: {}", path, e))?;
+                info!("已加载 {} 条锁定规则", rules.rules.len());
+                Ok(Some(rules))
+            },
+            None => Ok(None),
+        }
+    }
+
     pub fn new(
         window_info_repo: &WindowInfoRepository,
         config: GenshinArtifactScannerConfig,
         controller_config: GenshinRepositoryScannerLogicConfig,
         game_info: GameInfo,
     ) -> Result<Self> {
+        set_language_pack(LanguagePack::load(config.game_lang, &config.lang_dir)?);
         Ok(Self {
+            image_to_text: get_image_to_text(&config)?,
+            lock_rules: Self::get_lock_rules(&config)?,
+            dataset_dumper: get_dataset_dumper(&config)?,
             scanner_config: config,
             window_info: ArtifactScannerWindowInfo::from_window_info_repository(
                 game_info.window.to_rect_usize().size(),
@@ -71,9 +106,12 @@ impl GenshinArtifactScanner {
                 GenshinRepositoryScanController::new(window_info_repo, controller_config, game_info.clone(), true)?
             )),
             game_info,
-            image_to_text: Self::get_image_to_text()?,
             // item count will be set later, once the scan starts
             capturer: Self::get_capturer()?,
+            is_known: None,
+            system_control: SystemControl::new(),
+            observer: None,
+            interrupted: false,
         })
     }
 
@@ -88,20 +126,44 @@ impl GenshinArtifactScanner {
             game_info.platform,
             window_info_repo,
         )?;
+        let scanner_config = GenshinArtifactScannerConfig::from_arg_matches(arg_matches)?;
+        set_language_pack(LanguagePack::load(scanner_config.game_lang, &scanner_config.lang_dir)?);
         Ok(GenshinArtifactScanner {
-            scanner_config: GenshinArtifactScannerConfig::from_arg_matches(arg_matches)?,
+            image_to_text: get_image_to_text(&scanner_config)?,
+            lock_rules: Self::get_lock_rules(&scanner_config)?,
+            dataset_dumper: get_dataset_dumper(&scanner_config)?,
+            scanner_config,
             window_info,
             controller: Rc::new(RefCell::new(
                 GenshinRepositoryScanController::from_arg_matches(window_info_repo, arg_matches, game_info.clone(), true)?
             )),
             game_info,
-            image_to_text: Self::get_image_to_text()?,
             capturer: Self::get_capturer()?,
+            is_known: None,
+            system_control: SystemControl::new(),
+            observer: None,
+            interrupted: false,
         })
     }
 }
 
 impl GenshinArtifactScanner {
+    /// tell the artifacts already scanned before, for an incremental scan, see `stop_after_known` of the config
+    pub fn set_known_filter(&mut self, is_known: KnownFilter) {
+        self.is_known = Some(is_known);
+    }
+
+    /// report the progress and the results of the scan to `observer`, e.g. for a front-end
+    pub fn set_observer(&mut self, observer: Arc<dyn ScanObserver>) {
+        self.observer = Some(observer);
+    }
+
+    fn emit(&self, event: ScanEvent) {
+        if let Some(observer) = &self.observer {
+            observer.on_event(&event);
+        }
+    }
+
     pub fn capture_panel(&self) -> Result<RgbImage> {
         self.capturer.capture_relative_to(
             self.window_info.panel_rect.to_rect_i32(),
@@ -137,9 +199,33 @@ impl GenshinArtifactScanner {
         anyhow::Ok(ret)
     }
 
+    /// sampled at the center of the lock button of the panel
+    pub fn get_panel_lock(&self) -> Result<bool> {
+        let rect = self.window_info.lock_rect;
+        let pos: Pos<i32> = Pos {
+            x: self.game_info.window.left + (rect.left + rect.width / 2.0) as i32,
+            y: self.game_info.window.top + (rect.top + rect.height / 2.0) as i32,
+        };
+        let color = self.capturer.capture_color(pos)?;
+
+        Ok(color_distance(&LOCK_COLORS[0], &color) < color_distance(&LOCK_COLORS[1], &color))
+    }
+
+    /// click the lock button of the panel, which toggles the lock of the artifact shown
+    fn toggle_panel_lock(&mut self) -> Result<()> {
+        let rect = self.window_info.lock_rect;
+        self.system_control.mouse_move_to(
+            self.game_info.window.left + (rect.left + rect.width / 2.0) as i32,
+            self.game_info.window.top + (rect.top + rect.height / 2.0) as i32,
+        )?;
+        self.system_control.mouse_click()?;
+        utils::sleep(100);
+        Ok(())
+    }
+
     pub fn get_item_count(&self) -> Result<i32> {
         let count = self.scanner_config.number;
-        let item_name = "圣遗物";
+        let item_name = self.scanner_config.game_lang.artifact_count_name();
 
         let max_count = 1800;
         if count > 0 {
@@ -155,9 +241,9 @@ impl GenshinArtifactScanner {
 
         info!("物品信息: {}", s);
 
-        if s.starts_with(item_name) {
-            let chars = s.chars().collect::<Vec<char>>();
-            let count_str = chars[4..chars.len() - 5].iter().collect::<String>();
+        // e.g. "圣遗物 1234/1800"
+        if let Some(rest) = s.strip_prefix(item_name) {
+            let count_str = rest.split('/').next().unwrap_or_default().trim();
             Ok(match count_str.parse::<usize>() {
                 Ok(v) => (v as i32).min(max_count),
                 Err(_) => max_count,
@@ -167,22 +253,104 @@ impl GenshinArtifactScanner {
         }
     }
 
+    fn get_checkpoint_path(&self) -> PathBuf {
+        match &self.scanner_config.checkpoint_file {
+            Some(path) => PathBuf::from(path),
+            None => Checkpoint::<GenshinArtifactScanResult>::default_path("genshin_artifact"),
+        }
+    }
+
+    /// with `resume`, the checkpoint of the last scan, otherwise an empty one
+    fn load_checkpoint(&self, path: &Path) -> Result<Checkpoint<GenshinArtifactScanResult>> {
+        if self.scanner_config.resume {
+            let checkpoint = Checkpoint::load(path)?;
+            info!("继续上次的扫描，已扫描 {} 个物品", checkpoint.position);
+            Ok(checkpoint)
+        } else {
+            if path.exists() {
+                warn!("上次的扫描未完成，其进度将被覆盖，使用 --resume 可继续上次的扫描");
+            }
+            Ok(Checkpoint::default())
+        }
+    }
+
     pub fn scan(&mut self) -> Result<Vec<GenshinArtifactScanResult>> {
-        info!("开始扫描，使用鼠标右键中断扫描");
+        info!("开始扫描圣遗物");
+        ScanControl::global().start();
 
-        let now = SystemTime::now();
-        let (tx, rx) = mpsc::channel::<Option<SendItem>>();
         // let token = self.cancellation_token.clone();
         let count = self.get_item_count()?;
-        let worker = ArtifactScannerWorker::new(
-            self.window_info.clone(),
-            self.scanner_config.clone(),
+
+        let checkpoint_path = self.get_checkpoint_path();
+        let checkpoint = self.load_checkpoint(&checkpoint_path)?;
+        // only whole rows are skipped, the rest of a row is scanned again and deduplicated
+        let col = self.window_info.col as usize;
+        let skip_row = checkpoint.position.min(count as usize) / col;
+        let checkpoint = Checkpoint { position: skip_row * col, items: checkpoint.items };
+        let total = count as usize - checkpoint.position;
+
+        self.interrupted = false;
+        let results = self.recognize(
+            Some(total),
+            Some((checkpoint_path.clone(), checkpoint)),
+            self.lock_rules.clone(),
+            |scanner, tx, verdicts| scanner.send(tx, verdicts, count, skip_row),
         )?;
 
-        let join_handle = worker.run(rx);
+        if self.interrupted {
+            info!("扫描进度已保存到 {}，使用 --resume 继续扫描", checkpoint_path.display());
+        } else {
+            Checkpoint::<GenshinArtifactScanResult>::remove(&checkpoint_path)?;
+        }
+        Ok(results)
+    }
+
+    /// recognize the recorded frames of the repository instead of the live window, no mouse or keyboard is operated,
+    /// and a frame is recognized once its panel shows another artifact
+    pub fn scan_offline(&mut self, source: Box<dyn FrameSource>) -> Result<Vec<GenshinArtifactScanResult>> {
+        info!("开始离线识别");
+        if self.lock_rules.is_some() {
+            warn!("离线识别不会锁定或解锁圣遗物");
+        }
+
+        let capturer = Rc::new(FrameCapturer::new());
+        self.capturer = capturer.clone();
+        let frames = DistinctFrames::new(source, self.window_info.panel_rect.to_rect_u32())
+            .threshold(self.scanner_config.offline_threshold)
+            .stable_frames(self.scanner_config.offline_stable_frames);
+
+        self.recognize(None, None, None, move |scanner, tx, _| scanner.send_offline(tx, frames, &capturer))
+    }
+
+    /// run the workers on the items sent by `send`, of which `total` are expected if it's known
+    /// `send` receives the verdicts of the items which want one
+    /// with `checkpoint`, the results follow those of the checkpoint, and the progress is saved to its path
+    /// with `lock_rules`, the workers decide the locks, which `send` toggles by the verdicts
+    fn recognize<F>(
+        &mut self,
+        total: Option<usize>,
+        checkpoint: Option<(PathBuf, Checkpoint<GenshinArtifactScanResult>)>,
+        lock_rules: Option<LockRules>,
+        send: F,
+    ) -> Result<Vec<GenshinArtifactScanResult>>
+    where
+        F: FnOnce(&mut Self, &SyncSender<Option<SendItem>>, &Receiver<Verdict>),
+    {
+        let now = SystemTime::now();
+        let (tx, rx) = mpsc::sync_channel::<Option<SendItem>>(CAPTURE_QUEUE_SIZE);
+        let (verdict_tx, verdict_rx) = mpsc::channel::<Verdict>();
+        let workers = (0..self.scanner_config.ocr_threads.max(1))
+            .map(|_| {
+                let worker = ArtifactScannerWorker::new(self.window_info.clone(), self.scanner_config.clone(), self.dataset_dumper.clone())?;
+                Ok(worker.with_lock_rules(lock_rules.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.emit(ScanEvent::Start { total });
+        let join_handle = ArtifactScannerWorker::run(workers, rx, verdict_tx, self.is_known.take(), self.observer.clone(), total, checkpoint);
         info!("Worker created");
 
-        self.send(&tx, count);
+        send(self, &tx, &verdict_rx);
 
         match tx.send(None) {
             Ok(_) => info!("扫描结束，等待识别线程结束，请勿关闭程序"),
@@ -191,14 +359,16 @@ impl GenshinArtifactScanner {
 
         match join_handle.join() {
             Ok(v) => {
-                info!("识别耗时: {:?}", now.elapsed()?);
+                let elapsed = now.elapsed()?;
+                info!("识别耗时: {:?}", elapsed);
 
                 // filter min level
                 let min_level = self.scanner_config.min_level;
-                let v = v.iter().filter(|a| {
+                let v: Vec<_> = v.iter().filter(|a| {
                     a.level >= min_level
                 }).cloned().collect();
 
+                self.emit(ScanEvent::Finish { count: v.len(), elapsed_ms: elapsed.as_millis() });
                 Ok(v)
             }
             Err(_) => Err(anyhow::anyhow!("识别线程出现错误")),
@@ -210,7 +380,7 @@ impl GenshinArtifactScanner {
         let row = self.window_info.row;
 
         let page_size = col * row;
-        return cur_index % page_size == 0;
+        cur_index % page_size == 0
     }
 
     /// Get the starting row in the page where `cur_index` is in
@@ -222,27 +392,41 @@ impl GenshinArtifactScanner {
 
         let page_size = col * row;
         if max_count - cur_index >= page_size {
-            return 0;
+            0
         } else {
             let remain = max_count - cur_index;
             let remain_row = (remain + col - 1) / col;
             let scroll_row = remain_row.min(row);
-            return row - scroll_row;
+            row - scroll_row
+        }
+    }
+
+    /// capture the items after the first `skip_row` rows, whose locks are sampled from the panels if any are skipped,
+    /// for the pages don't start where the lock states of the list are expected
+    fn send(&mut self, tx: &SyncSender<Option<SendItem>>, verdicts: &Receiver<Verdict>, count: i32, skip_row: usize) {
+        if let Err(e) = self.send_items(tx, verdicts, count, skip_row) {
+            error!("扫描发生错误：{}", e);
+            self.emit(ScanEvent::Error { index: None, message: e.to_string() });
+            self.interrupted = true;
         }
     }
 
-    fn send(&mut self, tx: &Sender<Option<SendItem>>, count: i32) {
-        let mut generator = GenshinRepositoryScanController::get_generator(self.controller.clone(), count as usize);
+    fn send_items(&mut self, tx: &SyncSender<Option<SendItem>>, verdicts: &Receiver<Verdict>, count: i32, skip_row: usize) -> Result<()> {
+        let mut generator = GenshinRepositoryScanController::get_generator_from(self.controller.clone(), count as usize, skip_row);
         let mut artifact_index: i32 = 0;
+        let is_resumed = skip_row > 0;
+        // the workers gate the confidence and decide the lock, the panel stays until they accept it
+        let toggles_lock = self.lock_rules.is_some() && !self.scanner_config.lock_dry_run;
+        let wants_verdict = self.scanner_config.min_confidence > 0.0 || toggles_lock;
 
         loop {
             let pinned_generator = Pin::new(&mut generator);
             match pinned_generator.resume(()) {
                 CoroutineState::Yielded(_) => {
-                    let image = self.capture_panel().unwrap();
-                    let star = self.get_star().unwrap();
+                    let image = self.capture_panel()?;
+                    let star = self.get_star()?;
 
-                    let list_image = if self.is_page_first_artifact(artifact_index) {
+                    let list_image = if !is_resumed && self.is_page_first_artifact(artifact_index) {
                         let origin = self.game_info.window;
                         let margin = self.window_info.scan_margin_pos;
                         let gap = self.window_info.item_gap_size;
@@ -268,15 +452,14 @@ impl GenshinArtifactScanner {
                                 top,
                                 width,
                                 height,
-                            })
-                            .unwrap();
+                            })?;
                         Some(game_image)
                     } else {
                         None
                     };
 
 
-                    artifact_index = artifact_index + 1;
+                    artifact_index += 1;
 
                     // todo normalize types
                     if (star as i32) < self.scanner_config.min_star {
@@ -287,33 +470,127 @@ impl GenshinArtifactScanner {
                         break;
                     }
 
-                    if tx
-                        .send(Some(SendItem {
-                            panel_image: image,
-                            star,
-                            list_image,
-                        }))
-                        .is_err()
-                    {
+                    let lock_override = if is_resumed {
+                        Some(self.get_panel_lock().unwrap_or(false))
+                    } else {
+                        None
+                    };
+
+                    let item = SendItem {
+                        panel_image: image,
+                        star,
+                        list_image,
+                        lock_override,
+                        attempt: 0,
+                        wants_verdict,
+                    };
+                    if !self.send_item(tx, verdicts, item)? {
                         break;
                     }
 
                     // scanned_count += 1;
                 }
                 CoroutineState::Complete(result) => {
-                    match result {
-                        Err(e) => error!("扫描发生错误：{}", e),
-                        Ok(value) => {
-                            match value {
-                                GenshinRepositoryControllerReturnResult::Interrupted => info!("用户中断"),
-                                GenshinRepositoryControllerReturnResult::Finished => ()
-                            }
-                        }
+                    match result? {
+                        GenshinRepositoryControllerReturnResult::Interrupted => {
+                            info!("用户中断");
+                            self.interrupted = true;
+                        },
+                        GenshinRepositoryControllerReturnResult::Finished => ()
                     }
 
                     break;
                 }
             }
         }
+
+        Ok(())
+    }
+
+    /// send an item to the workers, and if it wants a verdict, recapture its panel as long as the verdict asks for it,
+    /// and toggle its lock if the verdict says so. returns false once the workers are gone
+    fn send_item(&mut self, tx: &SyncSender<Option<SendItem>>, verdicts: &Receiver<Verdict>, item: SendItem) -> Result<bool> {
+        let mut item = item;
+        loop {
+            let (attempt, wants_verdict, lock_override) = (item.attempt, item.wants_verdict, item.lock_override);
+            if tx.send(Some(item)).is_err() {
+                return Ok(false);
+            }
+            if !wants_verdict {
+                return Ok(true);
+            }
+
+            let Ok(verdict) = verdicts.recv() else {
+                return Ok(false);
+            };
+            if verdict.toggle_lock.is_some() {
+                self.toggle_panel_lock()?;
+            }
+            let Some(confidence) = verdict.recapture else {
+                return Ok(true);
+            };
+
+            self.emit(ScanEvent::Retry {
+                attempt: attempt + 1,
+                reason: format!("识别置信度 {:.3} 低于 {}", confidence, self.scanner_config.min_confidence),
+            });
+            utils::sleep(50);
+            item = SendItem {
+                panel_image: self.capture_panel()?,
+                star: self.get_star()?,
+                // the lock states of the list are parsed from the first capture
+                list_image: None,
+                lock_override,
+                attempt: attempt + 1,
+                wants_verdict,
+            };
+        }
     }
-}
\ No newline at end of file
+
+    /// the item of the current frame, without the list, whose lock is sampled from the panel
+    fn capture_frame_item(&self) -> Result<SendItem> {
+        Ok(SendItem {
+            panel_image: self.capture_panel()?,
+            star: self.get_star()?,
+            list_image: None,
+            lock_override: Some(self.get_panel_lock()?),
+            attempt: 0,
+            wants_verdict: false,
+        })
+    }
+
+    fn send_offline<S: FrameSource>(&mut self, tx: &SyncSender<Option<SendItem>>, mut frames: S, capturer: &FrameCapturer) {
+        let mut frame_count = 0;
+
+        loop {
+            let frame = match frames.next_frame() {
+                Ok(Some(frame)) => frame,
+                Ok(None) => break,
+                Err(e) => {
+                    error!("读取帧发生错误：{}", e);
+                    break;
+                },
+            };
+            capturer.set_frame(frame);
+            frame_count += 1;
+
+            let item = match self.capture_frame_item() {
+                Ok(item) => item,
+                Err(e) => {
+                    warn!("跳过第 {} 帧: {}", frame_count, e);
+                    continue;
+                },
+            };
+
+            if (item.star as i32) < self.scanner_config.min_star {
+                continue;
+            }
+
+            if tx.send(Some(item)).is_err() {
+                break;
+            }
+        }
+
+        info!("离线识别共读取 {} 个不同的画面", frame_count);
+    }
+}
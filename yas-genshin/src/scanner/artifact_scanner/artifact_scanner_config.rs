@@ -1,3 +1,7 @@
+use yas::events::EventFormat;
+
+use crate::language::GameLanguage;
+
 #[derive(Clone, clap::Args)]
 pub struct GenshinArtifactScannerConfig {
     /// Items with stars less than this will be ignored
@@ -19,4 +23,85 @@ pub struct GenshinArtifactScannerConfig {
     /// the exact amount to scan
     #[arg(id = "number", long, help = "指定圣遗物数量", value_name = "NUMBER", default_value_t = -1)]
     pub number: i32,
+
+    /// tell the stars, the lock and the equip status by template matching, rather than by sampled colors and ocr
+    #[arg(id = "detect-icons", long = "detect-icons", help = "使用模板匹配识别星级、锁定和装备状态")]
+    pub detect_icons: bool,
+
+    /// recapture the panel if any of its regions is recognized with a confidence less than this, 0 to disable
+    #[arg(id = "min-confidence", long = "min-confidence", help = "最低识别置信度，低于此值时重新截图识别，0 为不检查", value_name = "MIN_CONFIDENCE", default_value_t = 0.0)]
+    pub min_confidence: f32,
+
+    /// how many times a panel is recaptured before its low confidence result is accepted
+    #[arg(id = "max-recapture", long = "max-recapture", help = "低置信度时最多重新截图的次数", value_name = "MAX_RECAPTURE", default_value_t = 2)]
+    pub max_recapture: usize,
+
+    /// load this onnx model instead of the embedded one, requires `dict-path`
+    #[arg(id = "model-path", long = "model-path", help = "使用指定的 onnx 模型代替内置模型", value_name = "MODEL_PATH", requires = "dict-path")]
+    pub model_path: Option<String>,
+
+    /// the index to word json of `model-path`
+    #[arg(id = "dict-path", long = "dict-path", help = "指定模型对应的字典 json", value_name = "DICT_PATH", requires = "model-path")]
+    pub dict_path: Option<String>,
+
+    /// how many panels are recognized in parallel, each thread loads its own model
+    #[arg(id = "ocr-threads", long = "ocr-threads", help = "并行识别的线程数，每个线程加载一个模型", value_name = "OCR_THREADS", default_value_t = 1)]
+    pub ocr_threads: usize,
+
+    /// with `incremental`, stop after this many consecutive artifacts already in the previous export, 0 to never stop
+    #[arg(id = "stop-after-known", long = "stop-after-known", help = "增量扫描时，连续识别到此数量的已有圣遗物后停止，0 为不停止", value_name = "STOP_AFTER_KNOWN", default_value_t = 0)]
+    pub stop_after_known: usize,
+
+    /// lock or unlock the artifacts while scanning, by the rules of this toml or json file
+    #[arg(id = "lock-rules", long = "lock-rules", help = "扫描时按此规则文件（toml 或 json）锁定或解锁圣遗物", value_name = "LOCK_RULES")]
+    pub lock_rules: Option<String>,
+
+    /// with `lock-rules`, only report the artifacts whose lock would change
+    #[arg(id = "lock-dry-run", long = "lock-dry-run", help = "只输出将要锁定或解锁的圣遗物，不实际操作", requires = "lock-rules")]
+    pub lock_dry_run: bool,
+
+    /// recognize a directory of png screenshots or a recorded video instead of the live window,
+    /// without operating the mouse or the keyboard
+    #[arg(id = "offline", long = "offline", help = "从截图目录或录像文件离线识别，不操作鼠标和键盘", value_name = "PATH")]
+    pub offline: Option<String>,
+
+    /// with `offline`, how much the panel differs between frames to be another artifact
+    #[arg(id = "offline-threshold", long = "offline-threshold", help = "离线识别时判断面板变化的阈值", value_name = "OFFLINE_THRESHOLD", default_value_t = 8.0)]
+    pub offline_threshold: f64,
+
+    /// with `offline`, how many frames the panel holds still before it's recognized, 0 for screenshots taken one per artifact
+    #[arg(id = "offline-stable-frames", long = "offline-stable-frames", help = "离线识别时面板静止多少帧后识别，逐件截图时设为 0", value_name = "OFFLINE_STABLE_FRAMES", default_value_t = 2)]
+    pub offline_stable_frames: usize,
+
+    /// report the progress and the results as machine-readable events, for front-ends
+    #[arg(id = "output-events", long = "output-events", help = "输出扫描进度和结果事件，供图形界面使用", value_name = "FORMAT")]
+    pub output_events: Option<EventFormat>,
+
+    /// with `output-events`, write the events to this file rather than stdout
+    #[arg(id = "events-file", long = "events-file", help = "事件输出到此文件，默认为标准输出", value_name = "EVENTS_FILE", requires = "output-events")]
+    pub events_file: Option<String>,
+
+    /// save every preprocessed crop with its recognized text to this directory, as training data
+    #[arg(id = "dump-dataset", long = "dump-dataset", help = "将预处理后的截图和识别结果保存到此目录，作为训练数据", value_name = "DIR")]
+    pub dump_dataset: Option<String>,
+
+    /// with `dump-dataset`, only save the crops recognized with a confidence less than this
+    #[arg(id = "dump-max-confidence", long = "dump-max-confidence", help = "只保存识别置信度低于此值的样本", value_name = "DUMP_MAX_CONFIDENCE", requires = "dump-dataset")]
+    pub dump_max_confidence: Option<f32>,
+
+    /// the language of the game client, models other than chinese are loaded from `lang-dir`
+    #[arg(id = "game-lang", long = "game-lang", help = "游戏客户端语言", value_name = "GAME_LANG", value_enum, default_value_t = GameLanguage::Zh)]
+    pub game_lang: GameLanguage,
+
+    /// where the model, the dictionary and the artifact names of each language are, in a directory named by its code
+    #[arg(id = "lang-dir", long = "lang-dir", help = "各语言模型、字典和圣遗物名称表所在目录", value_name = "LANG_DIR", default_value = "./lang")]
+    pub lang_dir: String,
+
+    /// continue the last scan which was aborted or crashed, from its checkpoint
+    #[arg(id = "resume", long = "resume", help = "从上次中断的位置继续扫描")]
+    pub resume: bool,
+
+    /// where the progress is saved while scanning, in the temp directory by default
+    #[arg(id = "checkpoint-file", long = "checkpoint-file", help = "扫描进度的保存位置，默认在临时目录", value_name = "CHECKPOINT_FILE")]
+    pub checkpoint_file: Option<String>,
 }
@@ -32,6 +32,18 @@ pub struct ArtifactScannerWindowInfo {
     #[window_info(rename = "genshin_artifact_item_equip_rect")]
     pub item_equip_rect: Rect<f64>,
 
+    /// the rarity stars under the main stat, relative to window
+    #[window_info(rename = "genshin_artifact_star_rect")]
+    pub star_rect: Rect<f64>,
+
+    /// around the lock button of the panel, relative to window
+    #[window_info(rename = "genshin_artifact_lock_rect")]
+    pub lock_rect: Rect<f64>,
+
+    /// the avatar before the equip status, relative to window
+    #[window_info(rename = "genshin_artifact_equip_icon_rect")]
+    pub equip_icon_rect: Rect<f64>,
+
     /// the count of artifacts relative to window
     #[window_info(rename = "genshin_artifact_item_count_rect")]
     pub item_count_rect: Rect<f64>,
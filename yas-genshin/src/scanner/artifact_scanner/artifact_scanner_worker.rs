@@ -1,5 +1,7 @@
-use std::collections::HashSet;
-use std::sync::mpsc::Receiver;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread::JoinHandle;
 
 use anyhow::Result;
@@ -7,14 +9,19 @@ use image::Rgb;
 use image::{GenericImageView, RgbImage};
 use log::{error, info, warn};
 
+use yas::events::{ScanEvent, ScanObserver};
 use yas::ocr::ImageToText;
-use yas::ocr::yas_ocr_model;
+use yas::ocr::{yas_ocr_model, DatasetDumper, DumpingModel, Lexicon, YasOCRModel};
 use yas::positioning::{Pos, Rect};
+use yas::scan_control::Checkpoint;
 use yas::utils::color_distance;
 
+use crate::artifact::{GenshinArtifact, LockRules};
+use crate::language::{language_pack, GameLanguage, LanguagePack};
 use crate::scanner::artifact_scanner::artifact_scanner_window_info::ArtifactScannerWindowInfo;
+use crate::scanner::artifact_scanner::icon_templates::IconTemplates;
 use crate::scanner::artifact_scanner::GenshinArtifactScannerConfig;
-use crate::scanner::artifact_scanner::message_items::SendItem;
+use crate::scanner::artifact_scanner::message_items::{SendItem, Verdict};
 use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
 
 fn parse_level(s: &str) -> Result<i32> {
@@ -29,65 +36,210 @@ fn parse_level(s: &str) -> Result<i32> {
     anyhow::Ok(level)
 }
 
-fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-    let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(
-        yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")?
-    );
-    Ok(model)
+/// replace a recognized string with its canonical entry of `lexicon`, if any
+fn correct(lexicon: &Lexicon, raw: String) -> String {
+    match lexicon.correct(&raw) {
+        Some(m) if m.corrected => {
+            info!("校正识别结果: {} -> {}", raw, m.text);
+            m.text
+        },
+        _ => raw,
+    }
+}
+
+// e.g. "暴击率+3.9%", only the name is corrected
+fn correct_stat(language: &LanguagePack, raw: String) -> String {
+    match raw.split_once('+') {
+        Some((name, value)) => correct(&language.stat_name_lexicon, name.to_string()) + "+" + value,
+        None => raw,
+    }
 }
 
-/// run in a separate thread, accept captured image and get an artifact
+// e.g. "胡桃已装备"
+fn correct_equip(language: &LanguagePack, raw: String) -> String {
+    match language.language.equip_name(&raw) {
+        Some(name) => language.language.equip_status(&correct(&language.character_name_lexicon, name.to_string())),
+        None => raw,
+    }
+}
+
+/// the chinese model built into the binary
+pub fn get_embedded_artifact_model() -> Result<YasOCRModel> {
+    yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")
+}
+
+fn get_model(config: &GenshinArtifactScannerConfig) -> Result<YasOCRModel> {
+    match (&config.model_path, &config.dict_path) {
+        (Some(model_path), Some(dict_path)) => {
+            info!("使用外部模型: {}", model_path);
+            YasOCRModel::from_paths(Path::new(model_path), Path::new(dict_path))
+        },
+        _ if config.game_lang != GameLanguage::Zh => {
+            let dir = LanguagePack::dir(config.game_lang, &config.lang_dir);
+            info!("使用语言模型: {}", dir.display());
+            YasOCRModel::from_paths(&dir.join("model_training.onnx"), &dir.join("index_2_word.json"))
+        },
+        _ => get_embedded_artifact_model(),
+    }
+}
+
+/// the embedded model, the one of `--game-lang` in `--lang-dir`, or the one given by `--model-path` and `--dict-path`
+pub(crate) fn get_image_to_text(config: &GenshinArtifactScannerConfig) -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
+    Ok(Box::new(get_model(config)?))
+}
+
+/// the dataset of `--dump-dataset`, shared by the models of all the workers
+pub(crate) fn get_dataset_dumper(config: &GenshinArtifactScannerConfig) -> Result<Option<Arc<DatasetDumper>>> {
+    match &config.dump_dataset {
+        Some(dir) => {
+            info!("保存训练数据到: {}", dir);
+            Ok(Some(Arc::new(DatasetDumper::new(dir, config.dump_max_confidence)?)))
+        },
+        None => Ok(None),
+    }
+}
+
+/// a region of a captured panel, an error if it's not inside the panel
+fn crop_region(image: &RgbImage, rect: Rect<f64>) -> Result<RgbImage> {
+    let (left, top, width, height) = (rect.left as u32, rect.top as u32, rect.width as u32, rect.height as u32);
+    if rect.left < 0.0 || rect.top < 0.0 || left + width > image.width() || top + height > image.height() {
+        return Err(anyhow::anyhow!("区域 {:?} 超出了 {}x{} 的截图", rect, image.width(), image.height()));
+    }
+
+    Ok(image.view(left, top, width, height).to_image())
+}
+
+/// asks for a recapture while the confidence is below `min_confidence`, at most `max_recapture` times
+fn confidence_verdict(confidence: f32, attempt: usize, min_confidence: f32, max_recapture: usize) -> Verdict {
+    if min_confidence <= 0.0 || confidence >= min_confidence {
+        return Verdict::default();
+    }
+
+    if attempt < max_recapture {
+        warn!("识别置信度 {:.3} 低于 {}，重新截图", confidence, min_confidence);
+        Verdict { recapture: Some(confidence), ..Default::default() }
+    } else {
+        warn!("识别置信度 {:.3} 低于 {}，已放弃重新截图", confidence, min_confidence);
+        Verdict::default()
+    }
+}
+
+/// the lock the rules want for a recognized artifact, `None` if it's already so or should be left as is
+fn wanted_lock(rules: &LockRules, result: &GenshinArtifactScanResult) -> Option<bool> {
+    let artifact = GenshinArtifact::try_from(result).ok()?;
+    rules.evaluate(&artifact).filter(|&wanted| wanted != result.lock)
+}
+
+/// how many items are recognized between the checkpoints
+const CHECKPOINT_INTERVAL: usize = 20;
+
+/// an item for an ocr worker, numbered in capture order
+type Job = (usize, SendItem, bool);
+
+/// a recognized item with its confidence, see `scan_panel`
+type ItemResult = Result<(GenshinArtifactScanResult, f32)>;
+
+/// whether an artifact is already known, e.g. in the export an incremental scan is based on
+pub type KnownFilter = Box<dyn Fn(&GenshinArtifact) -> bool + Send>;
+
+/// recognizes captured panels into artifacts, with its own model
+/// `run` runs several of them in parallel, in threads separate from capturing
 pub struct ArtifactScannerWorker {
     model: Box<dyn ImageToText<RgbImage> + Send>,
+    /// with `detect_icons`
+    icons: Option<IconTemplates>,
     window_info: ArtifactScannerWindowInfo,
     config: GenshinArtifactScannerConfig,
+    language: Arc<LanguagePack>,
+    /// evaluated against every accepted result
+    lock_rules: Option<LockRules>,
 }
 
 impl ArtifactScannerWorker {
+    /// every recognition of the worker is saved to `dumper`, if any
     pub fn new(
         window_info: ArtifactScannerWindowInfo,
         config: GenshinArtifactScannerConfig,
+        dumper: Option<Arc<DatasetDumper>>,
     ) -> Result<Self> {
+        let model: Box<dyn ImageToText<RgbImage> + Send> = match dumper {
+            Some(dumper) => Box::new(DumpingModel::new(get_model(&config)?, dumper)),
+            None => get_image_to_text(&config)?,
+        };
         Ok(ArtifactScannerWorker {
-            model: get_image_to_text()?,
+            model,
+            icons: config.detect_icons.then(|| IconTemplates::new(&window_info)),
             window_info,
             config,
+            language: language_pack(),
+            lock_rules: None,
         })
     }
 
+    /// the lock of every accepted result is decided by `rules`, see `Verdict::toggle_lock`
+    pub fn with_lock_rules(mut self, rules: Option<LockRules>) -> Self {
+        self.lock_rules = rules;
+        self
+    }
+
     /// the captured_img is a panel of the artifact, the rect is a region of the panel
-    fn model_inference(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<String> {
+    fn model_inference(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<(String, f32)> {
         let relative_rect = rect.translate(Pos {
             x: -self.window_info.panel_rect.left,
             y: -self.window_info.panel_rect.top,
         });
 
-        let raw_img = captured_img.view(
-            relative_rect.left as u32, relative_rect.top as u32, relative_rect.width as u32, relative_rect.height as u32,
-        ).to_image();
-
-        let inference_result = self.model.image_to_text(&raw_img, false);
+        let raw_img = crop_region(captured_img, relative_rect)?;
 
-        inference_result
+        self.model.image_to_text_with_confidence(&raw_img, false)
     }
 
     /// Parse the captured result (of type SendItem) to a scanned artifact
-    fn scan_item_image(&self, item: SendItem, lock: bool) -> Result<GenshinArtifactScanResult> {
-        let image = &item.panel_image;
-
-        let str_title = self.model_inference(self.window_info.title_rect, image)?;
-        let str_main_stat_name = self.model_inference(self.window_info.main_stat_name_rect, image)?;
-        let str_main_stat_value = self.model_inference(self.window_info.main_stat_value_rect, image)?;
-
-        let str_sub_stat0 = self.model_inference(self.window_info.sub_stat_1, image)?;
-        let str_sub_stat1 = self.model_inference(self.window_info.sub_stat_2, image)?;
-        let str_sub_stat2 = self.model_inference(self.window_info.sub_stat_3, image)?;
-        let str_sub_stat3 = self.model_inference(self.window_info.sub_stat_4, image)?;
-
-        let str_level = self.model_inference(self.window_info.level_rect, image)?;
-        let str_equip = self.model_inference(self.window_info.item_equip_rect, image)?;
+    fn scan_item_image(&self, item: SendItem, lock: bool) -> ItemResult {
+        let (mut result, confidence) = self.scan_panel(&item.panel_image, item.star, lock)?;
+        // the panel was captured before the lock was toggled
+        if let Some(lock) = item.lock_override {
+            result.lock = lock;
+        }
+        Ok((result, confidence))
+    }
 
-        anyhow::Ok(GenshinArtifactScanResult {
+    /// recognize a captured panel, `star` and `lock` are sampled elsewhere and used unless the icons are detected,
+    /// with the least confidence among the recognized regions
+    pub(crate) fn scan_panel(&self, image: &RgbImage, star: usize, lock: bool) -> ItemResult {
+        let (star, lock, equipped) = match &self.icons {
+            Some(icons) => {
+                let detected = icons.detect(image);
+                (detected.star.unwrap_or(star), detected.lock, detected.equipped)
+            },
+            None => (star, lock, true),
+        };
+
+        let mut confidence: f32 = 1.0;
+        let mut inference = |rect: Rect<f64>| -> Result<String> {
+            let (text, c) = self.model_inference(rect, image)?;
+            confidence = confidence.min(c);
+            Ok(text)
+        };
+
+        let language = &self.language;
+        let str_title = correct(&language.artifact_name_lexicon, inference(self.window_info.title_rect)?);
+        let str_main_stat_name = correct(&language.stat_name_lexicon, inference(self.window_info.main_stat_name_rect)?);
+        let str_main_stat_value = inference(self.window_info.main_stat_value_rect)?;
+
+        let str_sub_stat0 = correct_stat(language, inference(self.window_info.sub_stat_1)?);
+        let str_sub_stat1 = correct_stat(language, inference(self.window_info.sub_stat_2)?);
+        let str_sub_stat2 = correct_stat(language, inference(self.window_info.sub_stat_3)?);
+        let str_sub_stat3 = correct_stat(language, inference(self.window_info.sub_stat_4)?);
+
+        let str_level = inference(self.window_info.level_rect)?;
+        let str_equip = if equipped {
+            correct_equip(language, inference(self.window_info.item_equip_rect)?)
+        } else {
+            String::new()
+        };
+
+        let result = GenshinArtifactScanResult {
             name: str_title,
             main_stat_name: str_main_stat_name,
             main_stat_value: str_main_stat_value,
@@ -99,132 +251,349 @@ impl ArtifactScannerWorker {
             ],
             level: parse_level(&str_level)?,
             equip: str_equip,
-            star: item.star as i32,
+            star: star as i32,
             lock,
-        })
+        };
+        anyhow::Ok((result, confidence))
+    }
+
+    /// the verdict of a recognized item, which was recaptured `attempt` times before
+    fn verdict(&self, result: &ItemResult, attempt: usize) -> Verdict {
+        match result {
+            Ok((_, confidence)) => confidence_verdict(*confidence, attempt, self.config.min_confidence, self.config.max_recapture),
+            Err(_) => Verdict::default(),
+        }
     }
 
-    /// Get all lock state from a list image
-    fn get_page_locks(&self, list_image: &RgbImage) -> Vec<bool> {
-        let mut result = Vec::new();
+    /// evaluate an accepted result against the lock rules, and take the lock the capturing thread will toggle it to
+    /// returns that lock, `None` if it's left as is, or only reported in a dry run
+    fn apply_lock_rules(&self, result: &mut ItemResult) -> Option<bool> {
+        let (Some(rules), Ok((result, _))) = (&self.lock_rules, result) else {
+            return None;
+        };
+        let wanted = wanted_lock(rules, result)?;
+
+        let action = if wanted { "锁定" } else { "解锁" };
+        if self.config.lock_dry_run {
+            info!("[试运行] 将{}: {} +{}", action, result.name, result.level);
+            return None;
+        }
+
+        info!("{}: {} +{}", action, result.name, result.level);
+        result.lock = wanted;
+        Some(wanted)
+    }
+
+    /// recognize the captured items with all the workers, results are in capture order
+    /// if `is_known` is given, the scan stops after `stop_after_known` consecutive known artifacts
+    /// the results are reported to `observer`, with the progress towards `total` if it's known
+    /// with `checkpoint`, the results follow those of the checkpoint, and the progress is saved to its path as it goes
+    /// the verdicts of the items which want one are sent to `verdict_tx`, a recaptured item keeps the index of its
+    /// first capture, and only its accepted capture is a result
+    pub fn run(
+        workers: Vec<ArtifactScannerWorker>,
+        rx: Receiver<Option<SendItem>>,
+        verdict_tx: Sender<Verdict>,
+        is_known: Option<KnownFilter>,
+        observer: Option<Arc<dyn ScanObserver>>,
+        total: Option<usize>,
+        checkpoint: Option<(PathBuf, Checkpoint<GenshinArtifactScanResult>)>,
+    ) -> JoinHandle<Vec<GenshinArtifactScanResult>> {
+        std::thread::spawn(move || {
+            let (checkpoint_path, resumed) = match checkpoint {
+                Some((path, checkpoint)) => (Some(path), checkpoint),
+                None => (None, Checkpoint::default()),
+            };
+            let save_checkpoint = |position: usize, results: &Vec<GenshinArtifactScanResult>| {
+                if let Some(path) = &checkpoint_path {
+                    let checkpoint = Checkpoint { position: resumed.position + position, items: results.clone() };
+                    if let Err(e) = checkpoint.save(path) {
+                        warn!("保存扫描进度失败: {}", e);
+                    }
+                }
+            };
+
+            let mut results = resumed.items.clone();
+            let mut hash: HashSet<GenshinArtifactScanResult> = results.iter().cloned().collect();
+            // if too many artifacts are same in consecutive, then an error has occurred
+            let mut consecutive_dup_count = 0;
+            let mut consecutive_known_count = 0;
+
+            let config = workers[0].config.clone();
+            let is_verbose = config.verbose;
+            let min_level = config.min_level;
+            let stop_after_known = config.stop_after_known;
+            let info = workers[0].window_info.clone();
+
+            let (job_tx, job_rx) = mpsc::sync_channel::<Job>(workers.len() * 2);
+            let job_rx = Arc::new(Mutex::new(job_rx));
+            let (result_tx, result_rx) = mpsc::channel::<(usize, ItemResult)>();
+
+            // the captured items are numbered in order and handed to the ocr workers, whose results may finish out of
+            // order, and are put back in order below
+            let dispatcher_info = info.clone();
+            std::thread::spawn(move || {
+                let mut locks = Vec::new();
+                let mut count = 0;
+                // receiving None, which means the worker should end
+                for item in rx.into_iter().map_while(|item| item) {
+                    // a recapture is the item before it again
+                    if item.attempt == 0 {
+                        count += 1;
+                    }
+                    let index = count - 1;
 
-        let row = self.window_info.row;
-        let col = self.window_info.col;
-        let gap = self.window_info.item_gap_size;
-        let size = self.window_info.item_size;
-        let lock_pos = self.window_info.lock_pos;
+                    // if there is a list image, then parse the lock state
+                    if let Some(v) = item.list_image.as_ref() {
+                        locks.extend(get_page_locks(&dispatcher_info, v));
+                    }
 
-        for r in 0..row {
-            if ((gap.height + size.height) * (r as f64)) as u32 > list_image.height() {
-                break;
+                    // unlocked if the list has fewer items than expected
+                    let lock = item.lock_override.unwrap_or_else(|| locks.get(index).copied().unwrap_or(false));
+                    if job_tx.send((index, item, lock)).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            for worker in workers {
+                let job_rx = job_rx.clone();
+                let result_tx = result_tx.clone();
+                let verdict_tx = verdict_tx.clone();
+                std::thread::spawn(move || loop {
+                    let job = job_rx.lock().unwrap().recv();
+                    let Ok((index, item, lock)) = job else {
+                        break;
+                    };
+
+                    let (attempt, wants_verdict) = (item.attempt, item.wants_verdict);
+                    let mut result = worker.scan_item_image(item, lock);
+                    let mut verdict = if wants_verdict { worker.verdict(&result, attempt) } else { Verdict::default() };
+                    if verdict.recapture.is_none() {
+                        verdict.toggle_lock = worker.apply_lock_rules(&mut result);
+                    }
+                    if wants_verdict {
+                        // the capturing thread is gone if this fails, the result is still kept
+                        let _ = verdict_tx.send(verdict);
+                        if verdict.recapture.is_some() {
+                            continue;
+                        }
+                    }
+
+                    if result_tx.send((index, result)).is_err() {
+                        break;
+                    }
+                });
             }
-            for c in 0..col {
-                let pos_x = (gap.width + size.width) * (c as f64) + lock_pos.x;
-                let pos_y = (gap.height + size.height) * (r as f64) + lock_pos.y;
-
-                let mut locked = false;
-                'sq: for dx in -1..1 {
-                    for dy in -10..10 {
-                        if pos_y as i32 + dy < 0 || (pos_y as i32 + dy) as u32 >= list_image.height() {
+            drop(result_tx);
+            // the capturing thread stops waiting for verdicts once all the workers are gone
+            drop(verdict_tx);
+
+            let mut pending = BTreeMap::new();
+            let mut next_index = 0;
+            'results: for (index, result) in result_rx.iter() {
+                pending.insert(index, result);
+
+                while let Some(result) = pending.remove(&next_index) {
+                    if next_index > 0 && next_index % CHECKPOINT_INTERVAL == 0 {
+                        save_checkpoint(next_index, &results);
+                    }
+
+                    let index = next_index;
+                    next_index += 1;
+                    if let (Some(observer), Some(total)) = (&observer, total) {
+                        observer.on_event(&ScanEvent::progress(next_index, total));
+                    }
+
+                    let (result, confidence) = match result {
+                        Ok(v) => v,
+                        Err(e) => {
+                            error!("识别错误: {}", e);
+                            if let Some(observer) = &observer {
+                                observer.on_event(&ScanEvent::Error { index: Some(index), message: e.to_string() });
+                            }
                             continue;
                         }
+                    };
+
+                    if is_verbose {
+                        info!("{:?}", result);
+                    }
 
-                        let color = list_image
-                            .get_pixel((pos_x as i32 + dx) as u32, (pos_y as i32 + dy) as u32);
+                    if result.level < min_level {
+                        info!(
+                            "找到满足最低等级要求 {} 的物品({})，准备退出……",
+                            min_level, result.level
+                        );
+                        break 'results;
+                    }
+
+                    if let Some(is_known) = &is_known {
+                        let known = GenshinArtifact::try_from(&result).is_ok_and(|artifact| is_known(&artifact));
+                        consecutive_known_count = if known { consecutive_known_count + 1 } else { 0 };
+                        if stop_after_known > 0 && consecutive_known_count >= stop_after_known {
+                            info!("连续识别到 {} 件已有圣遗物，停止扫描", consecutive_known_count);
+                            break 'results;
+                        }
+                    }
 
-                        if color_distance(color, &Rgb([255, 138, 117])) < 30 {
-                            locked = true;
-                            break 'sq;
+                    if hash.contains(&result) {
+                        consecutive_dup_count += 1;
+                        warn!("识别到重复物品: {:#?}", result);
+                    } else {
+                        consecutive_dup_count = 0;
+                        if let Some(observer) = &observer {
+                            observer.on_event(&ScanEvent::Item {
+                                index,
+                                confidence,
+                                item: serde_json::to_value(&result).unwrap_or_default(),
+                            });
                         }
+                        hash.insert(result.clone());
+                        results.push(result);
+                    }
+
+                    if consecutive_dup_count >= info.col && !config.ignore_dup {
+                        error!("识别到连续多个重复物品，可能为翻页错误，或者为非背包顶部开始扫描");
+                        break 'results;
                     }
                 }
-                result.push(locked);
             }
-        }
-        result
-    }
 
-    pub fn run(self, rx: Receiver<Option<SendItem>>) -> JoinHandle<Vec<GenshinArtifactScanResult>> {
-        std::thread::spawn(move || {
-            let mut results = Vec::new();
-            let mut hash: HashSet<GenshinArtifactScanResult> = HashSet::new();
-            // if too many artifacts are same in consecutive, then an error has occurred
-            let mut consecutive_dup_count = 0;
+            save_checkpoint(next_index, &results);
+            info!("识别结束，非重复物品数量: {}", hash.len());
+
+            results
+        })
+    }
+}
 
-            let is_verbose = self.config.verbose;
-            let min_level = self.config.min_level;
-            let info = self.window_info.clone();
-            // todo remove dump mode to another scanner
-            // let dump_mode = false;
-            // let model = self.model.clone();
-            // let panel_origin = Pos { x: self.window_info.panel_rect.left, y: self.window_info.panel_rect.top };
+/// Get all lock state from a list image
+fn get_page_locks(window_info: &ArtifactScannerWindowInfo, list_image: &RgbImage) -> Vec<bool> {
+    let mut result = Vec::new();
 
-            let mut locks = Vec::new();
-            let mut artifact_index: i32 = 0;
+    let row = window_info.row;
+    let col = window_info.col;
+    let gap = window_info.item_gap_size;
+    let size = window_info.item_size;
+    let lock_pos = window_info.lock_pos;
 
-            for item in rx.into_iter() {
-                // receiving None, which means the worker should end
-                let item = match item {
-                    Some(v) => v,
-                    None => break,
-                };
-
-                // if there is a list image, then parse the lock state
-                match item.list_image.as_ref() {
-                    Some(v) => {
-                        locks = vec![locks, self.get_page_locks(v)].concat()
-                    }
-                    None => {}
-                };
-
-                artifact_index += 1;
-                let result = match self.scan_item_image(item, locks[artifact_index as usize - 1]) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        error!("识别错误: {}", e);
+    for r in 0..row {
+        if ((gap.height + size.height) * (r as f64)) as u32 > list_image.height() {
+            break;
+        }
+        for c in 0..col {
+            let pos_x = (gap.width + size.width) * (c as f64) + lock_pos.x;
+            let pos_y = (gap.height + size.height) * (r as f64) + lock_pos.y;
+
+            let mut locked = false;
+            'sq: for dx in -1..1 {
+                for dy in -10..10 {
+                    if pos_y as i32 + dy < 0 || (pos_y as i32 + dy) as u32 >= list_image.height() {
                         continue;
                     }
-                };
 
-                if is_verbose {
-                    info!("{:?}", result);
-                }
+                    let color = list_image
+                        .get_pixel((pos_x as i32 + dx) as u32, (pos_y as i32 + dy) as u32);
 
-                if result.level < min_level {
-                    info!(
-                        "找到满足最低等级要求 {} 的物品({})，准备退出……",
-                        min_level, result.level
-                    );
-                    break;
+                    if color_distance(color, &Rgb([255, 138, 117])) < 30 {
+                        locked = true;
+                        break 'sq;
+                    }
                 }
+            }
+            result.push(locked);
+        }
+    }
+    result
+}
 
-                if hash.contains(&result) {
-                    consecutive_dup_count += 1;
-                    warn!("识别到重复物品: {:#?}", result);
-                } else {
-                    consecutive_dup_count = 0;
-                    hash.insert(result.clone());
-                    results.push(result);
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_confidence_is_recaptured_a_few_times() {
+        assert_eq!(confidence_verdict(0.5, 0, 0.0, 2).recapture, None);
+        assert_eq!(confidence_verdict(0.95, 0, 0.9, 2).recapture, None);
+        assert_eq!(confidence_verdict(0.5, 0, 0.9, 2).recapture, Some(0.5));
+        assert_eq!(confidence_verdict(0.5, 1, 0.9, 2).recapture, Some(0.5));
+        // accepted anyway after the last recapture
+        assert_eq!(confidence_verdict(0.5, 2, 0.9, 2).recapture, None);
+    }
 
-                if consecutive_dup_count >= info.col && !self.config.ignore_dup {
-                    error!("识别到连续多个重复物品，可能为翻页错误，或者为非背包顶部开始扫描");
-                    // token.cancel();
-                    break;
-                }
+    #[test]
+    fn page_locks_are_read_from_the_list() {
+        use yas::game_info::{Platform, UI};
+        use yas::window_info::FromWindowInfoRepository;
+
+        let repo = crate::application::ArtifactScannerApplication::get_window_info_repository();
+        let info = ArtifactScannerWindowInfo::from_window_info_repository(
+            yas::positioning::Size::new(1600, 900), UI::Desktop, Platform::Windows, &repo,
+        ).unwrap();
+        let (col, row) = (info.col as usize, info.row as usize);
+
+        let cell_width = info.item_gap_size.width + info.item_size.width;
+        let cell_height = info.item_gap_size.height + info.item_size.height;
+        let mut list = RgbImage::new((cell_width * col as f64) as u32, (cell_height * row as f64) as u32);
+        let locked = [0, 9, col * row - 1];
+        for &index in locked.iter() {
+            let x = cell_width * (index % col) as f64 + info.lock_pos.x;
+            let y = cell_height * (index / col) as f64 + info.lock_pos.y;
+            list.put_pixel(x as u32, y as u32, Rgb([255, 138, 117]));
+        }
 
-                // if token.cancelled() {
-                // error!("扫描任务被取消");
-                // break;
-                // }
-            }
+        let locks = get_page_locks(&info, &list);
+        assert_eq!(locks.len(), col * row);
+        for (index, lock) in locks.iter().enumerate() {
+            assert_eq!(*lock, locked.contains(&index), "{}", index);
+        }
+    }
 
-            info!("识别结束，非重复物品数量: {}", hash.len());
+    #[test]
+    fn regions_outside_the_panel_are_errors() {
+        let panel = RgbImage::new(100, 50);
+        let rect = |left, top, width, height| Rect { left, top, width, height };
+        assert_eq!(crop_region(&panel, rect(10.0, 10.0, 90.0, 40.0)).unwrap().dimensions(), (90, 40));
+        assert!(crop_region(&panel, rect(10.0, 10.0, 91.0, 20.0)).is_err());
+        assert!(crop_region(&panel, rect(-5.0, 10.0, 20.0, 20.0)).is_err());
+        assert!(crop_region(&panel, rect(0.0, 40.0, 20.0, 20.0)).is_err());
+    }
 
-            // progress_bar.finish();
-            // MULTI_PROGRESS.remove(&progress_bar);
 
-            results
-        })
+    fn scan_result(star: i32, lock: bool) -> GenshinArtifactScanResult {
+        GenshinArtifactScanResult {
+            name: String::from("角斗士的留恋"),
+            main_stat_name: String::from("生命值"),
+            main_stat_value: String::from("4,780"),
+            sub_stat: [
+                String::from("暴击率+10.5%"),
+                String::from("暴击伤害+21.0%"),
+                String::from("攻击力+19"),
+                String::from("元素精通+23"),
+            ],
+            level: 20,
+            equip: String::new(),
+            star,
+            lock,
+        }
+    }
+
+    #[test]
+    fn lock_rules_decide_the_toggles() {
+        let rules: LockRules = toml::from_str("unlock_others = true\n[[rules]]\nmin_star = 5\nmin_crit_value = 40.0").unwrap();
+        // crit value 42
+        assert_eq!(wanted_lock(&rules, &scan_result(5, false)), Some(true));
+        assert_eq!(wanted_lock(&rules, &scan_result(5, true)), None);
+        assert_eq!(wanted_lock(&rules, &scan_result(4, true)), Some(false));
+        assert_eq!(wanted_lock(&rules, &scan_result(4, false)), None);
+
+        let keep_others: LockRules = toml::from_str("[[rules]]\nmin_star = 5").unwrap();
+        assert_eq!(wanted_lock(&keep_others, &scan_result(4, true)), None);
+
+        // what can't be parsed is left as is
+        let mut unknown = scan_result(5, false);
+        unknown.name = String::from("未知");
+        assert_eq!(wanted_lock(&rules, &unknown), None);
     }
 }
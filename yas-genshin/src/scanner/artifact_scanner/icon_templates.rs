@@ -0,0 +1,127 @@
+use image::{GenericImageView, GrayImage, RgbImage};
+
+use yas::detector::{detect_equipped, detect_lock, detect_star_count, padlock_template, ring_template, star_template};
+use yas::positioning::{Pos, Rect};
+
+use super::ArtifactScannerWindowInfo;
+
+const STAR_THRESHOLD: f32 = 0.6;
+const LOCK_THRESHOLD: f32 = 0.5;
+const EQUIP_THRESHOLD: f32 = 0.5;
+
+/// the icons of the artifact panel, rendered rather than cropped from the game, so that they scale with the window
+pub struct IconTemplates {
+    star: GrayImage,
+    padlock: GrayImage,
+    avatar: GrayImage,
+    window_info: ArtifactScannerWindowInfo,
+}
+
+/// what the icons of a panel tell
+pub struct DetectedIcons {
+    /// `None` if no star is found, or too many
+    pub star: Option<usize>,
+    pub lock: bool,
+    pub equipped: bool,
+}
+
+impl IconTemplates {
+    pub fn new(window_info: &ArtifactScannerWindowInfo) -> IconTemplates {
+        IconTemplates {
+            star: star_template(window_info.star_rect.height as u32),
+            padlock: padlock_template((window_info.lock_rect.height * 0.6) as u32),
+            avatar: ring_template((window_info.equip_icon_rect.height * 0.9) as u32),
+            window_info: window_info.clone(),
+        }
+    }
+
+    /// the rect of the window in a captured panel, in gray
+    fn crop(&self, panel: &RgbImage, rect: Rect<f64>) -> GrayImage {
+        let rect = rect.translate(Pos {
+            x: -self.window_info.panel_rect.left,
+            y: -self.window_info.panel_rect.top,
+        });
+        let im = panel.view(rect.left as u32, rect.top as u32, rect.width as u32, rect.height as u32).to_image();
+        image::imageops::grayscale(&im)
+    }
+
+    pub fn detect(&self, panel: &RgbImage) -> DetectedIcons {
+        let info = &self.window_info;
+        let star = detect_star_count(&self.crop(panel, info.star_rect), &self.star, STAR_THRESHOLD);
+
+        DetectedIcons {
+            star: if (1..=5).contains(&star) { Some(star) } else { None },
+            lock: detect_lock(&self.crop(panel, info.lock_rect), &self.padlock, LOCK_THRESHOLD),
+            equipped: detect_equipped(&self.crop(panel, info.equip_icon_rect), &self.avatar, EQUIP_THRESHOLD),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::imageops::overlay;
+    use image::DynamicImage;
+
+    use yas::game_info::{Platform, UI};
+    use yas::positioning::Size;
+    use yas::window_info::FromWindowInfoRepository;
+
+    use super::*;
+
+    fn window_info(width: usize, height: usize) -> ArtifactScannerWindowInfo {
+        let repo = crate::application::ArtifactScannerApplication::get_window_info_repository();
+        ArtifactScannerWindowInfo::from_window_info_repository(Size::new(width, height), UI::Desktop, Platform::Windows, &repo)
+            .unwrap()
+    }
+
+    fn offset(rect: Rect<f64>, panel: Rect<f64>) -> (f64, f64, f64) {
+        (rect.left - panel.left, rect.top - panel.top, rect.width)
+    }
+
+    #[test]
+    fn icon_rects_are_scaled_from_1600x900() {
+        let base = window_info(1600, 900);
+        for (width, height) in [(1280, 960), (1440, 900), (2100, 900), (3440, 1440)] {
+            let info = window_info(width, height);
+            let scale = (width as f64 / 1600.0).min(height as f64 / 900.0);
+            for (rect, base_rect) in [(info.lock_rect, base.lock_rect), (info.star_rect, base.star_rect)] {
+                let (x, y, w) = offset(rect, info.panel_rect);
+                let (bx, by, bw) = offset(base_rect, base.panel_rect);
+                assert!((x - bx * scale).abs() < 0.1, "{}x{}: {} != {}", width, height, x, bx * scale);
+                assert!((y - by * scale).abs() < 0.1, "{}x{}: {} != {}", width, height, y, by * scale);
+                assert!((w - bw * scale).abs() < 0.1, "{}x{}: {} != {}", width, height, w, bw * scale);
+            }
+        }
+    }
+
+    fn paste(panel: &mut RgbImage, info: &ArtifactScannerWindowInfo, rect: Rect<f64>, icon: &GrayImage, x: f64) {
+        let icon = DynamicImage::ImageLuma8(icon.clone()).to_rgb8();
+        let left = (rect.left - info.panel_rect.left + x) as i64;
+        let top = (rect.top - info.panel_rect.top + (rect.height - icon.height() as f64) / 2.0) as i64;
+        overlay(panel, &icon, left, top);
+    }
+
+    #[test]
+    fn detect_the_icons_of_a_panel() {
+        let info = window_info(1600, 900);
+        let templates = IconTemplates::new(&info);
+        let blank = RgbImage::new(info.panel_rect.width as u32, info.panel_rect.height as u32);
+
+        let mut panel = blank.clone();
+        for i in 0..4 {
+            paste(&mut panel, &info, info.star_rect, &templates.star, 2.0 + 26.0 * i as f64);
+        }
+        paste(&mut panel, &info, info.lock_rect, &templates.padlock, 6.0);
+        paste(&mut panel, &info, info.equip_icon_rect, &templates.avatar, 2.0);
+
+        let icons = templates.detect(&panel);
+        assert_eq!(icons.star, Some(4));
+        assert!(icons.lock);
+        assert!(icons.equipped);
+
+        let icons = templates.detect(&blank);
+        assert_eq!(icons.star, None);
+        assert!(!icons.lock);
+        assert!(!icons.equipped);
+    }
+}
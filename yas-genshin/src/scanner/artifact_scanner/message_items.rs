@@ -5,4 +5,20 @@ pub struct SendItem {
     pub panel_image: RgbImage,
     pub star: usize,
     pub list_image: Option<RgbImage>,
+    /// the lock state known while capturing, e.g. sampled from the panel of a resumed scan,
+    /// or of a recorded frame without the list
+    pub lock_override: Option<bool>,
+    /// 0 for the first capture of an item, counts the recaptures asked by `Verdict::recapture`
+    pub attempt: usize,
+    /// the capturing thread waits for the `Verdict` of this item before moving on
+    pub wants_verdict: bool,
+}
+
+/// sent back by a worker for an item which `wants_verdict`, while the item is still shown
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Verdict {
+    /// the item is recognized with this low confidence, and should be captured again
+    pub recapture: Option<f32>,
+    /// the lock rules want the item locked (true) or unlocked (false), by clicking the lock button of the panel
+    pub toggle_lock: Option<bool>,
 }
@@ -3,9 +3,13 @@ pub use artifact_scanner_config::GenshinArtifactScannerConfig;
 pub use artifact_scanner_window_info::ArtifactScannerWindowInfo;
 pub use scan_result::GenshinArtifactScanResult;
 
+pub use artifact_scanner_worker::get_embedded_artifact_model;
+pub(crate) use artifact_scanner_worker::get_image_to_text;
+
 mod artifact_scanner;
 mod artifact_scanner_config;
 mod scan_result;
 mod artifact_scanner_worker;
 mod artifact_scanner_window_info;
 mod message_items;
+mod icon_templates;
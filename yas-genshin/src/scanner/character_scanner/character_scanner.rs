@@ -0,0 +1,206 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use clap::FromArgMatches;
+use image::RgbImage;
+use log::{info, warn};
+
+use yas::capture::{Capturer, GenericCapturer};
+use yas::game_info::GameInfo;
+use yas::ocr::ImageToText;
+use yas::positioning::{Pos, Rect};
+use yas::scan_control::ScanControl;
+use yas::system_control::SystemControl;
+use yas::utils;
+use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
+
+use crate::character::GenshinCharacter;
+use crate::language::{language_pack, set_language_pack, LanguagePack};
+use crate::scanner::artifact_scanner::{get_image_to_text, GenshinArtifactScannerConfig};
+use crate::scanner::level_parser::{ascension_from_max_level, parse_level_and_max, parse_number};
+
+use super::{CharacterScannerWindowInfo, GenshinCharacterScannerConfig};
+
+/// an activated constellation node is lit, a locked one is dimmed, tell them by the mean of the channels
+const ACTIVATED_CONSTELLATION_BRIGHTNESS: u32 = 150;
+
+/// scans the characters one by one in the character screen, from the current one until it comes back
+pub struct GenshinCharacterScanner {
+    scanner_config: GenshinCharacterScannerConfig,
+    window_info: CharacterScannerWindowInfo,
+    game_info: GameInfo,
+    image_to_text: Box<dyn ImageToText<RgbImage> + Send>,
+    language: Arc<LanguagePack>,
+    capturer: Rc<dyn Capturer<RgbImage>>,
+    system_control: SystemControl,
+}
+
+// constructor
+impl GenshinCharacterScanner {
+    pub fn new(
+        window_info_repo: &WindowInfoRepository,
+        config: GenshinCharacterScannerConfig,
+        artifact_config: &GenshinArtifactScannerConfig,
+        game_info: GameInfo,
+    ) -> Result<Self> {
+        set_language_pack(LanguagePack::load(artifact_config.game_lang, &artifact_config.lang_dir)?);
+        Ok(Self {
+            image_to_text: get_image_to_text(artifact_config)?,
+            language: language_pack(),
+            scanner_config: config,
+            window_info: CharacterScannerWindowInfo::from_window_info_repository(
+                game_info.window.to_rect_usize().size(),
+                game_info.ui,
+                game_info.platform,
+                window_info_repo,
+            )?,
+            game_info,
+            capturer: Rc::new(GenericCapturer::new()?),
+            system_control: SystemControl::new(),
+        })
+    }
+
+    /// the model is configured by the arguments of the artifact scanner
+    pub fn from_arg_matches(
+        window_info_repo: &WindowInfoRepository,
+        arg_matches: &clap::ArgMatches,
+        game_info: GameInfo,
+    ) -> Result<Self> {
+        Self::new(
+            window_info_repo,
+            GenshinCharacterScannerConfig::from_arg_matches(arg_matches)?,
+            &GenshinArtifactScannerConfig::from_arg_matches(arg_matches)?,
+            game_info,
+        )
+    }
+}
+
+impl GenshinCharacterScanner {
+    fn click(&mut self, pos: Pos<f64>) -> Result<()> {
+        self.system_control.mouse_move_to(
+            self.game_info.window.left + pos.x as i32,
+            self.game_info.window.top + pos.y as i32,
+        )?;
+        self.system_control.mouse_click()?;
+        utils::sleep(self.scanner_config.switch_delay);
+        Ok(())
+    }
+
+    fn recognize(&self, rect: Rect<f64>) -> Result<String> {
+        let im = self.capturer.capture_relative_to(rect.to_rect_i32(), self.game_info.window.origin())?;
+        self.image_to_text.image_to_text(&im, false)
+    }
+
+    /// leave the repository and open the character screen
+    fn open_character_screen(&mut self) -> Result<()> {
+        self.system_control.key_escape()?;
+        utils::sleep(1000);
+        self.system_control.key_click('c')?;
+        utils::sleep(1500);
+        Ok(())
+    }
+
+    fn get_constellation(&self) -> Result<i32> {
+        let info = &self.window_info;
+        let nodes = [
+            info.constellation_1,
+            info.constellation_2,
+            info.constellation_3,
+            info.constellation_4,
+            info.constellation_5,
+            info.constellation_6,
+        ];
+
+        let mut ans = 0;
+        for node in nodes.iter() {
+            let color = self.capturer.capture_color(Pos {
+                x: self.game_info.window.left + node.x as i32,
+                y: self.game_info.window.top + node.y as i32,
+            })?;
+            let brightness = color.0.iter().map(|&c| c as u32).sum::<u32>() / 3;
+            // constellations are activated in order
+            if brightness < ACTIVATED_CONSTELLATION_BRIGHTNESS {
+                break;
+            }
+            ans += 1;
+        }
+
+        Ok(ans)
+    }
+
+    fn get_talent(&self, rect: Rect<f64>) -> Result<i32> {
+        let s = self.recognize(rect)?;
+        parse_number(&s).ok_or_else(|| anyhow!("无法识别天赋等级: {}", s))
+    }
+
+    fn get_name(&self) -> Result<String> {
+        let raw_name = self.recognize(self.window_info.name_rect)?;
+        Ok(self.language.character_name(&raw_name))
+    }
+
+    /// the character currently shown, whose attribute tab is open
+    fn scan_character(&mut self, name: String) -> Result<GenshinCharacter> {
+        let str_level = self.recognize(self.window_info.level_rect)?;
+        let (level, max_level) = parse_level_and_max(&str_level)
+            .ok_or_else(|| anyhow!("无法识别角色等级: {}", str_level))?;
+
+        self.click(self.window_info.constellation_tab_pos)?;
+        let constellation = self.get_constellation()?;
+
+        self.click(self.window_info.talent_tab_pos)?;
+        let talent_auto = self.get_talent(self.window_info.talent_auto_rect)?;
+        let talent_skill = self.get_talent(self.window_info.talent_skill_rect)?;
+        let talent_burst = self.get_talent(self.window_info.talent_burst_rect)?;
+
+        Ok(GenshinCharacter {
+            name,
+            level,
+            ascension: ascension_from_max_level(max_level),
+            constellation,
+            talent_auto,
+            talent_skill,
+            talent_burst,
+        })
+    }
+
+    /// open the character screen and scan every character, until the first one shows again
+    pub fn scan(&mut self) -> Result<Vec<GenshinCharacter>> {
+        info!("开始扫描角色");
+        ScanControl::global().start();
+
+        self.open_character_screen()?;
+
+        let mut results: Vec<GenshinCharacter> = Vec::new();
+        let mut first_name: Option<String> = None;
+
+        for _ in 0..self.scanner_config.max_characters {
+            if ScanControl::global().should_stop() {
+                info!("用户中断");
+                break;
+            }
+
+            self.click(self.window_info.attribute_tab_pos)?;
+            let name = self.get_name()?;
+            if first_name.as_deref() == Some(name.as_str()) {
+                break;
+            }
+            if first_name.is_none() {
+                first_name = Some(name.clone());
+            }
+
+            match self.scan_character(name) {
+                Ok(character) => {
+                    info!("{:?}", character);
+                    results.push(character);
+                },
+                Err(e) => warn!("识别错误: {}", e),
+            }
+
+            self.click(self.window_info.next_pos)?;
+        }
+
+        info!("角色扫描结束，共识别到 {} 个角色", results.len());
+        Ok(results)
+    }
+}
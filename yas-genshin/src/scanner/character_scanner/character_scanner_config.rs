@@ -0,0 +1,14 @@
+#[derive(Clone, clap::Args)]
+pub struct GenshinCharacterScannerConfig {
+    /// scan the characters after the artifacts (and weapons), only exported in GOOD
+    #[arg(id = "scan-characters", long = "scan-characters", help = "扫描圣遗物后扫描角色（仅导出到 GOOD 格式）")]
+    pub scan_characters: bool,
+
+    /// stop after this many characters, in case the first one is never recognized again
+    #[arg(id = "max-characters", long = "max-characters", help = "最多扫描的角色数量", value_name = "MAX_CHARACTERS", default_value_t = 120)]
+    pub max_characters: usize,
+
+    /// how long to wait after switching a tab or a character, in ms
+    #[arg(id = "character-switch-delay", long = "character-switch-delay", help = "切换角色或页面后的等待时间（毫秒）", value_name = "DELAY", default_value_t = 800)]
+    pub switch_delay: u32,
+}
@@ -0,0 +1,47 @@
+use yas::positioning::{Pos, Rect};
+
+/// positions of the character screen, relative to window
+#[derive(Clone, yas_derive::YasWindowInfo, Debug)]
+pub struct CharacterScannerWindowInfo {
+    #[window_info(rename = "genshin_character_name_rect")]
+    pub name_rect: Rect<f64>,
+
+    /// e.g. "等级90/90", on the attribute tab
+    #[window_info(rename = "genshin_character_level_rect")]
+    pub level_rect: Rect<f64>,
+
+    #[window_info(rename = "genshin_character_attribute_tab_pos")]
+    pub attribute_tab_pos: Pos<f64>,
+
+    #[window_info(rename = "genshin_character_constellation_tab_pos")]
+    pub constellation_tab_pos: Pos<f64>,
+
+    #[window_info(rename = "genshin_character_talent_tab_pos")]
+    pub talent_tab_pos: Pos<f64>,
+
+    /// switches to the next character
+    #[window_info(rename = "genshin_character_next_pos")]
+    pub next_pos: Pos<f64>,
+
+    /// the levels of the talents, e.g. "Lv.10", on the talent tab
+    #[window_info(rename = "genshin_character_talent_auto_rect")]
+    pub talent_auto_rect: Rect<f64>,
+    #[window_info(rename = "genshin_character_talent_skill_rect")]
+    pub talent_skill_rect: Rect<f64>,
+    #[window_info(rename = "genshin_character_talent_burst_rect")]
+    pub talent_burst_rect: Rect<f64>,
+
+    /// the nodes of the constellations, on the constellation tab
+    #[window_info(rename = "genshin_character_constellation1_pos")]
+    pub constellation_1: Pos<f64>,
+    #[window_info(rename = "genshin_character_constellation2_pos")]
+    pub constellation_2: Pos<f64>,
+    #[window_info(rename = "genshin_character_constellation3_pos")]
+    pub constellation_3: Pos<f64>,
+    #[window_info(rename = "genshin_character_constellation4_pos")]
+    pub constellation_4: Pos<f64>,
+    #[window_info(rename = "genshin_character_constellation5_pos")]
+    pub constellation_5: Pos<f64>,
+    #[window_info(rename = "genshin_character_constellation6_pos")]
+    pub constellation_6: Pos<f64>,
+}
@@ -0,0 +1,7 @@
+pub use character_scanner::GenshinCharacterScanner;
+pub use character_scanner_config::GenshinCharacterScannerConfig;
+pub use character_scanner_window_info::CharacterScannerWindowInfo;
+
+mod character_scanner;
+mod character_scanner_config;
+mod character_scanner_window_info;
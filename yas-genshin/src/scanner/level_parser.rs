@@ -0,0 +1,24 @@
+/// the level and the max level of e.g. "Lv.90/90" or "等级80/90"
+pub(crate) fn parse_level_and_max(s: &str) -> Option<(i32, i32)> {
+    let (level, max_level) = s.split_once('/')?;
+    Some((parse_number(level)?, parse_number(max_level)?))
+}
+
+/// the digits in a recognized string, e.g. 1 of "精炼1阶", 10 of "Lv.10"
+pub(crate) fn parse_number(s: &str) -> Option<i32> {
+    let digits: String = s.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits.parse::<i32>().ok()
+}
+
+/// the ascension is told by the max level, which is raised by each ascension
+pub(crate) fn ascension_from_max_level(max_level: i32) -> i32 {
+    match max_level {
+        ..=20 => 0,
+        21..=40 => 1,
+        41..=50 => 2,
+        51..=60 => 3,
+        61..=70 => 4,
+        71..=80 => 5,
+        _ => 6,
+    }
+}
@@ -1,7 +1,12 @@
 pub use artifact_scanner::GenshinArtifactScanner;
 pub use artifact_scanner::GenshinArtifactScannerConfig;
 pub use artifact_scanner::GenshinArtifactScanResult;
+pub use artifact_scanner::get_embedded_artifact_model;
+pub use character_scanner::{GenshinCharacterScanner, GenshinCharacterScannerConfig};
+pub use weapon_scanner::{GenshinWeaponScanner, GenshinWeaponScannerConfig};
 
 mod artifact_scanner;
+mod character_scanner;
+mod weapon_scanner;
+mod level_parser;
 // mod item_scanner;
-
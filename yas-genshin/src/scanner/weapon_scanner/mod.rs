@@ -0,0 +1,7 @@
+pub use weapon_scanner::GenshinWeaponScanner;
+pub use weapon_scanner_config::GenshinWeaponScannerConfig;
+pub use weapon_scanner_window_info::WeaponScannerWindowInfo;
+
+mod weapon_scanner;
+mod weapon_scanner_config;
+mod weapon_scanner_window_info;
@@ -0,0 +1,250 @@
+use std::{cell::RefCell, ops::{Coroutine, CoroutineState}, pin::Pin, rc::Rc, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use clap::FromArgMatches;
+use image::{GenericImageView, RgbImage};
+use log::{error, info, warn};
+
+use yas::capture::{Capturer, GenericCapturer};
+use yas::game_info::GameInfo;
+use yas::ocr::ImageToText;
+use yas::positioning::{Pos, Rect};
+use yas::scan_control::ScanControl;
+use yas::system_control::SystemControl;
+use yas::utils;
+use yas::utils::color_distance;
+use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
+
+use crate::language::{language_pack, set_language_pack, LanguagePack};
+use crate::scanner::artifact_scanner::{get_image_to_text, GenshinArtifactScannerConfig};
+use crate::scanner::level_parser::{ascension_from_max_level, parse_level_and_max, parse_number};
+use crate::scanner_controller::repository_layout::{
+    GenshinRepositoryScanController,
+    GenshinRepositoryScannerLogicConfig,
+    ReturnResult as GenshinRepositoryControllerReturnResult,
+};
+use crate::weapon::GenshinWeapon;
+
+use super::{GenshinWeaponScannerConfig, WeaponScannerWindowInfo};
+
+/// the colors of the panel background at `star_pos`, of 1 to 5 stars
+const STAR_COLORS: [image::Rgb<u8>; 5] = [
+    image::Rgb([113, 119, 139]),
+    image::Rgb([42, 143, 114]),
+    image::Rgb([81, 127, 203]),
+    image::Rgb([161, 86, 224]),
+    image::Rgb([188, 105, 50]),
+];
+
+/// the colors of the lock button at `lock_pos`, locked and unlocked
+const LOCK_COLORS: [image::Rgb<u8>; 2] = [
+    image::Rgb([74, 83, 102]),
+    image::Rgb([243, 239, 234]),
+];
+
+/// scans the weapon tab of the repository, which is laid out like the artifact tab,
+/// so the same controller walks the grid. weapons are few, so they are recognized right after capturing
+pub struct GenshinWeaponScanner {
+    scanner_config: GenshinWeaponScannerConfig,
+    window_info: WeaponScannerWindowInfo,
+    game_info: GameInfo,
+    image_to_text: Box<dyn ImageToText<RgbImage> + Send>,
+    language: Arc<LanguagePack>,
+    controller: Rc<RefCell<GenshinRepositoryScanController>>,
+    capturer: Rc<dyn Capturer<RgbImage>>,
+    system_control: SystemControl,
+}
+
+// constructor
+impl GenshinWeaponScanner {
+    pub fn new(
+        window_info_repo: &WindowInfoRepository,
+        config: GenshinWeaponScannerConfig,
+        artifact_config: &GenshinArtifactScannerConfig,
+        controller_config: GenshinRepositoryScannerLogicConfig,
+        game_info: GameInfo,
+    ) -> Result<Self> {
+        set_language_pack(LanguagePack::load(artifact_config.game_lang, &artifact_config.lang_dir)?);
+        Ok(Self {
+            image_to_text: get_image_to_text(artifact_config)?,
+            language: language_pack(),
+            scanner_config: config,
+            window_info: WeaponScannerWindowInfo::from_window_info_repository(
+                game_info.window.to_rect_usize().size(),
+                game_info.ui,
+                game_info.platform,
+                window_info_repo,
+            )?,
+            controller: Rc::new(RefCell::new(
+                GenshinRepositoryScanController::new(window_info_repo, controller_config, game_info.clone(), false)?
+            )),
+            game_info,
+            capturer: Rc::new(GenericCapturer::new()?),
+            system_control: SystemControl::new(),
+        })
+    }
+
+    /// the model is configured by the arguments of the artifact scanner
+    pub fn from_arg_matches(
+        window_info_repo: &WindowInfoRepository,
+        arg_matches: &clap::ArgMatches,
+        game_info: GameInfo,
+    ) -> Result<Self> {
+        Self::new(
+            window_info_repo,
+            GenshinWeaponScannerConfig::from_arg_matches(arg_matches)?,
+            &GenshinArtifactScannerConfig::from_arg_matches(arg_matches)?,
+            GenshinRepositoryScannerLogicConfig::from_arg_matches(arg_matches)?,
+            game_info,
+        )
+    }
+}
+
+impl GenshinWeaponScanner {
+    fn capture_color(&self, pos: Pos<f64>) -> Result<image::Rgb<u8>> {
+        self.capturer.capture_color(Pos {
+            x: self.game_info.window.left + pos.x as i32,
+            y: self.game_info.window.top + pos.y as i32,
+        })
+    }
+
+    pub fn get_star(&self) -> Result<usize> {
+        let color = self.capture_color(self.window_info.star_pos)?;
+        let (index, _) = STAR_COLORS.iter()
+            .enumerate()
+            .min_by_key(|&(_, star_color)| color_distance(star_color, &color))
+            .unwrap();
+
+        Ok(index + 1)
+    }
+
+    pub fn get_lock(&self) -> Result<bool> {
+        let color = self.capture_color(self.window_info.lock_pos)?;
+        Ok(color_distance(&LOCK_COLORS[0], &color) < color_distance(&LOCK_COLORS[1], &color))
+    }
+
+    pub fn capture_panel(&self) -> Result<RgbImage> {
+        self.capturer.capture_relative_to(
+            self.window_info.panel_rect.to_rect_i32(),
+            self.game_info.window.origin(),
+        )
+    }
+
+    /// the rect is a region of the panel
+    fn model_inference(&self, rect: Rect<f64>, panel: &RgbImage) -> Result<String> {
+        let rect = rect.translate(Pos {
+            x: -self.window_info.panel_rect.left,
+            y: -self.window_info.panel_rect.top,
+        });
+        let im = panel.view(rect.left as u32, rect.top as u32, rect.width as u32, rect.height as u32).to_image();
+        self.image_to_text.image_to_text(&im, false)
+    }
+
+    fn open_weapon_tab(&mut self) -> Result<()> {
+        let pos = self.window_info.weapon_tab_pos;
+        self.system_control.mouse_move_to(
+            self.game_info.window.left + pos.x as i32,
+            self.game_info.window.top + pos.y as i32,
+        )?;
+        self.system_control.mouse_click()?;
+        utils::sleep(1000);
+        Ok(())
+    }
+
+    pub fn get_item_count(&self) -> Result<usize> {
+        let max_count = 2000;
+
+        let im = self.capturer.capture_relative_to(
+            self.window_info.item_count_rect.to_rect_i32(),
+            self.game_info.window.origin(),
+        )?;
+        let s = self.image_to_text.image_to_text(&im, false)?;
+        info!("物品信息: {}", s);
+
+        // e.g. "武器123/2000"
+        let count = s.strip_prefix(self.language.language.weapon_count_name())
+            .and_then(|s| s.split_once('/'))
+            .and_then(|(count, _)| parse_number(count));
+        Ok(match count {
+            Some(count) => (count as usize).min(max_count),
+            None => max_count,
+        })
+    }
+
+    fn scan_panel(&self, panel: &RgbImage, star: usize, lock: bool) -> Result<GenshinWeapon> {
+        let info = &self.window_info;
+
+        let raw_name = self.model_inference(info.title_rect, panel)?;
+        let name = self.language.weapon_name(&raw_name);
+
+        let str_level = self.model_inference(info.level_rect, panel)?;
+        let (level, max_level) = parse_level_and_max(&str_level)
+            .ok_or_else(|| anyhow!("无法识别武器等级: {}", str_level))?;
+
+        let str_refinement = self.model_inference(info.refinement_rect, panel)?;
+        let refinement = parse_number(&str_refinement)
+            .ok_or_else(|| anyhow!("无法识别精炼等级: {}", str_refinement))?;
+
+        // e.g. "胡桃已装备"
+        let str_equip = self.model_inference(info.item_equip_rect, panel)?;
+        let equip = self.language.language.equip_name(&str_equip)
+            .map(|name| self.language.character_name(name));
+
+        Ok(GenshinWeapon {
+            name,
+            star: star as i32,
+            level,
+            ascension: ascension_from_max_level(max_level),
+            refinement,
+            lock,
+            equip,
+        })
+    }
+
+    /// open the weapon tab of the repository and scan all the weapons in it
+    pub fn scan(&mut self) -> Result<Vec<GenshinWeapon>> {
+        info!("开始扫描武器");
+        ScanControl::global().start();
+
+        self.open_weapon_tab()?;
+        let count = self.get_item_count()?;
+
+        let mut generator = GenshinRepositoryScanController::get_generator(self.controller.clone(), count);
+        let mut results = Vec::new();
+
+        loop {
+            let pinned_generator = Pin::new(&mut generator);
+            match pinned_generator.resume(()) {
+                CoroutineState::Yielded(_) => {
+                    let star = self.get_star()?;
+                    if (star as i32) < self.scanner_config.weapon_min_star {
+                        info!(
+                            "找到满足最低星级要求 {} 的武器，准备退出……",
+                            self.scanner_config.weapon_min_star
+                        );
+                        break;
+                    }
+
+                    let panel = self.capture_panel()?;
+                    let lock = self.get_lock()?;
+                    match self.scan_panel(&panel, star, lock) {
+                        Ok(weapon) => results.push(weapon),
+                        Err(e) => warn!("识别错误: {}", e),
+                    }
+                },
+                CoroutineState::Complete(result) => {
+                    match result {
+                        Err(e) => error!("扫描发生错误：{}", e),
+                        Ok(GenshinRepositoryControllerReturnResult::Interrupted) => info!("用户中断"),
+                        Ok(GenshinRepositoryControllerReturnResult::Finished) => (),
+                    }
+
+                    break;
+                },
+            }
+        }
+
+        info!("武器扫描结束，共识别到 {} 把武器", results.len());
+        Ok(results)
+    }
+}
@@ -0,0 +1,10 @@
+#[derive(Clone, clap::Args)]
+pub struct GenshinWeaponScannerConfig {
+    /// scan the weapons after the artifacts, only exported in GOOD
+    #[arg(id = "scan-weapons", long = "scan-weapons", help = "扫描圣遗物后扫描武器（仅导出到 GOOD 格式）")]
+    pub scan_weapons: bool,
+
+    /// Weapons with stars less than this will be ignored
+    #[arg(id = "weapon-min-star", long = "weapon-min-star", help = "武器最小星级", value_name = "WEAPON_MIN_STAR", default_value_t = 3)]
+    pub weapon_min_star: i32,
+}
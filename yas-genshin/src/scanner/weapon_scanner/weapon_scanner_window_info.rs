@@ -0,0 +1,36 @@
+use yas::positioning::{Pos, Rect};
+
+/// the weapon panel shares the layout of the artifact panel, so most of the positions are those of artifacts
+#[derive(Clone, yas_derive::YasWindowInfo, Debug)]
+pub struct WeaponScannerWindowInfo {
+    /// the weapon tab at the top of the repository
+    #[window_info(rename = "genshin_repository_weapon_tab_pos")]
+    pub weapon_tab_pos: Pos<f64>,
+
+    #[window_info(rename = "genshin_artifact_title_rect")]
+    pub title_rect: Rect<f64>,
+
+    /// e.g. "Lv.90/90"
+    #[window_info(rename = "genshin_weapon_level_rect")]
+    pub level_rect: Rect<f64>,
+
+    /// e.g. "精炼1阶"
+    #[window_info(rename = "genshin_weapon_refinement_rect")]
+    pub refinement_rect: Rect<f64>,
+
+    #[window_info(rename = "genshin_artifact_item_equip_rect")]
+    pub item_equip_rect: Rect<f64>,
+
+    #[window_info(rename = "genshin_artifact_item_count_rect")]
+    pub item_count_rect: Rect<f64>,
+
+    #[window_info(rename = "genshin_artifact_star_pos")]
+    pub star_pos: Pos<f64>,
+
+    /// the lock button of the panel
+    #[window_info(rename = "genshin_weapon_lock_pos")]
+    pub lock_pos: Pos<f64>,
+
+    #[window_info(rename = "genshin_repository_panel_rect")]
+    pub panel_rect: Rect<f64>,
+}
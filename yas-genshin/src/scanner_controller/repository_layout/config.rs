@@ -1,5 +1,3 @@
-use clap::arg;
-
 #[derive(Clone, clap::Args)]
 pub struct GenshinRepositoryScannerLogicConfig {
     /// Max rows to scan
@@ -10,10 +8,14 @@ pub struct GenshinRepositoryScannerLogicConfig {
     /// Will the scanner capture only?
     // pub capture_only: bool,
 
-    /// The time to wait for scrolling. Consider increasing this value if the scrolling is not correct
-    #[arg(id = "scroll-delay", long = "scroll-delay", help = "翻页时滚轮停顿时间（ms）（翻页不正确可以考虑加大该选项）", default_value_t = 80)]
+    /// The time to wait for scrolling, which is tuned while scrolling unless `fixed-scroll-delay`
+    #[arg(id = "scroll-delay", long = "scroll-delay", help = "翻页时滚轮停顿时间（ms），扫描时会根据画面自动调整", default_value_t = 80)]
     pub scroll_delay: i32,
 
+    /// Keep the scroll delay, without checking the rows scrolled by the captures of the grid
+    #[arg(id = "fixed-scroll-delay", long = "fixed-scroll-delay", help = "使用固定的翻页停顿时间，不根据画面检查和修正翻页（翻页不正确可以考虑开启并加大 --scroll-delay）")]
+    pub fixed_scroll_delay: bool,
+
     /// Dump the captured image
     // pub dump_mode: bool,
 
@@ -32,6 +34,7 @@ impl Default for GenshinRepositoryScannerLogicConfig {
             max_row: -1,
             // capture_only: false,
             scroll_delay: 80,
+            fixed_scroll_delay: false,
             // number: -1,
             // dump_mode: false,
             max_wait_switch_item: 800,
@@ -6,11 +6,13 @@ use std::time::SystemTime;
 use anyhow::{anyhow, Result};
 use clap::{ArgMatches, FromArgMatches};
 use image::RgbImage;
-use log::{error, info};
+use log::{error, info, warn};
 
+use yas::scan_control::ScanControl;
 use yas::capture::{Capturer, GenericCapturer};
 use yas::game_info::GameInfo;
-use yas::positioning::{Pos, Size};
+use yas::positioning::{Pos, Rect};
+use yas::scroll_feedback::{self, AdaptiveDelay, GridFrame};
 use yas::system_control::SystemControl;
 use yas::utils;
 use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
@@ -26,6 +28,7 @@ pub struct GenshinRepositoryScanController {
     // for scrolls
     scrolled_rows: u32,
     avg_scroll_one_row: f64,
+    scroll_delay: AdaptiveDelay,
 
     avg_switch_time: f64,
     scanned_count: usize,
@@ -45,6 +48,17 @@ pub struct GenshinRepositoryScanController {
     is_artifact: bool,
 }
 
+// the range of the adaptive scroll delay, in ms
+const MIN_SCROLL_DELAY: u32 = 20;
+const MAX_SCROLL_DELAY: u32 = 400;
+
+// the grid is compared in thumbnails, downscaled by this
+const GRID_SCALE: u32 = 4;
+// the overlap of two frames differs less than this at the rows they moved
+const MAX_SHIFT_DIFFERENCE: f64 = 12.0;
+// how many times the rows which were missed or overshot are scrolled again
+const MAX_SCROLL_CORRECTIONS: usize = 3;
+
 fn calc_pool(row: &[u8]) -> f32 {
     let len = row.len() / 3;
     let mut pool: f32 = 0.0;
@@ -82,6 +96,7 @@ impl GenshinRepositoryScanController {
         )?;
         let row = window_info.genshin_repository_item_row;
         let col = window_info.genshin_repository_item_col;
+        let scroll_delay = AdaptiveDelay::new(config.scroll_delay.max(0) as u32, MIN_SCROLL_DELAY, MAX_SCROLL_DELAY);
 
         Ok(GenshinRepositoryScanController {
             system_control: SystemControl::new(),
@@ -98,6 +113,7 @@ impl GenshinRepositoryScanController {
 
             scrolled_rows: 0,
             avg_scroll_one_row: 0.0,
+            scroll_delay,
 
             avg_switch_time: 0.0,
             // scanned_count: 0,
@@ -133,13 +149,22 @@ pub enum ReturnResult {
 
 impl GenshinRepositoryScanController {
     pub fn get_generator(object: Rc<RefCell<GenshinRepositoryScanController>>, item_count: usize) -> impl Coroutine<Yield=(), Return=Result<ReturnResult>> {
+        Self::get_generator_from(object, item_count, 0)
+    }
+
+    /// like `get_generator`, but the first `skip_row` rows are scrolled past without yielding, to resume a scan
+    pub fn get_generator_from(
+        object: Rc<RefCell<GenshinRepositoryScanController>>,
+        item_count: usize,
+        skip_row: usize,
+    ) -> impl Coroutine<Yield=(), Return=Result<ReturnResult>> {
         let generator = #[coroutine] move || {
             let mut scanned_row = 0;
             let mut scanned_count = 0;
             let mut start_row = 0;
 
-            let total_row = (item_count + object.borrow().col - 1) / object.borrow().col;
-            let last_row_col = if item_count % object.borrow().col == 0 {
+            let total_row = item_count.div_ceil(object.borrow().col);
+            let last_row_col = if item_count.is_multiple_of(object.borrow().col) {
                 object.borrow().col
             } else {
                 item_count % object.borrow().col
@@ -163,6 +188,32 @@ impl GenshinRepositoryScanController {
 
             let row = object.borrow().row.min(total_row);
 
+            let skip_row = skip_row.min(total_row);
+            if skip_row > 0 {
+                // the list stops scrolling at its last page, whose rows before the skipped ones are scanned already
+                let scroll_row = skip_row.min(total_row - row);
+                info!("跳过已扫描的 {} 行", skip_row);
+                let scrolled_row = match object.borrow_mut().scroll_rows(scroll_row as i32) {
+                    ScrollResult::TimeLimitExceeded => {
+                        return Err(anyhow!("翻页超时，扫描终止……"));
+                    }
+                    ScrollResult::Interrupt => {
+                        return Ok(ReturnResult::Interrupted);
+                    }
+                    ScrollResult::EndOfList(rows) => rows,
+                    _ => scroll_row,
+                };
+                utils::sleep(100);
+
+                scanned_row = skip_row;
+                scanned_count = (skip_row * object.borrow().col).min(item_count);
+                start_row = skip_row - scrolled_row;
+                if start_row >= row {
+                    info!("已到达列表末尾");
+                    return Ok(ReturnResult::Finished);
+                }
+            }
+
             'outer: while scanned_count < item_count {
                 '_row: for row in start_row..row {
                     let row_item_count = if scanned_row == total_row - 1 {
@@ -173,7 +224,7 @@ impl GenshinRepositoryScanController {
 
                     '_col: for col in 0..row_item_count {
                         // 大于最大数量 或者 取消 或者 鼠标右键按下
-                        if utils::is_rmb_down() {
+                        if ScanControl::global().should_stop() {
                             return Ok(ReturnResult::Interrupted);
                         }
                         if scanned_count > item_count {
@@ -205,11 +256,11 @@ impl GenshinRepositoryScanController {
                 } // end '_row
 
                 let remain = item_count - scanned_count;
-                let remain_row = (remain + object.borrow().col - 1) / object.borrow().col;
+                let remain_row = remain.div_ceil(object.borrow().col);
                 let scroll_row = remain_row.min(object.borrow().row);
-                start_row = object.borrow().row - scroll_row;
 
-                match object.borrow_mut().scroll_rows(scroll_row as i32) {
+                // the list may end before the item count, when fewer rows than these come into view
+                let scrolled_row = match object.borrow_mut().scroll_rows(scroll_row as i32) {
                     ScrollResult::TimeLimitExceeded => {
                         // error!("");
                         return Err(anyhow!("翻页超时，扫描终止……"));
@@ -217,8 +268,14 @@ impl GenshinRepositoryScanController {
                     ScrollResult::Interrupt => {
                         return Ok(ReturnResult::Interrupted);
                     }
-                    _ => (),
-                }
+                    ScrollResult::EndOfList(0) => {
+                        info!("已到达列表末尾");
+                        break 'outer;
+                    }
+                    ScrollResult::EndOfList(rows) => rows,
+                    _ => scroll_row,
+                };
+                start_row = object.borrow().row - scrolled_row;
 
                 utils::sleep(100);
             }
@@ -261,7 +318,7 @@ impl GenshinRepositoryScanController {
 
             if color_distance(&self.initial_color, &color) > 10 {
                 self.mouse_scroll(1, false);
-                utils::sleep(self.config.scroll_delay.try_into().unwrap());
+                utils::sleep(self.get_scroll_delay());
             } else {
                 break;
             }
@@ -289,20 +346,25 @@ impl GenshinRepositoryScanController {
     }
 
     pub fn scroll_one_row(&mut self) -> ScrollResult {
+        self.scroll_one_row_by(1)
+    }
+
+    /// down if `direction` is positive, up otherwise
+    fn scroll_one_row_by(&mut self, direction: i32) -> ScrollResult {
         let mut state = 0;
         let mut count = 0;
         let max_scroll = 25;
 
         while count < max_scroll {
-            if utils::is_rmb_down() {
+            if ScanControl::global().should_stop() {
                 return ScrollResult::Interrupt;
             }
 
-            let _ = self.system_control.mouse_scroll(1, false);
+            let _ = self.system_control.mouse_scroll(direction.signum(), false);
 
             // self.mouse_scroll(1, count < 1);
 
-            utils::sleep(self.config.scroll_delay.try_into().unwrap());
+            utils::sleep(self.get_scroll_delay());
             count += 1;
 
             let color = match self.get_flag_color() {
@@ -313,7 +375,9 @@ impl GenshinRepositoryScanController {
             if state == 0 && color_distance(&self.initial_color, &color) > 10 {
                 state = 1;
             } else if state == 1 && color_distance(&self.initial_color, &color) <= 10 {
-                self.update_avg_row(count);
+                if direction > 0 {
+                    self.update_avg_row(count);
+                }
                 return ScrollResult::Success;
             }
         }
@@ -321,7 +385,79 @@ impl GenshinRepositoryScanController {
         ScrollResult::TimeLimitExceeded
     }
 
+    /// with the adaptive delay, the rows moved are told by the captures of the grid, and corrected if they're not `count`
     pub fn scroll_rows(&mut self, count: i32) -> ScrollResult {
+        // a single row is never seen in two frames
+        if self.config.fixed_scroll_delay || count <= 0 || self.row < 2 {
+            return self.scroll_rows_unchecked(count);
+        }
+
+        let mut scrolled = 0;
+        for rows in overlapping_scrolls(count as usize, self.row) {
+            let before = match self.capture_grid() {
+                Ok(frame) => frame,
+                Err(_) => return self.scroll_rows_unchecked((count as usize - scrolled) as i32),
+            };
+            match self.scroll_rows_checked(rows, before, MAX_SCROLL_CORRECTIONS) {
+                ScrollResult::Success | ScrollResult::Skip => scrolled += rows,
+                ScrollResult::EndOfList(rows) => return ScrollResult::EndOfList(scrolled + rows),
+                v => return v,
+            }
+        }
+
+        ScrollResult::Success
+    }
+
+    /// `count` is less than the rows of a page, so the frames before and after overlap
+    fn scroll_rows_checked(&mut self, count: usize, before: GridFrame, corrections: usize) -> ScrollResult {
+        let result = self.scroll_rows_unchecked(count as i32);
+        if matches!(result, ScrollResult::Interrupt | ScrollResult::Failed) {
+            return result;
+        }
+        let is_time_limit_exceeded = matches!(result, ScrollResult::TimeLimitExceeded);
+
+        let after = match self.wait_until_settled() {
+            Ok(frame) => frame,
+            Err(_) => return result,
+        };
+
+        let scrolled = match before.row_shift(&after, self.get_row_pitch(), count) {
+            Some(shift) if !shift.ambiguous && shift.difference < MAX_SHIFT_DIFFERENCE => shift.rows,
+            // the items look alike, or the frames don't match, so the flag color is trusted
+            _ => return result,
+        };
+
+        if is_time_limit_exceeded {
+            // the flag color stops changing at the end of the list
+            return ScrollResult::EndOfList(scrolled);
+        }
+        if scrolled == 0 && before.is_same(&after) {
+            return ScrollResult::EndOfList(0);
+        }
+        if scrolled == count || corrections == 0 {
+            return result;
+        }
+
+        if scrolled > count {
+            warn!("翻页多滚动了 {} 行，正在修正", scrolled - count);
+            self.scroll_delay.unsettled();
+            for _ in count..scrolled {
+                match self.scroll_one_row_by(-1) {
+                    ScrollResult::Success => (),
+                    v => return v,
+                }
+            }
+            ScrollResult::Success
+        } else {
+            warn!("翻页少滚动了 {} 行，正在修正", count - scrolled);
+            match self.scroll_rows_checked(count - scrolled, after, corrections - 1) {
+                ScrollResult::EndOfList(rows) => ScrollResult::EndOfList(scrolled + rows),
+                v => v,
+            }
+        }
+    }
+
+    fn scroll_rows_unchecked(&mut self, count: i32) -> ScrollResult {
         if cfg!(not(target_os = "macos")) && self.scrolled_rows >= 5 {
             let length = self.estimate_scroll_length(count);
 
@@ -332,7 +468,7 @@ impl GenshinRepositoryScanController {
 
             // self.mouse_scroll(length, false);
 
-            utils::sleep(self.config.scroll_delay.try_into().unwrap());
+            utils::sleep(self.get_scroll_delay());
 
             self.align_row();
             return ScrollResult::Skip;
@@ -352,6 +488,52 @@ impl GenshinRepositoryScanController {
         ScrollResult::Success
     }
 
+    fn get_scroll_delay(&self) -> u32 {
+        if self.config.fixed_scroll_delay {
+            self.config.scroll_delay.max(0) as u32
+        } else {
+            self.scroll_delay.get()
+        }
+    }
+
+    fn get_row_pitch(&self) -> f64 {
+        self.window_info.item_size.height + self.window_info.item_gap_size.height
+    }
+
+    /// the items of a page, relative to the window
+    fn get_grid_rect(&self) -> Rect<f64> {
+        let gap = self.window_info.item_gap_size;
+        let size = self.window_info.item_size;
+        let mut margin = self.window_info.scan_margin_pos;
+        if self.is_artifact {
+            margin = margin + self.window_info.artifact_panel_offset;
+        }
+
+        Rect::new(
+            margin.x,
+            margin.y,
+            (size.width + gap.width) * self.col as f64 - gap.width,
+            (size.height + gap.height) * self.row as f64 - gap.height,
+        )
+    }
+
+    fn capture_grid(&self) -> Result<GridFrame> {
+        let im = self.capturer.capture_relative_to(
+            self.get_grid_rect().to_rect_i32(),
+            self.game_info.window.origin(),
+        )?;
+        Ok(GridFrame::new(&im, GRID_SCALE))
+    }
+
+    fn wait_until_settled(&mut self) -> Result<GridFrame> {
+        let rect = self.get_grid_rect().to_rect_i32();
+        let origin = self.game_info.window.origin();
+        let capturer = &self.capturer;
+        scroll_feedback::wait_until_settled(&mut self.scroll_delay, || {
+            Ok(GridFrame::new(&capturer.capture_relative_to(rect, origin)?, GRID_SCALE))
+        })
+    }
+
     pub fn wait_until_switched(&mut self) -> Result<()> {
         if self.game_info.is_cloud {
             utils::sleep(self.config.cloud_wait_switch_item.try_into()?);
@@ -432,3 +614,28 @@ impl GenshinRepositoryScanController {
         ((self.avg_scroll_one_row * count as f64 - 2.0).round() as i32).max(0)
     }
 }
+
+/// `count` rows in scrolls of less than the `page_rows` of a page, the last one being the shortest
+fn overlapping_scrolls(count: usize, page_rows: usize) -> impl Iterator<Item = usize> {
+    let step = page_rows.saturating_sub(1).max(1);
+    (0..count).step_by(step).map(move |done| step.min(count - done))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_page_is_scrolled_in_overlapping_steps() {
+        assert_eq!(overlapping_scrolls(5, 5).collect::<Vec<_>>(), vec![4, 1]);
+        assert_eq!(overlapping_scrolls(10, 5).collect::<Vec<_>>(), vec![4, 4, 2]);
+        assert_eq!(overlapping_scrolls(3, 5).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(overlapping_scrolls(0, 5).count(), 0);
+    }
+
+    #[test]
+    fn every_row_overlaps_on_a_small_page() {
+        assert_eq!(overlapping_scrolls(3, 2).collect::<Vec<_>>(), vec![1, 1, 1]);
+        assert_eq!(overlapping_scrolls(2, 1).collect::<Vec<_>>(), vec![1, 1]);
+    }
+}
@@ -5,4 +5,6 @@ pub enum ScrollResult {
     Success,
     Failed,
     Skip,
+    /// the list ended after this many rows
+    EndOfList(usize),
 }
@@ -0,0 +1,5 @@
+pub use weapon::GenshinWeapon;
+pub use weapon_names::{weapon_name_to_good, WEAPON_NAMES_ZH_CN, WEAPON_NAME_LEXICON};
+
+mod weapon;
+mod weapon_names;
@@ -0,0 +1,12 @@
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+pub struct GenshinWeapon {
+    /// the chinese name, as shown in game
+    pub name: String,
+    pub star: i32,
+    pub level: i32,
+    pub ascension: i32,
+    /// 1 to 5
+    pub refinement: i32,
+    pub lock: bool,
+    pub equip: Option<String>,
+}
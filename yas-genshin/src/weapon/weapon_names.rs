@@ -0,0 +1,186 @@
+use lazy_static::lazy_static;
+
+use yas::ocr::Lexicon;
+
+/// the names of the weapons of 3 stars and above, with their keys in GOOD
+pub const WEAPON_NAMES_ZH_CN: [(&str, &str); 168] = [
+    ("风鹰剑", "AquilaFavonia"),
+    ("天空之刃", "SkywardBlade"),
+    ("斫峰之刃", "SummitShaper"),
+    ("磐岩结绿", "PrimordialJadeCutter"),
+    ("苍古自由之誓", "FreedomSworn"),
+    ("雾切之回光", "MistsplitterReforged"),
+    ("波乱月白经津", "HaranGeppakuFutsu"),
+    ("圣显之钥", "KeyOfKhajNisut"),
+    ("静水流涌之辉", "SplendorOfTranquilWaters"),
+    ("裁叶萃光", "LightOfFoliarIncision"),
+    ("有乐御簾切", "UrakuMisugiri"),
+    ("赦罪", "Absolution"),
+    ("西风剑", "FavoniusSword"),
+    ("祭礼剑", "SacrificialSword"),
+    ("笛剑", "TheFlute"),
+    ("匣里龙吟", "LionsRoar"),
+    ("黑剑", "TheBlackSword"),
+    ("黑岩长剑", "BlackcliffLongsword"),
+    ("宗室长剑", "RoyalLongsword"),
+    ("铁蜂刺", "IronSting"),
+    ("暗巷闪光", "TheAlleyFlash"),
+    ("降临之剑", "SwordOfDescension"),
+    ("试作斩岩", "PrototypeRancour"),
+    ("腐殖之剑", "FesteringDesire"),
+    ("天目影打刀", "AmenomaKageuchi"),
+    ("辰砂之纺锤", "CinnabarSpindle"),
+    ("西福斯的月光", "XiphosMoonlight"),
+    ("东花坊时雨", "ToukabouShigure"),
+    ("原木刀", "SapwoodBlade"),
+    ("笼钓瓶一心", "KagotsurubeIsshin"),
+    ("海渊终曲", "FinaleOfTheDeep"),
+    ("船坞长剑", "TheDockhandsAssistant"),
+    ("狼牙", "WolfFang"),
+    ("灰河渡手", "FleuveCendreFerryman"),
+    ("水仙十字之剑", "SwordOfNarzissenkreuz"),
+    ("黎明神剑", "HarbingerOfDawn"),
+    ("飞天御剑", "SkyriderSword"),
+    ("冷刃", "CoolSteel"),
+    ("旅行剑", "TravelersHandySword"),
+    ("吃虎鱼刀", "FilletBlade"),
+    ("暗铁剑", "DarkIronSword"),
+    ("狼的末路", "WolfsGravestone"),
+    ("天空之傲", "SkywardPride"),
+    ("无工之剑", "TheUnforged"),
+    ("松籁响起之时", "SongOfBrokenPines"),
+    ("赤角石溃杵", "RedhornStonethresher"),
+    ("苇海信标", "BeaconOfTheReedSea"),
+    ("裁断", "Verdict"),
+    ("山王长牙", "FangOfTheMountainKing"),
+    ("西风大剑", "FavoniusGreatsword"),
+    ("祭礼大剑", "SacrificialGreatsword"),
+    ("钟剑", "TheBell"),
+    ("雨裁", "Rainslasher"),
+    ("白影剑", "Whiteblind"),
+    ("黑岩斩刀", "BlackcliffSlasher"),
+    ("宗室大剑", "RoyalGreatsword"),
+    ("千岩古剑", "LithicBlade"),
+    ("螭骨剑", "SerpentSpine"),
+    ("试作古华", "PrototypeArchaic"),
+    ("恶王丸", "Akuoumaru"),
+    ("衔珠海皇", "LuxuriousSeaLord"),
+    ("桂木斩长正", "KatsuragikiriNagamasa"),
+    ("森林王器", "ForestRegalia"),
+    ("玛海菈的水色", "MakhairaAquamarine"),
+    ("浪影阔剑", "TidalShadow"),
+    ("聊聊棒", "TalkingStick"),
+    ("便携动力锯", "PortablePowerSaw"),
+    ("究极霸王超级魔剑", "UltimateOverlordsMegaMagicSword"),
+    ("以理服人", "DebateClub"),
+    ("白铁大剑", "WhiteIronGreatsword"),
+    ("飞天大御剑", "SkyriderGreatsword"),
+    ("沐浴龙血的剑", "BloodtaintedGreatsword"),
+    ("铁影阔剑", "FerrousShadow"),
+    ("和璞鸢", "PrimordialJadeWingedSpear"),
+    ("天空之脊", "SkywardSpine"),
+    ("贯虹之槊", "VortexVanquisher"),
+    ("护摩之杖", "StaffOfHoma"),
+    ("薙草之稻光", "EngulfingLightning"),
+    ("息灾", "CalamityQueller"),
+    ("赤沙之杖", "StaffOfTheScarletSands"),
+    ("赤月之形", "CrimsonMoonsSemblance"),
+    ("西风长枪", "FavoniusLance"),
+    ("匣里灭辰", "DragonsBane"),
+    ("流月针", "CrescentPike"),
+    ("决斗之枪", "Deathmatch"),
+    ("黑岩刺枪", "BlackcliffPole"),
+    ("宗室猎枪", "RoyalSpear"),
+    ("千岩长枪", "LithicSpear"),
+    ("试作星镰", "PrototypeStarglitter"),
+    ("喜多院十文字", "KitainCrossSpear"),
+    ("「渔获」", "TheCatch"),
+    ("断浪长鳍", "WavebreakersFin"),
+    ("风信之锋", "MissiveWindspear"),
+    ("峡湾长歌", "BalladOfTheFjords"),
+    ("公义的酬报", "RightfulReward"),
+    ("龙脊长枪", "DragonspineSpear"),
+    ("勘探钻机", "ProspectorsDrill"),
+    ("黑缨枪", "BlackTassel"),
+    ("白缨枪", "WhiteTassel"),
+    ("钺矛", "Halberd"),
+    ("天空之卷", "SkywardAtlas"),
+    ("四风原典", "LostPrayerToTheSacredWinds"),
+    ("尘世之锁", "MemoryOfDust"),
+    ("不灭月华", "EverlastingMoonglow"),
+    ("神乐之真意", "KagurasVerity"),
+    ("图莱杜拉的回忆", "TulaytullahsRemembrance"),
+    ("千夜浮梦", "AThousandFloatingDreams"),
+    ("金流监督", "CashflowSupervision"),
+    ("碧落之珑", "JadefallsSplendor"),
+    ("万世流涌大典", "TomeOfTheEternalFlow"),
+    ("鹤鸣余音", "CranesEchoingCall"),
+    ("西风秘典", "FavoniusCodex"),
+    ("祭礼残章", "SacrificialFragments"),
+    ("流浪乐章", "TheWidsith"),
+    ("昭心", "EyeOfPerception"),
+    ("万国诸海图谱", "MappaMare"),
+    ("黑岩绯玉", "BlackcliffAgate"),
+    ("宗室秘法录", "RoyalGrimoire"),
+    ("试作金珀", "PrototypeAmber"),
+    ("暗巷的酒与诗", "WineAndSong"),
+    ("忍冬之果", "FrostBearer"),
+    ("嘟嘟可故事集", "DodocoTales"),
+    ("白辰之环", "HakushinRing"),
+    ("证誓之明瞳", "OathswornEye"),
+    ("流浪的晚星", "WanderingEvenstar"),
+    ("盈满之实", "FruitOfFulfillment"),
+    ("纯水流华", "FlowingPurity"),
+    ("遗祀玉珑", "SacrificialJade"),
+    ("无垠蔚蓝之歌", "BalladOfTheBoundlessBlue"),
+    ("魔导绪论", "MagicGuide"),
+    ("讨龙英杰谭", "ThrillingTalesOfDragonSlayers"),
+    ("异世界行记", "OtherworldlyStory"),
+    ("翡玉法球", "EmeraldOrb"),
+    ("甲级宝珏", "TwinNephrite"),
+    ("天空之翼", "SkywardHarp"),
+    ("阿莫斯之弓", "AmosBow"),
+    ("终末嗟叹之诗", "ElegyForTheEnd"),
+    ("冬极白星", "PolarStar"),
+    ("飞雷之弦振", "ThunderingPulse"),
+    ("若水", "AquaSimulacra"),
+    ("猎人之径", "HuntersPath"),
+    ("最初的大魔术", "TheFirstGreatMagic"),
+    ("西风猎弓", "FavoniusWarbow"),
+    ("祭礼弓", "SacrificialBow"),
+    ("绝弦", "TheStringless"),
+    ("弓藏", "Rust"),
+    ("黑岩战弓", "BlackcliffWarbow"),
+    ("宗室长弓", "RoyalBow"),
+    ("苍翠猎弓", "TheViridescentHunt"),
+    ("暗巷猎手", "AlleyHunter"),
+    ("试作澹月", "PrototypeCrescent"),
+    ("破魔之弓", "Hamayumi"),
+    ("曚云之月", "MouunsMoon"),
+    ("落霞", "FadingTwilight"),
+    ("王下近侍", "KingsSquire"),
+    ("竭泽", "EndOfTheLine"),
+    ("烈阳之嗣", "ScionOfTheBlazingSun"),
+    ("静谧之曲", "SongOfStillness"),
+    ("鹮穿之喙", "IbisPiercer"),
+    ("钢轮弓", "CompoundBow"),
+    ("幽夜华尔兹", "MitternachtsWaltz"),
+    ("风花之颂", "WindblumeOde"),
+    ("测距规", "RangeGauge"),
+    ("弹弓", "Slingshot"),
+    ("鸦羽弓", "RavenBow"),
+    ("神射手之誓", "SharpshootersOath"),
+    ("反曲弓", "RecurveBow"),
+    ("信使", "Messenger"),
+];
+
+lazy_static! {
+    pub static ref WEAPON_NAME_LEXICON: Lexicon = Lexicon::new(WEAPON_NAMES_ZH_CN.iter().map(|(name, _)| *name));
+}
+
+/// the key of a weapon in GOOD, e.g. "AquilaFavonia"
+pub fn weapon_name_to_good(name: &str) -> Option<&'static str> {
+    WEAPON_NAMES_ZH_CN.iter()
+        .find(|(zh_cn, _)| *zh_cn == name)
+        .map(|(_, key)| *key)
+}
@@ -1,4 +1,5 @@
 use clap::{command, ArgMatches, Args};
+use yas::config_file::{with_flag_values, ConfigFileArgs};
 use yas::game_info::{GameInfo, GameInfoBuilder};
 use yas::window_info::{load_window_info_repo, WindowInfoRepository};
 use crate::export::{ExportRelicConfig, StarRailRelicExporter};
@@ -8,6 +9,8 @@ use anyhow::{anyhow, Result};
 use log::info;
 use yas::export::{AssetEmitter, ExportAssets};
 use crate::relic::StarRailRelic;
+use std::sync::Arc;
+use yas::events::ScanObserver;
 
 pub struct RelicScannerApplication {
     arg_matches: ArgMatches,
@@ -25,10 +28,12 @@ impl RelicScannerApplication {
         cmd = <StarRailRelicScannerConfig as Args>::augment_args_for_update(cmd);
         cmd = <StarRailRepositoryScannerLogicConfig as Args>::augment_args_for_update(cmd);
         cmd = <ExportRelicConfig as Args>::augment_args_for_update(cmd);
-        cmd
+        cmd = <ConfigFileArgs as Args>::augment_args_for_update(cmd);
+        // the options of the config file come first, and are overridden by those of the command line
+        with_flag_values(cmd).args_override_self(true)
     }
 
-    fn get_window_info_repository() -> WindowInfoRepository {
+    pub fn get_window_info_repository() -> WindowInfoRepository {
         load_window_info_repo!(
             "../../window_info/windows1920x1080.json"
         )
@@ -40,7 +45,7 @@ impl RelicScannerApplication {
     //         .init();
     // }
 
-    fn get_game_info() -> Result<GameInfo> {
+    pub fn get_game_info() -> Result<GameInfo> {
         let game_info = GameInfoBuilder::new()
             .add_local_window_name("崩坏：星穹铁道")
             .add_local_window_name("Honkai: Star Rail")
@@ -51,11 +56,20 @@ impl RelicScannerApplication {
 }
 
 impl RelicScannerApplication {
+    /// scan and export
     pub fn run(&self) -> Result<()> {
-        // Self::init();
+        let relics = self.scan(None, None)?;
+        self.export(&relics)
+    }
+
+    /// scan the window of `game_info`, or the window found by its title, the results are reported to `observer`
+    pub fn scan(&self, game_info: Option<GameInfo>, observer: Option<Arc<dyn ScanObserver>>) -> Result<Vec<StarRailRelic>> {
         let arg_matches = &self.arg_matches;
         let window_info_repository = Self::get_window_info_repository();
-        let game_info = Self::get_game_info()?;
+        let game_info = match game_info {
+            Some(game_info) => game_info,
+            None => Self::get_game_info()?,
+        };
 
         info!("window: {:?}", game_info.window);
         info!("ui: {:?}", game_info.ui);
@@ -72,17 +86,23 @@ impl RelicScannerApplication {
 
         let mut scanner = StarRailRelicScanner::from_arg_matches(
             &window_info_repository,
-            &arg_matches,
+            arg_matches,
             game_info.clone()
         )?;
+        if let Some(observer) = observer {
+            scanner.set_observer(observer);
+        }
 
         let results = scanner.scan()?;
-        let starrail_relics = results.iter()
-            .map(|x| StarRailRelic::try_from(x))
-            .filter(|x| x.is_ok())
-            .map(|x| x.unwrap())
-            .collect::<Vec<_>>();
-        let exporter = StarRailRelicExporter::new(&arg_matches, &starrail_relics)?;
+        info!("Yas 识别结束，共识别到 {} 件遗器。", results.len());
+        Ok(results.iter()
+            .flat_map(StarRailRelic::try_from)
+            .collect())
+    }
+
+    /// save the results as the options say
+    pub fn export(&self, relics: &[StarRailRelic]) -> Result<()> {
+        let exporter = StarRailRelicExporter::new(&self.arg_matches, relics)?;
         let mut export_assets = ExportAssets::new();
         exporter.emit(&mut export_assets);
 
@@ -93,7 +113,6 @@ impl RelicScannerApplication {
         for line in table.lines() {
             info!("{}", line);
         }
-        info!("Yas 识别结束，共识别到 {} 件圣遗物。", results.len());
 
         Ok(())
     }
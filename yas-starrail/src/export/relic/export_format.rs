@@ -1,13 +1,8 @@
 use clap::ValueEnum;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Default)]
 pub enum StarRailRelicExportFormat {
     March7th,
+    #[default]
     HSR,
-}
-
-impl Default for StarRailRelicExportFormat {
-    fn default() -> Self {
-        Self::HSR
-    }
 }
\ No newline at end of file
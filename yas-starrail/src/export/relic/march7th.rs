@@ -173,7 +173,7 @@ impl<'a> Serialize for March7thFormat<'a> {
 }
 
 impl<'a> March7thFormat<'a> {
-    pub fn new(results: &'a [StarRailRelic]) -> March7thFormat {
+    pub fn new(results: &'a [StarRailRelic]) -> March7thFormat<'a> {
         let mut head: Vec<&March7thRelic> = Vec::new();
         let mut hands: Vec<&March7thRelic> = Vec::new();
         let mut body: Vec<&March7thRelic> = Vec::new();
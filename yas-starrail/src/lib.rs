@@ -1,6 +1,8 @@
 #![feature(coroutines, coroutine_trait)]
 #![feature(stmt_expr_attributes)]
 #![allow(unused_imports)]
+// the modules are named after their main file, e.g. `foo/foo.rs`
+#![allow(clippy::module_inception)]
 
 pub mod scanner_controller;
 pub mod export;
@@ -156,7 +156,8 @@ impl RelicStatName {
 
     pub fn is_percentage(&self) -> bool {
         use RelicStatName::*;
-        match *self {
+        matches!(
+            *self,
             HPPercentage |
             ATKPercentage |
             DEFPercentage |
@@ -164,9 +165,8 @@ impl RelicStatName {
             CRITDMG |
             EffectRES |
             EffectHitRate |
-            BreakEffect => true,
-            _ => false
-        }
+            BreakEffect
+        )
     }
 }
 
@@ -179,10 +179,7 @@ impl RelicStat {
         }
 
         let is_percentage = temp[1].contains('%');
-        let stat_name = match RelicStatName::from_zh_cn(temp[0], is_percentage) {
-            Some(v) => v,
-            None => return None,
-        };
+        let stat_name = RelicStatName::from_zh_cn(temp[0], is_percentage)?;
 
         let re = Regex::new("[%,]").unwrap();
         let mut value = match re.replace_all(temp[1], "").parse::<f64>() {
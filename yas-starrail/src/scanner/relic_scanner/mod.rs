@@ -1,6 +1,7 @@
 pub use relic_scanner::{StarRailRelicScanner};
 pub use relic_scanner_config::StarRailRelicScannerConfig;
 pub use scan_result::StarRailRelicScanResult;
+pub use relic_scanner_worker::get_embedded_relic_model;
 // pub use relic_scanner_window_info::RelicScannerWindowInfo;
 
 mod relic_scanner;
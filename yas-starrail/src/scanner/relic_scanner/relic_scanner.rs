@@ -1,13 +1,15 @@
-use std::{cell::RefCell, ops::{Coroutine, CoroutineState}, pin::Pin, rc::Rc, sync::mpsc::{self, Sender}, time::SystemTime};
+use std::{cell::RefCell, ops::{Coroutine, CoroutineState}, pin::Pin, rc::Rc, sync::{mpsc::{self, Sender}, Arc}, time::SystemTime};
 
 use anyhow::Result;
 use clap::FromArgMatches;
 use image::RgbImage;
 use log::{error, info};
 
+use yas::scan_control::ScanControl;
 use yas::capture::{Capturer, GenericCapturer};
+use yas::events::ScanObserver;
 use yas::game_info::GameInfo;
-use yas::ocr::{ImageToText, yas_ocr_model};
+use yas::ocr::ImageToText;
 use yas::positioning::Pos;
 use yas::utils::color_distance;
 use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
@@ -15,7 +17,7 @@ use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
 use crate::scanner::relic_scanner::match_colors::{MATCH_COLORS, MatchColors};
 use crate::scanner::relic_scanner::message_items::SendItem;
 use crate::scanner::relic_scanner::relic_scanner_window_info::RelicScannerWindowInfo;
-use crate::scanner::relic_scanner::relic_scanner_worker::RelicScannerWorker;
+use crate::scanner::relic_scanner::relic_scanner_worker::{get_embedded_relic_model, RelicScannerWorker};
 use crate::scanner::relic_scanner::scan_result::StarRailRelicScanResult;
 use crate::scanner_controller::repository_layout::{ReturnResult, StarRailRepositoryScanController, StarRailRepositoryScannerLogicConfig};
 
@@ -30,14 +32,13 @@ pub struct StarRailRelicScanner {
     capturer: Rc<dyn Capturer<RgbImage>>,
 
     match_colors: MatchColors,
+    observer: Option<Arc<dyn ScanObserver>>,
 }
 
 // constructor
 impl StarRailRelicScanner {
     fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-        let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(
-            yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")?
-        );
+        let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(get_embedded_relic_model()?);
         // let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(yas::ocr::PPOCRChV4RecInfer::new()?);
         Ok(model)
     }
@@ -70,6 +71,7 @@ impl StarRailRelicScanner {
             capturer: Self::get_capturer()?,
 
             match_colors: MATCH_COLORS,
+            observer: None,
         })
     }
 
@@ -94,11 +96,17 @@ impl StarRailRelicScanner {
             image_to_text: Self::get_image_to_text()?,
             capturer: Self::get_capturer()?,
             match_colors: MATCH_COLORS,
+            observer: None,
         })
     }
 }
 
 impl StarRailRelicScanner {
+    /// report the results of the scan to `observer`
+    pub fn set_observer(&mut self, observer: Arc<dyn ScanObserver>) {
+        self.observer = Some(observer);
+    }
+
     pub fn capture_panel(&self) -> Result<RgbImage> {
         self.capturer.capture_relative_to(
             self.window_info.panel_rect.to_rect_i32(),
@@ -199,7 +207,8 @@ impl StarRailRelicScanner {
     }
 
     pub fn scan(&mut self) -> Result<Vec<StarRailRelicScanResult>> {
-        info!("开始扫描，使用鼠标右键中断扫描");
+        info!("开始扫描遗器");
+        ScanControl::global().start();
 
         let now = SystemTime::now();
         let (tx, rx) = mpsc::channel::<Option<SendItem>>();
@@ -210,7 +219,7 @@ impl StarRailRelicScanner {
             self.scanner_config.clone()
         )?;
 
-        let join_handle = worker.run(rx);
+        let join_handle = worker.run(rx, self.observer.clone());
         info!("Worker created");
 
         self.send(&tx, count);
@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::sync::Arc;
 use std::sync::mpsc::Receiver;
 use std::thread::JoinHandle;
 
@@ -6,7 +7,8 @@ use anyhow::Result;
 use image::{GenericImageView, RgbImage};
 use log::{error, info, warn};
 
-use yas::ocr::{yas_ocr_model, ImageToText};
+use yas::events::{ScanEvent, ScanObserver};
+use yas::ocr::{yas_ocr_model, ImageToText, YasOCRModel};
 use yas::positioning::{Pos, Rect};
 
 use crate::scanner::relic_scanner::message_items::SendItem;
@@ -29,13 +31,16 @@ fn parse_level(s: &str) -> Result<i32> {
     }
 
     let level = s[pos.unwrap()..].parse::<i32>()?;
-    return Ok(level);
+    Ok(level)
+}
+
+/// the model built into the binary
+pub fn get_embedded_relic_model() -> Result<YasOCRModel> {
+    yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")
 }
 
 fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-    let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(
-        yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")?
-    );
+    let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(get_embedded_relic_model()?);
     // let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(PPOCRChV4RecInfer::new()?);
     Ok(model)
 }
@@ -52,7 +57,8 @@ impl RelicScannerWorker {
         })
     }
 
-    fn model_inference(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<String> {
+    /// the text of a region of the panel, `confidence` is lowered to that of the text
+    fn model_inference(&self, rect: Rect<f64>, captured_img: &RgbImage, confidence: &mut f32) -> Result<String> {
         let relative_rect = rect.translate(Pos {
             x: -self.window_info.panel_rect.left,
             y: -self.window_info.panel_rect.top,
@@ -62,31 +68,34 @@ impl RelicScannerWorker {
             relative_rect.left as u32, relative_rect.top as u32, relative_rect.width as u32, relative_rect.height as u32
         ).to_image();
 
-        let inference_result = self.model.image_to_text(&raw_img, false);
+        let (text, text_confidence) = self.model.image_to_text_with_confidence(&raw_img, false)?;
+        *confidence = confidence.min(text_confidence);
 
-        inference_result
+        Ok(text)
     }
 
-    fn scan_item_image(&self, item: SendItem) -> Result<StarRailRelicScanResult> {
+    /// the result, with the least confidence of its text
+    fn scan_item_image(&self, item: SendItem) -> Result<(StarRailRelicScanResult, f32)> {
         let image = &item.panel_image;
+        let mut confidence = 1.0;
 
-        let str_title = self.model_inference(self.window_info.title_rect, &image)?;
-        let str_main_stat_name = self.model_inference(self.window_info.main_stat_name_rect, &image)?;
-        let str_main_stat_value = self.model_inference(self.window_info.main_stat_value_rect, &image)?;
+        let str_title = self.model_inference(self.window_info.title_rect, image, &mut confidence)?;
+        let str_main_stat_name = self.model_inference(self.window_info.main_stat_name_rect, image, &mut confidence)?;
+        let str_main_stat_value = self.model_inference(self.window_info.main_stat_value_rect, image, &mut confidence)?;
 
-        let str_sub_stat0_name = self.model_inference(self.window_info.sub_stat_name_1, &image)?;
-        let str_sub_stat1_name = self.model_inference(self.window_info.sub_stat_name_2, &image)?;
-        let str_sub_stat2_name = self.model_inference(self.window_info.sub_stat_name_3, &image)?;
-        let str_sub_stat3_name = self.model_inference(self.window_info.sub_stat_name_4, &image)?;
-        let str_sub_stat0_value = self.model_inference(self.window_info.sub_stat_value_1, &image)?;
-        let str_sub_stat1_value = self.model_inference(self.window_info.sub_stat_value_2, &image)?;
-        let str_sub_stat2_value = self.model_inference(self.window_info.sub_stat_value_3, &image)?;
-        let str_sub_stat3_value = self.model_inference(self.window_info.sub_stat_value_4, &image)?;
+        let str_sub_stat0_name = self.model_inference(self.window_info.sub_stat_name_1, image, &mut confidence)?;
+        let str_sub_stat1_name = self.model_inference(self.window_info.sub_stat_name_2, image, &mut confidence)?;
+        let str_sub_stat2_name = self.model_inference(self.window_info.sub_stat_name_3, image, &mut confidence)?;
+        let str_sub_stat3_name = self.model_inference(self.window_info.sub_stat_name_4, image, &mut confidence)?;
+        let str_sub_stat0_value = self.model_inference(self.window_info.sub_stat_value_1, image, &mut confidence)?;
+        let str_sub_stat1_value = self.model_inference(self.window_info.sub_stat_value_2, image, &mut confidence)?;
+        let str_sub_stat2_value = self.model_inference(self.window_info.sub_stat_value_3, image, &mut confidence)?;
+        let str_sub_stat3_value = self.model_inference(self.window_info.sub_stat_value_4, image, &mut confidence)?;
 
-        let str_level = self.model_inference(self.window_info.level_rect, &image)?;
-        let str_equip = self.model_inference(self.window_info.equip_rect, &image)?;
+        let str_level = self.model_inference(self.window_info.level_rect, image, &mut confidence)?;
+        let str_equip = self.model_inference(self.window_info.equip_rect, image, &mut confidence)?;
 
-        Ok(StarRailRelicScanResult {
+        let result = StarRailRelicScanResult {
             name: str_title,
             main_stat_name: str_main_stat_name,
             main_stat_value: str_main_stat_value,
@@ -107,10 +116,12 @@ impl RelicScannerWorker {
             star: item.star as i32,
             lock: item.lock,
             discard: item.discard,
-        })
+        };
+        Ok((result, confidence))
     }
 
-    pub fn run(self, rx: Receiver<Option<SendItem>>) -> JoinHandle<Vec<StarRailRelicScanResult>> {
+    /// the results are reported to `observer`
+    pub fn run(self, rx: Receiver<Option<SendItem>>, observer: Option<Arc<dyn ScanObserver>>) -> JoinHandle<Vec<StarRailRelicScanResult>> {
         std::thread::spawn(move || {
             let mut results = Vec::new();
             let mut hash = HashSet::new();
@@ -120,16 +131,19 @@ impl RelicScannerWorker {
             let min_level = self.config.min_level;
             let info = self.window_info.clone();
 
-            for (_cnt, item) in rx.into_iter().enumerate() {
+            for (index, item) in rx.into_iter().enumerate() {
                 let item = match item {
                     Some(v) => v,
                     None => break,
                 };
 
-                let result = match self.scan_item_image(item) {
+                let (result, confidence) = match self.scan_item_image(item) {
                     Ok(v) => v,
                     Err(e) => {
                         error!("识别错误: {}", e);
+                        if let Some(observer) = &observer {
+                            observer.on_event(&ScanEvent::Error { index: Some(index), message: e.to_string() });
+                        }
                         continue;
                     },
                 };
@@ -152,6 +166,13 @@ impl RelicScannerWorker {
                     warn!("识别到重复物品: {:#?}", result);
                 } else {
                     consecutive_dup_count = 0;
+                    if let Some(observer) = &observer {
+                        observer.on_event(&ScanEvent::Item {
+                            index,
+                            confidence,
+                            item: serde_json::to_value(&result).unwrap_or_default(),
+                        });
+                    }
                     hash.insert(result.clone());
                     results.push(result);
                 }
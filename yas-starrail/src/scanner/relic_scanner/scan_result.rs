@@ -1,4 +1,6 @@
-#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Hash, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StarRailRelicScanResult {
     pub name: String,
     pub main_stat_name: String,
@@ -2,16 +2,19 @@ use std::cell::RefCell;
 use std::ops::Coroutine;
 use std::rc::Rc;
 use image::{Rgb, RgbImage};
+use yas::scan_control::ScanControl;
 use yas::game_info::GameInfo;
 use crate::scanner_controller::repository_layout::config::StarRailRepositoryScannerLogicConfig;
 use yas::utils;
-use log::{info, error};
+use log::{info, error, warn};
 use std::time::SystemTime;
 use yas::capture::{Capturer, GenericCapturer};
 use yas::system_control::SystemControl;
 use crate::scanner_controller::repository_layout::window_info::StarRailRepositoryScanControllerWindowInfo;
 use anyhow::{anyhow, Result};
 use clap::{ArgMatches, FromArgMatches};
+use yas::positioning::Rect;
+use yas::scroll_feedback::{self, AdaptiveDelay, GridFrame};
 use yas::utils::color_distance;
 use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
 use crate::scanner_controller::repository_layout::scroll_result::ScrollResult;
@@ -26,6 +29,7 @@ pub struct StarRailRepositoryScanController {
     // for scrolls
     scrolled_rows: u32,
     avg_scroll_one_row: f64,
+    scroll_delay: AdaptiveDelay,
 
     avg_switch_time: f64,
     scanned_count: usize,
@@ -42,7 +46,14 @@ pub struct StarRailRepositoryScanController {
     capturer: Rc<dyn Capturer<RgbImage>>,
 }
 
-fn calc_pool(row: &Vec<u8>) -> f32 {
+// the range of the adaptive scroll delay, in ms
+const MIN_SCROLL_DELAY: u32 = 20;
+const MAX_SCROLL_DELAY: u32 = 400;
+
+// the grid is compared in thumbnails, downscaled by this
+const GRID_SCALE: u32 = 4;
+
+fn calc_pool(row: &[u8]) -> f32 {
     let len = row.len() / 3;
     let mut pool: f32 = 0.0;
 
@@ -72,6 +83,7 @@ impl StarRailRepositoryScanController {
 
         let row_count = window_info.starrail_repository_item_row;
         let col_count = window_info.starrail_repository_item_col;
+        let scroll_delay = AdaptiveDelay::new(config.scroll_delay.max(0) as u32, MIN_SCROLL_DELAY, MAX_SCROLL_DELAY);
 
         Ok(StarRailRepositoryScanController {
             system_control: SystemControl::new(),
@@ -88,6 +100,7 @@ impl StarRailRepositoryScanController {
 
             scrolled_rows: 0,
             avg_scroll_one_row: 0.0,
+            scroll_delay,
 
             avg_switch_time: 0.0,
 
@@ -126,8 +139,8 @@ impl StarRailRepositoryScanController {
             let mut scanned_count = 0;
             let mut start_row = 0;
 
-            let total_row = (item_count + object.borrow().col - 1) / object.borrow().col;
-            let last_row_col = if item_count % object.borrow().col == 0 {
+            let total_row = item_count.div_ceil(object.borrow().col);
+            let last_row_col = if item_count.is_multiple_of(object.borrow().col) {
                 object.borrow().col
             } else {
                 item_count % object.borrow().col
@@ -161,7 +174,7 @@ impl StarRailRepositoryScanController {
 
                     '_col: for col in 0..row_item_count {
                         // Exit if right mouse button is down, or if we've scanned more than the maximum count
-                        if utils::is_rmb_down() {
+                        if ScanControl::global().should_stop() {
                             return Ok(ReturnResult::Interrupted);
                         }
                         if scanned_count > item_count {
@@ -193,7 +206,7 @@ impl StarRailRepositoryScanController {
                 } // end '_row
 
                 let remain = item_count - scanned_count;
-                let remain_row = (remain + object.borrow().col - 1) / object.borrow().col;
+                let remain_row = remain.div_ceil(object.borrow().col);
                 let scroll_row = remain_row.min(object.borrow().row);
                 start_row = object.borrow().row - scroll_row;
 
@@ -231,9 +244,8 @@ impl StarRailRepositoryScanController {
 
         // Gap size between repository top and first item row varies with resolution.
         // At 1920x1080, it's 20 pixels.
-        for y in 0..self.window_info.flag_rect.height as usize {
-            let color = im.get_pixel(0, y as u32);
-            flag[y] = color.clone();
+        for (y, color) in flag.iter_mut().enumerate().take(self.window_info.flag_rect.height as usize) {
+            *color = *im.get_pixel(0, y as u32);
         }
 
         Ok(flag)
@@ -244,8 +256,8 @@ impl StarRailRepositoryScanController {
         let flag = self.capture_flag()?;
         // println!("{:?}", &flag[..20]);
         // let mut same_count = 0;
-        for y in 0..self.window_info.flag_rect.height as usize {
-            if color_distance(&self.initial_flag[y], &flag[y]) < 10 {
+        for (initial, color) in self.initial_flag.iter().zip(flag.iter()).take(self.window_info.flag_rect.height as usize) {
+            if color_distance(initial, color) < 10 {
                 // same_count += 1;
                 return Ok(())
             }
@@ -263,7 +275,7 @@ impl StarRailRepositoryScanController {
         for _ in 0..10 {
             if self.check_flag().is_err() {
                 self.mouse_scroll(1, false);
-                utils::sleep(self.config.scroll_delay.try_into().unwrap());
+                utils::sleep(self.get_scroll_delay());
             } else {
                 break;
             }
@@ -293,14 +305,14 @@ impl StarRailRepositoryScanController {
         let max_scroll = 25;
 
         while count < max_scroll {
-            if utils::is_rmb_down() {
+            if ScanControl::global().should_stop() {
                 return ScrollResult::Interrupt;
             }
 
             #[cfg(windows)]
             let _ = self.system_control.mouse_scroll(1, false);
 
-            utils::sleep(self.config.scroll_delay.try_into().unwrap());
+            utils::sleep(self.get_scroll_delay());
             count += 1;
 
             match (state, self.check_flag()) {
@@ -316,7 +328,18 @@ impl StarRailRepositoryScanController {
         ScrollResult::TimeLimitExceeded
     }
 
+    /// with the adaptive delay, waits for the grid to settle after the scroll, which tunes the delay
     pub fn scroll_rows(&mut self, count: i32) -> ScrollResult {
+        let result = self.scroll_rows_unchecked(count);
+        if !self.config.fixed_scroll_delay && matches!(result, ScrollResult::Success | ScrollResult::Skip) {
+            if let Err(e) = self.wait_until_settled() {
+                warn!("{}", e);
+            }
+        }
+        result
+    }
+
+    fn scroll_rows_unchecked(&mut self, count: i32) -> ScrollResult {
         if cfg!(not(target_os = "macos")) && self.scrolled_rows >= 5 {
             let length = self.estimate_scroll_length(count);
 
@@ -327,7 +350,7 @@ impl StarRailRepositoryScanController {
                 }
             }
 
-            utils::sleep(self.config.scroll_delay.try_into().unwrap());
+            utils::sleep(self.get_scroll_delay());
 
             self.align_row();
             return ScrollResult::Skip;
@@ -347,6 +370,37 @@ impl StarRailRepositoryScanController {
         ScrollResult::Success
     }
 
+    fn get_scroll_delay(&self) -> u32 {
+        if self.config.fixed_scroll_delay {
+            self.config.scroll_delay.max(0) as u32
+        } else {
+            self.scroll_delay.get()
+        }
+    }
+
+    /// the items of a page, relative to the window
+    fn get_grid_rect(&self) -> Rect<f64> {
+        let gap = self.window_info.item_gap_size;
+        let size = self.window_info.item_size;
+        let margin = self.window_info.scan_margin_pos;
+
+        Rect::new(
+            margin.x,
+            margin.y,
+            (size.width + gap.width) * self.col as f64 - gap.width,
+            (size.height + gap.height) * self.row as f64 - gap.height,
+        )
+    }
+
+    fn wait_until_settled(&mut self) -> Result<GridFrame> {
+        let rect = self.get_grid_rect().to_rect_i32();
+        let origin = self.game_info.window.origin();
+        let capturer = &self.capturer;
+        scroll_feedback::wait_until_settled(&mut self.scroll_delay, || {
+            Ok(GridFrame::new(&capturer.capture_relative_to(rect, origin)?, GRID_SCALE))
+        })
+    }
+
     pub fn wait_until_switched(&mut self) -> Result<()> {
         if self.game_info.is_cloud {
             utils::sleep(self.config.cloud_wait_switch_item.try_into()?);
@@ -390,7 +444,7 @@ impl StarRailRepositoryScanController {
         self.system_control.mouse_scroll(length, try_find).unwrap();
 
         #[cfg(target_os = "linux")]
-        self.system_control.mouse_scroll(length, try_find);
+        let _ = self.system_control.mouse_scroll(length, try_find);
 
         #[cfg(target_os = "macos")]
         {
@@ -1,6 +1,7 @@
 use anyhow::anyhow;
 use clap::{ArgMatches, Args, command};
 use log::info;
+use yas::config_file::{with_flag_values, ConfigFileArgs};
 use yas::export::ExportAssets;
 use yas::game_info::{GameInfo, GameInfoBuilder};
 use yas::window_info::{load_window_info_repo, WindowInfoRepository};
@@ -24,7 +25,9 @@ impl WWEchoScannerApplication {
         cmd = <WWEchoScannerConfig as Args>::augment_args_for_update(cmd);
         cmd = <WWRepositoryLayoutConfig as Args>::augment_args_for_update(cmd);
         // cmd = <ExportRelicConfig as Args>::augment_args_for_update(cmd);
-        cmd
+        cmd = <ConfigFileArgs as Args>::augment_args_for_update(cmd);
+        // the options of the config file come first, and are overridden by those of the command line
+        with_flag_values(cmd).args_override_self(true)
     }
 
     fn get_window_info_repository() -> WindowInfoRepository {
@@ -1,8 +1,9 @@
 #![feature(coroutine_trait, coroutines)]
 #![feature(stmt_expr_attributes)]
+// the modules are named after their main file, e.g. `foo/foo.rs`
+#![allow(clippy::module_inception)]
 
 #[allow(unused_imports)]
-
 pub mod echo;
 pub mod scanner_controller;
 pub mod scanner;
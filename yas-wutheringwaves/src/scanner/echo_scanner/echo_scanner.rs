@@ -15,6 +15,7 @@ use clap::FromArgMatches;
 use yas::capture::{Capturer, GenericCapturer, StreamingCapturer};
 use yas::game_info::GameInfo;
 use yas::ocr::{ImageToText, yas_ocr_model};
+use yas::scan_control::ScanControl;
 use yas::window_info::{WindowInfoRepository, FromWindowInfoRepository};
 
 use crate::scanner::echo_scanner::echo_scanner_config::WWEchoScannerConfig;
@@ -128,7 +129,8 @@ impl WWEchoScanner {
     }
 
     pub fn scan(&mut self) -> Result<Vec<WWEchoScanResult>> {
-        info!("开始扫描，使用鼠标右键中断扫描");
+        info!("开始扫描声骸");
+        ScanControl::global().start();
 
         let now = SystemTime::now();
 
@@ -6,10 +6,14 @@ pub struct WWRepositoryLayoutConfig {
     #[arg(id = "max-row", long = "max-row", help = "最大扫描行数")]
     pub max_row: Option<usize>,
 
-    /// The time to wait for scrolling. Consider increasing this value if the scrolling is not correct
-    #[arg(id = "scroll-delay", long = "scroll-delay", help = "翻页时滚轮停顿时间（ms）（翻页不正确可以考虑加大该选项）", default_value_t = 80)]
+    /// The time to wait for scrolling, which is tuned while scrolling unless `fixed-scroll-delay`
+    #[arg(id = "scroll-delay", long = "scroll-delay", help = "翻页时滚轮停顿时间（ms），扫描时会根据画面自动调整", default_value_t = 80)]
     pub scroll_delay: i32,
 
+    /// Keep the scroll delay, without waiting for the grid to settle after a scroll
+    #[arg(id = "fixed-scroll-delay", long = "fixed-scroll-delay", help = "使用固定的翻页停顿时间，不根据画面调整（翻页不正确可以考虑开启并加大 --scroll-delay）")]
+    pub fixed_scroll_delay: bool,
+
     /// Dump the captured image
     // pub dump_mode: bool,
 
@@ -27,6 +31,7 @@ impl Default for WWRepositoryLayoutConfig {
         Self {
             max_row: None,
             scroll_delay: 80,
+            fixed_scroll_delay: false,
             max_wait_switch_item: 800,
             cloud_wait_switch_item: 800,
         }
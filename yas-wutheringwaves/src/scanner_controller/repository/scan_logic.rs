@@ -7,12 +7,14 @@ use std::time::SystemTime;
 use anyhow::{anyhow, Result};
 use clap::{ArgMatches, FromArgMatches};
 use image::{Rgb, RgbImage};
-use log::{error, info};
+use log::{error, info, warn};
 
 use yas::capture::{Capturer, GenericCapturer};
 use yas::game_info::GameInfo;
-use yas::positioning::Pos;
+use yas::positioning::{Pos, Rect};
 use yas::profiler::Profiler;
+use yas::scan_control::ScanControl;
+use yas::scroll_feedback::{self, AdaptiveDelay, GridFrame};
 use yas::system_control::SystemControl;
 use yas::utils;
 use yas::utils::color_distance;
@@ -31,6 +33,8 @@ pub struct WWRepositoryLayoutScanController {
     scrolled_rows: u32,
     /// Average wheel event to scroll a row
     avg_scroll_one_row: f64,
+    /// The time to wait after a wheel event, tuned by whether the grid settles in time
+    scroll_delay: AdaptiveDelay,
 
     /// Average waiting time for an Echo to be switched and fully displayed
     avg_switch_time: f64,
@@ -56,6 +60,13 @@ pub struct WWRepositoryLayoutScanController {
     pub profiler: RefCell<Profiler>,
 }
 
+// the range of the adaptive scroll delay, in ms
+const MIN_SCROLL_DELAY: u32 = 20;
+const MAX_SCROLL_DELAY: u32 = 400;
+
+// the grid is compared in thumbnails, downscaled by this
+const GRID_SCALE: u32 = 4;
+
 impl WWRepositoryLayoutScanController {
     pub fn new(
         window_info_repo: &WindowInfoRepository,
@@ -71,6 +82,7 @@ impl WWRepositoryLayoutScanController {
 
         let row_count = window_info.ww_repository_item_row;
         let col_count = window_info.ww_repository_item_col;
+        let scroll_delay = AdaptiveDelay::new(config.scroll_delay.max(0) as u32, MIN_SCROLL_DELAY, MAX_SCROLL_DELAY);
 
         let capturer = Rc::new(GenericCapturer::new()?);
 
@@ -89,6 +101,7 @@ impl WWRepositoryLayoutScanController {
 
             scrolled_rows: 0,
             avg_scroll_one_row: 0.0,
+            scroll_delay,
 
             avg_switch_time: 0.0,
 
@@ -192,7 +205,7 @@ impl WWRepositoryLayoutScanController {
 
                     '_col: for col in 0..row_item_count {
                         // Exit if right mouse button is down, or if we've scanned more than the maximum count
-                        if utils::is_rmb_down() {
+                        if ScanControl::global().should_stop() {
                             return Ok(ReturnResult::Interrupted);
                         }
                         if scanned_count > item_count {
@@ -284,7 +297,7 @@ impl WWRepositoryLayoutScanController {
             let check_result = self.check_flag()?;
             if !check_result {
                 self.mouse_scroll(1, false);
-                utils::sleep(self.config.scroll_delay.try_into()?);
+                utils::sleep(self.get_scroll_delay());
             } else {
                 break;
             }
@@ -312,14 +325,14 @@ impl WWRepositoryLayoutScanController {
 
     pub fn scroll_one_row(&mut self) -> Result<ScrollResult> {
         for _ in 0..8 {
-            if utils::is_rmb_down() {
+            if ScanControl::global().should_stop() {
                 return Ok(ScrollResult::Interrupt);
             }
 
             #[cfg(windows)]
             self.system_control.mouse_scroll(1, false)?;
 
-            utils::sleep(self.config.scroll_delay.try_into().unwrap());
+            utils::sleep(self.get_scroll_delay());
         }
 
         Ok(ScrollResult::Success)
@@ -352,7 +365,18 @@ impl WWRepositoryLayoutScanController {
         // Ok(ScrollResult::TimeLimitExceeded)
     }
 
+    /// With the adaptive delay, waits for the grid to settle after the scroll, which tunes the delay
     fn scroll_rows(&mut self, count: i32) -> Result<ScrollResult> {
+        let result = self.scroll_rows_unchecked(count)?;
+        if !self.config.fixed_scroll_delay && matches!(result, ScrollResult::Success | ScrollResult::Skip) {
+            if let Err(e) = self.wait_until_settled() {
+                warn!("{}", e);
+            }
+        }
+        Ok(result)
+    }
+
+    fn scroll_rows_unchecked(&mut self, count: i32) -> Result<ScrollResult> {
         if cfg!(not(target_os = "macos")) && self.scrolled_rows >= 5 {
             let length = self.estimate_scroll_length(count);
 
@@ -360,7 +384,7 @@ impl WWRepositoryLayoutScanController {
                 self.system_control.mouse_scroll(1, false)?;
             }
 
-            utils::sleep(self.config.scroll_delay.try_into().unwrap());
+            utils::sleep(self.get_scroll_delay());
 
             self.align_row()?;
             return Ok(ScrollResult::Skip);
@@ -380,6 +404,37 @@ impl WWRepositoryLayoutScanController {
         Ok(ScrollResult::Success)
     }
 
+    fn get_scroll_delay(&self) -> u32 {
+        if self.config.fixed_scroll_delay {
+            self.config.scroll_delay.max(0) as u32
+        } else {
+            self.scroll_delay.get()
+        }
+    }
+
+    /// The items of a page, relative to the window
+    fn get_grid_rect(&self) -> Rect<f64> {
+        let gap = self.window_info.item_gap_size;
+        let size = self.window_info.item_size;
+        let margin = self.window_info.scan_margin_pos;
+
+        Rect::new(
+            margin.x,
+            margin.y,
+            (size.width + gap.width) * self.col as f64 - gap.width,
+            (size.height + gap.height) * self.row as f64 - gap.height,
+        )
+    }
+
+    fn wait_until_settled(&mut self) -> Result<GridFrame> {
+        let rect = self.get_grid_rect().to_rect_i32();
+        let origin = self.game_info.window.origin();
+        let capturer = &self.capturer;
+        scroll_feedback::wait_until_settled(&mut self.scroll_delay, || {
+            Ok(GridFrame::new(&capturer.capture_relative_to(rect, origin)?, GRID_SCALE))
+        })
+    }
+
     /// An aggressive wait scheme. If any change happens, mark this as valid switch.
     /// This may create duplicate Echoes, but will not overlook any Echo
     fn wait_until_switched(&mut self) -> Result<bool> {
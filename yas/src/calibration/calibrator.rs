@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+use image::RgbImage;
+use log::{info, warn};
+
+use crate::game_info::{Platform, UI};
+use crate::positioning::{Pos, Rect, Size};
+use crate::window_info::{WindowInfoRepository, WindowInfoTemplatePerSize, WindowInfoType};
+
+use super::detect::{locate_edges, locate_text};
+
+/// a detected anchor whose size differs from the static table by more than this is taken as a misdetection
+const MAX_SCALE_DEVIATION: f64 = 0.1;
+/// the padding kept around the text of a text anchor
+const TEXT_PADDING: f64 = 2.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnchorDetector {
+    /// the borders of a panel, see `locate_edges`
+    Edges,
+    /// a line of text, whose rect is grown to hold all of it, see `locate_text`
+    Text,
+}
+
+struct Anchor {
+    key: String,
+    detector: AnchorDetector,
+    /// the keys positioned relative to the anchor
+    dependents: Vec<String>,
+}
+
+/// locates anchor elements of the ui in a screenshot, and moves the window info keys positioned relative to them
+/// to where the anchors actually are. the keys of an anchor which is not found keep their static values
+pub struct Calibrator {
+    anchors: Vec<Anchor>,
+    /// how far an anchor is searched from its static position, relative to the window height
+    margin: f64,
+}
+
+/// the calibrated window info of a window size, which can be cached to disk in the format of the window info files
+pub struct Calibration {
+    window_size: Size<usize>,
+    ui: UI,
+    platform: Platform,
+    data: HashMap<String, WindowInfoType>,
+}
+
+impl Calibrator {
+    pub fn new() -> Calibrator {
+        Calibrator {
+            anchors: Vec::new(),
+            margin: 0.03,
+        }
+    }
+
+    pub fn margin(&mut self, margin: f64) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// `key` is a rect of the window info, `dependents` are the keys placed relative to it
+    pub fn anchor(&mut self, key: &str, detector: AnchorDetector, dependents: &[&str]) -> &mut Self {
+        self.anchors.push(Anchor {
+            key: String::from(key),
+            detector,
+            dependents: dependents.iter().map(|s| String::from(*s)).collect(),
+        });
+        self
+    }
+
+    fn detect(&self, gray: &image::GrayImage, anchor: &Anchor, expected: Rect<f64>) -> Option<Rect<f64>> {
+        let margin = (gray.height() as f64 * self.margin) as u32;
+        let approx = expected.to_rect_u32();
+
+        match anchor.detector {
+            AnchorDetector::Edges => {
+                let found = locate_edges(gray, approx, margin)?.to_rect_f64();
+                let scale_x = found.width / expected.width;
+                let scale_y = found.height / expected.height;
+                if (scale_x - 1.0).abs() > MAX_SCALE_DEVIATION || (scale_y - 1.0).abs() > MAX_SCALE_DEVIATION {
+                    return None;
+                }
+                Some(found)
+            },
+            AnchorDetector::Text => {
+                let text = locate_text(gray, approx, margin)?.to_rect_f64();
+                let left = expected.left.min(text.left - TEXT_PADDING);
+                let top = expected.top.min(text.top - TEXT_PADDING);
+                let right = (expected.left + expected.width).max(text.left + text.width + TEXT_PADDING);
+                let bottom = (expected.top + expected.height).max(text.top + text.height + TEXT_PADDING);
+                Some(Rect::new(left, top, right - left, bottom - top))
+            },
+        }
+    }
+
+    /// calibrate the window info of the window in `screenshot`, which is of the whole window
+    pub fn calibrate(&self, screenshot: &RgbImage, repo: &WindowInfoRepository, ui: UI, platform: Platform) -> Calibration {
+        let window_size = Size::new(screenshot.width() as usize, screenshot.height() as usize);
+        let gray = image::imageops::grayscale(screenshot);
+        let mut data = HashMap::new();
+
+        for anchor in self.anchors.iter() {
+            let Some(expected) = repo.get_auto_scale::<Rect<f64>>(&anchor.key, window_size, ui, platform) else {
+                warn!("窗口信息中没有 {}，跳过校准", anchor.key);
+                continue;
+            };
+            let Some(found) = self.detect(&gray, anchor, expected) else {
+                warn!("未能定位 {}，使用默认位置", anchor.key);
+                continue;
+            };
+            info!("校准 {}: {} -> {}", anchor.key, expected, found);
+
+            data.insert(anchor.key.clone(), WindowInfoType::Rect(found));
+            for key in anchor.dependents.iter() {
+                match repo.get_auto_scale::<WindowInfoType>(key, window_size, ui, platform) {
+                    Some(value) => {
+                        data.insert(key.clone(), relocate(value, expected, found));
+                    },
+                    None => warn!("窗口信息中没有 {}，跳过校准", key),
+                }
+            }
+        }
+
+        Calibration {
+            window_size,
+            ui,
+            platform,
+            data,
+        }
+    }
+}
+
+impl Default for Calibrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// move a value placed relative to `from` to the same place relative to `to`
+fn relocate(value: WindowInfoType, from: Rect<f64>, to: Rect<f64>) -> WindowInfoType {
+    let scale_x = to.width / from.width;
+    let scale_y = to.height / from.height;
+    let x = |x: f64| to.left + (x - from.left) * scale_x;
+    let y = |y: f64| to.top + (y - from.top) * scale_y;
+
+    match value {
+        WindowInfoType::Rect(r) => WindowInfoType::Rect(Rect::new(x(r.left), y(r.top), r.width * scale_x, r.height * scale_y)),
+        WindowInfoType::Pos(p) => WindowInfoType::Pos(Pos::new(x(p.x), y(p.y))),
+        WindowInfoType::Size(s) => WindowInfoType::Size(Size::new(s.width * scale_x, s.height * scale_y)),
+        other => other,
+    }
+}
+
+impl Calibration {
+    /// whether it was calibrated for the window
+    pub fn is_for(&self, window_size: Size<usize>, ui: UI, platform: Platform) -> bool {
+        self.window_size == window_size && self.ui == ui && self.platform == platform
+    }
+
+    /// how many keys are calibrated
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// the calibrated keys take the place of the static ones of this window size
+    pub fn inject_into(&self, repo: &mut WindowInfoRepository) {
+        for (key, value) in self.data.iter() {
+            repo.add(key, self.window_size, self.ui, self.platform, *value);
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Calibration> {
+        let content = std::fs::read_to_string(path)?;
+        let template: WindowInfoTemplatePerSize = serde_json::from_str(&content)?;
+        Ok(Calibration {
+            window_size: template.current_resolution,
+            ui: template.ui,
+            platform: template.platform,
+            data: template.data,
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let template = WindowInfoTemplatePerSize {
+            current_resolution: self.window_size,
+            platform: self.platform,
+            ui: self.ui,
+            data: self.data.clone(),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&template)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+
+    use super::*;
+
+    const UI_: UI = UI::Desktop;
+    const PLATFORM: Platform = Platform::Windows;
+
+    fn window_size() -> Size<usize> {
+        Size::new(400, 300)
+    }
+
+    fn repo() -> WindowInfoRepository {
+        let mut repo = WindowInfoRepository::new();
+        repo.add("panel", window_size(), UI_, PLATFORM, WindowInfoType::Rect(Rect::new(100.0, 50.0, 200.0, 150.0)));
+        repo.add("inside", window_size(), UI_, PLATFORM, WindowInfoType::Pos(Pos::new(150.0, 100.0)));
+        repo.add("count", window_size(), UI_, PLATFORM, WindowInfoType::Rect(Rect::new(20.0, 250.0, 40.0, 20.0)));
+        repo
+    }
+
+    fn calibrator() -> Calibrator {
+        let mut calibrator = Calibrator::new();
+        calibrator
+            .anchor("panel", AnchorDetector::Edges, &["inside"])
+            .anchor("count", AnchorDetector::Text, &[]);
+        calibrator
+    }
+
+    fn fill(image: &mut RgbImage, left: u32, top: u32, width: u32, height: u32, value: u8) {
+        for y in top..top + height {
+            for x in left..left + width {
+                image.put_pixel(x, y, Rgb([value, value, value]));
+            }
+        }
+    }
+
+    /// the panel moved by (8, 6) from the static table, and a count text wider than its static rect
+    fn screenshot() -> RgbImage {
+        let mut image = RgbImage::from_pixel(400, 300, Rgb([30, 30, 30]));
+        fill(&mut image, 108, 56, 200, 150, 200);
+        fill(&mut image, 15, 255, 50, 10, 230);
+        image
+    }
+
+    #[test]
+    fn anchors_and_dependents_follow_the_screenshot() {
+        let calibration = calibrator().calibrate(&screenshot(), &repo(), UI_, PLATFORM);
+        assert_eq!(calibration.len(), 3);
+
+        let mut repo = repo();
+        calibration.inject_into(&mut repo);
+        let panel: Rect<f64> = repo.get_auto_scale("panel", window_size(), UI_, PLATFORM).unwrap();
+        assert_eq!(panel, Rect::new(108.0, 56.0, 200.0, 150.0));
+        let inside: Pos<f64> = repo.get_auto_scale("inside", window_size(), UI_, PLATFORM).unwrap();
+        assert_eq!(inside, Pos::new(158.0, 106.0));
+        // grown to hold the text, with the padding
+        let count: Rect<f64> = repo.get_auto_scale("count", window_size(), UI_, PLATFORM).unwrap();
+        assert_eq!(count, Rect::new(13.0, 250.0, 54.0, 20.0));
+    }
+
+    #[test]
+    fn anchors_not_found_keep_the_static_values() {
+        let blank = RgbImage::from_pixel(400, 300, Rgb([30, 30, 30]));
+        assert!(calibrator().calibrate(&blank, &repo(), UI_, PLATFORM).is_empty());
+
+        // a panel much smaller than the static one is a misdetection
+        let mut small = blank.clone();
+        fill(&mut small, 95, 45, 150, 150, 200);
+        let calibration = calibrator().calibrate(&small, &repo(), UI_, PLATFORM);
+        assert!(calibration.is_empty());
+    }
+
+    #[test]
+    fn cache_is_for_the_calibrated_window() {
+        let calibration = calibrator().calibrate(&screenshot(), &repo(), UI_, PLATFORM);
+        let path = std::env::temp_dir().join(format!("yas_calibration_{}.json", std::process::id()));
+        calibration.save(&path).unwrap();
+        let loaded = Calibration::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.len(), calibration.len());
+        assert!(loaded.is_for(window_size(), UI_, PLATFORM));
+        assert!(!loaded.is_for(Size::new(800, 600), UI_, PLATFORM));
+        assert!(!loaded.is_for(window_size(), UI::Mobile, PLATFORM));
+    }
+}
@@ -0,0 +1,94 @@
+use image::GrayImage;
+
+use crate::positioning::Rect;
+
+/// the mean gradient along an edge, below which there is no edge
+const MIN_EDGE_STRENGTH: f64 = 6.0;
+/// how much a pixel differs from the background to be ink
+const INK_THRESHOLD: i32 = 48;
+
+/// `rect` grown by `margin` on every side, clamped to the image
+fn expand(im: &GrayImage, rect: Rect<u32>, margin: u32) -> Rect<u32> {
+    let left = rect.left.saturating_sub(margin);
+    let top = rect.top.saturating_sub(margin);
+    let right = (rect.left + rect.width + margin).min(im.width());
+    let bottom = (rect.top + rect.height + margin).min(im.height());
+    Rect::new(left, top, right.saturating_sub(left), bottom.saturating_sub(top))
+}
+
+fn gray(im: &GrayImage, x: u32, y: u32) -> f64 {
+    im.get_pixel(x, y)[0] as f64
+}
+
+/// the column in `xs` with the strongest horizontal gradient over the rows `ys`, with its mean gradient
+fn strongest_column(im: &GrayImage, xs: std::ops::Range<u32>, ys: std::ops::Range<u32>) -> Option<(u32, f64)> {
+    let n = ys.len() as f64;
+    xs.filter(|&x| x > 0 && x < im.width())
+        .map(|x| (x, ys.clone().map(|y| (gray(im, x, y) - gray(im, x - 1, y)).abs()).sum::<f64>() / n))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// the row in `ys` with the strongest vertical gradient over the columns `xs`, with its mean gradient
+fn strongest_row(im: &GrayImage, xs: std::ops::Range<u32>, ys: std::ops::Range<u32>) -> Option<(u32, f64)> {
+    let n = xs.len() as f64;
+    ys.filter(|&y| y > 0 && y < im.height())
+        .map(|y| (y, xs.clone().map(|x| (gray(im, x, y) - gray(im, x, y - 1)).abs()).sum::<f64>() / n))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// find the borders of a panel around `approx`, each within `margin` pixels of the side of `approx`
+/// a border is the line with the strongest gradient, `None` if any of the four sides has no clear edge
+pub fn locate_edges(im: &GrayImage, approx: Rect<u32>, margin: u32) -> Option<Rect<u32>> {
+    if approx.width == 0 || approx.height == 0 {
+        return None;
+    }
+    let right = approx.left + approx.width;
+    let bottom = approx.top + approx.height;
+    // measure along the middle half of the sides, clear of the corners
+    let xs = approx.left + approx.width / 4..right - approx.width / 4;
+    let ys = approx.top + approx.height / 4..bottom - approx.height / 4;
+
+    let (left, s1) = strongest_column(im, approx.left.saturating_sub(margin)..approx.left + margin, ys.clone())?;
+    let (right, s2) = strongest_column(im, right.saturating_sub(margin)..right + margin, ys)?;
+    let (top, s3) = strongest_row(im, xs.clone(), approx.top.saturating_sub(margin)..approx.top + margin)?;
+    let (bottom, s4) = strongest_row(im, xs, bottom.saturating_sub(margin)..bottom + margin)?;
+
+    if [s1, s2, s3, s4].iter().any(|&s| s < MIN_EDGE_STRENGTH) || right <= left || bottom <= top {
+        return None;
+    }
+    Some(Rect::new(left, top, right - left, bottom - top))
+}
+
+/// the bounding box of the text around `approx`, searched within `margin` pixels of it
+/// ink is what differs from the most common gray level of the searched region, `None` if there is none
+pub fn locate_text(im: &GrayImage, approx: Rect<u32>, margin: u32) -> Option<Rect<u32>> {
+    let search = expand(im, approx, margin);
+    if search.width == 0 || search.height == 0 {
+        return None;
+    }
+
+    let mut histogram = [0_usize; 256];
+    for y in search.top..search.top + search.height {
+        for x in search.left..search.left + search.width {
+            histogram[im.get_pixel(x, y)[0] as usize] += 1;
+        }
+    }
+    let background = (0..256).max_by_key(|&i| histogram[i]).unwrap() as i32;
+
+    let (mut left, mut top, mut right, mut bottom) = (u32::MAX, u32::MAX, 0, 0);
+    for y in search.top..search.top + search.height {
+        for x in search.left..search.left + search.width {
+            if (im.get_pixel(x, y)[0] as i32 - background).abs() > INK_THRESHOLD {
+                left = left.min(x);
+                top = top.min(y);
+                right = right.max(x + 1);
+                bottom = bottom.max(y + 1);
+            }
+        }
+    }
+
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(Rect::new(left, top, right - left, bottom - top))
+}
@@ -0,0 +1,5 @@
+pub use calibrator::{AnchorDetector, Calibration, Calibrator};
+pub use detect::{locate_edges, locate_text};
+
+mod calibrator;
+mod detect;
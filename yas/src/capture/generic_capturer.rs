@@ -4,9 +4,9 @@ use crate::capture::WindowsCapturer;
 pub type GenericCapturer = WindowsCapturer;
 
 #[cfg(target_os = "linux")]
-use crate::capture::LibwayshotCapturer;
+use crate::capture::LinuxCapturer;
 #[cfg(target_os = "linux")]
-pub type GenericCapturer = LibwayshotCapturer;
+pub type GenericCapturer = LinuxCapturer;
 
 // #[cfg(target_os = "macos")]
 // pub type GenericCapturer = 
\ No newline at end of file
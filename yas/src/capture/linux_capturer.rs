@@ -0,0 +1,47 @@
+use anyhow::Result;
+use image::RgbImage;
+use log::info;
+#[cfg(feature = "capturer_libwayshot")]
+use log::warn;
+
+use crate::capture::Capturer;
+use crate::positioning::Rect;
+
+/// the capturer of the session: wlroots screencopy on wayland with `capturer_libwayshot`, x11 with `x11`
+pub struct LinuxCapturer {
+    inner: Box<dyn Capturer<RgbImage> + Send>,
+}
+
+impl LinuxCapturer {
+    pub fn new() -> Result<Self> {
+        #[cfg(feature = "capturer_libwayshot")]
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            match crate::capture::LibwayshotCapturer::new() {
+                Ok(capturer) => {
+                    info!("使用 wlroots screencopy 截图");
+                    return Ok(Self { inner: Box::new(capturer) });
+                },
+                Err(e) => warn!("无法使用 wlroots screencopy 截图（{}）", e),
+            }
+        }
+
+        #[cfg(feature = "x11")]
+        {
+            info!("使用 X11 截图");
+            Ok(Self {
+                inner: Box::new(crate::capture::XcbCapturer::new()?),
+            })
+        }
+
+        #[cfg(not(feature = "x11"))]
+        {
+            Err(anyhow::anyhow!("没有可用的截图方式，X11 截图需要启用 x11 特性"))
+        }
+    }
+}
+
+impl Capturer<RgbImage> for LinuxCapturer {
+    fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+        self.inner.capture_rect(rect)
+    }
+}
@@ -23,8 +23,16 @@ pub use winapi_capturer::WinapiCapturer;
 pub use windows_capturer::WindowsCapturer;
 
 // linux
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", feature = "capturer_libwayshot"))]
 mod libwayshot_capturer;
-
+#[cfg(all(target_os = "linux", feature = "x11"))]
+mod xcb_capturer;
 #[cfg(target_os = "linux")]
+mod linux_capturer;
+
+#[cfg(all(target_os = "linux", feature = "capturer_libwayshot"))]
 pub use libwayshot_capturer::LibwayshotCapturer;
+#[cfg(all(target_os = "linux", feature = "x11"))]
+pub use xcb_capturer::XcbCapturer;
+#[cfg(target_os = "linux")]
+pub use linux_capturer::LinuxCapturer;
@@ -0,0 +1,157 @@
+use std::cell::RefCell;
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+use xcb::{shm, x, Xid};
+
+use crate::capture::Capturer;
+use crate::positioning::Rect;
+
+/// a shared memory segment the x server writes the captured pixels to
+struct ShmSegment {
+    seg: shm::Seg,
+    addr: *mut u8,
+    size: usize,
+}
+
+// the segment is mapped for the whole process, and only accessed through the capturer owning it
+unsafe impl Send for ShmSegment {}
+
+impl ShmSegment {
+    fn new(conn: &xcb::Connection, size: usize) -> Result<ShmSegment> {
+        unsafe {
+            let shmid = libc::shmget(libc::IPC_PRIVATE, size, libc::IPC_CREAT | 0o600);
+            if shmid < 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            let addr = libc::shmat(shmid, std::ptr::null(), 0);
+            // removed once it's detached by both sides
+            libc::shmctl(shmid, libc::IPC_RMID, std::ptr::null_mut());
+            if addr as isize == -1 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+
+            let seg: shm::Seg = conn.generate_id();
+            if let Err(e) = conn.send_and_check_request(&shm::Attach { shmseg: seg, shmid: shmid as u32, read_only: false }) {
+                libc::shmdt(addr);
+                return Err(e.into());
+            }
+
+            Ok(ShmSegment { seg, addr: addr as *mut u8, size })
+        }
+    }
+
+    fn release(self, conn: &xcb::Connection) {
+        conn.send_request(&shm::Detach { shmseg: self.seg });
+        let _ = conn.flush();
+        unsafe {
+            libc::shmdt(self.addr as *const libc::c_void);
+        }
+    }
+}
+
+/// captures the root window of x11 by XShm, or by GetImage if the server has no shm extension.
+/// the windows of wine and proton are x11 windows, so it also captures them under xwayland
+pub struct XcbCapturer {
+    conn: xcb::Connection,
+    root: x::Window,
+    /// with the shm extension, grown to the largest capture so far
+    shm: Option<RefCell<Option<ShmSegment>>>,
+}
+
+impl XcbCapturer {
+    pub fn new() -> Result<Self> {
+        let (conn, screen_num) = xcb::Connection::connect_with_extensions(None, &[], &[xcb::Extension::Shm])?;
+        let root = conn.get_setup()
+            .roots()
+            .nth(screen_num as usize)
+            .ok_or_else(|| anyhow!("找不到 X 屏幕"))?
+            .root();
+        let has_shm = conn.active_extensions().any(|ext| ext == xcb::Extension::Shm);
+
+        Ok(Self {
+            conn,
+            root,
+            shm: has_shm.then(|| RefCell::new(None)),
+        })
+    }
+
+    fn check_depth(depth: u8) -> Result<()> {
+        // 24 and 32 bits are both 4 bytes per pixel, in BGRX
+        if depth != 24 && depth != 32 {
+            return Err(anyhow!("不支持的颜色深度: {}", depth));
+        }
+        Ok(())
+    }
+
+    fn capture_shm(&self, shm: &RefCell<Option<ShmSegment>>, rect: Rect<i32>) -> Result<Vec<u8>> {
+        let size = rect.width as usize * rect.height as usize * 4;
+        let mut segment = shm.borrow_mut();
+        if segment.as_ref().is_none_or(|s| s.size < size) {
+            if let Some(old) = segment.take() {
+                old.release(&self.conn);
+            }
+            *segment = Some(ShmSegment::new(&self.conn, size)?);
+        }
+        let segment = segment.as_ref().unwrap();
+
+        let cookie = self.conn.send_request(&shm::GetImage {
+            drawable: x::Drawable::Window(self.root),
+            x: rect.left as i16,
+            y: rect.top as i16,
+            width: rect.width as u16,
+            height: rect.height as u16,
+            plane_mask: u32::MAX,
+            format: x::ImageFormat::ZPixmap as u8,
+            shmseg: segment.seg,
+            offset: 0,
+        });
+        let reply = self.conn.wait_for_reply(cookie)?;
+        Self::check_depth(reply.depth())?;
+
+        Ok(unsafe { std::slice::from_raw_parts(segment.addr, size) }.to_vec())
+    }
+
+    fn capture_get_image(&self, rect: Rect<i32>) -> Result<Vec<u8>> {
+        let cookie = self.conn.send_request(&x::GetImage {
+            format: x::ImageFormat::ZPixmap,
+            drawable: x::Drawable::Window(self.root),
+            x: rect.left as i16,
+            y: rect.top as i16,
+            width: rect.width as u16,
+            height: rect.height as u16,
+            plane_mask: u32::MAX,
+        });
+        let reply = self.conn.wait_for_reply(cookie)?;
+        Self::check_depth(reply.depth())?;
+
+        Ok(reply.data().to_vec())
+    }
+}
+
+impl Capturer<RgbImage> for XcbCapturer {
+    fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+        if rect.width <= 0 || rect.height <= 0 {
+            return Err(anyhow!("截取区域为空: {:?}", rect));
+        }
+
+        let bgrx = match &self.shm {
+            Some(shm) => self.capture_shm(shm, rect)?,
+            None => self.capture_get_image(rect)?,
+        };
+        let rgb = bgrx.chunks_exact(4)
+            .flat_map(|p| [p[2], p[1], p[0]])
+            .collect::<Vec<u8>>();
+
+        RgbImage::from_raw(rect.width as u32, rect.height as u32, rgb)
+            .ok_or_else(|| anyhow!("截图数据不完整"))
+    }
+}
+
+impl Drop for XcbCapturer {
+    fn drop(&mut self) {
+        if let Some(segment) = self.shm.take().and_then(|shm| shm.into_inner()) {
+            segment.release(&self.conn);
+        }
+    }
+}
@@ -31,3 +31,12 @@ impl Default for CancellationToken {
         Self::new()
     }
 }
+
+/// share an existing flag, e.g. the stop flag of a scanner, setting it cancels the token
+impl From<Arc<AtomicBool>> for CancellationToken {
+    fn from(cancelled: Arc<AtomicBool>) -> Self {
+        CancellationToken {
+            cancelled,
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use image::{GrayImage, ImageBuffer, Luma};
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use image::{GrayImage, ImageBuffer, Luma, RgbImage};
 
 pub trait ToF32GrayImage {
     fn to_f32_gray_image(&self) -> ImageBuffer<Luma<f32>, Vec<f32>>;
@@ -12,3 +14,111 @@ impl ToF32GrayImage for GrayImage {
         })
     }
 }
+
+pub trait ToU8GrayImage {
+    fn to_u8_gray_image(&self) -> GrayImage;
+}
+
+impl ToU8GrayImage for ImageBuffer<Luma<f32>, Vec<f32>> {
+    fn to_u8_gray_image(&self) -> GrayImage {
+        ImageBuffer::from_fn(self.width(), self.height(), |x, y| {
+            let pv = self.get_pixel(x, y)[0];
+            Luma([(pv.clamp(0.0, 1.0) * 255.0).round() as u8])
+        })
+    }
+}
+
+/// decode a png / jpeg file into an rgb image
+/// with the `memmap` feature, the file is memory mapped instead of read into a buffer, which lowers the peak memory
+/// when processing many large captures. falls back to reading the file if it cannot be mapped
+pub fn from_mmap<P: AsRef<Path>>(path: P) -> Result<RgbImage> {
+    #[cfg(feature = "memmap")]
+    {
+        let mapped = std::fs::File::open(path.as_ref())
+            .and_then(|file| unsafe { memmap2::Mmap::map(&file) });
+        match mapped {
+            // the file must not be modified while it's mapped, which holds for captures on disk
+            Ok(mmap) => return Ok(image::load_from_memory(&mmap)?.to_rgb8()),
+            Err(e) => log::warn!("映射文件 {} 失败，改为直接读取：{}", path.as_ref().display(), e),
+        }
+    }
+
+    Ok(image::open(path)?.to_rgb8())
+}
+
+/// byte layout of a raw framebuffer, 8 bits per channel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgb8,
+    Bgr8,
+    Rgba8,
+    /// common on windows
+    Bgra8,
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::Rgb8 | PixelFormat::Bgr8 => 3,
+            PixelFormat::Rgba8 | PixelFormat::Bgra8 => 4,
+        }
+    }
+}
+
+/// copy a raw framebuffer, whose rows are `stride` bytes apart, into an rgb image, dropping alpha
+pub fn rgb_image_from_raw(buf: &[u8], width: u32, height: u32, stride: usize, format: PixelFormat) -> Result<RgbImage> {
+    let bpp = format.bytes_per_pixel();
+    let row_len = width as usize * bpp;
+    if stride < row_len {
+        return Err(anyhow!("stride {} is less than a row of {} bytes", stride, row_len));
+    }
+    if height > 0 && buf.len() < (height as usize - 1) * stride + row_len {
+        return Err(anyhow!("buffer of {} bytes is too small for {}x{} with stride {}", buf.len(), width, height, stride));
+    }
+
+    let mut im = RgbImage::new(width, height);
+    for (y, row) in im.rows_mut().enumerate() {
+        let src = &buf[y * stride..y * stride + row_len];
+        for (p, src) in row.zip(src.chunks_exact(bpp)) {
+            p.0 = match format {
+                PixelFormat::Rgb8 | PixelFormat::Rgba8 => [src[0], src[1], src[2]],
+                PixelFormat::Bgr8 | PixelFormat::Bgra8 => [src[2], src[1], src[0]],
+            };
+        }
+    }
+
+    Ok(im)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "tract_onnx")]
+    #[test]
+    fn recognize_a_region_of_a_mapped_file() {
+        use crate::ocr::ImageToText;
+        use crate::ocr::test_model;
+
+        // the text at the right of a larger capture
+        let text = test_model::text_image("abc");
+        let mut capture = RgbImage::new(200, 48);
+        image::imageops::replace(&mut capture, &text, 100, 8);
+        let path = std::env::temp_dir().join(format!("yas_from_mmap_{}.png", std::process::id()));
+        capture.save(&path).unwrap();
+
+        let loaded = from_mmap(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded, capture);
+
+        let region = image::imageops::crop_imm(&loaded, 100, 8, text.width(), text.height()).to_image();
+        let model = test_model::model();
+        assert_eq!(model.image_to_text(&region, false).unwrap(), "abc");
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        assert!(from_mmap(std::env::temp_dir().join("yas_no_such_capture.png")).is_err());
+    }
+}
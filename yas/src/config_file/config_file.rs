@@ -0,0 +1,288 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use clap::{value_parser, ArgAction, Command};
+use log::{info, warn};
+use toml::value::{Table, Value};
+
+pub const DEFAULT_CONFIG_FILE: &str = "yas.toml";
+
+const DEFAULT_CONFIG: &str = r#"# yas 的配置文件，键为命令行选项去掉开头的 "--"，命令行中给出的选项优先
+# 顶层的选项用于所有游戏，游戏没有的选项会被忽略
+# [genshin]、[starrail]、[ww_echo] 中的选项只用于对应的游戏，覆盖顶层的选项
+# 使用 --profile <名称> 选择下方的配置，其中的选项再覆盖以上的选项
+# 开关选项可以设为 false，以关闭前面设为 true 的选项
+
+# 最小星级
+# min-star = 4
+# 最小等级
+# min-level = 0
+# 翻页时滚轮停顿时间（ms），扫描时会根据画面自动调整
+# scroll-delay = 80
+# 使用固定的翻页停顿时间
+# fixed-scroll-delay = false
+# 输出格式
+# format = "mona"
+# 输出目录
+# output-dir = "."
+
+# 原神
+[genshin]
+# 同时扫描武器
+# scan-weapons = false
+
+# 鸣潮声骸
+[ww_echo]
+# min-star = 5
+
+# 完整扫描
+[profile.full]
+# min-star = 1
+# min-level = 0
+
+# 快速扫描，只扫描高等级的五星物品
+[profile.quick]
+# min-star = 5
+# min-level = 16
+"#;
+
+/// the default options of the command line, by their long names, e.g.
+/// ```toml
+/// min-star = 4
+///
+/// [ww_echo]
+/// min-star = 5
+///
+/// [profile.quick]
+/// min-level = 16
+/// ```
+/// the top level and the profiles are for every command, a table of the name of a command is for it only
+#[derive(Debug, Clone)]
+pub struct ConfigFile {
+    options: Table,
+    sections: Table,
+    profiles: Table,
+}
+
+/// `--name value` for every option. a flag is given if true, and turned off by `--name=false` if it takes a value,
+/// see `with_flag_values`
+fn push_args(args: &mut Vec<String>, name: &str, value: &Value, takes_value: bool) -> Result<()> {
+    match value {
+        Value::Boolean(true) if !takes_value => args.push(format!("--{}", name)),
+        Value::Boolean(false) if !takes_value => {},
+        Value::Boolean(v) => args.push(format!("--{}={}", name, v)),
+        Value::String(s) => args.extend([format!("--{}", name), s.clone()]),
+        Value::Integer(v) => args.extend([format!("--{}", name), v.to_string()]),
+        Value::Float(v) => args.extend([format!("--{}", name), v.to_string()]),
+        Value::Array(values) => {
+            for value in values.iter() {
+                push_args(args, name, value, takes_value)?;
+            }
+        },
+        _ => return Err(anyhow!("配置项 {} 的值不支持: {}", name, value)),
+    }
+    Ok(())
+}
+
+/// lets the flags of `command` take a value, e.g. `--verbose=false`, so a flag set by the config file can be turned
+/// off again by a profile or the command line
+pub fn with_flag_values(command: Command) -> Command {
+    command.mut_args(|arg| match arg.get_action() {
+        ArgAction::SetTrue => arg
+            .action(ArgAction::Set)
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_value("false")
+            .default_missing_value("true")
+            .value_parser(value_parser!(bool)),
+        _ => arg,
+    })
+}
+
+impl ConfigFile {
+    pub fn parse(content: &str) -> Result<ConfigFile> {
+        let options: Table = toml::from_str(content)?;
+        let mut config_file = ConfigFile { options: Table::new(), sections: Table::new(), profiles: Table::new() };
+        for (name, value) in options {
+            match value {
+                Value::Table(profiles) if name == "profile" => config_file.profiles = profiles,
+                Value::Table(_) => { config_file.sections.insert(name, value); },
+                _ if name == "profile" => return Err(anyhow!("profile 应为表")),
+                _ => { config_file.options.insert(name, value); },
+            }
+        }
+        Ok(config_file)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ConfigFile> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("无法读取配置文件 {}: {}", path.display(), e))?;
+        Self::parse(&content).map_err(|e| anyhow!("配置文件 {} 格式错误: {}", path.display(), e))
+    }
+
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.profiles.keys().map(|name| name.as_str()).collect()
+    }
+
+    /// the options as command line arguments of `command`, those of its table override the top level ones,
+    /// and those of `profile` override both. the shared options `command` doesn't have are left out,
+    /// those of its own table must be its options
+    pub fn to_args(&self, command: &Command, profile: Option<&str>) -> Result<Vec<String>> {
+        let command_name = command.get_name();
+        let mut options = self.options.clone();
+        let section = match self.sections.get(command_name) {
+            Some(Value::Table(section)) => section.clone(),
+            _ => Table::new(),
+        };
+        options.extend(section.clone());
+        if let Some(name) = profile {
+            match self.profiles.get(name) {
+                Some(Value::Table(profile)) => options.extend(profile.clone()),
+                Some(_) => return Err(anyhow!("配置 {} 应为表", name)),
+                None => return Err(anyhow!("配置文件中没有配置 {}，可用的配置: {}", name, self.profile_names().join(", "))),
+            }
+        }
+
+        let mut args = Vec::new();
+        for (name, value) in options.iter() {
+            let Some(arg) = command.get_arguments().find(|arg| arg.get_long() == Some(name.as_str())) else {
+                if section.contains_key(name) {
+                    return Err(anyhow!("[{}] 中的配置项 {} 不是它的选项", command_name, name));
+                }
+                warn!("配置项 {} 不是 {} 的选项，已忽略", name, command_name);
+                continue;
+            };
+            let takes_value = !matches!(arg.get_action(), ArgAction::SetTrue | ArgAction::SetFalse);
+            push_args(&mut args, name, value, takes_value)?;
+        }
+        Ok(args)
+    }
+}
+
+/// the value of `--name value` or `--name=value`
+fn find_option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let flag = format!("--{}", name);
+    let prefix = format!("--{}=", name);
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if *arg == flag {
+            args.get(i + 1).map(|s| s.as_str())
+        } else {
+            arg.strip_prefix(&prefix)
+        }
+    })
+}
+
+/// insert the options of the config file given by `--config`, or `yas.toml` if it exists, at `at` of the
+/// arguments, e.g. after the program name and the subcommand, for the arguments of `command`
+/// the options of the command line come later and override them, which requires `Command::args_override_self`
+pub fn apply_config_file(mut args: Vec<String>, at: usize, command: &Command) -> Result<Vec<String>> {
+    let at = at.min(args.len());
+    let explicit = find_option(&args[at..], "config").map(String::from);
+    let profile = find_option(&args[at..], "profile").map(String::from);
+
+    let config_file = match &explicit {
+        Some(path) => ConfigFile::load(path)?,
+        None if Path::new(DEFAULT_CONFIG_FILE).exists() => ConfigFile::load(DEFAULT_CONFIG_FILE)?,
+        None => {
+            if let Some(profile) = profile {
+                return Err(anyhow!("未找到配置文件，无法使用配置 {}", profile));
+            }
+            return Ok(args);
+        },
+    };
+    info!("使用配置文件: {}", explicit.as_deref().unwrap_or(DEFAULT_CONFIG_FILE));
+
+    let file_args = config_file.to_args(command, profile.as_deref())?;
+    args.splice(at..at, file_args);
+    Ok(args)
+}
+
+/// write a commented default config file, which must not exist
+pub fn init_config_file<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    if path.exists() {
+        return Err(anyhow!("配置文件 {} 已存在", path.display()));
+    }
+    std::fs::write(path, DEFAULT_CONFIG)?;
+    info!("已生成配置文件: {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Arg;
+
+    use super::*;
+
+    fn command() -> Command {
+        let command = Command::new("genshin")
+            .arg(Arg::new("min-star").long("min-star"))
+            .arg(Arg::new("verbose").long("verbose").action(ArgAction::SetTrue));
+        with_flag_values(command).args_override_self(true)
+    }
+
+    fn parse(config: &str, profile: Option<&str>, command_line: &[&str]) -> clap::ArgMatches {
+        let file_args = ConfigFile::parse(config).unwrap().to_args(&command(), profile).unwrap();
+        let args = std::iter::once("genshin").chain(file_args.iter().map(|arg| arg.as_str())).chain(command_line.iter().copied());
+        command().try_get_matches_from(args).unwrap()
+    }
+
+    const CONFIG: &str = r#"
+min-star = 4
+verbose = true
+format = "mona"
+
+[genshin]
+min-star = 5
+
+[ww_echo]
+min-star = 3
+
+[profile.quiet]
+verbose = false
+"#;
+
+    #[test]
+    fn the_table_of_the_command_overrides_the_top_level() {
+        let matches = parse(CONFIG, None, &[]);
+        assert_eq!(matches.get_one::<String>("min-star").unwrap(), "5");
+        assert!(matches.get_flag("verbose"));
+
+        let matches = parse(CONFIG, None, &["--min-star", "1"]);
+        assert_eq!(matches.get_one::<String>("min-star").unwrap(), "1");
+    }
+
+    #[test]
+    fn false_turns_a_flag_off() {
+        assert!(!parse(CONFIG, Some("quiet"), &[]).get_flag("verbose"));
+        assert!(parse(CONFIG, Some("quiet"), &["--verbose"]).get_flag("verbose"));
+        assert!(!parse(CONFIG, None, &["--verbose=false"]).get_flag("verbose"));
+    }
+
+    #[test]
+    fn a_flag_without_values() {
+        let command = Command::new("genshin").arg(Arg::new("verbose").long("verbose").action(ArgAction::SetTrue));
+        let to_args = |config: &str| ConfigFile::parse(config).unwrap().to_args(&command, None).unwrap();
+        assert_eq!(to_args("verbose = true"), vec!["--verbose"]);
+        assert!(to_args("verbose = false").is_empty());
+    }
+
+    #[test]
+    fn the_options_of_the_table_must_be_of_the_command() {
+        let config = ConfigFile::parse("no-such-option = 1").unwrap();
+        // shared by other commands
+        assert!(config.to_args(&command(), None).unwrap().is_empty());
+
+        let config = ConfigFile::parse("[genshin]\nno-such-option = 1").unwrap();
+        assert!(config.to_args(&command(), None).is_err());
+        assert!(ConfigFile::parse(CONFIG).unwrap().to_args(&command(), Some("none")).is_err());
+    }
+
+    #[test]
+    fn the_default_config_is_valid() {
+        let config = ConfigFile::parse(DEFAULT_CONFIG).unwrap();
+        assert_eq!(config.profile_names(), vec!["full", "quick"]);
+        assert!(config.to_args(&command(), Some("quick")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,11 @@
+/// declares the options `apply_config_file` reads, so they are accepted and listed in the help
+#[derive(Clone, clap::Args)]
+pub struct ConfigFileArgs {
+    /// the toml file of the default options, `yas.toml` of the current directory if it exists
+    #[arg(id = "config", long = "config", help = "配置文件路径，默认为当前目录下的 yas.toml", value_name = "CONFIG")]
+    pub config: Option<String>,
+
+    /// a profile of the config file, whose options are added to the top level ones
+    #[arg(id = "profile", long = "profile", help = "使用配置文件中的指定配置", value_name = "PROFILE")]
+    pub profile: Option<String>,
+}
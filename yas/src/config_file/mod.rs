@@ -0,0 +1,5 @@
+pub use config_file::{apply_config_file, init_config_file, with_flag_values, ConfigFile, DEFAULT_CONFIG_FILE};
+pub use config_file_args::ConfigFileArgs;
+
+mod config_file;
+mod config_file_args;
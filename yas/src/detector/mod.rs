@@ -0,0 +1,5 @@
+pub use shapes::{padlock_template, ring_template, star_template};
+pub use template_matching::{detect_equipped, detect_lock, detect_star_count, find_matches, match_template, ncc};
+
+mod shapes;
+mod template_matching;
@@ -0,0 +1,69 @@
+use image::{GrayImage, Luma};
+
+/// subsamples per pixel on each axis, for anti-aliased edges
+const SUPERSAMPLE: u32 = 4;
+
+/// render a `width * height` template, the shape is bright on black
+/// `inside` takes a point in 0.0 ~ 1.0 of both axes
+fn render(width: u32, height: u32, inside: impl Fn(f64, f64) -> bool) -> GrayImage {
+    let mut im = GrayImage::new(width.max(1), height.max(1));
+    let (w, h) = im.dimensions();
+    let n = SUPERSAMPLE * SUPERSAMPLE;
+    for y in 0..h {
+        for x in 0..w {
+            let mut hits = 0;
+            for sy in 0..SUPERSAMPLE {
+                for sx in 0..SUPERSAMPLE {
+                    let u = (x as f64 + (sx as f64 + 0.5) / SUPERSAMPLE as f64) / w as f64;
+                    let v = (y as f64 + (sy as f64 + 0.5) / SUPERSAMPLE as f64) / h as f64;
+                    if inside(u, v) {
+                        hits += 1;
+                    }
+                }
+            }
+            im.put_pixel(x, y, Luma([(hits * 255 / n) as u8]));
+        }
+    }
+    im
+}
+
+/// a five-pointed star, as the rarity stars
+pub fn star_template(size: u32) -> GrayImage {
+    // the ten vertices, alternating the outer and the inner radius, starting from the top
+    let vertices: Vec<(f64, f64)> = (0..10)
+        .map(|i| {
+            let r = if i % 2 == 0 { 0.5 } else { 0.2 };
+            let angle = std::f64::consts::PI * (i as f64 / 5.0 - 0.5);
+            (0.5 + r * angle.cos(), 0.5 + r * angle.sin())
+        })
+        .collect();
+
+    render(size, size, |u, v| {
+        // even-odd rule
+        let mut inside = false;
+        for (&(x1, y1), &(x2, y2)) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+            if (y1 > v) != (y2 > v) && u < (x2 - x1) * (v - y1) / (y2 - y1) + x1 {
+                inside = !inside;
+            }
+        }
+        inside
+    })
+}
+
+/// a padlock: a body in the lower half and an arched shackle above it
+pub fn padlock_template(size: u32) -> GrayImage {
+    render(size, size, |u, v| {
+        let body = (0.15..=0.85).contains(&u) && (0.45..=0.95).contains(&v);
+        let d = ((u - 0.5).powi(2) + (v - 0.45).powi(2)).sqrt();
+        let shackle = v <= 0.45 && (0.2..=0.32).contains(&d);
+        body || shackle
+    })
+}
+
+/// a circle outline, as the frame of an avatar
+pub fn ring_template(size: u32) -> GrayImage {
+    render(size, size, |u, v| {
+        let d = ((u - 0.5).powi(2) + (v - 0.5).powi(2)).sqrt();
+        (0.4..=0.5).contains(&d)
+    })
+}
@@ -0,0 +1,148 @@
+use image::GrayImage;
+
+use crate::positioning::Pos;
+
+/// normalized cross-correlation of `template` and the region of `image` at `pos`, in -1.0 ~ 1.0
+/// a flat template or region (without variance) correlates with nothing, and scores 0.0
+pub fn ncc(image: &GrayImage, template: &GrayImage, pos: Pos<u32>) -> f32 {
+    let (tw, th) = template.dimensions();
+    if pos.x + tw > image.width() || pos.y + th > image.height() || tw == 0 || th == 0 {
+        return 0.0;
+    }
+
+    let n = (tw * th) as f64;
+    let mut sum_i = 0.0;
+    let mut sum_t = 0.0;
+    for y in 0..th {
+        for x in 0..tw {
+            sum_i += image.get_pixel(pos.x + x, pos.y + y)[0] as f64;
+            sum_t += template.get_pixel(x, y)[0] as f64;
+        }
+    }
+    let mean_i = sum_i / n;
+    let mean_t = sum_t / n;
+
+    let mut cross = 0.0;
+    let mut var_i = 0.0;
+    let mut var_t = 0.0;
+    for y in 0..th {
+        for x in 0..tw {
+            let i = image.get_pixel(pos.x + x, pos.y + y)[0] as f64 - mean_i;
+            let t = template.get_pixel(x, y)[0] as f64 - mean_t;
+            cross += i * t;
+            var_i += i * i;
+            var_t += t * t;
+        }
+    }
+
+    if var_i <= f64::EPSILON || var_t <= f64::EPSILON {
+        return 0.0;
+    }
+    (cross / (var_i * var_t).sqrt()) as f32
+}
+
+/// the best match of `template` in `image`, `None` if the template is larger than the image
+pub fn match_template(image: &GrayImage, template: &GrayImage) -> Option<(Pos<u32>, f32)> {
+    let (tw, th) = template.dimensions();
+    if tw > image.width() || th > image.height() {
+        return None;
+    }
+
+    let mut best: Option<(Pos<u32>, f32)> = None;
+    for y in 0..=image.height() - th {
+        for x in 0..=image.width() - tw {
+            let pos = Pos::new(x, y);
+            let score = ncc(image, template, pos);
+            if best.is_none_or(|(_, s)| score > s) {
+                best = Some((pos, score));
+            }
+        }
+    }
+    best
+}
+
+/// every match of `template` scoring at least `threshold`, best first
+/// a match overlapping a better one by more than half the template is suppressed
+pub fn find_matches(image: &GrayImage, template: &GrayImage, threshold: f32) -> Vec<(Pos<u32>, f32)> {
+    let (tw, th) = template.dimensions();
+    if tw > image.width() || th > image.height() {
+        return Vec::new();
+    }
+
+    let mut candidates = Vec::new();
+    for y in 0..=image.height() - th {
+        for x in 0..=image.width() - tw {
+            let pos = Pos::new(x, y);
+            let score = ncc(image, template, pos);
+            if score >= threshold {
+                candidates.push((pos, score));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut matches: Vec<(Pos<u32>, f32)> = Vec::new();
+    for (pos, score) in candidates {
+        let overlaps = matches.iter().any(|(p, _)| {
+            p.x.abs_diff(pos.x) < tw / 2 && p.y.abs_diff(pos.y) < th / 2
+        });
+        if !overlaps {
+            matches.push((pos, score));
+        }
+    }
+    matches
+}
+
+/// how many stars are in a crop of the rarity row
+pub fn detect_star_count(image: &GrayImage, star: &GrayImage, threshold: f32) -> usize {
+    find_matches(image, star, threshold).len()
+}
+
+/// whether the padlock is in a crop around the lock button
+pub fn detect_lock(image: &GrayImage, padlock: &GrayImage, threshold: f32) -> bool {
+    match_template(image, padlock).is_some_and(|(_, score)| score >= threshold)
+}
+
+/// whether the avatar of the equipping character is in a crop before the equip text
+pub fn detect_equipped(image: &GrayImage, avatar: &GrayImage, threshold: f32) -> bool {
+    match_template(image, avatar).is_some_and(|(_, score)| score >= threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::imageops::overlay;
+
+    use super::*;
+    use crate::detector::{padlock_template, star_template};
+
+    #[test]
+    fn ncc_of_a_template_with_itself_is_one() {
+        let star = star_template(16);
+        assert!((ncc(&star, &star, Pos::new(0, 0)) - 1.0).abs() < 1e-5);
+        assert_eq!(ncc(&GrayImage::new(16, 16), &star, Pos::new(0, 0)), 0.0);
+        // out of the image
+        assert_eq!(ncc(&star, &star, Pos::new(1, 0)), 0.0);
+    }
+
+    #[test]
+    fn count_the_stars_of_a_row() {
+        let star = star_template(20);
+        let mut row = GrayImage::new(130, 22);
+        for i in 0..4 {
+            overlay(&mut row, &star, 3 + 24 * i, 1);
+        }
+        assert_eq!(detect_star_count(&row, &star, 0.6), 4);
+        assert_eq!(detect_star_count(&GrayImage::new(130, 22), &star, 0.6), 0);
+    }
+
+    #[test]
+    fn find_the_padlock() {
+        let padlock = padlock_template(18);
+        let mut crop = GrayImage::new(30, 30);
+        overlay(&mut crop, &padlock, 5, 7);
+        assert_eq!(match_template(&crop, &padlock).unwrap().0, Pos::new(5, 7));
+        assert!(detect_lock(&crop, &padlock, 0.5));
+        assert!(!detect_lock(&GrayImage::new(30, 30), &padlock, 0.5));
+        assert!(match_template(&padlock, &crop).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+use image::{Rgb, RgbImage};
+use crate::positioning::Rect;
+
+const BOX_COLORS: [Rgb<u8>; 4] = [
+    Rgb([255, 0, 0]),
+    Rgb([0, 200, 0]),
+    Rgb([0, 0, 255]),
+    Rgb([255, 0, 255]),
+];
+
+// 3 * 5 bitmaps of the digits, one row per element, the highest bit is the left column
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const DIGIT_SCALE: u32 = 2;
+
+fn put_pixel_checked(image: &mut RgbImage, x: i64, y: i64, color: Rgb<u8>) {
+    if x >= 0 && y >= 0 && x < image.width() as i64 && y < image.height() as i64 {
+        image.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+fn draw_box(image: &mut RgbImage, rect: &Rect<f64>, color: Rgb<u8>) {
+    let left = rect.left as i64;
+    let top = rect.top as i64;
+    let right = (rect.left + rect.width) as i64;
+    let bottom = (rect.top + rect.height) as i64;
+
+    for x in left..=right {
+        put_pixel_checked(image, x, top, color);
+        put_pixel_checked(image, x, bottom, color);
+    }
+    for y in top..=bottom {
+        put_pixel_checked(image, left, y, color);
+        put_pixel_checked(image, right, y, color);
+    }
+}
+
+fn draw_number(image: &mut RgbImage, number: usize, x: i64, y: i64, color: Rgb<u8>) {
+    let digit_width = (3 + 1) * DIGIT_SCALE as i64;
+    for (i, c) in number.to_string().chars().enumerate() {
+        let bitmap = DIGITS[c.to_digit(10).unwrap() as usize];
+        let origin_x = x + i as i64 * digit_width;
+        for (row, bits) in bitmap.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                for dy in 0..DIGIT_SCALE as i64 {
+                    for dx in 0..DIGIT_SCALE as i64 {
+                        let px = origin_x + col as i64 * DIGIT_SCALE as i64 + dx;
+                        let py = y + row as i64 * DIGIT_SCALE as i64 + dy;
+                        put_pixel_checked(image, px, py, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// draw the box of every crop with its index onto a copy of the screenshot
+/// there is no font for the recognized text, so look the index up in `debug_overlay_svg` or the results themselves
+pub fn debug_overlay(image: &RgbImage, results: &[(Rect<f64>, String)]) -> RgbImage {
+    let mut overlay = image.clone();
+    for (index, (rect, _)) in results.iter().enumerate() {
+        let color = BOX_COLORS[index % BOX_COLORS.len()];
+        draw_box(&mut overlay, rect, color);
+        draw_number(&mut overlay, index, rect.left as i64 + 2, rect.top as i64 + 2, color);
+    }
+    overlay
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// an svg of the boxes and the recognized text of every crop, to be laid over a screenshot of `width * height`
+pub fn debug_overlay_svg(width: u32, height: u32, results: &[(Rect<f64>, String)]) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+    for (index, (rect, text)) in results.iter().enumerate() {
+        let Rgb([r, g, b]) = BOX_COLORS[index % BOX_COLORS.len()];
+        svg += &format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"rgb({},{},{})\"/>\n",
+            rect.left, rect.top, rect.width, rect.height, r, g, b
+        );
+        svg += &format!(
+            "<text x=\"{}\" y=\"{}\" fill=\"rgb({},{},{})\" font-size=\"14\">{}: {}</text>\n",
+            rect.left, rect.top - 2.0, r, g, b, index, escape_xml(text)
+        );
+    }
+    svg += "</svg>\n";
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results() -> Vec<(Rect<f64>, String)> {
+        vec![
+            (Rect::new(10.0, 20.0, 60.0, 30.0), String::from("攻击力")),
+            (Rect::new(100.0, 50.0, 80.0, 40.0), String::from("<+46.6%>")),
+            // partly outside the image
+            (Rect::new(150.0, 80.0, 100.0, 100.0), String::from("")),
+        ]
+    }
+
+    /// whether `(x, y)` is on the border of `rect`, or in the area of its index
+    fn is_drawn(rect: &Rect<f64>, x: u32, y: u32) -> bool {
+        let (x, y) = (x as i64, y as i64);
+        let (left, top) = (rect.left as i64, rect.top as i64);
+        let (right, bottom) = ((rect.left + rect.width) as i64, (rect.top + rect.height) as i64);
+        let on_border = ((x == left || x == right) && (top..=bottom).contains(&y))
+            || ((y == top || y == bottom) && (left..=right).contains(&x));
+        let on_index = (left + 2..left + 2 + 3 * DIGIT_SCALE as i64).contains(&x)
+            && (top + 2..top + 2 + 5 * DIGIT_SCALE as i64).contains(&y);
+        on_border || on_index
+    }
+
+    #[test]
+    fn overlay_draws_only_the_boxes() {
+        let image = RgbImage::from_pixel(200, 100, Rgb([50, 50, 50]));
+        let results = results();
+        let overlay = debug_overlay(&image, &results);
+
+        assert_eq!(overlay.dimensions(), image.dimensions());
+        for (index, (rect, _)) in results.iter().enumerate() {
+            let color = BOX_COLORS[index % BOX_COLORS.len()];
+            let (left, top) = (rect.left as u32, rect.top as u32);
+            assert_eq!(*overlay.get_pixel(left, top + 15), color);
+            assert_eq!(*overlay.get_pixel(left + 30, top), color);
+        }
+        let (rect, _) = &results[0];
+        assert_eq!(*overlay.get_pixel((rect.left + rect.width) as u32, 35), BOX_COLORS[0]);
+        assert_eq!(*overlay.get_pixel(40, (rect.top + rect.height) as u32), BOX_COLORS[0]);
+
+        for (x, y, p) in overlay.enumerate_pixels() {
+            if !results.iter().any(|(rect, _)| is_drawn(rect, x, y)) {
+                assert_eq!(p, image.get_pixel(x, y), "({}, {})", x, y);
+            }
+        }
+        assert_ne!(overlay, image);
+        assert_eq!(debug_overlay(&image, &[]), image);
+    }
+
+    #[test]
+    fn svg_has_a_box_and_the_text_of_every_result() {
+        let svg = debug_overlay_svg(200, 100, &results());
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"200\" height=\"100\" viewBox=\"0 0 200 100\">"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<rect ").count(), 3);
+        assert!(svg.contains("<rect x=\"10\" y=\"20\" width=\"60\" height=\"30\" fill=\"none\" stroke=\"rgb(255,0,0)\"/>"));
+        assert!(svg.contains(">0: 攻击力</text>"));
+        assert!(svg.contains(">1: &lt;+46.6%&gt;</text>"));
+        assert!(!svg.contains("<+46.6%>"));
+    }
+}
@@ -1 +1,2 @@
 pub mod draw_capture_region;
+pub mod debug_overlay;
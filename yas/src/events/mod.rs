@@ -0,0 +1,5 @@
+pub use scan_event::{EventFormat, ScanEvent};
+pub use scan_observer::{JsonLinesObserver, ScanObserver, ScanObservers};
+
+mod scan_event;
+mod scan_observer;
@@ -0,0 +1,69 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// how the events are written, see `JsonLinesObserver`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum EventFormat {
+    /// one json object per line
+    Jsonl,
+}
+
+/// what happens during a scan, for front-ends to show the progress
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ScanEvent {
+    /// `total` is how many items are expected, if known, the scan may stop before
+    Start { total: Option<usize> },
+    /// an item of the results, i.e. recognized and not a duplicate, `index` is in capture order,
+    /// `confidence` is the least among its text regions
+    Item { index: usize, confidence: f32, item: serde_json::Value },
+    Error { index: Option<usize>, message: String },
+    /// the next item is captured again, `attempt` counts from 1
+    Retry { attempt: usize, reason: String },
+    Progress { done: usize, total: usize, percent: f64 },
+    Finish { count: usize, elapsed_ms: u128 },
+}
+
+impl ScanEvent {
+    pub fn progress(done: usize, total: usize) -> ScanEvent {
+        let percent = if total == 0 {
+            100.0
+        } else {
+            (done as f64 / total as f64 * 100.0).min(100.0)
+        };
+        ScanEvent::Progress { done, total, percent }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_tagged() {
+        let event = ScanEvent::Item { index: 3, confidence: 0.5, item: serde_json::json!({ "level": 20 }) };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "event": "item", "index": 3, "confidence": 0.5, "item": { "level": 20 } })
+        );
+        assert_eq!(
+            serde_json::to_value(ScanEvent::Start { total: None }).unwrap(),
+            serde_json::json!({ "event": "start", "total": null })
+        );
+        assert_eq!(
+            serde_json::to_value(ScanEvent::Finish { count: 2, elapsed_ms: 10 }).unwrap(),
+            serde_json::json!({ "event": "finish", "count": 2, "elapsed_ms": 10 })
+        );
+    }
+
+    #[test]
+    fn progress_percent() {
+        let percent = |event: ScanEvent| match event {
+            ScanEvent::Progress { percent, .. } => percent,
+            _ => unreachable!(),
+        };
+        assert_eq!(percent(ScanEvent::progress(1, 4)), 25.0);
+        assert_eq!(percent(ScanEvent::progress(5, 4)), 100.0);
+        assert_eq!(percent(ScanEvent::progress(0, 0)), 100.0);
+    }
+}
@@ -0,0 +1,119 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use super::ScanEvent;
+
+/// receives the events of a scan, possibly from the recognition threads
+pub trait ScanObserver: Send + Sync {
+    fn on_event(&self, event: &ScanEvent);
+}
+
+/// writes each event as a line of json, flushed right away for the reader on the other side
+pub struct JsonLinesObserver {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonLinesObserver {
+    pub fn new(writer: Box<dyn Write + Send>) -> JsonLinesObserver {
+        JsonLinesObserver {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// the logs go to stderr, so stdout holds nothing but the events
+    pub fn stdout() -> JsonLinesObserver {
+        Self::new(Box::new(std::io::stdout()))
+    }
+
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<JsonLinesObserver> {
+        Ok(Self::new(Box::new(File::create(path)?)))
+    }
+}
+
+impl ScanObserver for JsonLinesObserver {
+    fn on_event(&self, event: &ScanEvent) {
+        let Ok(line) = serde_json::to_string(event) else {
+            return;
+        };
+        let mut writer = self.writer.lock().unwrap();
+        // a front-end which went away is none of the scan's business
+        let _ = writeln!(writer, "{}", line);
+        let _ = writer.flush();
+    }
+}
+
+/// passes the events on to each of the observers, e.g. the events file and a library's callback
+#[derive(Default)]
+pub struct ScanObservers(pub Vec<Arc<dyn ScanObserver>>);
+
+impl ScanObservers {
+    /// the only observer as is, `None` if there's none
+    pub fn into_observer(mut self) -> Option<Arc<dyn ScanObserver>> {
+        match self.0.len() {
+            0 => None,
+            1 => self.0.pop(),
+            _ => Some(Arc::new(self)),
+        }
+    }
+}
+
+impl ScanObserver for ScanObservers {
+    fn on_event(&self, event: &ScanEvent) {
+        for observer in self.0.iter() {
+            observer.on_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a writer whose contents can be read after it's handed to the observer
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn one_json_object_per_line() {
+        let buffer = Shared::default();
+        let observer = JsonLinesObserver::new(Box::new(buffer.clone()));
+        observer.on_event(&ScanEvent::Start { total: Some(2) });
+        observer.on_event(&ScanEvent::Retry { attempt: 1, reason: String::from("a\nb") });
+        observer.on_event(&ScanEvent::progress(1, 2));
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<serde_json::Value> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0]["event"], "start");
+        assert_eq!(events[1]["reason"], "a\nb");
+        assert_eq!(events[2]["percent"], 50.0);
+    }
+
+    #[test]
+    fn every_observer_gets_the_events() {
+        let buffers = [Shared::default(), Shared::default()];
+        let observers = ScanObservers(buffers.iter()
+            .map(|buffer| Arc::new(JsonLinesObserver::new(Box::new(buffer.clone()))) as Arc<dyn ScanObserver>)
+            .collect());
+        observers.into_observer().unwrap().on_event(&ScanEvent::Start { total: None });
+
+        for buffer in buffers {
+            assert_eq!(buffer.0.lock().unwrap().as_slice(), b"{\"event\":\"start\",\"total\":null}\n");
+        }
+        assert!(ScanObservers::default().into_observer().is_none());
+    }
+}
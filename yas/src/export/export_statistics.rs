@@ -5,6 +5,7 @@ use prettytable::{row, Row, Table};
 
 use crate::export::StatisticItem;
 
+#[derive(Default)]
 pub struct ExportStatistics {
     pub exported_assets: Vec<StatisticItem>,
     pub failed_items: Vec<StatisticItem>,
@@ -6,6 +6,7 @@ use log::error;
 
 use crate::export::{ExportItem, ExportStatistics, StatisticItem};
 
+#[derive(Default)]
 pub struct ExportAssets {
     pub assets: Vec<ExportItem>
 }
@@ -39,13 +40,10 @@ impl ExportAssets {
                 },
             };
 
-            match file.write_all(&item.contents) {
-                Err(why) => {
-                    stat.failed_items.push(StatisticItem::from_export_item(item));
-                    error!("无法写入文件 {:?}: {}", &item.filename, why);
-                    continue;
-                },
-                Ok(_) => (),
+            if let Err(why) = file.write_all(&item.contents) {
+                stat.failed_items.push(StatisticItem::from_export_item(item));
+                error!("无法写入文件 {:?}: {}", &item.filename, why);
+                continue;
             }
 
             stat.exported_assets.push(StatisticItem::from_export_item(item));
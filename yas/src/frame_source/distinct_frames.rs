@@ -0,0 +1,152 @@
+use anyhow::Result;
+use image::RgbImage;
+
+use crate::positioning::{Rect, Size};
+
+use super::FrameSource;
+
+/// the mean absolute difference of the channels of two frames in `region`, in 0.0 ~ 255.0
+/// frames of different sizes differ completely
+pub fn mean_difference(a: &RgbImage, b: &RgbImage, region: Rect<u32>) -> f64 {
+    if a.dimensions() != b.dimensions()
+        || region.left + region.width > a.width()
+        || region.top + region.height > a.height()
+        || region.width == 0
+        || region.height == 0
+    {
+        return 255.0;
+    }
+
+    let mut sum: u64 = 0;
+    for y in region.top..region.top + region.height {
+        for x in region.left..region.left + region.width {
+            let p = a.get_pixel(x, y);
+            let q = b.get_pixel(x, y);
+            sum += p.0.iter().zip(q.0.iter()).map(|(&c1, &c2)| c1.abs_diff(c2) as u64).sum::<u64>();
+        }
+    }
+    sum as f64 / (region.width as u64 * region.height as u64 * 3) as f64
+}
+
+/// the frames of another source where `region` shows something new, one per item.
+/// a recording holds many frames of each item, and some during the transitions between them,
+/// so a frame is taken once the region changes and then holds still for `stable_frames` frames
+pub struct DistinctFrames<S> {
+    source: S,
+    region: Rect<u32>,
+    /// how much the region differs between frames to be changing, see `mean_difference`
+    threshold: f64,
+    stable_frames: usize,
+    previous: Option<RgbImage>,
+    /// the last frame taken
+    taken: Option<RgbImage>,
+    stable_count: usize,
+}
+
+impl<S: FrameSource> DistinctFrames<S> {
+    pub fn new(source: S, region: Rect<u32>) -> DistinctFrames<S> {
+        DistinctFrames {
+            source,
+            region,
+            threshold: 8.0,
+            stable_frames: 2,
+            previous: None,
+            taken: None,
+            stable_count: 0,
+        }
+    }
+
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// 0 takes every changed frame, for screenshots taken one per item
+    pub fn stable_frames(mut self, stable_frames: usize) -> Self {
+        self.stable_frames = stable_frames;
+        self
+    }
+}
+
+impl<S: FrameSource> FrameSource for DistinctFrames<S> {
+    fn next_frame(&mut self) -> Result<Option<RgbImage>> {
+        while let Some(frame) = self.source.next_frame()? {
+            let still = self.previous.as_ref()
+                .is_some_and(|previous| mean_difference(previous, &frame, self.region) < self.threshold);
+            self.stable_count = if still { self.stable_count + 1 } else { 0 };
+            self.previous = Some(frame.clone());
+
+            if self.stable_count < self.stable_frames {
+                continue;
+            }
+            let is_new = self.taken.as_ref()
+                .is_none_or(|taken| mean_difference(taken, &frame, self.region) >= self.threshold);
+            if is_new {
+                self.taken = Some(frame.clone());
+                return Ok(Some(frame));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn frame_size(&self) -> Result<Size<usize>> {
+        self.source.frame_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use image::Rgb;
+
+    use super::*;
+
+    struct Frames(VecDeque<RgbImage>);
+
+    impl FrameSource for Frames {
+        fn next_frame(&mut self) -> Result<Option<RgbImage>> {
+            Ok(self.0.pop_front())
+        }
+
+        fn frame_size(&self) -> Result<Size<usize>> {
+            Ok(Size::new(8, 8))
+        }
+    }
+
+    fn frame(value: u8) -> RgbImage {
+        RgbImage::from_pixel(8, 8, Rgb([value, value, value]))
+    }
+
+    fn values(frames: Vec<RgbImage>, stable_frames: usize) -> Vec<u8> {
+        let mut distinct = DistinctFrames::new(Frames(frames.into()), Rect::new(0, 0, 8, 8)).stable_frames(stable_frames);
+        let mut values = Vec::new();
+        while let Some(frame) = distinct.next_frame().unwrap() {
+            values.push(frame.get_pixel(0, 0)[0]);
+        }
+        values
+    }
+
+    #[test]
+    fn mean_difference_of_frames() {
+        let region = Rect::new(0, 0, 8, 8);
+        assert_eq!(mean_difference(&frame(10), &frame(10), region), 0.0);
+        assert_eq!(mean_difference(&frame(10), &frame(30), region), 20.0);
+        assert_eq!(mean_difference(&frame(10), &RgbImage::new(4, 4), region), 255.0);
+        assert_eq!(mean_difference(&frame(10), &frame(10), Rect::new(4, 4, 8, 8)), 255.0);
+    }
+
+    #[test]
+    fn one_frame_per_still_item() {
+        // two items with a transition frame between them, and the first item shown again
+        let frames = [100, 100, 100, 160, 200, 200, 200, 200, 100, 100, 100].map(frame).to_vec();
+        assert_eq!(values(frames, 2), vec![100, 200, 100]);
+    }
+
+    #[test]
+    fn every_change_without_stable_frames() {
+        let frames = [100, 100, 160, 200, 202].map(frame).to_vec();
+        assert_eq!(values(frames, 0), vec![100, 160, 200]);
+    }
+}
@@ -0,0 +1,70 @@
+use std::cell::RefCell;
+
+use anyhow::{anyhow, Result};
+use image::{GenericImageView, RgbImage};
+
+use crate::capture::Capturer;
+use crate::positioning::Rect;
+
+/// captures from the current frame of a `FrameSource`, whose origin is the origin of the window
+pub struct FrameCapturer {
+    frame: RefCell<RgbImage>,
+}
+
+impl FrameCapturer {
+    pub fn new() -> FrameCapturer {
+        FrameCapturer {
+            frame: RefCell::new(RgbImage::new(0, 0)),
+        }
+    }
+
+    pub fn set_frame(&self, frame: RgbImage) {
+        *self.frame.borrow_mut() = frame;
+    }
+}
+
+impl Default for FrameCapturer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Capturer<RgbImage> for FrameCapturer {
+    fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+        let frame = self.frame.borrow();
+        if rect.left < 0
+            || rect.top < 0
+            || rect.width < 0
+            || rect.height < 0
+            || (rect.left + rect.width) as u32 > frame.width()
+            || (rect.top + rect.height) as u32 > frame.height()
+        {
+            return Err(anyhow!("截取区域 {:?} 超出帧的范围 {}x{}", rect, frame.width(), frame.height()));
+        }
+
+        Ok(frame.view(rect.left as u32, rect.top as u32, rect.width as u32, rect.height as u32).to_image())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+
+    use super::*;
+
+    #[test]
+    fn capture_from_the_current_frame() {
+        let capturer = FrameCapturer::new();
+        assert!(capturer.capture_rect(Rect::new(0, 0, 1, 1)).is_err());
+
+        let mut frame = RgbImage::new(10, 10);
+        frame.put_pixel(3, 4, Rgb([255, 0, 0]));
+        capturer.set_frame(frame);
+
+        let im = capturer.capture_rect(Rect::new(3, 4, 2, 2)).unwrap();
+        assert_eq!(im.dimensions(), (2, 2));
+        assert_eq!(*im.get_pixel(0, 0), Rgb([255, 0, 0]));
+        assert!(capturer.capture_rect(Rect::new(9, 0, 2, 2)).is_err());
+        assert!(capturer.capture_rect(Rect::new(-1, 0, 2, 2)).is_err());
+    }
+}
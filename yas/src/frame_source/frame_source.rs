@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+
+use crate::positioning::Size;
+
+use super::ImageDirSource;
+
+/// frames of the game window recorded elsewhere, which are scanned in place of the live window
+pub trait FrameSource {
+    /// the next frame, `None` when there is no more
+    fn next_frame(&mut self) -> Result<Option<RgbImage>>;
+
+    /// the size of the frames, which is the size of the window
+    fn frame_size(&self) -> Result<Size<usize>>;
+}
+
+impl FrameSource for Box<dyn FrameSource> {
+    fn next_frame(&mut self) -> Result<Option<RgbImage>> {
+        (**self).next_frame()
+    }
+
+    fn frame_size(&self) -> Result<Size<usize>> {
+        (**self).frame_size()
+    }
+}
+
+/// a directory of png frames, or a video file with the `video` feature
+pub fn open_frame_source<P: AsRef<Path>>(path: P) -> Result<Box<dyn FrameSource>> {
+    let path = path.as_ref();
+    if path.is_dir() {
+        return Ok(Box::new(ImageDirSource::new(path)?));
+    }
+
+    #[cfg(feature = "video")]
+    {
+        Ok(Box::new(super::VideoSource::new(path)?))
+    }
+
+    #[cfg(not(feature = "video"))]
+    {
+        Err(anyhow!("{} 不是目录，读取录像需要启用 video 特性", path.display()))
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+
+use crate::positioning::Size;
+
+use super::FrameSource;
+
+/// the png files of a directory, in the order of their file names
+pub struct ImageDirSource {
+    paths: Vec<PathBuf>,
+    next: usize,
+}
+
+impl ImageDirSource {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<ImageDirSource> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png")) {
+                paths.push(path);
+            }
+        }
+        if paths.is_empty() {
+            return Err(anyhow!("{} 中没有 png 图片", dir.as_ref().display()));
+        }
+        paths.sort();
+
+        Ok(ImageDirSource { paths, next: 0 })
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+impl FrameSource for ImageDirSource {
+    fn next_frame(&mut self) -> Result<Option<RgbImage>> {
+        let Some(path) = self.paths.get(self.next) else {
+            return Ok(None);
+        };
+        self.next += 1;
+
+        let image = image::open(path)
+            .map_err(|e| anyhow!("无法读取 {}: {}", path.display(), e))?;
+        Ok(Some(image.to_rgb8()))
+    }
+
+    fn frame_size(&self) -> Result<Size<usize>> {
+        let (width, height) = image::image_dimensions(&self.paths[0])?;
+        Ok(Size::new(width as usize, height as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+
+    use super::*;
+
+    #[test]
+    fn png_frames_in_the_order_of_names() {
+        let dir = std::env::temp_dir().join(format!("yas_frames_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, value) in [("002.png", 2), ("001.png", 1), ("010.PNG", 10)] {
+            RgbImage::from_pixel(6, 4, Rgb([value, 0, 0])).save(dir.join(name)).unwrap();
+        }
+        std::fs::write(dir.join("notes.txt"), "not a frame").unwrap();
+
+        let mut source = ImageDirSource::new(&dir).unwrap();
+        assert_eq!(source.len(), 3);
+        assert_eq!(source.frame_size().unwrap(), Size::new(6, 4));
+        let mut values = Vec::new();
+        while let Some(frame) = source.next_frame().unwrap() {
+            values.push(frame.get_pixel(0, 0)[0]);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(values, vec![1, 2, 10]);
+    }
+
+    #[test]
+    fn a_dir_without_png_is_an_error() {
+        let dir = std::env::temp_dir().join(format!("yas_no_frames_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = ImageDirSource::new(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(source.is_err());
+    }
+}
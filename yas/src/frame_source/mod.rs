@@ -0,0 +1,13 @@
+pub use frame_source::{open_frame_source, FrameSource};
+pub use image_dir_source::ImageDirSource;
+pub use frame_capturer::FrameCapturer;
+pub use distinct_frames::{mean_difference, DistinctFrames};
+#[cfg(feature = "video")]
+pub use video_source::VideoSource;
+
+mod frame_source;
+mod image_dir_source;
+mod frame_capturer;
+mod distinct_frames;
+#[cfg(feature = "video")]
+mod video_source;
@@ -0,0 +1,73 @@
+use std::io::Read;
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Stdio};
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+
+use crate::positioning::Size;
+
+use super::FrameSource;
+
+/// the frames of a video file, decoded by the `ffmpeg` and `ffprobe` executables in PATH
+pub struct VideoSource {
+    child: Child,
+    stdout: ChildStdout,
+    width: u32,
+    height: u32,
+}
+
+fn probe_size(path: &Path) -> Result<(u32, u32)> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0", "-show_entries", "stream=width,height", "-of", "csv=p=0"])
+        .arg(path)
+        .output()
+        .map_err(|e| anyhow!("无法运行 ffprobe: {}", e))?;
+    let s = String::from_utf8_lossy(&output.stdout);
+
+    // e.g. "1920,1080"
+    let (width, height) = s.trim()
+        .split_once(',')
+        .ok_or_else(|| anyhow!("无法获取录像尺寸: {}", s.trim()))?;
+    Ok((width.parse()?, height.parse()?))
+}
+
+impl VideoSource {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<VideoSource> {
+        let path = path.as_ref();
+        let (width, height) = probe_size(path)?;
+
+        let mut child = Command::new("ffmpeg")
+            .args(["-v", "error", "-i"])
+            .arg(path)
+            .args(["-f", "rawvideo", "-pix_fmt", "rgb24", "-"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("无法运行 ffmpeg: {}", e))?;
+        let stdout = child.stdout.take().unwrap();
+
+        Ok(VideoSource { child, stdout, width, height })
+    }
+}
+
+impl FrameSource for VideoSource {
+    fn next_frame(&mut self) -> Result<Option<RgbImage>> {
+        let mut buf = vec![0; self.width as usize * self.height as usize * 3];
+        match self.stdout.read_exact(&mut buf) {
+            Ok(()) => Ok(RgbImage::from_raw(self.width, self.height, buf)),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn frame_size(&self) -> Result<Size<usize>> {
+        Ok(Size::new(self.width as usize, self.height as usize))
+    }
+}
+
+impl Drop for VideoSource {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
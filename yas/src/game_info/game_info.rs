@@ -1,6 +1,8 @@
+use anyhow::{anyhow, Result};
+
 use crate::game_info::{ResolutionFamily, UI};
 use crate::game_info::ui::Platform;
-use crate::positioning::Rect;
+use crate::positioning::{Rect, Size};
 
 #[derive(Clone, Debug)]
 pub struct GameInfo {
@@ -10,3 +12,18 @@ pub struct GameInfo {
     pub ui: UI,
     pub platform: Platform,
 }
+
+impl GameInfo {
+    /// for frames recorded elsewhere, of which the window is the whole frame
+    pub fn from_frame_size(size: Size<usize>, ui: UI, platform: Platform) -> Result<GameInfo> {
+        let resolution_family = ResolutionFamily::nearest(size)
+            .ok_or_else(|| anyhow!("不支持的分辨率: {}x{}", size.width, size.height))?;
+        Ok(GameInfo {
+            window: Rect::new(0, 0, size.width as i32, size.height as i32),
+            resolution_family,
+            is_cloud: false,
+            ui,
+            platform,
+        })
+    }
+}
@@ -1,6 +1,7 @@
 use super::game_info::GameInfo;
 use anyhow::Result;
 
+#[derive(Default)]
 pub struct GameInfoBuilder {
     pub local_window_names: Vec<String>,
     pub cloud_window_names: Vec<String>,
@@ -1,4 +1,4 @@
-use anyhow::{Result, anyhow};
+use anyhow::{anyhow, Result};
 
 use crate::game_info::{GameInfo, Platform, UI, ResolutionFamily};
 use crate::positioning::Rect;
@@ -17,7 +17,7 @@ pub fn get_game_info() -> Result<GameInfo> {
     let position_size = String::from_utf8(
             std::process::Command::new("sh")
                 .arg("-c")
-                .arg(&format!(r#" xwininfo -id {window_id}|cut -f 2 -d :|tr -cd "0-9\n"|grep -v "^$"|sed -n "1,2p;5,6p" "#))
+                .arg(format!(r#" xwininfo -id {window_id}|cut -f 2 -d :|tr -cd "0-9\n"|grep -v "^$"|sed -n "1,2p;5,6p" "#))
                 .output()
                 .unwrap()
                 .stdout,
@@ -31,7 +31,8 @@ pub fn get_game_info() -> Result<GameInfo> {
     let height = info.next().unwrap().parse().unwrap();
 
     let rect = Rect::new(left, top, width, height);
-    let rf = ResolutionFamily::new(rect.size()).ok_or(anyhow!("unknown resolution family"))?;
+    let rf = ResolutionFamily::nearest(rect.size())
+        .ok_or_else(|| anyhow!("不支持的分辨率: {}x{}", rect.width, rect.height))?;
 
     Ok(GameInfo {
         window: rect.to_rect_i32(),
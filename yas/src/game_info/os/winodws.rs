@@ -73,10 +73,12 @@ pub fn get_game_info(window_names: &[&str]) -> Result<GameInfo> {
     utils::sleep(1000);
 
     let rect = utils::get_client_rect(hwnd)?;
+    let resolution_family = ResolutionFamily::nearest(rect.to_rect_usize().size())
+        .ok_or_else(|| anyhow!("不支持的分辨率: {}x{}", rect.width, rect.height))?;
 
     Ok(GameInfo {
         window: rect,
-        resolution_family: ResolutionFamily::new(rect.to_rect_usize().size()).unwrap(),
+        resolution_family,
         is_cloud,
         ui: UI::Desktop,
         platform: Platform::Windows
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::positioning::Size;
+use crate::positioning::{Size, MIN_VIEWPORT_SIZE};
 
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ResolutionFamily {
@@ -36,4 +36,52 @@ impl ResolutionFamily {
             None
         }
     }
+
+    /// the family of the closest aspect ratio for a size none of them fits exactly, such as an ultrawide window or a resized one,
+    /// its ui is then laid out in the viewport letterboxed in the window.
+    /// `None` for a window smaller than `MIN_VIEWPORT_SIZE`
+    pub fn nearest(size: Size<usize>) -> Option<Self> {
+        if size.width < MIN_VIEWPORT_SIZE.width || size.height < MIN_VIEWPORT_SIZE.height {
+            return None;
+        }
+        if let Some(family) = Self::new(size) {
+            return Some(family);
+        }
+
+        let aspect = size.width as f64 / size.height as f64;
+        [
+            (ResolutionFamily::Windows43x18, 43.0 / 18.0),
+            (ResolutionFamily::Windows7x3, 7.0 / 3.0),
+            (ResolutionFamily::Windows16x9, 16.0 / 9.0),
+            (ResolutionFamily::Windows8x5, 8.0 / 5.0),
+            (ResolutionFamily::Windows4x3, 4.0 / 3.0),
+        ]
+            .into_iter()
+            .min_by(|a, b| (a.1 - aspect).abs().total_cmp(&(b.1 - aspect).abs()))
+            .map(|x| x.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_of_an_exact_size() {
+        assert_eq!(ResolutionFamily::nearest(Size::new(1920, 1080)), Some(ResolutionFamily::Windows16x9));
+        assert_eq!(ResolutionFamily::nearest(Size::new(3440, 1440)), Some(ResolutionFamily::Windows43x18));
+    }
+
+    #[test]
+    fn nearest_of_an_odd_size() {
+        assert_eq!(ResolutionFamily::nearest(Size::new(2560, 1080)), Some(ResolutionFamily::Windows43x18));
+        assert_eq!(ResolutionFamily::nearest(Size::new(1366, 768)), Some(ResolutionFamily::Windows16x9));
+        assert_eq!(ResolutionFamily::nearest(Size::new(1280, 1024)), Some(ResolutionFamily::Windows4x3));
+    }
+
+    #[test]
+    fn a_small_window_is_rejected() {
+        assert_eq!(ResolutionFamily::nearest(Size::new(1024, 768)), None);
+        assert_eq!(ResolutionFamily::nearest(Size::new(1280, 600)), None);
+    }
 }
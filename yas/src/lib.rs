@@ -1,6 +1,8 @@
 #![feature(decl_macro)]
 #![feature(concat_idents)]
 #![allow(unused_imports)]
+// the modules are named after their main file, e.g. `foo/foo.rs`
+#![allow(clippy::module_inception)]
 
 #[cfg(all(feature = "ort", feature = "tract_onnx"))]
 compile_error!("feature \"ort\" and \"tract_onnx\" cannot be enabled at the same time");
@@ -19,3 +21,10 @@ pub mod system_control;
 pub mod ocr;
 pub mod positioning;
 pub mod profiler;
+pub mod calibration;
+pub mod detector;
+pub mod frame_source;
+pub mod events;
+pub mod config_file;
+pub mod scan_control;
+pub mod scroll_feedback;
@@ -0,0 +1,185 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use image::GrayImage;
+use serde::Deserialize;
+
+use crate::ocr::ImageToText;
+
+/// a line of the manifest of `DatasetDumper`, whose confidence is of no use here
+#[derive(Deserialize)]
+struct LabeledSample {
+    image: String,
+    text: String,
+}
+
+/// the preprocessed crops saved by `DatasetDumper`, with their texts as the labels
+pub struct LabeledDataset {
+    dir: PathBuf,
+    samples: Vec<LabeledSample>,
+}
+
+impl LabeledDataset {
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<LabeledDataset> {
+        let dir = dir.as_ref().to_path_buf();
+        let manifest = dir.join("manifest.jsonl");
+        let content = std::fs::read_to_string(&manifest)
+            .map_err(|e| anyhow!("无法读取数据集 {}: {}", manifest.display(), e))?;
+
+        let mut samples = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let sample = serde_json::from_str(line)
+                .map_err(|e| anyhow!("{} 第 {} 行格式错误: {}", manifest.display(), index + 1, e))?;
+            samples.push(sample);
+        }
+
+        Ok(LabeledDataset { dir, samples })
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+/// a sample recognized other than its label
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    pub image: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BenchmarkReport {
+    pub samples: usize,
+    pub exact_matches: usize,
+    /// the edit distance of the texts to their labels, summed
+    pub char_errors: usize,
+    pub label_chars: usize,
+    pub total_latency: Duration,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl BenchmarkReport {
+    pub fn exact_match_rate(&self) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        self.exact_matches as f64 / self.samples as f64
+    }
+
+    /// the edit distance per character of the labels
+    pub fn char_error_rate(&self) -> f64 {
+        if self.label_chars == 0 {
+            return if self.char_errors == 0 { 0.0 } else { 1.0 };
+        }
+        self.char_errors as f64 / self.label_chars as f64
+    }
+
+    pub fn mean_latency(&self) -> Duration {
+        if self.samples == 0 {
+            return Duration::ZERO;
+        }
+        self.total_latency / self.samples as u32
+    }
+
+    /// how this is worse than `baseline` by more than `tolerance`, in fractions of the rates
+    pub fn regressions(&self, baseline: &BenchmarkReport, tolerance: f64) -> Vec<String> {
+        let mut regressions = Vec::new();
+        let exact_match_drop = baseline.exact_match_rate() - self.exact_match_rate();
+        if exact_match_drop > tolerance {
+            regressions.push(format!("完全匹配率下降 {:.2}%", exact_match_drop * 100.0));
+        }
+        let char_error_rise = self.char_error_rate() - baseline.char_error_rate();
+        if char_error_rise > tolerance {
+            regressions.push(format!("字符错误率上升 {:.2}%", char_error_rise * 100.0));
+        }
+        regressions
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let substitution = previous[j - 1] + (a[i - 1] != b[j - 1]) as usize;
+            current[j] = substitution.min(previous[j] + 1).min(current[j - 1] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// recognize every sample of the dataset, which are preprocessed already, so the model must take the same input width
+pub fn benchmark<M>(model: &M, dataset: &LabeledDataset) -> Result<BenchmarkReport>
+where
+    M: ImageToText<GrayImage> + ?Sized,
+{
+    let mut report = BenchmarkReport::default();
+    for sample in dataset.samples.iter() {
+        let path = dataset.dir.join(&sample.image);
+        let image = image::open(&path)
+            .map_err(|e| anyhow!("无法读取样本 {}: {}", path.display(), e))?
+            .to_luma8();
+
+        let now = Instant::now();
+        let text = model.image_to_text(&image, true)?;
+        report.total_latency += now.elapsed();
+
+        report.samples += 1;
+        report.label_chars += sample.text.chars().count();
+        if text == sample.text {
+            report.exact_matches += 1;
+        } else {
+            report.char_errors += edit_distance(&text, &sample.text);
+            report.mismatches.push(Mismatch {
+                image: sample.image.clone(),
+                expected: sample.text.clone(),
+                actual: text,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+/// a row of `comparison_table`: its title, and the cell of a report
+type ComparisonRow = (&'static str, fn(&BenchmarkReport) -> String);
+
+/// the reports of the models side by side, a column each
+pub fn comparison_table(columns: &[(&str, &BenchmarkReport)]) -> String {
+    let rows: [ComparisonRow; 4] = [
+        ("样本数", |r| r.samples.to_string()),
+        ("完全匹配率", |r| format!("{:.2}%", r.exact_match_rate() * 100.0)),
+        ("字符错误率", |r| format!("{:.2}%", r.char_error_rate() * 100.0)),
+        ("平均耗时", |r| format!("{:.2}ms", r.mean_latency().as_secs_f64() * 1000.0)),
+    ];
+
+    let mut table = String::new();
+    let _ = write!(table, "{:<12}", "");
+    for (name, _) in columns.iter() {
+        let _ = write!(table, "{:>16}", name);
+    }
+    for (title, cell) in rows.iter() {
+        // the titles are of full width characters, which take two columns each
+        let _ = write!(table, "\n{}{}", title, " ".repeat(12 - title.chars().count() * 2));
+        for (_, report) in columns.iter() {
+            let _ = write!(table, "{:>16}", cell(report));
+        }
+    }
+    table
+}
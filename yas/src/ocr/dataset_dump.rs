@@ -0,0 +1,177 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use image::{GrayImage, RgbImage};
+use log::warn;
+use serde::Serialize;
+
+use crate::ocr::{ImageToText, YasOCRModel};
+
+/// a line of the manifest
+#[derive(Serialize)]
+struct DatasetSample<'a> {
+    /// relative to the dataset directory
+    image: String,
+    text: &'a str,
+    confidence: f32,
+}
+
+/// saves preprocessed crops with the text recognized from them, as training data for new models.
+/// the crops go to `images` under the directory, and `manifest.jsonl` gets a line of each, appended across runs
+pub struct DatasetDumper {
+    dir: PathBuf,
+    manifest: Mutex<BufWriter<File>>,
+    /// the file names of a run are prefixed by when it started, so runs don't overwrite each other
+    run_id: u64,
+    next_id: AtomicUsize,
+    /// only samples less confident than this are saved
+    max_confidence: Option<f32>,
+}
+
+impl DatasetDumper {
+    pub fn new<P: AsRef<Path>>(dir: P, max_confidence: Option<f32>) -> Result<DatasetDumper> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(dir.join("images"))?;
+        let manifest = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("manifest.jsonl"))?;
+
+        Ok(DatasetDumper {
+            dir,
+            manifest: Mutex::new(BufWriter::new(manifest)),
+            run_id: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs(),
+            next_id: AtomicUsize::new(0),
+            max_confidence,
+        })
+    }
+
+    /// save a preprocessed crop, unless it's more confident than `max_confidence`
+    pub fn dump(&self, input: &GrayImage, text: &str, confidence: f32) -> Result<()> {
+        if self.max_confidence.is_some_and(|max| confidence >= max) {
+            return Ok(());
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let image = format!("images/{}_{:06}.png", self.run_id, id);
+        input.save(self.dir.join(&image))?;
+
+        let line = serde_json::to_string(&DatasetSample { image, text, confidence })?;
+        let mut manifest = self.manifest.lock().unwrap();
+        writeln!(manifest, "{}", line)?;
+        manifest.flush()?;
+        Ok(())
+    }
+}
+
+/// a model whose every recognition is saved to a `DatasetDumper`
+pub struct DumpingModel {
+    model: YasOCRModel,
+    dumper: Arc<DatasetDumper>,
+}
+
+impl DumpingModel {
+    pub fn new(model: YasOCRModel, dumper: Arc<DatasetDumper>) -> DumpingModel {
+        DumpingModel { model, dumper }
+    }
+}
+
+impl ImageToText<RgbImage> for DumpingModel {
+    fn image_to_text(&self, image: &RgbImage, is_preprocessed: bool) -> Result<String> {
+        Ok(self.image_to_text_with_confidence(image, is_preprocessed)?.0)
+    }
+
+    fn image_to_text_with_confidence(&self, image: &RgbImage, is_preprocessed: bool) -> Result<(String, f32)> {
+        assert!(!is_preprocessed);
+
+        let (text, confidence, input) = self.model.image_to_text_with_input_and_confidence(image)?;
+        // blank crops teach nothing
+        if let Some(input) = input {
+            if let Err(e) = self.dumper.dump(&input, &text, confidence) {
+                warn!("保存数据集样本失败: {}", e);
+            }
+        }
+        Ok((text, confidence))
+    }
+
+    fn get_average_inference_time(&self) -> Option<Duration> {
+        self.model.get_average_inference_time()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("yas_dataset_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn manifest(dir: &Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(dir.join("manifest.jsonl"))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn only_less_confident_samples_are_dumped() {
+        let dir = temp_dir("confidence");
+        let dumper = DatasetDumper::new(&dir, Some(0.9)).unwrap();
+        dumper.dump(&GrayImage::new(40, 32), "low", 0.5).unwrap();
+        dumper.dump(&GrayImage::new(40, 32), "high", 0.95).unwrap();
+        drop(dumper);
+
+        let samples = manifest(&dir);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0]["text"], "low");
+        assert_eq!(samples[0]["confidence"], 0.5);
+        let image = image::open(dir.join(samples[0]["image"].as_str().unwrap())).unwrap();
+        assert_eq!((image.width(), image.height()), (40, 32));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn runs_append_to_the_manifest() {
+        let dir = temp_dir("append");
+        for text in ["first", "second"] {
+            DatasetDumper::new(&dir, None).unwrap().dump(&GrayImage::new(8, 32), text, 1.0).unwrap();
+        }
+
+        let samples = manifest(&dir);
+        assert_eq!(samples.iter().map(|s| s["text"].as_str().unwrap()).collect::<Vec<_>>(), vec!["first", "second"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "tract_onnx")]
+    #[test]
+    fn dumping_model_saves_its_input() {
+        use crate::ocr::test_model;
+
+        let dir = temp_dir("model");
+        let dumper = Arc::new(DatasetDumper::new(&dir, None).unwrap());
+        let model = DumpingModel::new(test_model::model(), dumper.clone());
+
+        let (text, _) = model.image_to_text_with_confidence(&test_model::text_image("abc"), false).unwrap();
+        assert_eq!(text, "abc");
+        // nothing is recognized on a blank crop
+        model.image_to_text_with_confidence(&RgbImage::new(64, 32), false).unwrap();
+        drop(model);
+        drop(dumper);
+
+        let samples = manifest(&dir);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0]["text"], "abc");
+        let image = image::open(dir.join(samples[0]["image"].as_str().unwrap())).unwrap();
+        assert_eq!(image.height(), 32);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,290 @@
+use std::cell::RefCell;
+use std::time::{Duration, SystemTime};
+use anyhow::{anyhow, Result};
+use image::{imageops, GrayImage, RgbImage};
+use crate::ocr::ImageToText;
+use crate::ocr::preprocess;
+
+const TEMPLATE_WIDTH: u32 = 12;
+const TEMPLATE_HEIGHT: u32 = 20;
+
+/// the characters a `DigitMatcher` can recognize
+pub const DIGIT_MATCHER_CHARS: &str = "0123456789.%+";
+
+struct Glyph {
+    c: char,
+    /// the glyph scaled to `TEMPLATE_WIDTH * TEMPLATE_HEIGHT`, zero mean and unit norm
+    pixels: Vec<f32>,
+    /// height of the glyph relative to the line, tells "." from "0"
+    relative_height: f32,
+}
+
+/// a connected run of ink columns of a line
+struct Component {
+    left: u32,
+    right: u32,
+    top: u32,
+    bottom: u32,
+}
+
+/// a tiny recognizer for numeric fields such as "46.6%", which doesn't need an onnx model
+/// the line is segmented into glyphs by the gaps between them, and every glyph is correlated with the templates
+/// learned from labeled samples by `add_sample`. glyphs which touch each other are not supported
+pub struct DigitMatcher {
+    glyphs: Vec<Glyph>,
+
+    inference_time: RefCell<Duration>,
+    invoke_count: RefCell<usize>,
+}
+
+impl DigitMatcher {
+    pub fn new() -> DigitMatcher {
+        DigitMatcher {
+            glyphs: Vec::new(),
+            inference_time: RefCell::new(Duration::new(0, 0)),
+            invoke_count: RefCell::new(0),
+        }
+    }
+
+    /// learn the templates from labeled crops, e.g. a crop of "0123456789.%+" rendered in the game font
+    pub fn from_samples(samples: &[(GrayImage, &str)]) -> Result<DigitMatcher> {
+        let mut matcher = DigitMatcher::new();
+        for (image, text) in samples.iter() {
+            matcher.add_sample(image, text)?;
+        }
+        Ok(matcher)
+    }
+
+    /// learn a template for every char of `text` from a crop of it
+    pub fn add_sample(&mut self, image: &GrayImage, text: &str) -> Result<()> {
+        let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if let Some(c) = chars.iter().find(|c| !DIGIT_MATCHER_CHARS.contains(**c)) {
+            return Err(anyhow!("unsupported char {:?}", c));
+        }
+
+        let Some(ink) = Self::ink_mask(image) else {
+            return Err(anyhow!("the sample has only one color"));
+        };
+        let components = Self::segment(&ink);
+        if components.len() != chars.len() {
+            return Err(anyhow!("found {} glyphs in the sample of {:?}", components.len(), text));
+        }
+
+        let line_height = Self::line_height(&components);
+        for (component, c) in components.iter().zip(chars) {
+            self.glyphs.push(Glyph {
+                c,
+                pixels: Self::glyph_pixels(&ink, component),
+                relative_height: (component.bottom - component.top) as f32 / line_height,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// normalize a crop so that the text is bright and binarize it, `None` if it has only one color
+    fn ink_mask(image: &GrayImage) -> Option<GrayImage> {
+        let mut im = image.clone();
+        if !preprocess::normalize_u8(&mut im, true) {
+            return None;
+        }
+        for p in im.pixels_mut() {
+            p[0] = if p[0] >= 128 { 255 } else { 0 };
+        }
+        Some(im)
+    }
+
+    fn segment(ink: &GrayImage) -> Vec<Component> {
+        let column_has_ink = |x: u32| (0..ink.height()).any(|y| ink.get_pixel(x, y)[0] > 0);
+
+        let mut components = Vec::new();
+        let mut start = None;
+        for x in 0..=ink.width() {
+            let has_ink = x < ink.width() && column_has_ink(x);
+            match (has_ink, start) {
+                (true, None) => start = Some(x),
+                (false, Some(left)) => {
+                    let rows: Vec<u32> = (0..ink.height())
+                        .filter(|&y| (left..x).any(|x| ink.get_pixel(x, y)[0] > 0))
+                        .collect();
+                    components.push(Component {
+                        left,
+                        right: x,
+                        top: rows[0],
+                        bottom: rows[rows.len() - 1] + 1,
+                    });
+                    start = None;
+                },
+                _ => {},
+            }
+        }
+
+        components
+    }
+
+    fn line_height(components: &[Component]) -> f32 {
+        let top = components.iter().map(|c| c.top).min().unwrap_or(0);
+        let bottom = components.iter().map(|c| c.bottom).max().unwrap_or(1);
+        std::cmp::max(bottom - top, 1) as f32
+    }
+
+    fn glyph_pixels(ink: &GrayImage, component: &Component) -> Vec<f32> {
+        let glyph = imageops::crop_imm(ink, component.left, component.top, component.right - component.left, component.bottom - component.top).to_image();
+        let glyph = imageops::resize(&glyph, TEMPLATE_WIDTH, TEMPLATE_HEIGHT, imageops::FilterType::Triangle);
+
+        let mut pixels: Vec<f32> = glyph.pixels().map(|p| p[0] as f32 / 255.0).collect();
+        let mean = pixels.iter().sum::<f32>() / pixels.len() as f32;
+        pixels.iter_mut().for_each(|p| *p -= mean);
+        let norm = pixels.iter().map(|p| p * p).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            pixels.iter_mut().for_each(|p| *p /= norm);
+        }
+        pixels
+    }
+
+    fn best_match(&self, pixels: &[f32], relative_height: f32) -> char {
+        let mut best = (' ', f32::NEG_INFINITY);
+        for glyph in self.glyphs.iter() {
+            let correlation: f32 = glyph.pixels.iter().zip(pixels).map(|(a, b)| a * b).sum();
+            let score = correlation - (glyph.relative_height - relative_height).abs();
+            if score > best.1 {
+                best = (glyph.c, score);
+            }
+        }
+        best.0
+    }
+
+    pub fn recognize(&self, image: &GrayImage) -> Result<String> {
+        if self.glyphs.is_empty() {
+            return Err(anyhow!("the digit matcher has no template"));
+        }
+
+        let now = SystemTime::now();
+
+        let Some(ink) = Self::ink_mask(image) else {
+            return Ok(String::new());
+        };
+        let components = Self::segment(&ink);
+        let line_height = Self::line_height(&components);
+        let ans = components.iter()
+            .map(|component| {
+                let relative_height = (component.bottom - component.top) as f32 / line_height;
+                self.best_match(&Self::glyph_pixels(&ink, component), relative_height)
+            })
+            .collect();
+
+        *self.invoke_count.borrow_mut() += 1;
+        *self.inference_time.borrow_mut() += now.elapsed()?;
+
+        Ok(ans)
+    }
+
+    pub fn get_average_inference_time(&self) -> Option<Duration> {
+        let count = *self.invoke_count.borrow();
+        if count == 0 {
+            None
+        } else {
+            Some(self.inference_time.borrow().div_f64(count as f64))
+        }
+    }
+}
+
+impl Default for DigitMatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImageToText<GrayImage> for DigitMatcher {
+    fn image_to_text(&self, image: &GrayImage, is_preprocessed: bool) -> Result<String> {
+        assert!(!is_preprocessed);
+        self.recognize(image)
+    }
+
+    fn get_average_inference_time(&self) -> Option<Duration> {
+        self.get_average_inference_time()
+    }
+}
+
+impl ImageToText<RgbImage> for DigitMatcher {
+    fn image_to_text(&self, image: &RgbImage, is_preprocessed: bool) -> Result<String> {
+        assert!(!is_preprocessed);
+        self.recognize(&imageops::grayscale(image))
+    }
+
+    fn get_average_inference_time(&self) -> Option<Duration> {
+        self.get_average_inference_time()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    const SCALE: u32 = 3;
+
+    /// a 5x7 bitmap of each char
+    fn bitmap(c: char) -> [&'static str; 7] {
+        match c {
+            '0' => [".111.", "1...1", "1..11", "1.1.1", "11..1", "1...1", ".111."],
+            '1' => ["..1..", ".11..", "..1..", "..1..", "..1..", "..1..", ".111."],
+            '2' => [".111.", "1...1", "....1", "...1.", "..1..", ".1...", "11111"],
+            '3' => ["11111", "...1.", "..1..", "...1.", "....1", "1...1", ".111."],
+            '4' => ["...1.", "..11.", ".1.1.", "1..1.", "11111", "...1.", "...1."],
+            '5' => ["11111", "1....", "1111.", "....1", "....1", "1...1", ".111."],
+            '6' => ["..11.", ".1...", "1....", "1111.", "1...1", "1...1", ".111."],
+            '7' => ["11111", "....1", "...1.", "..1..", ".1...", ".1...", ".1..."],
+            '8' => [".111.", "1...1", "1...1", ".111.", "1...1", "1...1", ".111."],
+            '9' => [".111.", "1...1", "1...1", ".1111", "....1", "...1.", ".11.."],
+            '.' => [".....", ".....", ".....", ".....", ".....", ".11..", ".11.."],
+            '%' => ["11..1", "11..1", "...1.", "..1..", ".1...", "1..11", "1..11"],
+            '+' => [".....", "..1..", "..1..", "11111", "..1..", "..1..", "....."],
+            _ => unreachable!(),
+        }
+    }
+
+    /// dark text on a light background, like the panels of the game
+    fn render(text: &str) -> GrayImage {
+        let cell = 7 * SCALE;
+        let mut im = GrayImage::from_pixel(cell * text.len() as u32 + 2 * SCALE, 9 * SCALE, Luma([230]));
+        for (index, c) in text.chars().enumerate() {
+            for (row, line) in bitmap(c).iter().enumerate() {
+                for (column, pixel) in line.chars().enumerate() {
+                    if pixel != '1' {
+                        continue;
+                    }
+                    let left = SCALE * (2 + column as u32) + cell * index as u32;
+                    let top = SCALE * (1 + row as u32);
+                    for x in left..left + SCALE {
+                        for y in top..top + SCALE {
+                            im.put_pixel(x, y, Luma([30]));
+                        }
+                    }
+                }
+            }
+        }
+        im
+    }
+
+    #[test]
+    fn recognize_a_percentage() {
+        let matcher = DigitMatcher::from_samples(&[(render(DIGIT_MATCHER_CHARS), DIGIT_MATCHER_CHARS)]).unwrap();
+        assert_eq!(matcher.recognize(&render("46.6%")).unwrap(), "46.6%");
+        assert_eq!(matcher.recognize(&render("+1980")).unwrap(), "+1980");
+        assert_eq!(matcher.recognize(&GrayImage::new(20, 20)).unwrap(), "");
+    }
+
+    #[test]
+    fn a_sample_must_match_its_label() {
+        let mut matcher = DigitMatcher::new();
+        assert!(matcher.recognize(&render("1")).is_err());
+        assert!(matcher.add_sample(&render("12"), "1").is_err());
+        assert!(matcher.add_sample(&render("12"), "1a").is_err());
+        assert!(matcher.is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::ocr::postprocess;
+
+/// the result of correcting a recognized string against a `Lexicon`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexiconMatch {
+    /// the canonical entry
+    pub text: String,
+    /// whether it differs from the recognized string
+    pub corrected: bool,
+    /// see `postprocess::similarity`, 1.0 if the recognized string is an entry
+    pub similarity: f32,
+}
+
+/// the closed vocabulary of a field (e.g. stat names, artifact names), used to correct misread characters
+pub struct Lexicon {
+    /// sorted, so that the entries starting with a prefix are a contiguous range
+    entries: Vec<String>,
+    cutoff: f32,
+}
+
+impl Lexicon {
+    pub fn new<I, S>(entries: I) -> Lexicon
+    where I: IntoIterator<Item = S>, S: Into<String> {
+        let mut entries: Vec<String> = entries.into_iter().map(|s| s.into()).collect();
+        entries.sort();
+        entries.dedup();
+
+        Lexicon {
+            entries,
+            cutoff: postprocess::DICTIONARY_MATCH_CUTOFF,
+        }
+    }
+
+    /// entries less similar to the recognized string than this are not matched, see `postprocess::DICTIONARY_MATCH_CUTOFF`
+    pub fn with_cutoff(mut self, cutoff: f32) -> Self {
+        self.cutoff = cutoff;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, s: &str) -> bool {
+        self.entries.binary_search_by(|e| e.as_str().cmp(s)).is_ok()
+    }
+
+    /// the entries starting with `prefix`
+    pub fn with_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a String> + 'a {
+        let start = self.entries.partition_point(|e| e.as_str() < prefix);
+        self.entries[start..].iter().take_while(move |e| e.starts_with(prefix))
+    }
+
+    /// the canonical entry of a recognized string, which is
+    /// - the string itself, if it's an entry
+    /// - the only entry it's a prefix of (of at least 2 chars), for text cut off at the right edge
+    /// - otherwise the most similar entry by edit distance, if it's similar enough
+    pub fn correct(&self, raw: &str) -> Option<LexiconMatch> {
+        if raw.is_empty() {
+            return None;
+        }
+        if self.contains(raw) {
+            return Some(LexiconMatch { text: raw.to_string(), corrected: false, similarity: 1.0 });
+        }
+
+        let mut prefixed = self.with_prefix(raw);
+        if let (true, Some(entry), None) = (raw.chars().count() >= 2, prefixed.next(), prefixed.next()) {
+            return Some(LexiconMatch {
+                text: entry.clone(),
+                corrected: true,
+                similarity: postprocess::similarity(raw, entry),
+            });
+        }
+
+        postprocess::match_dictionary(raw, &self.entries, self.cutoff)
+            .map(|(text, similarity)| LexiconMatch { text, corrected: true, similarity })
+    }
+
+    /// the canonical entry, or the recognized string as is (with a similarity of 0.0) if no entry matches
+    pub fn correct_or_raw(&self, raw: &str) -> LexiconMatch {
+        self.correct(raw).unwrap_or_else(|| LexiconMatch {
+            text: raw.to_string(),
+            corrected: false,
+            similarity: 0.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat_names() -> Lexicon {
+        Lexicon::new(["暴击率", "暴击伤害", "攻击力", "攻击力", "元素充能效率", "元素精通"])
+    }
+
+    #[test]
+    fn exact_entries_are_kept() {
+        let lexicon = stat_names();
+        assert_eq!(lexicon.len(), 5);
+        assert_eq!(lexicon.correct("暴击率"), Some(LexiconMatch { text: "暴击率".to_string(), corrected: false, similarity: 1.0 }));
+        assert_eq!(lexicon.correct(""), None);
+    }
+
+    #[test]
+    fn misread_and_truncated_strings_are_corrected() {
+        let lexicon = stat_names();
+
+        let misread = lexicon.correct("暴去伤害").unwrap();
+        assert_eq!(misread.text, "暴击伤害");
+        assert!(misread.corrected);
+        assert!(misread.similarity < 1.0);
+
+        // the only entry starting with it
+        assert_eq!(lexicon.correct("元素充").unwrap().text, "元素充能效率");
+        // more than one entry starts with "元素", the closest is taken
+        assert_eq!(lexicon.with_prefix("元素").count(), 2);
+        assert_eq!(lexicon.correct("元素").unwrap().text, "元素精通");
+    }
+
+    #[test]
+    fn unrelated_strings_are_not_matched() {
+        let lexicon = stat_names();
+        assert_eq!(lexicon.correct("胡桃已装备"), None);
+
+        let raw = lexicon.correct_or_raw("胡桃已装备");
+        assert_eq!(raw.text, "胡桃已装备");
+        assert!(!raw.corrected);
+        assert_eq!(raw.similarity, 0.0);
+    }
+}
@@ -1,10 +1,31 @@
 mod traits;
 mod yas_model;
 mod paddle_paddle_model;
+mod recognition_cache;
+mod digit_matcher;
+mod lexicon;
+mod dataset_dump;
+mod benchmark;
 
-pub use yas_model::yas_ocr_model::YasOCRModel;
+pub use yas_model::yas_ocr_model::{ErrorPolicy, ModelType, OcrBackend, ScanOutcome, YasOCRModel, DEFAULT_STATS_TAG};
+pub use yas_model::yas_ocr_model_builder::YasOCRModelBuilder;
+pub use yas_model::model_registry::ModelRegistry;
+pub use yas_model::recording::{Diff, RecordedInference};
+pub use yas_model::stats::OcrStats;
+pub use yas_model::language_model::{LanguageModel, NgramModel};
+pub use yas_model::preprocess;
+pub use yas_model::postprocess;
 pub use yas_model::yas_ocr_model::yas_ocr_model;
+pub use yas_model::yas_ocr_model::yas_ocr_labels;
+pub use yas_model::yas_ocr_model::parse_index_to_word;
 pub use traits::ImageToText;
+#[cfg(all(test, feature = "tract_onnx"))]
+pub(crate) use yas_model::test_model;
+pub use recognition_cache::RecognitionCache;
+pub use digit_matcher::{DigitMatcher, DIGIT_MATCHER_CHARS};
+pub use lexicon::{Lexicon, LexiconMatch};
+pub use dataset_dump::{DatasetDumper, DumpingModel};
+pub use benchmark::{benchmark, comparison_table, BenchmarkReport, LabeledDataset, Mismatch};
 pub use paddle_paddle_model::PPOCRModel;
 pub use paddle_paddle_model::PPOCRChV4RecInfer;
 pub use paddle_paddle_model::ppocr_model;
@@ -16,8 +16,7 @@ pub fn resize_img(rec_image_shape: Shape3D<u32>, img: &RgbImage) -> RgbImage {
 
     let resized_width = (wh_ratio * rec_image_shape.y as f64) as u32;
 
-    let resized_image = resize(img, resized_width, rec_image_shape.y, FilterType::Triangle);
-    resized_image
+    resize(img, resized_width, rec_image_shape.y, FilterType::Triangle)
 }
 
 #[cfg(feature = "ort")]
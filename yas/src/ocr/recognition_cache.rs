@@ -0,0 +1,196 @@
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use anyhow::Result;
+use image::RgbImage;
+use super::traits::ImageToText;
+
+/// a least recently used cache of recognition results, keyed by the pixels of the crop
+/// a page which is scanned again hits the cache instead of invoking the model, and a hit borrows the cached string
+pub struct RecognitionCache {
+    capacity: usize,
+    entries: HashMap<u64, Entry>,
+    /// keys from the least recently used to the most recently used
+    order: VecDeque<u64>,
+    hit_count: usize,
+    miss_count: usize,
+}
+
+/// the crop is kept with its text, two crops whose hashes collide are told apart by their pixels
+struct Entry {
+    dimensions: (u32, u32),
+    pixels: Vec<u8>,
+    text: String,
+}
+
+impl Entry {
+    fn matches(&self, image: &RgbImage) -> bool {
+        self.dimensions == image.dimensions() && self.pixels == image.as_raw().as_slice()
+    }
+}
+
+impl RecognitionCache {
+    pub fn new(capacity: usize) -> RecognitionCache {
+        RecognitionCache {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            hit_count: 0,
+            miss_count: 0,
+        }
+    }
+
+    fn key(image: &RgbImage) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        image.dimensions().hash(&mut hasher);
+        image.as_raw().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn touch(&mut self, key: u64) {
+        if let Some(pos) = self.order.iter().position(|&k| k == key) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(key);
+    }
+
+    /// recognize `image` with `model`, unless the same crop is cached
+    /// a hit borrows the cached string, only a miss allocates
+    pub fn recognize<'a>(&'a mut self, model: &dyn ImageToText<RgbImage>, image: &RgbImage) -> Result<Cow<'a, str>> {
+        let key = Self::key(image);
+
+        // a different crop with the same hash is a miss, and replaces the entry
+        if self.entries.get(&key).is_some_and(|entry| entry.matches(image)) {
+            self.hit_count += 1;
+            self.touch(key);
+            return Ok(Cow::Borrowed(self.entries[&key].text.as_str()));
+        }
+
+        self.miss_count += 1;
+        let text = model.image_to_text(image, false)?;
+        if self.capacity == 0 {
+            return Ok(Cow::Owned(text));
+        }
+
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, Entry {
+            dimensions: image.dimensions(),
+            pixels: image.as_raw().clone(),
+            text: text.clone(),
+        });
+        self.touch(key);
+
+        Ok(Cow::Owned(text))
+    }
+
+    pub fn hit_count(&self) -> usize {
+        self.hit_count
+    }
+
+    pub fn miss_count(&self) -> usize {
+        self.miss_count
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    /// reads the width of the image, and counts its invocations
+    struct CountingModel {
+        calls: Cell<usize>,
+    }
+
+    impl ImageToText<RgbImage> for CountingModel {
+        fn image_to_text(&self, image: &RgbImage, _is_preprocessed: bool) -> Result<String> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(image.width().to_string())
+        }
+
+        fn get_average_inference_time(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn hit_borrows_the_cached_string() {
+        let model = CountingModel { calls: Cell::new(0) };
+        let mut cache = RecognitionCache::new(2);
+        let image = RgbImage::new(10, 4);
+
+        assert!(matches!(cache.recognize(&model, &image).unwrap(), Cow::Owned(ref text) if text == "10"));
+        assert!(matches!(cache.recognize(&model, &image).unwrap(), Cow::Borrowed("10")));
+        assert_eq!(model.calls.get(), 1);
+        assert_eq!((cache.hit_count(), cache.miss_count()), (1, 1));
+    }
+
+    #[test]
+    fn least_recently_used_is_evicted() {
+        let model = CountingModel { calls: Cell::new(0) };
+        let mut cache = RecognitionCache::new(2);
+        let (a, b, c) = (RgbImage::new(1, 1), RgbImage::new(2, 1), RgbImage::new(3, 1));
+
+        cache.recognize(&model, &a).unwrap();
+        cache.recognize(&model, &b).unwrap();
+        cache.recognize(&model, &a).unwrap();
+        // evicts b
+        cache.recognize(&model, &c).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        cache.recognize(&model, &a).unwrap();
+        assert_eq!(model.calls.get(), 3);
+        cache.recognize(&model, &b).unwrap();
+        assert_eq!(model.calls.get(), 4);
+    }
+
+    #[test]
+    fn colliding_hash_of_a_different_crop_is_a_miss() {
+        let model = CountingModel { calls: Cell::new(0) };
+        let mut cache = RecognitionCache::new(2);
+        let (image, other) = (RgbImage::new(10, 4), RgbImage::new(20, 2));
+
+        // the entry of `other` under the key of `image`, as if their hashes collided
+        let key = RecognitionCache::key(&image);
+        cache.entries.insert(key, Entry {
+            dimensions: other.dimensions(),
+            pixels: other.as_raw().clone(),
+            text: "20".to_string(),
+        });
+        cache.order.push_back(key);
+
+        assert_eq!(cache.recognize(&model, &image).unwrap(), "10");
+        assert_eq!(model.calls.get(), 1);
+        assert_eq!((cache.hit_count(), cache.miss_count(), cache.len()), (0, 1, 1));
+
+        // the same dimensions with different pixels don't match either
+        let mut brighter = image.clone();
+        brighter.put_pixel(0, 0, image::Rgb([255, 255, 255]));
+        cache.entries.get_mut(&key).unwrap().pixels = brighter.into_raw();
+        assert_eq!(cache.recognize(&model, &image).unwrap(), "10");
+        assert_eq!(model.calls.get(), 2);
+
+        assert!(matches!(cache.recognize(&model, &image).unwrap(), Cow::Borrowed("10")));
+        assert_eq!(model.calls.get(), 2);
+        assert_eq!(cache.order.len(), 1);
+    }
+}
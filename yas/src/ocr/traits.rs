@@ -5,6 +5,12 @@ use anyhow::Result;
 pub trait ImageToText<ImageType> {
     fn image_to_text(&self, image: &ImageType, is_preprocessed: bool) -> Result<String>;
 
+    /// the text and how confident the model is of it, in `[0, 1]`
+    /// models which cannot tell are always confident
+    fn image_to_text_with_confidence(&self, image: &ImageType, is_preprocessed: bool) -> Result<(String, f32)> {
+        Ok((self.image_to_text(image, is_preprocessed)?, 1.0))
+    }
+
     fn get_average_inference_time(&self) -> Option<Duration>;
 }
 
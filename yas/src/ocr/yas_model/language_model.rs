@@ -0,0 +1,69 @@
+use std::collections::{HashMap, HashSet};
+
+/// scores how likely a word follows a decoded prefix, used by `postprocess::prefix_beam_search`
+pub trait LanguageModel {
+    /// natural log of the probability that `next` follows `context`
+    fn log_prob(&self, context: &str, next: &str) -> f32;
+}
+
+/// marks the start of a word in the contexts of `NgramModel`
+const START: char = '\u{2}';
+
+/// a character n-gram model over a word list (e.g. all artifact set names), with add-one smoothing
+pub struct NgramModel {
+    n: usize,
+    /// context of `n - 1` chars -> next char -> count
+    counts: HashMap<String, HashMap<char, usize>>,
+    /// context -> total count
+    totals: HashMap<String, usize>,
+    vocab_size: usize,
+}
+
+impl NgramModel {
+    pub fn new(words: &[String], n: usize) -> NgramModel {
+        let n = std::cmp::max(n, 1);
+        let mut counts: HashMap<String, HashMap<char, usize>> = HashMap::new();
+        let mut totals: HashMap<String, usize> = HashMap::new();
+        let mut vocab = HashSet::new();
+
+        for word in words.iter() {
+            let mut chars: Vec<char> = vec![START; n - 1];
+            for c in word.chars() {
+                vocab.insert(c);
+                let context: String = chars[chars.len() + 1 - n..].iter().collect();
+                *counts.entry(context.clone()).or_default().entry(c).or_insert(0) += 1;
+                *totals.entry(context).or_insert(0) += 1;
+                chars.push(c);
+            }
+        }
+
+        NgramModel {
+            n,
+            counts,
+            totals,
+            // one more for the unseen chars
+            vocab_size: vocab.len() + 1,
+        }
+    }
+
+    fn char_log_prob(&self, context: &str, c: char) -> f32 {
+        let count = self.counts.get(context).and_then(|m| m.get(&c)).copied().unwrap_or(0);
+        let total = self.totals.get(context).copied().unwrap_or(0);
+        ((count + 1) as f32 / (total + self.vocab_size) as f32).ln()
+    }
+}
+
+impl LanguageModel for NgramModel {
+    fn log_prob(&self, context: &str, next: &str) -> f32 {
+        let mut chars: Vec<char> = vec![START; self.n - 1];
+        chars.extend(context.chars());
+
+        let mut ans = 0.0;
+        for c in next.chars() {
+            let context: String = chars[chars.len() + 1 - self.n..].iter().collect();
+            ans += self.char_log_prob(&context, c);
+            chars.push(c);
+        }
+        ans
+    }
+}
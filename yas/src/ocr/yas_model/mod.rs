@@ -1,5 +1,13 @@
 pub mod preprocess;
+pub mod postprocess;
 pub mod yas_ocr_model;
+pub mod yas_ocr_model_builder;
+pub mod model_registry;
+pub mod recording;
+pub mod stats;
+pub mod language_model;
+#[cfg(all(test, feature = "tract_onnx"))]
+pub(crate) mod test_model;
 
 // pub use preprocess::to_gray;
 // pub use preprocess::pre_process;
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use log::warn;
+use super::yas_ocr_model::YasOCRModel;
+
+/// models loaded from a directory by convention
+/// every `name.onnx` file is paired with a `name.json` index to word file, and is keyed by `name`
+pub struct ModelRegistry {
+    models: HashMap<String, YasOCRModel>,
+    errors: Vec<(String, anyhow::Error)>,
+}
+
+impl ModelRegistry {
+    /// load all models in a directory
+    /// a model which fails to load (e.g. its json is missing) doesn't abort the whole load, the error is recorded
+    /// in `errors` instead
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Result<ModelRegistry> {
+        let mut models = HashMap::new();
+        let mut errors = Vec::new();
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_file() || path.extension().is_none_or(|ext| ext != "onnx") {
+                continue;
+            }
+            let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            let json_path = path.with_extension("json");
+            let model = if json_path.is_file() {
+                YasOCRModel::new_from_file(&path, &json_path)
+            } else {
+                Err(anyhow!("index to word file {} not found", json_path.display()))
+            };
+
+            match model {
+                Ok(model) => {
+                    models.insert(name, model);
+                },
+                Err(e) => {
+                    warn!("加载模型 {} 失败：{}", name, e);
+                    errors.push((name, e));
+                },
+            }
+        }
+
+        Ok(ModelRegistry {
+            models,
+            errors,
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&YasOCRModel> {
+        self.models.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.models.keys().map(|name| name.as_str())
+    }
+
+    /// models which failed to load, with the reason
+    pub fn errors(&self) -> &[(String, anyhow::Error)] {
+        &self.errors
+    }
+}
+
+#[cfg(all(test, feature = "tract_onnx"))]
+mod tests {
+    use super::*;
+    use super::super::test_model::{self, LABELS};
+    use crate::ocr::traits::ImageToText;
+
+    #[test]
+    fn load_dir_with_two_model_pairs() {
+        let dir = std::env::temp_dir().join(format!("yas_model_registry_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["artifact", "weapon"] {
+            std::fs::write(dir.join(format!("{}.onnx", name)), test_model::onnx(false, false)).unwrap();
+            std::fs::write(dir.join(format!("{}.json", name)), LABELS).unwrap();
+        }
+        // no index to word
+        std::fs::write(dir.join("broken.onnx"), test_model::onnx(false, false)).unwrap();
+
+        let registry = ModelRegistry::load_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let registry = registry.unwrap();
+
+        let mut names: Vec<&str> = registry.names().collect();
+        names.sort();
+        assert_eq!(names, ["artifact", "weapon"]);
+        assert_eq!(registry.errors().len(), 1);
+        assert_eq!(registry.errors()[0].0, "broken");
+
+        let image = test_model::text_image("abc");
+        assert_eq!(registry.get("weapon").unwrap().image_to_text(&image, false).unwrap(), "abc");
+        assert!(registry.get("broken").is_none());
+    }
+}
@@ -0,0 +1,585 @@
+use std::collections::HashMap;
+use ndarray::{Array2, ArrayView1};
+use super::language_model::LanguageModel;
+
+/// softmax each row (timestep) of a `[time, vocab]` matrix in place
+/// the row max is subtracted before exponentiating, so large or very negative logits won't overflow
+pub fn softmax_rows(arr: &mut Array2<f32>) {
+    for mut row in arr.rows_mut() {
+        let max = row.fold(f32::NEG_INFINITY, |acc, &v| acc.max(v));
+        if !max.is_finite() {
+            let len = row.len() as f32;
+            row.fill(1.0 / len);
+            continue;
+        }
+
+        row.mapv_inplace(|v| (v - max).exp());
+        let sum = row.sum();
+        row.mapv_inplace(|v| v / sum);
+    }
+}
+
+/// whether every row (timestep) already is a probability distribution, e.g. the model ends with a softmax
+/// rows which are softmaxed again lose most of their contrast, see `softmax_rows`
+pub fn is_normalized(arr: &Array2<f32>) -> bool {
+    arr.rows()
+        .into_iter()
+        .all(|row| row.iter().all(|v| (0.0..=1.0).contains(v)) && (row.sum() - 1.0).abs() < 1e-3)
+}
+
+/// same as `softmax_rows`, but returns a new matrix
+pub fn softmax(arr: &Array2<f32>) -> Array2<f32> {
+    let mut result = arr.clone();
+    softmax_rows(&mut result);
+    result
+}
+
+/// argmax label index of every timestep, blanks included
+pub fn argmax_rows(probs: &Array2<f32>) -> Vec<usize> {
+    probs.rows()
+        .into_iter()
+        .map(|row| {
+            let mut max_index = 0;
+            let mut max_value = f32::NEG_INFINITY;
+            for (j, &value) in row.iter().enumerate() {
+                if value > max_value {
+                    max_value = value;
+                    max_index = j;
+                }
+            }
+            max_index
+        })
+        .collect()
+}
+
+/// max probability of every timestep, blanks included
+pub fn timestep_confidences(probs: &Array2<f32>) -> Vec<f32> {
+    probs.rows()
+        .into_iter()
+        .map(|row| row.fold(0.0_f32, |acc, &v| acc.max(v)))
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct DecodeOptions {
+    /// when the max probability of a timestep is below this floor, `unknown_placeholder` is emitted instead of the argmax word
+    /// `None` always emits the argmax word
+    pub unknown_threshold: Option<f32>,
+    pub unknown_placeholder: String,
+    /// map full-width forms to ascii after decoding, see `to_ascii_punct`
+    pub ascii_punctuation: bool,
+    /// characters removed from the decoded string, after the blanks are collapsed and `ascii_punctuation` is applied
+    /// useful when a model emits stray separators between real characters, empty by default
+    pub strip_chars: Vec<char>,
+    /// weight of the language model score in `prefix_beam_search`
+    pub lm_alpha: f32,
+    /// how many prefixes `prefix_beam_search` keeps at every timestep
+    pub beam_width: usize,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions {
+            unknown_threshold: None,
+            unknown_placeholder: String::from("\u{FFFD}"),
+            ascii_punctuation: false,
+            strip_chars: Vec::new(),
+            lm_alpha: 0.5,
+            beam_width: 8,
+        }
+    }
+}
+
+/// how per word confidences are aggregated into the confidence of the whole string
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ConfidenceAgg {
+    /// the least confident word, strict
+    Min,
+    /// balanced
+    #[default]
+    GeometricMean,
+    /// lenient
+    ArithmeticMean,
+}
+
+impl ConfidenceAgg {
+    pub fn aggregate(&self, values: &[f32]) -> f32 {
+        if values.is_empty() {
+            return 0.0;
+        }
+
+        match *self {
+            ConfidenceAgg::Min => values.iter().fold(f32::INFINITY, |acc, &v| acc.min(v)),
+            ConfidenceAgg::GeometricMean => {
+                let log_sum: f32 = values.iter().map(|v| v.max(f32::MIN_POSITIVE).ln()).sum();
+                (log_sum / values.len() as f32).exp()
+            },
+            ConfidenceAgg::ArithmeticMean => values.iter().sum::<f32>() / values.len() as f32,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TextWithConfidence {
+    pub text: String,
+    /// max probability of every emitted word, in order
+    pub char_confidences: Vec<f32>,
+    /// confidence of the whole string
+    pub confidence: f32,
+}
+
+/// quality hints of a greedy decode
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DecodeFlags {
+    /// a word is the argmax of consecutive timesteps, and is emitted once. this is also how two identical characters
+    /// without a blank between them decode (e.g. "100" as "10"), so it's a hint to double check numbers
+    pub collapsed_duplicates: bool,
+    /// see `ends_with_emission`
+    pub emitted_at_last_timestep: bool,
+}
+
+/// the word decoded at a timestep and its probability
+fn timestep_word<'a>(row: ArrayView1<f32>, index_to_word: &'a [String], options: &'a DecodeOptions) -> (&'a str, f32) {
+    let mut max_index = 0;
+    let mut max_value = f32::NEG_INFINITY;
+    for j in 0..index_to_word.len() {
+        let value = row[j];
+        if value > max_value {
+            max_value = value;
+            max_index = j;
+        }
+    }
+
+    let word = match options.unknown_threshold {
+        Some(threshold) if max_value < threshold => options.unknown_placeholder.as_str(),
+        _ => index_to_word[max_index].as_str(),
+    };
+    (word, max_value)
+}
+
+/// whether the last timestep emits a word rather than the blank, which hints that the text runs past the input
+pub fn ends_with_emission(probs: &Array2<f32>, index_to_word: &[String], options: &DecodeOptions) -> bool {
+    match probs.rows().into_iter().last() {
+        Some(row) => timestep_word(row, index_to_word, options).0 != "-",
+        None => false,
+    }
+}
+
+pub fn decode_flags(probs: &Array2<f32>, index_to_word: &[String], options: &DecodeOptions) -> DecodeFlags {
+    let mut collapsed_duplicates = false;
+    let mut last_word = "";
+    for row in probs.rows() {
+        let (word, _) = timestep_word(row, index_to_word, options);
+        if word == last_word && word != "-" {
+            collapsed_duplicates = true;
+            break;
+        }
+        last_word = word;
+    }
+
+    DecodeFlags {
+        collapsed_duplicates,
+        emitted_at_last_timestep: ends_with_emission(probs, index_to_word, options),
+    }
+}
+
+/// greedy ctc decode, takes the argmax of every timestep, collapses repeated words and removes the blank "-"
+pub fn greedy_decode(probs: &Array2<f32>, index_to_word: &[String], options: &DecodeOptions) -> String {
+    let mut ans = String::new();
+    greedy_decode_into(probs, index_to_word, options, &mut ans);
+    ans
+}
+
+/// same as `greedy_decode`, but clears and writes into `out`, reusing its allocation
+pub fn greedy_decode_into(probs: &Array2<f32>, index_to_word: &[String], options: &DecodeOptions, out: &mut String) {
+    out.clear();
+    let mut last_word = "";
+    for row in probs.rows() {
+        let (word, _) = timestep_word(row, index_to_word, options);
+        if word != last_word && word != "-" {
+            out.push_str(word);
+        }
+
+        last_word = word;
+    }
+
+    finish_decode(options, out);
+}
+
+/// the steps after collapsing, `ascii_punctuation` and `strip_chars`
+fn finish_decode(options: &DecodeOptions, out: &mut String) {
+    if options.ascii_punctuation {
+        let mapped = to_ascii_punct(out);
+        out.clear();
+        out.push_str(&mapped);
+    }
+
+    if !options.strip_chars.is_empty() {
+        out.retain(|c| !options.strip_chars.contains(&c));
+    }
+}
+
+fn log_sum_exp(a: f32, b: f32) -> f32 {
+    if a == f32::NEG_INFINITY {
+        return b;
+    }
+    if b == f32::NEG_INFINITY {
+        return a;
+    }
+    let max = a.max(b);
+    max + ((a - max).exp() + (b - max).exp()).ln()
+}
+
+/// ctc prefix beam search, where a prefix is scored by `log P_ctc + lm_alpha * log P_lm`
+/// keeps `beam_width` prefixes at every timestep, and only extends them with the `beam_width` most probable labels
+/// of the timestep. `unknown_threshold` doesn't apply
+pub fn prefix_beam_search(probs: &Array2<f32>, index_to_word: &[String], lm: &dyn LanguageModel, options: &DecodeOptions) -> String {
+    let blank = index_to_word.iter().position(|word| word == "-");
+    let beam_width = std::cmp::max(options.beam_width, 1);
+
+    // label indices -> (log prob ending in blank, log prob ending in a label)
+    let mut beams: HashMap<Vec<usize>, (f32, f32)> = HashMap::new();
+    beams.insert(Vec::new(), (0.0, f32::NEG_INFINITY));
+
+    let text_of = |prefix: &[usize]| prefix.iter().map(|&i| index_to_word[i].as_str()).collect::<String>();
+
+    for row in probs.rows() {
+        let mut candidates: Vec<usize> = (0..index_to_word.len()).collect();
+        if candidates.len() > beam_width {
+            candidates.select_nth_unstable_by(beam_width - 1, |&a, &b| row[b].total_cmp(&row[a]));
+            candidates.truncate(beam_width);
+        }
+
+        let mut next: HashMap<Vec<usize>, (f32, f32)> = HashMap::new();
+        for (prefix, &(p_b, p_nb)) in beams.iter() {
+            let p_total = log_sum_exp(p_b, p_nb);
+            for &c in candidates.iter() {
+                let p = row[c].max(f32::MIN_POSITIVE).ln();
+
+                if Some(c) == blank {
+                    let entry = next.entry(prefix.clone()).or_insert((f32::NEG_INFINITY, f32::NEG_INFINITY));
+                    entry.0 = log_sum_exp(entry.0, p_total + p);
+                    continue;
+                }
+
+                let mut extended = prefix.clone();
+                extended.push(c);
+                let lm_score = options.lm_alpha * lm.log_prob(&text_of(prefix), &index_to_word[c]);
+
+                if prefix.last() == Some(&c) {
+                    // a repeated label only extends the prefix after a blank, otherwise it's collapsed
+                    let entry = next.entry(extended).or_insert((f32::NEG_INFINITY, f32::NEG_INFINITY));
+                    entry.1 = log_sum_exp(entry.1, p_b + p + lm_score);
+                    let entry = next.entry(prefix.clone()).or_insert((f32::NEG_INFINITY, f32::NEG_INFINITY));
+                    entry.1 = log_sum_exp(entry.1, p_nb + p);
+                } else {
+                    let entry = next.entry(extended).or_insert((f32::NEG_INFINITY, f32::NEG_INFINITY));
+                    entry.1 = log_sum_exp(entry.1, p_total + p + lm_score);
+                }
+            }
+        }
+
+        let mut sorted: Vec<(Vec<usize>, (f32, f32))> = next.into_iter().collect();
+        sorted.sort_by(|(_, a), (_, b)| log_sum_exp(b.0, b.1).total_cmp(&log_sum_exp(a.0, a.1)));
+        sorted.truncate(beam_width);
+        beams = sorted.into_iter().collect();
+    }
+
+    let best = beams.into_iter()
+        .max_by(|(_, a), (_, b)| log_sum_exp(a.0, a.1).total_cmp(&log_sum_exp(b.0, b.1)))
+        .map(|(prefix, _)| prefix)
+        .unwrap_or_default();
+
+    let mut ans = text_of(&best);
+    finish_decode(options, &mut ans);
+    ans
+}
+
+/// same as `greedy_decode`, but every emitted word is a separate entry, so positions are unambiguous even for multi
+/// byte (or multi char) words. the entries concatenated are the result of `greedy_decode`
+pub fn greedy_decode_words(probs: &Array2<f32>, index_to_word: &[String], options: &DecodeOptions) -> Vec<String> {
+    let mut ans = Vec::new();
+    let mut last_word = "";
+    for row in probs.rows() {
+        let (word, _) = timestep_word(row, index_to_word, options);
+        if word != last_word && word != "-" {
+            let mut word = if options.ascii_punctuation {
+                to_ascii_punct(word)
+            } else {
+                word.to_string()
+            };
+            word.retain(|c| !options.strip_chars.contains(&c));
+            if !word.is_empty() {
+                ans.push(word);
+            }
+        }
+
+        last_word = word;
+    }
+
+    ans
+}
+
+/// same as `greedy_decode`, and also computes the confidence from the probabilities
+/// the confidence of a word is its max probability over the timesteps it spans, and the confidence of the string is
+/// the words aggregated by `agg`. if nothing is emitted, the confidence is how sure the model is about the blanks
+pub fn greedy_decode_with_confidence(probs: &Array2<f32>, index_to_word: &[String], options: &DecodeOptions, agg: ConfidenceAgg) -> TextWithConfidence {
+    let mut ans = String::new();
+    let mut char_confidences: Vec<f32> = Vec::new();
+    let mut timestep_confidences: Vec<f32> = Vec::with_capacity(probs.nrows());
+    let mut last_word = "";
+    for row in probs.rows() {
+        let (word, value) = timestep_word(row, index_to_word, options);
+        timestep_confidences.push(value);
+
+        if word != last_word && word != "-" {
+            ans.push_str(word);
+            char_confidences.push(value);
+        } else if word == last_word && word != "-" {
+            let last = char_confidences.last_mut().unwrap();
+            *last = last.max(value);
+        }
+
+        last_word = word;
+    }
+
+    if options.ascii_punctuation {
+        ans = to_ascii_punct(&ans);
+    }
+
+    if !options.strip_chars.is_empty() {
+        if ans.chars().count() == char_confidences.len() {
+            // one char per word, so the confidences of the stripped chars can be dropped as well
+            let (text, confidences) = ans.chars()
+                .zip(char_confidences.iter())
+                .filter(|(c, _)| !options.strip_chars.contains(c))
+                .unzip();
+            ans = text;
+            char_confidences = confidences;
+        } else {
+            ans.retain(|c| !options.strip_chars.contains(&c));
+        }
+    }
+
+    let confidence = if char_confidences.is_empty() {
+        agg.aggregate(&timestep_confidences)
+    } else {
+        agg.aggregate(&char_confidences)
+    };
+
+    TextWithConfidence {
+        text: ans,
+        char_confidences,
+        confidence,
+    }
+}
+
+/// map full-width forms (e.g. `％，．＋`, full-width digits, the ideographic space) to their ascii counterparts
+/// other characters are kept as is
+pub fn to_ascii_punct(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
+/// split a "label: value" line on the first separator, both parts are trimmed
+/// returns `None` if there's no separator
+pub fn split_kv(s: &str, separators: &[char]) -> Option<(String, String)> {
+    let s = s.trim();
+    let index = s.find(|c: char| separators.contains(&c))?;
+    let separator_len = s[index..].chars().next().unwrap().len_utf8();
+
+    let key = s[..index].trim();
+    let value = s[index + separator_len..].trim();
+    Some((key.to_string(), value.to_string()))
+}
+
+/// similarity of two strings in `[0, 1]`, one minus the edit distance normalized by the longer length
+pub fn similarity(a: &str, b: &str) -> f32 {
+    let max_len = std::cmp::max(a.chars().count(), b.chars().count());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    let distance = edit_distance::edit_distance(a, b);
+    1.0 - distance as f32 / max_len as f32
+}
+
+/// set names whose similarity to the recognized string is below this are not matched
+pub const SET_NAME_MATCH_CUTOFF: f32 = 0.5;
+
+/// find the canonical set name closest to a recognized (possibly noisy) one
+/// returns the set name and its similarity, or `None` if nothing is similar enough
+pub fn match_set_name(raw: &str, sets: &[String]) -> Option<(String, f32)> {
+    match_dictionary(raw, sets, SET_NAME_MATCH_CUTOFF)
+}
+
+/// the default cutoff of `match_dictionary`
+pub const DICTIONARY_MATCH_CUTOFF: f32 = 0.5;
+
+/// find the dictionary entry closest to a recognized string, `None` if even the closest is less similar than `cutoff`
+pub fn match_dictionary(raw: &str, dict: &[String], cutoff: f32) -> Option<(String, f32)> {
+    let mut best: Option<(&String, f32)> = None;
+    for entry in dict.iter() {
+        let score = similarity(raw, entry);
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((entry, score));
+        }
+    }
+
+    match best {
+        Some((entry, score)) if score >= cutoff => Some((entry.clone(), score)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::language_model::NgramModel;
+    use ndarray::array;
+
+    #[test]
+    fn softmax_rows_sum_to_one() {
+        let mut arr = array![[1.0, 2.0, 3.0], [0.0, 0.0, 0.0], [-5.0, 10.0, 2.5]];
+        softmax_rows(&mut arr);
+
+        for row in arr.rows() {
+            assert!((row.sum() - 1.0).abs() < 1e-5);
+        }
+        assert!(arr[[0, 2]] > arr[[0, 1]] && arr[[0, 1]] > arr[[0, 0]]);
+        assert!((arr[[1, 0]] - 1.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn softmax_rows_large_and_negative_logits() {
+        let mut arr = array![[1000.0, 999.0, -1000.0], [-1e30, -1e30, -1e30], [f32::NEG_INFINITY, 0.0, f32::NEG_INFINITY]];
+        softmax_rows(&mut arr);
+
+        assert!(arr.iter().all(|v| v.is_finite()));
+        for row in arr.rows() {
+            assert!((row.sum() - 1.0).abs() < 1e-5);
+        }
+        assert!(arr[[0, 0]] > arr[[0, 1]] && arr[[0, 2]] < 1e-6);
+        assert_eq!(arr[[2, 1]], 1.0);
+    }
+
+    #[test]
+    fn is_normalized_tells_logits_from_probabilities() {
+        let logits = array![[2.0, -1.0, 0.5], [0.1, 0.2, 0.3]];
+        assert!(!is_normalized(&logits));
+
+        let probs = softmax(&logits);
+        assert!(is_normalized(&probs));
+        // softmaxing probabilities again flattens them
+        assert!(timestep_confidences(&softmax(&probs))[0] < timestep_confidences(&probs)[0]);
+    }
+
+    fn labels() -> Vec<String> {
+        ["-", "a", "b"].iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn near_uniform_timestep_is_unknown() {
+        let probs = array![[0.05, 0.9, 0.05], [0.34, 0.33, 0.33], [0.9, 0.05, 0.05], [0.05, 0.05, 0.9]];
+        let options = DecodeOptions {
+            unknown_threshold: Some(0.5),
+            unknown_placeholder: String::from("?"),
+            ..DecodeOptions::default()
+        };
+
+        assert_eq!(greedy_decode(&probs, &labels(), &options), "a?b");
+        assert_eq!(greedy_decode(&probs, &labels(), &DecodeOptions::default()), "ab");
+    }
+
+    #[test]
+    fn beam_search_with_a_language_model_snaps_to_the_vocabulary() {
+        let labels: Vec<String> = ["-", "a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let words: Vec<String> = ["abc", "cab", "bca"].iter().map(|s| s.to_string()).collect();
+        let lm = NgramModel::new(&words, 2);
+        // a - b - then `b` a little ahead of `c`
+        let probs = array![
+            [0.05, 0.9, 0.025, 0.025],
+            [0.9, 0.05, 0.025, 0.025],
+            [0.05, 0.025, 0.9, 0.025],
+            [0.9, 0.05, 0.025, 0.025],
+            [0.05, 0.0, 0.5, 0.45],
+        ];
+
+        assert_eq!(greedy_decode(&probs, &labels, &DecodeOptions::default()), "abb");
+        assert_eq!(prefix_beam_search(&probs, &labels, &lm, &DecodeOptions::default()), "abc");
+
+        // without the language model, or without room for the second best label, the beam agrees with greedy
+        let no_lm = DecodeOptions { lm_alpha: 0.0, ..DecodeOptions::default() };
+        assert_eq!(prefix_beam_search(&probs, &labels, &lm, &no_lm), "abb");
+        let narrow = DecodeOptions { beam_width: 1, ..DecodeOptions::default() };
+        assert_eq!(prefix_beam_search(&probs, &labels, &lm, &narrow), "abb");
+    }
+
+    fn set_names() -> Vec<String> {
+        ["角斗士的终幕礼", "流浪大地的乐团", "绝缘之旗印"].iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn match_set_name_exact() {
+        assert_eq!(match_set_name("绝缘之旗印", &set_names()), Some((String::from("绝缘之旗印"), 1.0)));
+    }
+
+    #[test]
+    fn match_set_name_close() {
+        let (name, score) = match_set_name("角斗土的终幕礼", &set_names()).unwrap();
+        assert_eq!(name, "角斗士的终幕礼");
+        assert!((SET_NAME_MATCH_CUTOFF..1.0).contains(&score));
+    }
+
+    #[test]
+    fn match_set_name_unrelated() {
+        assert_eq!(match_set_name("攻击力", &set_names()), None);
+    }
+
+    #[test]
+    fn confidence_aggregation_strategies() {
+        let values = [0.9, 0.5, 0.8];
+
+        assert_eq!(ConfidenceAgg::Min.aggregate(&values), 0.5);
+        assert!((ConfidenceAgg::ArithmeticMean.aggregate(&values) - 2.2 / 3.0).abs() < 1e-6);
+        let geometric = (0.9_f32 * 0.5 * 0.8).powf(1.0 / 3.0);
+        assert!((ConfidenceAgg::GeometricMean.aggregate(&values) - geometric).abs() < 1e-6);
+        assert_eq!(ConfidenceAgg::default(), ConfidenceAgg::GeometricMean);
+
+        for agg in [ConfidenceAgg::Min, ConfidenceAgg::GeometricMean, ConfidenceAgg::ArithmeticMean] {
+            assert_eq!(agg.aggregate(&[]), 0.0);
+        }
+    }
+
+    #[test]
+    fn full_width_punctuation_to_ascii() {
+        assert_eq!(to_ascii_punct("４６．６％"), "46.6%");
+        assert_eq!(to_ascii_punct("＋３１１，暴击率"), "+311,暴击率");
+        assert_eq!(to_ascii_punct("生命值\u{3000}４７８０"), "生命值 4780");
+        assert_eq!(to_ascii_punct("+46.6%"), "+46.6%");
+    }
+
+    #[test]
+    fn strip_noise_chars() {
+        let labels: Vec<String> = ["-", "4", "6", ".", "%", "·"].iter().map(|s| s.to_string()).collect();
+        // 4 · 6 - . 6 %
+        let indices = [1, 5, 2, 0, 3, 2, 4];
+        let mut probs = Array2::zeros((indices.len(), labels.len()));
+        for (t, &index) in indices.iter().enumerate() {
+            probs[[t, index]] = 1.0;
+        }
+        let options = DecodeOptions {
+            strip_chars: vec!['·'],
+            ..DecodeOptions::default()
+        };
+
+        assert_eq!(greedy_decode(&probs, &labels, &DecodeOptions::default()), "4·6.6%");
+        assert_eq!(greedy_decode(&probs, &labels, &options), "46.6%");
+        assert_eq!(greedy_decode_with_confidence(&probs, &labels, &options, ConfidenceAgg::Min).text, "46.6%");
+    }
+}
@@ -1,5 +1,65 @@
-use image::{ImageBuffer, Luma, RgbImage, GenericImageView};
+use image::{GrayImage, ImageBuffer, Luma, Pixel, Primitive, Rgb, RgbImage, GenericImageView};
+use crate::common::image_ext::ToF32GrayImage;
+use crate::positioning::Rect;
 use image::imageops;
+use log::warn;
+use num_traits::{NumCast, ToPrimitive};
+
+/// the height of the model input
+pub const INPUT_HEIGHT: u32 = 32;
+/// the width of the model input, unless the model is built with another width
+pub const DEFAULT_INPUT_WIDTH: u32 = 384;
+/// the widest input of models with a dynamic input width
+pub const MAX_NATIVE_WIDTH: u32 = 1024;
+/// native widths are rounded up to a multiple of this, so that the time axis of the output is not cut mid cell
+const NATIVE_WIDTH_ALIGN: u32 = 8;
+
+/// how the resized image is binarized
+#[derive(Clone, Debug)]
+pub enum ThresholdMode {
+    /// pixels below the value become 0, others become 1
+    Fixed(f32),
+    /// a global threshold chosen by otsu's method
+    Otsu,
+    /// local mean thresholding, a pixel becomes 1 if it is brighter than the mean of the `block_size * block_size`
+    /// window around it by more than `c`. handles backgrounds with varying lighting
+    Adaptive { block_size: u32, c: f32 },
+}
+
+impl Default for ThresholdMode {
+    fn default() -> Self {
+        ThresholdMode::Fixed(0.53)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PreprocessOptions {
+    /// crops whose long side exceeds this are downscaled before any other processing
+    /// trades a little accuracy for speed on very high resolution captures, `None` disables it
+    pub max_long_side: Option<u32>,
+    /// value used to pad the resized image to the input width, defaults to 0.0 (black)
+    pub pad_value: f32,
+    pub threshold: ThresholdMode,
+    /// erode the binarized image with a square kernel of this size, thinning strokes so outlined glyphs don't
+    /// merge. `None` disables it
+    pub erode_kernel: Option<u32>,
+    /// crops whose `ink_ratio` is outside `(min, max)` are rejected as if they had only one color
+    /// an almost full crop is usually a mis-cropped icon rather than text, `None` disables it
+    pub ink_ratio_band: Option<(f32, f32)>,
+    /// resize in two steps, first to this height (e.g. 64) with a quality filter, then to 32, and stretch the result
+    /// back to the full contrast. preserves the strokes of thin anti-aliased fonts better than resizing directly,
+    /// `None` disables it
+    pub supersample_height: Option<u32>,
+    /// crop vertically to the tallest band of rows containing ink (see `crop_to_main_band`), which drops slivers of
+    /// neighboring lines at the top or bottom of the crop
+    pub main_band_only: bool,
+    /// before converting an rgb crop to gray, replace the pixels which are close to the background and of low
+    /// saturation with the background, see `suppress_translucent_background`. for text over translucent panels
+    pub suppress_translucent_background: bool,
+    /// keep the aspect ratio of the crop instead of padding it to the full width, the result is only as wide as the
+    /// resized text (rounded up to a multiple of 8), at most the width. set by models with a dynamic input width
+    pub native_width: bool,
+}
 
 /// convert rgb image to f32 gray image
 pub fn to_gray(raw: &RgbImage) -> ImageBuffer<Luma<f32>, Vec<f32>> {
@@ -20,6 +80,83 @@ pub fn to_gray(raw: &RgbImage) -> ImageBuffer<Luma<f32>, Vec<f32>> {
     new_gray
 }
 
+/// same as `to_gray`, but applies the rgb options of `options` first
+pub fn to_gray_with_options(raw: &RgbImage, options: &PreprocessOptions) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+    if options.suppress_translucent_background {
+        to_gray(&suppress_translucent_background(raw))
+    } else {
+        to_gray(raw)
+    }
+}
+
+/// a translucent panel lets the background bleed through as washed out, low saturation colors around the panel
+/// color. such pixels are replaced with the panel color (the mean of the border pixels), which leaves the text
+pub fn suppress_translucent_background(raw: &RgbImage) -> RgbImage {
+    let (width, height) = raw.dimensions();
+    if width == 0 || height == 0 {
+        return raw.clone();
+    }
+
+    let mut sum = [0_u64; 3];
+    let mut count = 0_u64;
+    for (x, y, p) in raw.enumerate_pixels() {
+        if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+            for (s, v) in sum.iter_mut().zip(p.0) {
+                *s += v as u64;
+            }
+            count += 1;
+        }
+    }
+    let background = Rgb([
+        (sum[0] / count) as u8,
+        (sum[1] / count) as u8,
+        (sum[2] / count) as u8,
+    ]);
+
+    let mut im = raw.clone();
+    for p in im.pixels_mut() {
+        let max = *p.0.iter().max().unwrap() as f32;
+        let min = *p.0.iter().min().unwrap() as f32;
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        let distance = (0..3)
+            .map(|c| (p[c] as f32 - background[c] as f32).powi(2))
+            .sum::<f32>()
+            .sqrt();
+
+        if saturation < 0.25 && distance < 64.0 {
+            *p = background;
+        }
+    }
+
+    im
+}
+
+/// the mean color of the text pixels of an rgb crop, i.e. those which are ink once the crop is normalized and
+/// binarized with `options.threshold`. `[0, 0, 0]` if the crop has only one color
+pub fn dominant_text_color(raw: &RgbImage, options: &PreprocessOptions) -> [u8; 3] {
+    let mut gray = to_gray_with_options(raw, options);
+    if !normalize(&mut gray, true) {
+        return [0, 0, 0];
+    }
+    threshold(&mut gray, &options.threshold);
+
+    let mut sum = [0_u64; 3];
+    let mut count = 0_u64;
+    for (p, mask) in raw.pixels().zip(gray.pixels()) {
+        if mask[0] == 1.0 {
+            for (s, v) in sum.iter_mut().zip(p.0) {
+                *s += v as u64;
+            }
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return [0, 0, 0];
+    }
+    [(sum[0] / count) as u8, (sum[1] / count) as u8, (sum[2] / count) as u8]
+}
+
 /// normalize an f32 gray image
 /// which makes the bright pixel brighter, the dark pixels darker
 fn normalize(im: &mut ImageBuffer<Luma<f32>, Vec<f32>>, auto_inverse: bool) -> bool {
@@ -27,7 +164,7 @@ fn normalize(im: &mut ImageBuffer<Luma<f32>, Vec<f32>>, auto_inverse: bool) -> b
     let height = im.height();
 
     if width == 0 || height == 0 {
-        println!("wrong width or height");
+        warn!("图片的宽或高为 0");
         return false;
     }
 
@@ -72,8 +209,45 @@ fn normalize(im: &mut ImageBuffer<Luma<f32>, Vec<f32>>, auto_inverse: bool) -> b
     true
 }
 
-/// crop an f32 gray image to only where there is text
-fn crop(im: &ImageBuffer<Luma<f32>, Vec<f32>>) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+/// same as `normalize`, but for an u8 gray image
+pub(crate) fn normalize_u8(im: &mut GrayImage, auto_inverse: bool) -> bool {
+    let width = im.width();
+    let height = im.height();
+
+    if width == 0 || height == 0 {
+        warn!("图片的宽或高为 0");
+        return false;
+    }
+
+    let mut max = u8::MIN;
+    let mut min = u8::MAX;
+    for p in im.pixels() {
+        max = max.max(p[0]);
+        min = min.min(p[0]);
+    }
+
+    if max == min {
+        return false;
+    }
+
+    let flag_pixel = if width >= 2 {
+        im.get_pixel(width - 2, height - 1)[0]
+    } else {
+        im.get_pixel(width - 1, height - 1)[0]
+    };
+    let range = (max - min) as u32;
+    let inverse = auto_inverse && (flag_pixel - min) as u32 * 2 > range;
+
+    for p in im.pixels_mut() {
+        let new_pv = (((p[0] - min) as u32 * 255 + range / 2) / range) as u8;
+        p[0] = if inverse { 255 - new_pv } else { new_pv };
+    }
+
+    true
+}
+
+/// crop a gray image to only where there is text, `is_text` tells whether a pixel value belongs to text
+fn crop<P: Pixel + 'static>(im: &ImageBuffer<P, Vec<P::Subpixel>>, is_text: impl Fn(P::Subpixel) -> bool) -> ImageBuffer<P, Vec<P::Subpixel>> {
     let width = im.width();
     let height = im.height();
 
@@ -84,8 +258,8 @@ fn crop(im: &ImageBuffer<Luma<f32>, Vec<f32>>) -> ImageBuffer<Luma<f32>, Vec<f32
 
     for i in 0..width {
         for j in 0..height {
-            let p = im.get_pixel(i, j)[0];
-            if p > 0.7 {
+            let p = im.get_pixel(i, j).channels()[0];
+            if is_text(p) {
                 if i < min_col {
                     min_col = i;
                 }
@@ -99,8 +273,8 @@ fn crop(im: &ImageBuffer<Luma<f32>, Vec<f32>>) -> ImageBuffer<Luma<f32>, Vec<f32
 
     for j in 0..height {
         for i in 0..width {
-            let p = im.get_pixel(i, j)[0];
-            if p > 0.7 {
+            let p = im.get_pixel(i, j).channels()[0];
+            if is_text(p) {
                 if j < min_row {
                     min_row = j;
                 }
@@ -125,56 +299,757 @@ fn crop(im: &ImageBuffer<Luma<f32>, Vec<f32>>) -> ImageBuffer<Luma<f32>, Vec<f32
     cropped_im
 }
 
-/// resize an f32 gray image to 384 * 32, if not wide enough, then pad with background
-fn resize_and_pad(im: &ImageBuffer<Luma<f32>, Vec<f32>>) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+/// resize a gray image to `width * 32`, if not wide enough, then pad with `pad_value`
+/// if `supersample_height` is set and the image is taller, it is first downscaled to that height with a quality filter
+/// if `native`, it is only padded to a multiple of `NATIVE_WIDTH_ALIGN`, see `PreprocessOptions::native_width`
+fn resize_and_pad<P: Pixel + 'static>(im: &ImageBuffer<P, Vec<P::Subpixel>>, width: u32, pad_value: P, supersample_height: Option<u32>, native: bool) -> ImageBuffer<P, Vec<P::Subpixel>> {
+    let supersampled;
+    let (im, is_supersampled) = match supersample_height {
+        Some(height) if height > INPUT_HEIGHT && im.height() > height => {
+            let w = std::cmp::max((im.width() as f64 * height as f64 / im.height() as f64) as u32, 1);
+            supersampled = imageops::resize(im, w, height, image::imageops::FilterType::CatmullRom);
+            (&supersampled, true)
+        },
+        _ => (im, false),
+    };
+
     let w = im.width();
     let h = im.height();
+    let width_f = width as f64;
+    let height_f = INPUT_HEIGHT as f64;
 
-    let new_width = if w as f64 / (h as f64) > 384.0 / 32.0 {
-        384
+    let new_width = if w as f64 / (h as f64) > width_f / height_f {
+        width
     } else {
-        std::cmp::min((32.0 / h as f64 * w as f64) as u32, 384)
+        std::cmp::min((height_f / h as f64 * w as f64) as u32, width)
     };
 
-    let new_height = std::cmp::min((384.0 / w as f64 * h as f64) as u32, 32);
+    let new_height = std::cmp::min((width_f / w as f64 * h as f64) as u32, INPUT_HEIGHT);
 
-    let img = imageops::resize(
+    let mut img = imageops::resize(
         im,
         new_width,
         new_height,
         image::imageops::FilterType::Triangle,
     );
+    if is_supersampled {
+        restore_contrast(&mut img);
+    }
 
-    let data: Vec<f32> = vec![0.0; 32 * 384];
-    let mut padded_im = ImageBuffer::from_vec(384, 32, data).unwrap();
+    let padded_width = if native {
+        std::cmp::min(std::cmp::max(new_width, 1).div_ceil(NATIVE_WIDTH_ALIGN) * NATIVE_WIDTH_ALIGN, width)
+    } else {
+        width
+    };
+    let mut padded_im = ImageBuffer::from_pixel(padded_width, INPUT_HEIGHT, pad_value);
     imageops::overlay(&mut padded_im, &img, 0, 0);
     padded_im
 }
 
-/// transform an f32 gray image to a preprocessed image
-/// if the image has only one color, then return false, but this is not an error
-pub fn pre_process(im: ImageBuffer<Luma<f32>, Vec<f32>>) -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
-    let mut im = im;
-    if !normalize(&mut im, true) {
-        return (im, false);
+/// stretch the gray levels of a resized image back to the full range
+/// strokes thinner than a pixel of the result are averaged into faint gray, which the threshold would drop
+fn restore_contrast<P: Pixel + 'static>(im: &mut ImageBuffer<P, Vec<P::Subpixel>>) {
+    let value = |p: &P| p.channels()[0].to_f32().unwrap_or(0.0);
+    let (min, max) = im.pixels().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| {
+        (min.min(value(p)), max.max(value(p)))
+    });
+    if min >= max {
+        return;
     }
-    let mut im = crop(&im);
 
-    normalize(&mut im, false);
+    let full = P::Subpixel::DEFAULT_MAX_VALUE.to_f32().unwrap_or(1.0);
+    // integer levels are rounded, f32 levels are kept as is
+    let round = full > 1.0;
+    for p in im.pixels_mut() {
+        let stretched = (value(p) - min) / (max - min) * full;
+        let stretched = if round { stretched.round() } else { stretched };
+        if let Some(stretched) = NumCast::from(stretched) {
+            p.channels_mut()[0] = stretched;
+        }
+    }
+}
+
+/// downscale a gray image so that its long side is at most `max_long_side`, keeping the aspect ratio
+fn cap_long_side<P: Pixel + 'static>(im: ImageBuffer<P, Vec<P::Subpixel>>, max_long_side: u32) -> ImageBuffer<P, Vec<P::Subpixel>> {
+    let w = im.width();
+    let h = im.height();
+    let long_side = std::cmp::max(w, h);
+
+    if long_side <= max_long_side || max_long_side == 0 {
+        return im;
+    }
+
+    let scale = max_long_side as f64 / long_side as f64;
+    let new_width = std::cmp::max((w as f64 * scale) as u32, 1);
+    let new_height = std::cmp::max((h as f64 * scale) as u32, 1);
+
+    imageops::resize(&im, new_width, new_height, image::imageops::FilterType::Triangle)
+}
+
+/// find a global threshold of an f32 gray image with otsu's method
+fn otsu_threshold(im: &ImageBuffer<Luma<f32>, Vec<f32>>) -> f32 {
+    let mut histogram = [0_usize; 256];
+    for p in im.pixels() {
+        let bin = (p[0].clamp(0.0, 1.0) * 255.0) as usize;
+        histogram[bin] += 1;
+    }
+
+    // pixels in the best bin belong to the background
+    (otsu_best_bin(&histogram) + 1) as f32 / 255.0
+}
+
+/// the bin maximizing the between class variance, when bins up to it are the background
+fn otsu_best_bin(histogram: &[usize; 256]) -> usize {
+    let total: f64 = histogram.iter().map(|&c| c as f64).sum();
+    let sum_all: f64 = histogram.iter().enumerate().map(|(i, &c)| i as f64 * c as f64).sum();
+
+    let mut sum_background = 0.0;
+    let mut weight_background = 0.0;
+    let mut best_variance = -1.0;
+    let mut best_bin = 0;
+    for (i, &count) in histogram.iter().enumerate() {
+        weight_background += count as f64;
+        if weight_background == 0.0 {
+            continue;
+        }
+        let weight_foreground = total - weight_background;
+        if weight_foreground == 0.0 {
+            break;
+        }
+
+        sum_background += i as f64 * count as f64;
+        let mean_background = sum_background / weight_background;
+        let mean_foreground = (sum_all - sum_background) / weight_foreground;
+        let variance = weight_background * weight_foreground * (mean_background - mean_foreground).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best_bin = i;
+        }
+    }
+
+    best_bin
+}
+
+/// suggest a fixed threshold from sample text crops, by otsu's method over the histogram of all samples
+/// the samples are normalized like in `pre_process` first, so the result is in the same space as
+/// `ThresholdMode::Fixed`, i.e. use `ThresholdMode::Fixed(t as f32 / 255.0)`. samples with only one color are skipped
+pub fn estimate_threshold(samples: &[GrayImage]) -> u8 {
+    let mut histogram = [0_usize; 256];
+    for sample in samples.iter() {
+        let mut im = sample.clone();
+        if !normalize_u8(&mut im, true) {
+            continue;
+        }
+        for p in im.pixels() {
+            histogram[p[0] as usize] += 1;
+        }
+    }
+
+    // pixels in the best bin belong to the background
+    std::cmp::min(otsu_best_bin(&histogram) + 1, 255) as u8
+}
+
+/// binarize an f32 gray image with local mean thresholding, using an integral image
+fn adaptive_threshold(im: &mut ImageBuffer<Luma<f32>, Vec<f32>>, block_size: u32, c: f32) {
+    let width = im.width() as usize;
+    let height = im.height() as usize;
 
-    let mut im = resize_and_pad(&im);
+    // integral[y][x] is the sum of all pixels above and to the left of (x, y), exclusive
+    let mut integral = vec![0.0_f64; (width + 1) * (height + 1)];
+    for y in 0..height {
+        let mut row_sum = 0.0;
+        for x in 0..width {
+            row_sum += im.get_pixel(x as u32, y as u32)[0] as f64;
+            integral[(y + 1) * (width + 1) + x + 1] = integral[y * (width + 1) + x + 1] + row_sum;
+        }
+    }
+
+    let radius = (block_size / 2) as usize;
+    for y in 0..height {
+        for x in 0..width {
+            let x0 = x.saturating_sub(radius);
+            let y0 = y.saturating_sub(radius);
+            let x1 = std::cmp::min(x + radius + 1, width);
+            let y1 = std::cmp::min(y + radius + 1, height);
+
+            let sum = integral[y1 * (width + 1) + x1] - integral[y0 * (width + 1) + x1]
+                - integral[y1 * (width + 1) + x0] + integral[y0 * (width + 1) + x0];
+            let mean = sum / ((x1 - x0) * (y1 - y0)) as f64;
+
+            let p = im.get_pixel_mut(x as u32, y as u32);
+            p[0] = if p[0] as f64 > mean + c as f64 { 1.0 } else { 0.0 };
+        }
+    }
+}
+
+/// morphological erosion with a `kernel * kernel` square, every pixel becomes the min of its neighbourhood
+/// thins bright strokes, which separates touching characters
+pub fn erode(im: &ImageBuffer<Luma<f32>, Vec<f32>>, kernel: u32) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+    let width = im.width();
+    let height = im.height();
+    let radius = kernel / 2;
+
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let x0 = x.saturating_sub(radius);
+        let y0 = y.saturating_sub(radius);
+        let x1 = std::cmp::min(x + radius, width.saturating_sub(1));
+        let y1 = std::cmp::min(y + radius, height.saturating_sub(1));
+
+        let mut min = f32::INFINITY;
+        for i in x0..=x1 {
+            for j in y0..=y1 {
+                min = min.min(im.get_pixel(i, j)[0]);
+            }
+        }
+        Luma([min])
+    })
+}
+
+/// binarize an f32 gray image
+fn threshold(im: &mut ImageBuffer<Luma<f32>, Vec<f32>>, mode: &ThresholdMode) {
+    let value = match *mode {
+        ThresholdMode::Fixed(value) => value,
+        ThresholdMode::Otsu => otsu_threshold(im),
+        ThresholdMode::Adaptive { block_size, c } => {
+            adaptive_threshold(im, block_size, c);
+            return;
+        },
+    };
 
     for i in 0..im.width() {
         for j in 0..im.height() {
             let p = im.get_pixel_mut(i, j);
             let pv = p[0];
-            if pv < 0.53 {
+            if pv < value {
                 p[0] = 0.0;
             } else {
                 p[0] = 1.0;
             }
         }
     }
+}
+
+/// transform an f32 gray image to a preprocessed image
+/// if the image has only one color, then return false, but this is not an error
+pub fn pre_process(im: ImageBuffer<Luma<f32>, Vec<f32>>) -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
+    pre_process_with_options(im, DEFAULT_INPUT_WIDTH, &PreprocessOptions::default())
+}
+
+/// same as `pre_process`, but the result is `width * 32`, and processed with `options`
+pub fn pre_process_with_options(im: ImageBuffer<Luma<f32>, Vec<f32>>, width: u32, options: &PreprocessOptions) -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
+    let mut im = match options.max_long_side {
+        Some(max_long_side) => cap_long_side(im, max_long_side),
+        None => im,
+    };
+    if !normalize(&mut im, true) {
+        return (im, false);
+    }
+    if options.ink_ratio_band.is_some() && !ink_ratio_in_band(normalized_ink_ratio(&im, |p| p >= 0.5), options) {
+        return (im, false);
+    }
+    if options.main_band_only {
+        im = crop_normalized_to_main_band(im, |p| p >= 0.5);
+    }
+    let mut im = crop(&im, |p| p > 0.7);
+
+    normalize(&mut im, false);
 
-    (im, true)
+    let im = resize_and_pad(&im, width, Luma([options.pad_value]), options.supersample_height, options.native_width);
+
+    (binarize(im, options), true)
+}
+
+/// same as `pre_process`, but normalizes, crops and resizes in u8 space, and only converts the final 384 * 32 image
+/// to f32. this avoids converting the whole crop to f32 first, the result matches `pre_process` up to rounding
+pub fn pre_process_u8(im: &GrayImage) -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
+    pre_process_u8_with_options(im, DEFAULT_INPUT_WIDTH, &PreprocessOptions::default())
+}
+
+pub fn pre_process_u8_with_options(im: &GrayImage, width: u32, options: &PreprocessOptions) -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
+    let mut im = match options.max_long_side {
+        Some(max_long_side) => cap_long_side(im.clone(), max_long_side),
+        None => im.clone(),
+    };
+    if !normalize_u8(&mut im, true) {
+        return (im.to_f32_gray_image(), false);
+    }
+    if options.ink_ratio_band.is_some() && !ink_ratio_in_band(normalized_ink_ratio(&im, |p| p >= 128), options) {
+        return (im.to_f32_gray_image(), false);
+    }
+    if options.main_band_only {
+        im = crop_normalized_to_main_band(im, |p| p >= 128);
+    }
+    // 0.7 in u8 space
+    let mut im = crop(&im, |p| p > 178);
+
+    normalize_u8(&mut im, false);
+
+    let pad_value = (options.pad_value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let im = resize_and_pad(&im, width, Luma([pad_value]), options.supersample_height, options.native_width);
+
+    (binarize(im.to_f32_gray_image(), options), true)
+}
+
+/// crop vertically to the tallest run of consecutive rows containing ink, found by horizontal projection
+/// the image is returned as is if it has only one color
+pub fn crop_to_main_band(im: &GrayImage) -> GrayImage {
+    let mut normalized = im.clone();
+    if !normalize_u8(&mut normalized, true) {
+        return im.clone();
+    }
+
+    match main_band(&normalized, |p| p >= 128) {
+        Some((top, bottom)) => imageops::crop_imm(im, 0, top, im.width(), bottom - top).to_image(),
+        None => im.clone(),
+    }
+}
+
+/// rows `[top, bottom)` of the tallest run of rows which contain ink
+fn main_band<P: Pixel + 'static>(im: &ImageBuffer<P, Vec<P::Subpixel>>, is_ink: impl Fn(P::Subpixel) -> bool) -> Option<(u32, u32)> {
+    let mut best: Option<(u32, u32)> = None;
+    let mut start: Option<u32> = None;
+    for y in 0..=im.height() {
+        let has_ink = y < im.height() && (0..im.width()).any(|x| is_ink(im.get_pixel(x, y).channels()[0]));
+        match (has_ink, start) {
+            (true, None) => start = Some(y),
+            (false, Some(top)) => {
+                if best.is_none_or(|(best_top, best_bottom)| y - top > best_bottom - best_top) {
+                    best = Some((top, y));
+                }
+                start = None;
+            },
+            _ => {},
+        }
+    }
+
+    best
+}
+
+/// crop `im` to its main band, `im` is normalized so that the text is bright
+fn crop_normalized_to_main_band<P: Pixel + 'static>(im: ImageBuffer<P, Vec<P::Subpixel>>, is_ink: impl Fn(P::Subpixel) -> bool) -> ImageBuffer<P, Vec<P::Subpixel>> {
+    match main_band(&im, is_ink) {
+        Some((top, bottom)) if bottom - top < im.height() => imageops::crop_imm(&im, 0, top, im.width(), bottom - top).to_image(),
+        _ => im,
+    }
+}
+
+/// find the cells of a regular grid (e.g. the artifacts of the inventory) by projection analysis
+/// a pixel belongs to a cell if it differs from the background (the most common gray level). runs of columns and rows
+/// with content whose length is near `expected_cell` become the grid lines, and every grid position with content is a
+/// cell, so a partially filled last row yields only its filled cells. the cells are in row-major order
+pub fn detect_panels(im: &GrayImage, expected_cell: (u32, u32)) -> Vec<Rect<u32>> {
+    let (width, height) = im.dimensions();
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+
+    let mut histogram = [0_usize; 256];
+    for p in im.pixels() {
+        histogram[p[0] as usize] += 1;
+    }
+    let background = (0..256).max_by_key(|&i| histogram[i]).unwrap() as i32;
+    let is_content = |x: u32, y: u32| (im.get_pixel(x, y)[0] as i32 - background).abs() > 24;
+
+    let columns: Vec<f32> = (0..width)
+        .map(|x| (0..height).filter(|&y| is_content(x, y)).count() as f32 / height as f32)
+        .collect();
+    let rows: Vec<f32> = (0..height)
+        .map(|y| (0..width).filter(|&x| is_content(x, y)).count() as f32 / width as f32)
+        .collect();
+
+    let column_spans = content_spans(&columns, expected_cell.0);
+    let row_spans = content_spans(&rows, expected_cell.1);
+
+    let mut panels = Vec::new();
+    for &(top, bottom) in row_spans.iter() {
+        for &(left, right) in column_spans.iter() {
+            let area = ((right - left) * (bottom - top)) as f32;
+            let content = (top..bottom)
+                .map(|y| (left..right).filter(|&x| is_content(x, y)).count())
+                .sum::<usize>();
+            if content as f32 / area > 0.1 {
+                panels.push(Rect::new(left, top, right - left, bottom - top));
+            }
+        }
+    }
+
+    panels
+}
+
+/// runs `[start, end)` of the profile above a small fraction, whose length is within 60% ~ 140% of `expected`
+fn content_spans(profile: &[f32], expected: u32) -> Vec<(u32, u32)> {
+    let min_len = (expected as f32 * 0.6) as u32;
+    let max_len = (expected as f32 * 1.4).ceil() as u32;
+
+    let mut spans = Vec::new();
+    let mut start = None;
+    for i in 0..=profile.len() {
+        let has_content = i < profile.len() && profile[i] > 0.02;
+        match (has_content, start) {
+            (true, None) => start = Some(i as u32),
+            (false, Some(s)) => {
+                let len = i as u32 - s;
+                if len >= min_len && len <= max_len {
+                    spans.push((s, i as u32));
+                }
+                start = None;
+            },
+            _ => {},
+        }
+    }
+
+    spans
+}
+
+/// a 64 bit perceptual hash (dct hash) of a crop, near duplicate crops (e.g. the same row in overlapping scroll
+/// frames) have hashes within a small hamming distance, compare them with `(a ^ b).count_ones()`
+pub fn phash(im: &GrayImage) -> u64 {
+    const SIZE: usize = 32;
+    const LOW: usize = 8;
+
+    if im.width() == 0 || im.height() == 0 {
+        return 0;
+    }
+    let small = imageops::resize(im, SIZE as u32, SIZE as u32, image::imageops::FilterType::Triangle);
+    let pixels: Vec<f32> = small.pixels().map(|p| p[0] as f32).collect();
+
+    // the low frequencies of a 2d dct-ii
+    let cos_table: Vec<f32> = (0..LOW * SIZE)
+        .map(|i| {
+            let (u, x) = (i / SIZE, i % SIZE);
+            ((2 * x + 1) as f32 * u as f32 * std::f32::consts::PI / (2 * SIZE) as f32).cos()
+        })
+        .collect();
+    let mut coefficients = [0.0_f32; LOW * LOW];
+    for v in 0..LOW {
+        for u in 0..LOW {
+            let mut sum = 0.0;
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    sum += pixels[y * SIZE + x] * cos_table[u * SIZE + x] * cos_table[v * SIZE + y];
+                }
+            }
+            coefficients[v * LOW + u] = sum;
+        }
+    }
+
+    // the dc term is left out of the median, it only carries the mean brightness
+    let mut sorted = coefficients[1..].to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+
+    let mut hash = 0_u64;
+    for (i, &c) in coefficients.iter().enumerate() {
+        if c > median {
+            hash |= 1 << i;
+        }
+    }
+    hash
+}
+
+/// the fraction of pixels which are ink (text) once the crop is normalized, 0.0 for an image with only one color
+/// the text is the bright part after normalizing, dark text on a bright background is inverted first
+pub fn ink_ratio(im: &GrayImage) -> f32 {
+    let mut im = im.clone();
+    if !normalize_u8(&mut im, true) {
+        return 0.0;
+    }
+    normalized_ink_ratio(&im, |p| p >= 128)
+}
+
+fn normalized_ink_ratio<P: Pixel + 'static>(im: &ImageBuffer<P, Vec<P::Subpixel>>, is_ink: impl Fn(P::Subpixel) -> bool) -> f32 {
+    let total = im.width() as usize * im.height() as usize;
+    if total == 0 {
+        return 0.0;
+    }
+    let ink = im.pixels().filter(|p| is_ink(p.channels()[0])).count();
+    ink as f32 / total as f32
+}
+
+fn ink_ratio_in_band(ratio: f32, options: &PreprocessOptions) -> bool {
+    match options.ink_ratio_band {
+        Some((min, max)) => ratio >= min && ratio <= max,
+        None => true,
+    }
+}
+
+/// a preprocessed image, and false if it had only one color
+pub type Preprocessed = (ImageBuffer<Luma<f32>, Vec<f32>>, bool);
+
+/// `pre_process_u8` every image, in parallel if the `rayon` feature is enabled
+/// the results are in the same order as `ims`
+pub fn pre_process_batch(ims: &[GrayImage]) -> Vec<Preprocessed> {
+    pre_process_batch_with_options(ims, DEFAULT_INPUT_WIDTH, &PreprocessOptions::default())
+}
+
+pub fn pre_process_batch_with_options(ims: &[GrayImage], width: u32, options: &PreprocessOptions) -> Vec<Preprocessed> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        ims.par_iter().map(|im| pre_process_u8_with_options(im, width, options)).collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        ims.iter().map(|im| pre_process_u8_with_options(im, width, options)).collect()
+    }
+}
+
+/// threshold and optionally erode a resized image
+fn binarize(im: ImageBuffer<Luma<f32>, Vec<f32>>, options: &PreprocessOptions) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+    let mut im = im;
+    threshold(&mut im, &options.threshold);
+
+    if let Some(kernel) = options.erode_kernel {
+        im = erode(&im, kernel);
+    }
+
+    im
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// white blocks of `GLYPH` columns with gaps as wide on black, `height` high
+    fn glyphs(count: u32, height: u32) -> GrayImage {
+        const GLYPH: u32 = 8;
+        GrayImage::from_fn(GLYPH * (2 * count + 1), height, |x, _| {
+            let slot = x / GLYPH;
+            Luma([if slot % 2 == 1 && slot < 2 * count { 255 } else { 0 }])
+        })
+    }
+
+    #[test]
+    fn pad_value_fills_the_padding() {
+        let im = glyphs(3, 32).to_f32_gray_image();
+        let (default, _) = pre_process(im.clone());
+        let (black, _) = pre_process_with_options(im.clone(), DEFAULT_INPUT_WIDTH, &PreprocessOptions::default());
+        let (white, _) = pre_process_with_options(im, DEFAULT_INPUT_WIDTH, &PreprocessOptions {
+            pad_value: 1.0,
+            ..PreprocessOptions::default()
+        });
+
+        assert_eq!(default.as_raw(), black.as_raw());
+        let edge = DEFAULT_INPUT_WIDTH - 1;
+        assert_eq!(black.get_pixel(edge, 0)[0], 0.0);
+        assert_eq!(white.get_pixel(edge, 0)[0], 1.0);
+        // the text is the same
+        assert_eq!(black.get_pixel(0, 0), white.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn adaptive_threshold_handles_gradient_background() {
+        // glyphs a little brighter than a background which brightens from left to right
+        let (width, height, count) = (160, 32, 10);
+        let gradient = ImageBuffer::from_fn(width, height, |x, y| {
+            let background = 0.6 * x as f32 / width as f32;
+            let is_glyph = (x / 8) % 2 == 1 && (8..24).contains(&y);
+            Luma([if is_glyph { background + 0.3 } else { background }])
+        });
+        let glyph_centers = (0..count).map(|i| 16 * i + 12);
+        let gap_centers = (0..count).map(|i| 16 * i + 4);
+        let recovered = |im: &ImageBuffer<Luma<f32>, Vec<f32>>| {
+            glyph_centers.clone().all(|x| im.get_pixel(x, 16)[0] == 1.0)
+                && gap_centers.clone().all(|x| im.get_pixel(x, 16)[0] == 0.0)
+        };
+
+        let mut global = gradient.clone();
+        threshold(&mut global, &ThresholdMode::default());
+        assert!(!recovered(&global));
+
+        let mut adaptive = gradient;
+        threshold(&mut adaptive, &ThresholdMode::Adaptive { block_size: 15, c: 0.05 });
+        assert!(recovered(&adaptive));
+    }
+
+    #[test]
+    fn ink_ratio_band_rejects_extremes() {
+        let options = PreprocessOptions {
+            ink_ratio_band: Some((0.05, 0.6)),
+            ..PreprocessOptions::default()
+        };
+        let passes = |im: &GrayImage| pre_process_u8_with_options(im, DEFAULT_INPUT_WIDTH, &options).1;
+
+        // a bright icon, only its bottom row is background
+        let full = GrayImage::from_fn(64, 32, |_, y| Luma([if y == 31 { 0 } else { 255 }]));
+        assert!(ink_ratio(&full) > 0.9);
+        assert!(!passes(&full));
+
+        // a single speck of ink
+        let mut sparse = GrayImage::new(64, 32);
+        sparse.put_pixel(10, 10, Luma([255]));
+        assert!(ink_ratio(&sparse) < 0.01);
+        assert!(!passes(&sparse));
+
+        let text = glyphs(3, 32);
+        assert!((ink_ratio(&text) - 3.0 / 7.0).abs() < 1e-6);
+        assert!(passes(&text));
+    }
+
+    #[test]
+    fn batch_preprocessing_matches_sequential() {
+        let ims: Vec<GrayImage> = (1..6).map(|count| glyphs(count, 24 + count * 4)).collect();
+
+        let batch = pre_process_batch(&ims);
+        assert_eq!(batch.len(), ims.len());
+        for (im, (result, non_mono)) in ims.iter().zip(batch.iter()) {
+            let (expected, expected_non_mono) = pre_process_u8(im);
+            assert_eq!(result.as_raw(), expected.as_raw());
+            assert_eq!(*non_mono, expected_non_mono);
+        }
+    }
+
+    #[test]
+    fn main_band_drops_a_stray_partial_line() {
+        // the bottom of the line above at the top of the crop, the line itself in rows 12..36
+        let line = glyphs(4, 24);
+        let mut im = GrayImage::new(line.width(), 44);
+        imageops::replace(&mut im, &line, 0, 12);
+        for x in 20..50 {
+            for y in 0..4 {
+                im.put_pixel(x, y, Luma([255]));
+            }
+        }
+
+        let band = crop_to_main_band(&im);
+        assert_eq!(band.dimensions(), (im.width(), 24));
+        assert_eq!(band, line);
+
+        // nothing to crop in a single color image
+        let blank = GrayImage::new(32, 32);
+        assert_eq!(crop_to_main_band(&blank), blank);
+    }
+
+    #[test]
+    fn estimated_threshold_is_between_the_modes() {
+        // a dark background around 40 and bright text around 205, the extremes keep normalization an identity
+        let sample = |seed: u32| GrayImage::from_fn(64, 32, |x, y| {
+            let noise = (x * 7 + y * 13 + seed * 31) % 41;
+            let value = match (x, y) {
+                (0, 0) => 0,
+                (1, 0) => 255,
+                _ if (x / 8) % 2 == 1 && y > 4 && y < 28 => 185 + noise,
+                _ => 20 + noise,
+            };
+            Luma([value as u8])
+        });
+        let samples: Vec<GrayImage> = (0..4).map(sample).chain(std::iter::once(GrayImage::new(16, 16))).collect();
+
+        let threshold = estimate_threshold(&samples);
+        assert!(threshold > 60 && threshold <= 185, "{}", threshold);
+    }
+
+    #[test]
+    fn native_width_keeps_the_aspect_ratio() {
+        let options = PreprocessOptions { native_width: true, ..PreprocessOptions::default() };
+        // 7 glyphs of 8 columns at 32 high, 104 columns once the margins are cropped
+        let (im, _) = pre_process_u8_with_options(&glyphs(7, 32), MAX_NATIVE_WIDTH, &options);
+        assert_eq!(im.dimensions(), (104, INPUT_HEIGHT));
+
+        // capped at the width
+        let (im, _) = pre_process_u8_with_options(&glyphs(7, 32), 64, &options);
+        assert_eq!(im.width(), 64);
+        let (im, _) = pre_process_u8(&glyphs(7, 32));
+        assert_eq!(im.width(), DEFAULT_INPUT_WIDTH);
+    }
+
+    #[test]
+    fn dominant_color_of_green_text() {
+        // green glyphs on a dark background, with a darker anti-aliased column at both edges of every glyph
+        let (core, edge, background) = ([40, 200, 60], [30, 115, 42], [20, 30, 25]);
+        let im = RgbImage::from_fn(8 * 7, 32, |x, y| {
+            let in_glyph = (x / 8) % 2 == 1 && x / 8 < 6 && (4..28).contains(&y);
+            Rgb(match (in_glyph, x % 8) {
+                (true, 0) | (true, 7) => edge,
+                (true, _) => core,
+                _ => background,
+            })
+        });
+
+        // the edges normalize to about 0.5, below the default threshold
+        assert_eq!(dominant_text_color(&im, &PreprocessOptions::default()), core);
+
+        let low = PreprocessOptions { threshold: ThresholdMode::Fixed(0.3), ..PreprocessOptions::default() };
+        let mixed = dominant_text_color(&im, &low);
+        assert_ne!(mixed, core);
+        assert!((edge[1]..core[1]).contains(&mixed[1]), "{:?}", mixed);
+        assert!(mixed[1] > mixed[0] && mixed[1] > mixed[2]);
+
+        assert_eq!(dominant_text_color(&RgbImage::from_pixel(16, 16, Rgb(core)), &PreprocessOptions::default()), [0, 0, 0]);
+    }
+
+    #[test]
+    fn detect_panels_of_a_partially_filled_grid() {
+        // 3 columns of 40 * 50 cells, 10 pixels apart, two full rows and one cell in the last row
+        let (cell, gap, margin) = ((40, 50), 10, 10);
+        let origin = |col: u32, row: u32| (margin + col * (cell.0 + gap), margin + row * (cell.1 + gap));
+        let filled: Vec<(u32, u32)> = (0..7).map(|index| (index % 3, index / 3)).collect();
+        let im = GrayImage::from_fn(2 * margin + 3 * cell.0 + 2 * gap, 2 * margin + 3 * cell.1 + 2 * gap, |x, y| {
+            let in_cell = filled.iter().any(|&(col, row)| {
+                let (left, top) = origin(col, row);
+                (left..left + cell.0).contains(&x) && (top..top + cell.1).contains(&y)
+            });
+            Luma([if in_cell { 200 } else { 30 }])
+        });
+
+        let panels = detect_panels(&im, cell);
+        let expected: Vec<Rect<u32>> = filled.iter()
+            .map(|&(col, row)| {
+                let (left, top) = origin(col, row);
+                Rect::new(left, top, cell.0, cell.1)
+            })
+            .collect();
+        assert_eq!(panels.len(), 7);
+        assert_eq!(panels, expected);
+
+        assert!(detect_panels(&GrayImage::new(0, 0), cell).is_empty());
+    }
+
+    #[test]
+    fn phash_of_shifted_copies_is_close() {
+        // a row of glyphs of different heights, drawn `shift` pixels to the right
+        let row = |heights: &[u32], shift: u32| GrayImage::from_fn(128, 48, |x, y| {
+            let slot = x.saturating_sub(shift) / 8;
+            let is_glyph = slot % 2 == 1 && heights.get(slot as usize / 2).is_some_and(|&h| y >= 48 - h);
+            Luma([if is_glyph { 230 } else { 20 }])
+        });
+        let heights = [40, 12, 30, 44, 8, 24, 36, 16];
+        let distance = |a: &GrayImage, b: &GrayImage| (phash(a) ^ phash(b)).count_ones();
+
+        let im = row(&heights, 0);
+        assert_eq!(distance(&im, &im), 0);
+        assert!(distance(&im, &row(&heights, 1)) <= 6, "{}", distance(&im, &row(&heights, 1)));
+        assert!(distance(&im, &row(&heights, 2)) <= 6, "{}", distance(&im, &row(&heights, 2)));
+
+        let other = row(&[8, 44, 16, 12, 40, 36, 8, 44], 0);
+        assert!(distance(&im, &other) >= 16, "{}", distance(&im, &other));
+    }
+
+    #[cfg(feature = "tract_onnx")]
+    #[test]
+    fn suppressing_a_translucent_background_drops_the_bleed() {
+        use crate::ocr::traits::ImageToText;
+        use super::super::test_model;
+
+        // dim text over a gray panel, with a washed out patch of the scene behind it between `a` and `b`
+        let mut image = test_model::text_image("abc");
+        for p in image.pixels_mut() {
+            *p = if p[0] == 0 { Rgb([100, 100, 100]) } else { Rgb([160, 160, 160]) };
+        }
+        for x in 16..24 {
+            for y in 4..28 {
+                image.put_pixel(x, y, Rgb([138, 135, 132]));
+            }
+        }
+
+        let plain = test_model::model();
+        assert_eq!(plain.image_to_text(&image, false).unwrap(), "acbc");
+
+        let suppressed = test_model::model().with_preprocess_options(PreprocessOptions {
+            suppress_translucent_background: true,
+            ..PreprocessOptions::default()
+        });
+        assert_eq!(suppressed.image_to_text(&image, false).unwrap(), "abc");
+    }
 }
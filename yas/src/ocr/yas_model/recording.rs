@@ -0,0 +1,52 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use image::{ImageBuffer, Luma};
+use serde::{Deserialize, Serialize};
+
+/// a preprocessed input and what the model recognized from it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedInference {
+    pub width: u32,
+    pub height: u32,
+    /// the preprocessed image, row major
+    pub pixels: Vec<f32>,
+    pub text: String,
+}
+
+impl RecordedInference {
+    pub fn new(img: &ImageBuffer<Luma<f32>, Vec<f32>>, text: &str) -> RecordedInference {
+        RecordedInference {
+            width: img.width(),
+            height: img.height(),
+            pixels: img.as_raw().clone(),
+            text: text.to_string(),
+        }
+    }
+
+    pub fn to_image(&self) -> Result<ImageBuffer<Luma<f32>, Vec<f32>>> {
+        ImageBuffer::from_raw(self.width, self.height, self.pixels.clone())
+            .ok_or_else(|| anyhow!("recorded pixels don't match the size {}x{}", self.width, self.height))
+    }
+}
+
+/// a recorded input which is recognized differently now
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diff {
+    /// index in the recording
+    pub index: usize,
+    pub expected: String,
+    pub actual: String,
+}
+
+pub fn write_recording<P: AsRef<Path>>(path: P, recording: &[RecordedInference]) -> Result<()> {
+    let file = File::create(path)?;
+    serde_json::to_writer(BufWriter::new(file), recording)?;
+    Ok(())
+}
+
+pub fn read_recording<P: AsRef<Path>>(path: P) -> Result<Vec<RecordedInference>> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
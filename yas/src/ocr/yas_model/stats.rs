@@ -0,0 +1,95 @@
+use std::iter::Sum;
+use std::ops::AddAssign;
+use std::time::Duration;
+
+/// a snapshot of the statistics of a model, snapshots of several models can be summed into a total
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OcrStats {
+    pub invoke_count: usize,
+    pub inference_time: Duration,
+    pub preprocess_count: usize,
+    pub preprocess_time: Duration,
+    pub mono_skip_count: usize,
+    pub confidence_count: usize,
+    pub confidence_sum: f64,
+}
+
+impl OcrStats {
+    pub fn average_inference_time(&self) -> Option<Duration> {
+        if self.invoke_count == 0 {
+            None
+        } else {
+            Some(self.inference_time.div_f64(self.invoke_count as f64))
+        }
+    }
+
+    pub fn average_preprocess_time(&self) -> Option<Duration> {
+        if self.preprocess_count == 0 {
+            None
+        } else {
+            Some(self.preprocess_time.div_f64(self.preprocess_count as f64))
+        }
+    }
+
+    pub fn average_confidence(&self) -> Option<f64> {
+        if self.confidence_count == 0 {
+            None
+        } else {
+            Some(self.confidence_sum / self.confidence_count as f64)
+        }
+    }
+}
+
+impl AddAssign for OcrStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.invoke_count += rhs.invoke_count;
+        self.inference_time += rhs.inference_time;
+        self.preprocess_count += rhs.preprocess_count;
+        self.preprocess_time += rhs.preprocess_time;
+        self.mono_skip_count += rhs.mono_skip_count;
+        self.confidence_count += rhs.confidence_count;
+        self.confidence_sum += rhs.confidence_sum;
+    }
+}
+
+impl Sum for OcrStats {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut total = OcrStats::default();
+        for stats in iter {
+            total += stats;
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_average_is_weighted_by_count() {
+        let fast = OcrStats {
+            invoke_count: 3,
+            inference_time: Duration::from_millis(30),
+            confidence_count: 1,
+            confidence_sum: 0.5,
+            ..OcrStats::default()
+        };
+        let slow = OcrStats {
+            invoke_count: 1,
+            inference_time: Duration::from_millis(50),
+            confidence_count: 1,
+            confidence_sum: 1.0,
+            ..OcrStats::default()
+        };
+
+        let mut merged = fast.clone();
+        merged += slow.clone();
+        assert_eq!(merged.invoke_count, 4);
+        assert_eq!(merged.average_inference_time(), Some(Duration::from_millis(20)));
+        assert_eq!(merged.average_confidence(), Some(0.75));
+        assert_eq!(merged.average_preprocess_time(), None);
+
+        assert_eq!(vec![fast, slow].into_iter().sum::<OcrStats>(), merged);
+    }
+}
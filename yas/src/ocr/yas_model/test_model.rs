@@ -0,0 +1,220 @@
+//! a tiny onnx model for tests, hand encoded so no trained model is needed
+//!
+//! the model looks at every 4 columns of the `[1, 1, 32, width]` input, and tells by where the ink is:
+//! - ink only in the top half: label 1 (`a` of `LABELS`)
+//! - ink only in the bottom half: label 2 (`b`)
+//! - ink in both halves: label 3 (`c`)
+//! - no ink: the blank, label 0
+//!
+//! `text_image` draws such glyphs. preprocessing crops to the ink, so a test image should contain a full height glyph
+//! (`c`), or both `a` and `b`, otherwise the lone half glyph fills the crop and reads as `c`
+
+use image::{Rgb, RgbImage};
+use super::yas_ocr_model::YasOCRModel;
+
+pub const LABELS: &str = r#"{"0": "-", "1": "a", "2": "b", "3": "c"}"#;
+
+/// columns of every timestep of the output
+pub const STRIDE: u32 = 4;
+/// width of a glyph of `text_image`, and of the gap between glyphs
+pub const GLYPH_WIDTH: u32 = 8;
+
+const VOCAB: usize = 4;
+const HEIGHT: usize = 32;
+
+// onnx enums
+const ATTRIBUTE_INT: u64 = 2;
+const ATTRIBUTE_INTS: u64 = 7;
+const TENSOR_FLOAT: u64 = 1;
+const TENSOR_INT64: u64 = 7;
+
+fn varint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn int_field(out: &mut Vec<u8>, field: u64, value: i64) {
+    varint(out, field << 3);
+    varint(out, value as u64);
+}
+
+fn bytes_field(out: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    varint(out, field << 3 | 2);
+    varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn int_attribute(name: &str, value: i64) -> Vec<u8> {
+    let mut out = Vec::new();
+    bytes_field(&mut out, 1, name.as_bytes());
+    int_field(&mut out, 3, value);
+    int_field(&mut out, 20, ATTRIBUTE_INT as i64);
+    out
+}
+
+fn ints_attribute(name: &str, values: &[i64]) -> Vec<u8> {
+    let mut out = Vec::new();
+    bytes_field(&mut out, 1, name.as_bytes());
+    for &value in values {
+        int_field(&mut out, 8, value);
+    }
+    int_field(&mut out, 20, ATTRIBUTE_INTS as i64);
+    out
+}
+
+fn node(op_type: &str, inputs: &[&str], output: &str, attributes: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for input in inputs {
+        bytes_field(&mut out, 1, input.as_bytes());
+    }
+    bytes_field(&mut out, 2, output.as_bytes());
+    bytes_field(&mut out, 3, output.as_bytes());
+    bytes_field(&mut out, 4, op_type.as_bytes());
+    for attribute in attributes {
+        bytes_field(&mut out, 5, attribute);
+    }
+    out
+}
+
+fn tensor(name: &str, dims: &[i64], data_type: u64, raw_data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for &dim in dims {
+        int_field(&mut out, 1, dim);
+    }
+    int_field(&mut out, 2, data_type as i64);
+    bytes_field(&mut out, 8, name.as_bytes());
+    bytes_field(&mut out, 9, raw_data);
+    out
+}
+
+fn f32_tensor(name: &str, dims: &[i64], values: &[f32]) -> Vec<u8> {
+    let raw: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    tensor(name, dims, TENSOR_FLOAT, &raw)
+}
+
+fn i64_tensor(name: &str, values: &[i64]) -> Vec<u8> {
+    let raw: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    tensor(name, &[values.len() as i64], TENSOR_INT64, &raw)
+}
+
+/// a float tensor value info, `None` dims are symbolic
+fn value_info(name: &str, dims: Option<&[Option<i64>]>) -> Vec<u8> {
+    let mut tensor_type = Vec::new();
+    int_field(&mut tensor_type, 1, TENSOR_FLOAT as i64);
+    if let Some(dims) = dims {
+        let mut shape = Vec::new();
+        for dim in dims {
+            let mut dimension = Vec::new();
+            match dim {
+                Some(value) => int_field(&mut dimension, 1, *value),
+                None => bytes_field(&mut dimension, 2, b"width"),
+            }
+            bytes_field(&mut shape, 1, &dimension);
+        }
+        bytes_field(&mut tensor_type, 2, &shape);
+    }
+
+    let mut type_proto = Vec::new();
+    bytes_field(&mut type_proto, 1, &tensor_type);
+
+    let mut out = Vec::new();
+    bytes_field(&mut out, 1, name.as_bytes());
+    bytes_field(&mut out, 2, &type_proto);
+    out
+}
+
+/// `[vocab, 1, 32, STRIDE]` conv weights and the `[vocab]` bias
+fn weights() -> (Vec<f32>, Vec<f32>) {
+    let mut weight = Vec::with_capacity(VOCAB * HEIGHT * STRIDE as usize);
+    for label in 0..VOCAB {
+        for y in 0..HEIGHT {
+            let top = y < HEIGHT / 2;
+            let w = match label {
+                1 => if top { 1.0 } else { -1.0 },
+                2 => if top { -1.0 } else { 1.0 },
+                3 => 0.5,
+                _ => 0.0,
+            };
+            weight.extend(std::iter::repeat_n(w, STRIDE as usize));
+        }
+    }
+
+    // the blank wins over a few stray pixels, and over a single column of a full height glyph
+    let bias = vec![8.0, 0.0, 0.0, -10.0];
+    (weight, bias)
+}
+
+/// the model, its output is `[time, batch, vocab]` of logits, or probabilities if `softmax`
+/// the input is declared `[1, 1, 32, 384]`, or `[1, 1, 32, "width"]` if `dynamic_width`
+pub fn onnx(dynamic_width: bool, softmax: bool) -> Vec<u8> {
+    let (weight, bias) = weights();
+
+    let mut graph = Vec::new();
+    bytes_field(&mut graph, 1, &node("Conv", &["input", "weight", "bias"], "conv", &[
+        ints_attribute("kernel_shape", &[HEIGHT as i64, STRIDE as i64]),
+        ints_attribute("strides", &[HEIGHT as i64, STRIDE as i64]),
+    ]));
+    // [batch, vocab, 1, time] -> [batch, vocab, time]
+    bytes_field(&mut graph, 1, &node("Reshape", &["conv", "shape"], "reshaped", &[]));
+    let transposed = if softmax { "logits" } else { "output" };
+    bytes_field(&mut graph, 1, &node("Transpose", &["reshaped"], transposed, &[
+        ints_attribute("perm", &[2, 0, 1]),
+    ]));
+    if softmax {
+        bytes_field(&mut graph, 1, &node("Softmax", &["logits"], "output", &[int_attribute("axis", 2)]));
+    }
+    bytes_field(&mut graph, 2, b"test_model");
+    bytes_field(&mut graph, 5, &f32_tensor("weight", &[VOCAB as i64, 1, HEIGHT as i64, STRIDE as i64], &weight));
+    bytes_field(&mut graph, 5, &f32_tensor("bias", &[VOCAB as i64], &bias));
+    bytes_field(&mut graph, 5, &i64_tensor("shape", &[0, VOCAB as i64, -1]));
+    let width = if dynamic_width { None } else { Some(384) };
+    bytes_field(&mut graph, 11, &value_info("input", Some(&[Some(1), Some(1), Some(HEIGHT as i64), width])));
+    bytes_field(&mut graph, 12, &value_info("output", None));
+
+    let mut opset = Vec::new();
+    bytes_field(&mut opset, 1, b"");
+    int_field(&mut opset, 2, 13);
+
+    let mut model = Vec::new();
+    int_field(&mut model, 1, 7);
+    bytes_field(&mut model, 7, &graph);
+    bytes_field(&mut model, 8, &opset);
+    model
+}
+
+/// the fixed width test model with the `a`, `b`, `c` labels
+pub fn model() -> YasOCRModel {
+    YasOCRModel::new(&onnx(false, false), LABELS).unwrap()
+}
+
+/// white glyphs on black, 32 pixels high. `a` is the top half, `b` the bottom half, `c` the full height, a space is
+/// a blank glyph. glyphs are `GLYPH_WIDTH` wide with gaps as wide between them and around the text
+pub fn text_image(text: &str) -> RgbImage {
+    let count = text.chars().count() as u32;
+    let width = GLYPH_WIDTH * (2 * count + 1);
+    let mut image = RgbImage::new(width, HEIGHT as u32);
+
+    for (index, c) in text.chars().enumerate() {
+        let (top, bottom) = match c {
+            'a' => (0, HEIGHT as u32 / 2),
+            'b' => (HEIGHT as u32 / 2, HEIGHT as u32),
+            'c' => (0, HEIGHT as u32),
+            _ => continue,
+        };
+        let left = GLYPH_WIDTH * (2 * index as u32 + 1);
+        for x in left..left + GLYPH_WIDTH {
+            for y in top..bottom {
+                image.put_pixel(x, y, Rgb([255, 255, 255]));
+            }
+        }
+    }
+
+    image
+}
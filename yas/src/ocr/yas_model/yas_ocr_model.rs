@@ -1,184 +1,1299 @@
 use std::{cell::RefCell, time::Duration};
+use std::collections::HashMap;
 use std::time::SystemTime;
-use image::{EncodableLayout, GrayImage, ImageBuffer, Luma, RgbImage};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "tract_onnx")]
+use std::rc::Rc;
+use image::{EncodableLayout, GenericImageView, GrayImage, ImageBuffer, Luma, RgbImage};
 // use tract_onnx::prelude::*;
 use crate::ocr::traits::ImageToText;
 use super::preprocess;
-use anyhow::Result;
+use super::yas_ocr_model_builder::YasOCRModelBuilder;
+use super::preprocess::PreprocessOptions;
+use super::postprocess;
+use super::postprocess::{ConfidenceAgg, DecodeFlags, DecodeOptions, TextWithConfidence};
+use super::recording;
+use super::stats::OcrStats;
+use super::language_model::LanguageModel;
+use super::recording::{Diff, RecordedInference};
+use anyhow::{anyhow, Result};
+use log::warn;
 use crate::common::image_ext::*;
+use crate::common::cancel::CancellationToken;
+use ndarray::{Array2, Array4, ArrayView3, ArrayViewD, Axis, Ix3};
 #[cfg(feature = "tract_onnx")]
 use tract_onnx::prelude::*;
 
+#[cfg(feature = "ort")]
+pub type ModelType = ort::Session;
 #[cfg(feature = "tract_onnx")]
-type ModelType = RunnableModel<TypedFact, Box<dyn TypedOp>, Graph<TypedFact, Box<dyn TypedOp>>>;
+pub type ModelType = RunnableModel<TypedFact, Box<dyn TypedOp>, Graph<TypedFact, Box<dyn TypedOp>>>;
 
-pub struct YasOCRModel {
+/// the tag of the statistics of untagged inferences
+pub const DEFAULT_STATS_TAG: &str = "default";
+
+/// the properties `save_optimized` stores the input width in
+#[cfg(feature = "tract_onnx")]
+const INPUT_WIDTH_PROPERTY: &str = "yas_input_width";
+#[cfg(feature = "tract_onnx")]
+const DYNAMIC_WIDTH_PROPERTY: &str = "yas_dynamic_width";
+
+thread_local! {
+    /// the input tensor of the last run on this thread, reused while the input shape stays the same, see `run_input`
     #[cfg(feature = "ort")]
-    model: ort::Session,
+    static INPUT_SCRATCH: RefCell<Array4<f32>> = RefCell::new(Array4::zeros((0, 0, 0, 0)));
+    /// shared with the backend during a run, and owned by the scratch alone again afterwards
+    #[cfg(feature = "tract_onnx")]
+    static INPUT_SCRATCH: RefCell<Option<Rc<Tensor>>> = const { RefCell::new(None) };
+}
+
+/// results of a batch which may be cancelled
+#[derive(Clone, Debug)]
+pub struct ScanOutcome {
+    /// results of the images processed before cancellation, in input order
+    pub results: Vec<String>,
+    pub cancelled: bool,
+}
+
+/// what a batch does when recognizing one of its images fails
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// return the error, discarding the whole batch
+    #[default]
+    FailFast,
+    /// record an empty string for the failed image, and continue with the rest
+    SkipToEmpty,
+}
+
+/// where the model runs, see `YasOCRModelBuilder::backend`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OcrBackend {
+    #[default]
+    Cpu,
+    /// requires the `cuda` feature
+    Cuda,
+    /// requires the `directml` feature, windows only
+    DirectML,
+}
+
+pub struct YasOCRModel {
+    model: Arc<ModelType>,
+    /// the decluttered graph before the final optimization, kept for serialization
+    /// not available for models built from a precompiled model
     #[cfg(feature = "tract_onnx")]
-    model: ModelType,
+    typed_model: Option<TypedModel>,
     index_to_word: Vec<String>,
+    /// width of the model input, the height is always 32
+    /// for models with a dynamic width, this is the widest input
+    input_width: u32,
+    /// whether the onnx graph declares a dynamic width, then every preprocessed image keeps its native width
+    dynamic_width: bool,
+    /// pixels of the input are clamped into `[lo, hi]`, NaN becomes `lo`
+    input_clamp: Option<(f32, f32)>,
+    decode_options: DecodeOptions,
+    preprocess_options: PreprocessOptions,
 
-    inference_time: RefCell<Duration>,   // in seconds
-    invoke_count: RefCell<usize>,
+    // the statistics are locked rather than in cells, so that threads can share a model
+    stats: Mutex<OcrStats>,
+    last_inference_time: Mutex<Option<Duration>>,
+    /// inference statistics by the tag of `inference_string_tagged`, untagged inferences go to `DEFAULT_STATS_TAG`
+    stats_by_tag: Mutex<HashMap<String, OcrStats>>,
+    /// label index -> how many timesteps it's the argmax of, only tracked if enabled
+    emitted_label_histogram: Option<Mutex<HashMap<usize, usize>>>,
+    /// inputs and outputs of `inference_string`, only recorded if enabled
+    recording: Option<Mutex<Vec<RecordedInference>>>,
+    /// the same model with a wider input, to retry text which may be truncated
+    wide_model: Option<Box<YasOCRModel>>,
+}
+
+/// parse the index to word json, which maps label index to the word
+pub fn parse_index_to_word(content: &str) -> Result<Vec<String>> {
+    let json = serde_json::from_str::<serde_json::Value>(content)?;
+
+    let mut index_to_word = json
+        .as_object()
+        .ok_or_else(|| anyhow!("index to word should be a json object"))?
+        .iter()
+        .map(|(k, v)| -> Result<(usize, String)> {
+            let word = v.as_str().ok_or_else(|| anyhow!("word of index {} is not a string", k))?;
+            Ok((k.parse::<usize>()?, word.to_string()))
+        })
+        .collect::<Result<Vec<(usize, String)>>>()?;
+
+    index_to_word.sort_by_key(|(k, _)| *k);
+
+    Ok(index_to_word.into_iter().map(|(_, v)| v).collect())
 }
 
 impl YasOCRModel {
     pub fn get_average_inference_time(&self) -> Option<Duration> {
-        let count = *self.invoke_count.borrow();
-        let total_time = *self.inference_time.borrow();
-        
-        if count == 0 {
-            None
-        } else {
-            Some(total_time.div_f64(count as f64))
+        self.stats().average_inference_time()
+    }
+
+    /// a snapshot of all the statistics, which can be summed with those of other models
+    pub fn stats(&self) -> OcrStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// inference statistics by tag, only the inference count and time are tracked per tag
+    pub fn get_stats_by_tag(&self) -> HashMap<String, OcrStats> {
+        self.stats_by_tag.lock().unwrap().clone()
+    }
+
+    /// time of the most recent inference, `None` before any inference
+    pub fn get_last_inference_time(&self) -> Option<Duration> {
+        *self.last_inference_time.lock().unwrap()
+    }
+
+    /// average time of preprocessing, images passed in as already preprocessed are not counted
+    pub fn get_average_preprocess_time(&self) -> Option<Duration> {
+        self.stats().average_preprocess_time()
+    }
+
+    /// average confidence of all inferences which computed confidence
+    pub fn get_average_confidence(&self) -> Option<f64> {
+        self.stats().average_confidence()
+    }
+
+    /// how many images were skipped without inference because they have only one color
+    pub fn get_mono_skip_count(&self) -> usize {
+        self.stats.lock().unwrap().mono_skip_count
+    }
+
+    /// all labels of the model, indexed by label index
+    pub fn index_to_word(&self) -> &[String] {
+        &self.index_to_word
+    }
+
+    /// number of labels of the model
+    pub fn vocab_size(&self) -> usize {
+        self.index_to_word.len()
+    }
+
+    /// index of the ctc blank label "-", if the model has one
+    pub fn blank_index(&self) -> Option<usize> {
+        self.index_to_word.iter().position(|word| word == "-")
+    }
+
+    /// track how many timesteps each label is emitted at (blanks included), across all inferences
+    /// useful to audit vocabulary coverage. this is off by default, so the hot path isn't slowed
+    pub fn with_emitted_label_histogram(mut self) -> Self {
+        self.emitted_label_histogram = Some(Mutex::new(HashMap::new()));
+        self
+    }
+
+    /// record every preprocessed input of `inference_string` together with its result, so they can be saved with
+    /// `record_to` and replayed later with `verify_against`, e.g. to check a new tract version
+    pub fn with_recording(mut self) -> Self {
+        self.recording = Some(Mutex::new(Vec::new()));
+        self
+    }
+
+    /// save the recorded inferences, errors if recording is not enabled with `with_recording`
+    pub fn record_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        match &self.recording {
+            Some(recording) => recording::write_recording(path, &recording.lock().unwrap()),
+            None => Err(anyhow!("recording is not enabled")),
+        }
+    }
+
+    /// re-run the inputs saved by `record_to`, and report those recognized differently from the recording
+    pub fn verify_against<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Diff>> {
+        let mut diffs = Vec::new();
+        let mut actual = String::new();
+        for (index, entry) in recording::read_recording(path)?.into_iter().enumerate() {
+            let img = entry.to_image()?;
+            self.inference_string_into(&img, &mut actual)?;
+            if actual != entry.text {
+                diffs.push(Diff {
+                    index,
+                    expected: entry.text,
+                    actual: actual.clone(),
+                });
+            }
+        }
+
+        Ok(diffs)
+    }
+
+    /// label index -> emitted count, empty if tracking is not enabled with `with_emitted_label_histogram`
+    pub fn emitted_label_histogram(&self) -> HashMap<usize, usize> {
+        match &self.emitted_label_histogram {
+            Some(histogram) => histogram.lock().unwrap().clone(),
+            None => HashMap::new(),
         }
     }
 
+    pub fn with_decode_options(mut self, options: DecodeOptions) -> Self {
+        self.wide_model = self.wide_model.map(|model| Box::new(model.with_decode_options(options.clone())));
+        self.decode_options = options;
+        self
+    }
+
+    pub fn with_preprocess_options(mut self, options: PreprocessOptions) -> Self {
+        self.wide_model = self.wide_model.map(|model| Box::new(model.with_preprocess_options(options.clone())));
+        self.preprocess_options = PreprocessOptions {
+            // decided by the model, the input of a fixed width model cannot be narrower
+            native_width: self.dynamic_width,
+            ..options
+        };
+        self
+    }
+
     pub fn new(model: &[u8], content: &str) -> Result<YasOCRModel> {
+        YasOCRModelBuilder::new(model, content).build()
+    }
+
+    /// build a model whose input is `width * 32` instead of the default 384 * 32
+    /// for tract, the width is baked into the graph, which keeps it fully static
+    pub fn new_with_width(model: &[u8], content: &str, width: u32) -> Result<YasOCRModel> {
+        YasOCRModelBuilder::new(model, content).width(width).build()
+    }
+
+    pub(crate) fn from_builder(builder: &YasOCRModelBuilder) -> Result<YasOCRModel> {
+        let index_to_word = parse_index_to_word(builder.index_to_word)?;
+        if let Some((lo, hi)) = builder.input_clamp {
+            // `f32::clamp` panics otherwise
+            if lo.is_nan() || hi.is_nan() || lo > hi {
+                return Err(anyhow!("invalid input clamp [{}, {}]", lo, hi));
+            }
+        }
+
+        #[cfg(feature = "ort")]
+        let mut result = {
+            let model = Self::ort_session(builder.model, builder.deterministic, builder.backend)?;
+            let dynamic_width = Self::has_dynamic_width(&model);
+            let mut result = Self::from_parts(Arc::new(model), index_to_word);
+            result.dynamic_width = dynamic_width;
+            result
+        };
+
+        #[cfg(feature = "tract_onnx")]
+        let mut result = {
+            if builder.backend != OcrBackend::Cpu {
+                return Err(anyhow!("tract only runs on the cpu, the {:?} backend requires the ort feature", builder.backend));
+            }
+            let model = Self::read_onnx(builder.model)?;
+            let dynamic_width = Self::has_dynamic_width(&model);
+            let typed_model = Self::type_model(model, if dynamic_width { None } else { Some(builder.width) })?;
+            let mut result = Self::from_typed_model(typed_model, index_to_word)?;
+            result.dynamic_width = dynamic_width;
+            result
+        };
+
+        // legacy models declare a fixed width, which the builder may override
+        result.input_width = if result.dynamic_width { preprocess::MAX_NATIVE_WIDTH } else { builder.width };
+        result.preprocess_options.native_width = result.dynamic_width;
+        result.input_clamp = builder.input_clamp;
+
+        // nothing is cut at a fixed width to retry
+        if let (false, Some(retry_width)) = (result.dynamic_width, builder.retry_width) {
+            if retry_width <= builder.width {
+                return Err(anyhow!("the retry width {} is not wider than the width {}", retry_width, builder.width));
+            }
+
+            let wide_builder = YasOCRModelBuilder {
+                width: retry_width,
+                retry_width: None,
+                ..*builder
+            };
+            result.wide_model = Some(Box::new(wide_builder.build()?));
+        }
+
+        Ok(result)
+    }
+
+    /// compile a model once, so that many `YasOCRModel`s (e.g. with different labels or options) can share it
+    /// use `new_from_precompiled` to build models from the returned handle
+    pub fn precompile(model: &[u8]) -> Result<Arc<ModelType>> {
         #[cfg(feature = "ort")]
-        let model = ort::Session::builder()?
+        {
+            Ok(Arc::new(Self::ort_session(model, false, OcrBackend::Cpu)?))
+        }
+
+        #[cfg(feature = "tract_onnx")]
+        {
+            let model = Self::read_onnx(model)?;
+            let width = if Self::has_dynamic_width(&model) { None } else { Some(preprocess::DEFAULT_INPUT_WIDTH) };
+            let model = Self::type_model(model, width)?
+                .into_optimized()?
+                .into_runnable()?;
+            Ok(Arc::new(model))
+        }
+    }
+
+    /// the input width and whether it's dynamic are read from the compiled model
+    pub fn new_from_precompiled(model: Arc<ModelType>, content: &str) -> Result<YasOCRModel> {
+        let index_to_word = parse_index_to_word(content)?;
+        let (input_width, dynamic_width) = Self::compiled_input_width(&model);
+
+        let mut result = Self::from_parts(model, index_to_word);
+        result.input_width = input_width;
+        result.dynamic_width = dynamic_width;
+        result.preprocess_options.native_width = dynamic_width;
+        Ok(result)
+    }
+
+    /// `(input width, dynamic)` of a compiled model, the widest input is `preprocess::MAX_NATIVE_WIDTH` for a dynamic
+    /// width, and models which declare no width take the default width
+    #[cfg(feature = "ort")]
+    fn compiled_input_width(session: &ort::Session) -> (u32, bool) {
+        match session.inputs.first().map(|input| &input.input_type) {
+            Some(ort::ValueType::Tensor { dimensions, .. }) => match dimensions.get(3) {
+                Some(&width) if width > 0 => (width as u32, false),
+                Some(_) => (preprocess::MAX_NATIVE_WIDTH, true),
+                None => (preprocess::DEFAULT_INPUT_WIDTH, false),
+            },
+            _ => (preprocess::DEFAULT_INPUT_WIDTH, false),
+        }
+    }
+
+    #[cfg(feature = "tract_onnx")]
+    fn compiled_input_width(model: &ModelType) -> (u32, bool) {
+        let width = model.model().input_fact(0).ok().and_then(|fact| fact.shape.get(3).cloned());
+        match width.map(|width| width.to_i64()) {
+            Some(Ok(width)) => (width as u32, false),
+            Some(Err(_)) => (preprocess::MAX_NATIVE_WIDTH, true),
+            None => (preprocess::DEFAULT_INPUT_WIDTH, false),
+        }
+    }
+
+    #[cfg(feature = "ort")]
+    fn ort_session(model: &[u8], deterministic: bool, backend: OcrBackend) -> Result<ort::Session> {
+        let threads = if deterministic { 1 } else { 4 };
+        // if the provider is unavailable (e.g. no gpu), onnxruntime warns and falls back to the cpu
+        let providers = match backend {
+            OcrBackend::Cpu => vec![],
+            OcrBackend::Cuda => vec![ort::CUDAExecutionProvider::default().build()],
+            OcrBackend::DirectML => vec![ort::DirectMLExecutionProvider::default().build()],
+        };
+        let session = ort::Session::builder()?
+            .with_execution_providers(providers)?
             .with_optimization_level(ort::GraphOptimizationLevel::Level3)?
-            .with_intra_threads(4)?
+            .with_intra_threads(threads)?
             .commit_from_memory(model)?;
-        #[cfg(feature = "tract_onnx")]
-        let model = tract_onnx::onnx()
-            .model_for_read(&mut model.as_bytes())?
-            .with_input_fact(0, f32::fact([1, 1, 32, 384]).into())?
-            .into_optimized()?
-            .into_runnable()?;
 
-        let json = serde_json::from_str::<serde_json::Value>(content)?;
+        Ok(session)
+    }
+
+    /// whether the width of the input declared by the onnx graph is dynamic (e.g. `[1, 1, 32, "width"]`)
+    /// graphs which declare no input shape are taken as fixed width
+    #[cfg(feature = "ort")]
+    fn has_dynamic_width(session: &ort::Session) -> bool {
+        match session.inputs.first().map(|input| &input.input_type) {
+            Some(ort::ValueType::Tensor { dimensions, .. }) => dimensions.get(3).is_some_and(|&d| d < 0),
+            _ => false,
+        }
+    }
+
+    #[cfg(feature = "tract_onnx")]
+    fn has_dynamic_width(model: &InferenceModel) -> bool {
+        use tract_onnx::tract_hir::infer::GenericFactoid;
+
+        let Ok(fact) = model.input_fact(0) else {
+            return false;
+        };
+        match fact.shape.dims().nth(3) {
+            Some(GenericFactoid::Only(dim)) => dim.to_i64().is_err(),
+            _ => false,
+        }
+    }
+
+    #[cfg(feature = "tract_onnx")]
+    fn read_onnx(model: &[u8]) -> Result<InferenceModel> {
+        tract_onnx::onnx().model_for_read(&mut model.as_bytes())
+    }
+
+    /// fix the input to `[1, 1, 32, width]`, or `[1, 1, 32, W]` with a symbolic `W` for a dynamic width
+    #[cfg(feature = "tract_onnx")]
+    fn type_model(model: InferenceModel, width: Option<u32>) -> Result<TypedModel> {
+        let width = match width {
+            Some(width) => (width as usize).to_dim(),
+            None => model.symbol_table.sym("W").to_dim(),
+        };
+        let shape = [1.to_dim(), 1.to_dim(), (preprocess::INPUT_HEIGHT as usize).to_dim(), width];
+
+        let typed_model = model
+            .with_input_fact(0, f32::fact(shape).into())?
+            .into_typed()?
+            .into_decluttered()?;
+
+        Ok(typed_model)
+    }
+
+    pub fn new_from_file<P1, P2>(onnx_file: P1, index_to_word_file: P2) -> Result<YasOCRModel> where P1: AsRef<Path>, P2: AsRef<Path> {
+        let model = std::fs::read(onnx_file)?;
+        let content = std::fs::read_to_string(index_to_word_file)?;
+
+        Self::new(&model, &content)
+    }
+
+    /// load a model and its index to word json at runtime instead of embedding them with `yas_ocr_model!`, e.g. to try a
+    /// newly trained model without rebuilding
+    /// the model is validated by a blank inference, which fails if it doesn't take a `[1, 1, 32, width]` input, or
+    /// outputs a different number of labels than the index to word has
+    pub fn from_paths(model: &Path, index_to_word: &Path) -> Result<YasOCRModel> {
+        let model_bytes = std::fs::read(model)
+            .map_err(|e| anyhow!("cannot read the model {}: {}", model.display(), e))?;
+        let content = std::fs::read_to_string(index_to_word)
+            .map_err(|e| anyhow!("cannot read the index to word {}: {}", index_to_word.display(), e))?;
+
+        let result = Self::new(&model_bytes, &content)?;
+        result.validate()
+            .map_err(|e| anyhow!("invalid model {}: {}", model.display(), e))?;
+
+        Ok(result)
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.run_input(self.input_shape(), |pixels| pixels.fill(0.0))?;
+        Ok(())
+    }
+
+    /// load a model previously written by `save_optimized`, with the input width it was saved with
+    #[cfg(feature = "tract_onnx")]
+    pub fn new_from_optimized<P: AsRef<Path>>(path: P, content: &str) -> Result<YasOCRModel> {
+        let index_to_word = parse_index_to_word(content)?;
+        let typed_model = tract_nnef::nnef()
+            .with_tract_core()
+            .with_onnx()
+            .model_for_path(path)?;
+        let saved_width = Self::saved_input_width(&typed_model)?;
+
+        let mut result = Self::from_typed_model(typed_model, index_to_word)?;
+        // archives written before the width was saved only have the graph to go by
+        let (input_width, dynamic_width) = saved_width.unwrap_or_else(|| Self::compiled_input_width(&result.model));
+        result.input_width = input_width;
+        result.dynamic_width = dynamic_width;
+        result.preprocess_options.native_width = dynamic_width;
+        Ok(result)
+    }
+
+    /// `(input width, dynamic)` stored in the properties of an archive by `save_optimized`
+    #[cfg(feature = "tract_onnx")]
+    fn saved_input_width(typed_model: &TypedModel) -> Result<Option<(u32, bool)>> {
+        let properties = &typed_model.properties;
+        match (properties.get(INPUT_WIDTH_PROPERTY), properties.get(DYNAMIC_WIDTH_PROPERTY)) {
+            (Some(width), Some(dynamic)) => {
+                let (width, dynamic) = (width.to_scalar::<String>()?, dynamic.to_scalar::<String>()?);
+                let width = width.parse::<u32>().ok().filter(|&width| width > 0)
+                    .ok_or_else(|| anyhow!("invalid input width {} in the optimized model", width))?;
+                let dynamic = dynamic.parse::<bool>()
+                    .map_err(|_| anyhow!("invalid dynamic width {} in the optimized model", dynamic))?;
+                Ok(Some((width, dynamic)))
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// write the loaded graph as an NNEF tar archive, which can be loaded by `new_from_optimized`
+    /// this skips onnx parsing, type analysis and decluttering on startup
+    /// tract cannot serialize the ops produced by the final optimization pass, so the decluttered graph is written, and the
+    /// (comparatively cheap) optimization runs again when loaded
+    #[cfg(feature = "tract_onnx")]
+    pub fn save_optimized(&self, path: impl AsRef<Path>) -> Result<()> {
+        let typed_model = self.typed_model
+            .as_ref()
+            .ok_or_else(|| anyhow!("the graph is not kept for models built from a precompiled model"))?;
+
+        // the graph of a fixed width model has its width baked in, but a dynamic width model reads up to `input_width`
+        // nnef can only write string and float properties without a graph to compute them in
+        let mut typed_model = typed_model.clone();
+        typed_model.properties.insert(INPUT_WIDTH_PROPERTY.to_string(), rctensor0(self.input_width.to_string()));
+        typed_model.properties.insert(DYNAMIC_WIDTH_PROPERTY.to_string(), rctensor0(self.dynamic_width.to_string()));
+
+        let file = std::fs::File::create(path)?;
+        tract_nnef::nnef()
+            .with_tract_core()
+            .with_onnx()
+            .write_to_tar(&typed_model, file)?;
 
-        let mut index_to_word = json
-            .as_object()
-            .unwrap()
-            .iter()
-            .map(|(k, v)| (k.parse::<usize>().unwrap(), v.as_str().unwrap().to_string()))
-            .collect::<Vec<(usize, String)>>();
+        Ok(())
+    }
 
-        index_to_word.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+    #[cfg(feature = "tract_onnx")]
+    fn from_typed_model(typed_model: TypedModel, index_to_word: Vec<String>) -> Result<YasOCRModel> {
+        let model = typed_model.clone()
+            .into_optimized()?
+            .into_runnable()?;
 
-        let index_to_word = index_to_word.into_iter().map(|(_, v)| v).collect();
+        let mut result = Self::from_parts(Arc::new(model), index_to_word);
+        result.typed_model = Some(typed_model);
+        Ok(result)
+    }
 
-        Ok(YasOCRModel {
+    fn from_parts(model: Arc<ModelType>, index_to_word: Vec<String>) -> YasOCRModel {
+        YasOCRModel {
             model,
+            #[cfg(feature = "tract_onnx")]
+            typed_model: None,
             index_to_word,
-            inference_time: RefCell::new(Duration::new(0, 0)),
-            invoke_count: RefCell::new(0),
-        })
+            input_width: preprocess::DEFAULT_INPUT_WIDTH,
+            dynamic_width: false,
+            input_clamp: None,
+            decode_options: DecodeOptions::default(),
+            preprocess_options: PreprocessOptions::default(),
+            stats: Mutex::new(OcrStats::default()),
+            last_inference_time: Mutex::new(None),
+            stats_by_tag: Mutex::new(HashMap::new()),
+            emitted_label_histogram: None,
+            recording: None,
+            wide_model: None,
+        }
     }
 
-    pub fn inference_string(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<String> {
-        let now = SystemTime::now();
+    fn inc_statistics(&self, time: Duration) {
+        self.inc_statistics_tagged(time, DEFAULT_STATS_TAG);
+    }
 
-        #[cfg(feature = "ort")]
-        let tensor = ndarray::Array4::from_shape_fn((1, 1, 32, 384), |(_, _, y, x)| {
-            img.get_pixel(x as u32, y as u32)[0]
-        });
-        #[cfg(feature = "tract_onnx")]
-        let tensor: Tensor =
-            tract_ndarray::Array4::from_shape_fn((1, 1, 32, 384), |(_, _, y, x)| {
-                img.get_pixel(x as u32, y as u32)[0]
-            }).into();
+    fn inc_statistics_tagged(&self, time: Duration, tag: &str) {
+        {
+            let mut stats = self.stats.lock().unwrap();
+            stats.invoke_count += 1;
+            stats.inference_time += time;
+        }
+        *self.last_inference_time.lock().unwrap() = Some(time);
 
-        #[cfg(feature = "ort")]
-        let result = self.model.run(ort::inputs![tensor]?)?;
-        #[cfg(feature = "tract_onnx")]
-        let result = self.model.run(tvec!(tensor.into()))?;
+        let mut stats_by_tag = self.stats_by_tag.lock().unwrap();
+        if !stats_by_tag.contains_key(tag) {
+            stats_by_tag.insert(tag.to_string(), OcrStats::default());
+        }
+        let stats = stats_by_tag.get_mut(tag).unwrap();
+        stats.invoke_count += 1;
+        stats.inference_time += time;
+    }
 
-        #[cfg(feature = "ort")]
-        let arr = result[0].try_extract_tensor()?;
-        #[cfg(feature = "tract_onnx")]
-        let arr = result[0].to_array_view::<f32>()?;
+    fn accepts_width(&self, width: u32) -> bool {
+        if self.dynamic_width {
+            width > 0 && width <= self.input_width
+        } else {
+            width == self.input_width
+        }
+    }
 
-        let shape = arr.shape();
+    fn check_input_size(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<()> {
+        if !self.accepts_width(img.width()) || img.height() != preprocess::INPUT_HEIGHT {
+            return Err(anyhow!(
+                "expect a preprocessed image of size {}x{}, got {}x{}",
+                self.input_width, preprocess::INPUT_HEIGHT, img.width(), img.height()
+            ));
+        }
+        Ok(())
+    }
 
-        let mut ans = String::new();
-        let mut last_word = String::new();
-        for i in 0..shape[0] {
-            let mut max_index = 0;
-            let mut max_value = -1.0_f32;
-            for j in 0..self.index_to_word.len() {
-                let value = arr[[i, 0, j]];
-                if value > max_value {
-                    max_value = value;
-                    max_index = j;
+    fn clamp_input(&self, pixels: &mut [f32]) {
+        if let Some((lo, hi)) = self.input_clamp {
+            for p in pixels.iter_mut() {
+                *p = if p.is_nan() { lo } else { p.clamp(lo, hi) };
+            }
+        }
+    }
+
+    /// run the model on a preprocessed image
+    /// returns the softmaxed output, of shape `[time, vocab]`
+    fn run_model(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<Array2<f32>> {
+        self.check_input_size(img)?;
+
+        let shape = (1, 1, preprocess::INPUT_HEIGHT as usize, img.width() as usize);
+        // the pixels are row major, which is exactly the [1, 1, y, x] layout
+        let mut probs = self.run_input(shape, |pixels| pixels.copy_from_slice(img.as_raw()))?;
+        probs.pop().ok_or_else(|| anyhow!("the model outputs an empty batch"))
+    }
+
+    /// run the model on preprocessed images in one invocation, with the batch as the first axis of the input
+    /// returns the softmaxed outputs in input order
+    /// tract models are typed with a batch of 1, and so are some onnx exports, then the images are run one by one
+    fn run_model_batch(&self, imgs: &[ImageBuffer<Luma<f32>, Vec<f32>>]) -> Result<Vec<Array2<f32>>> {
+        for img in imgs.iter() {
+            self.check_input_size(img)?;
+        }
+
+        // images of a dynamic width model are batched only if they're equally wide
+        #[cfg(feature = "ort")]
+        if imgs.len() > 1 && imgs.iter().all(|img| img.width() == imgs[0].width()) {
+            let shape = (imgs.len(), 1, preprocess::INPUT_HEIGHT as usize, imgs[0].width() as usize);
+            let fill = |pixels: &mut [f32]| {
+                for (item, img) in pixels.chunks_exact_mut(shape.2 * shape.3).zip(imgs.iter()) {
+                    item.copy_from_slice(img.as_raw());
                 }
+            };
+
+            match self.run_input(shape, fill) {
+                Ok(probs) => return Ok(probs),
+                Err(e) => warn!("批量推理失败，逐张推理：{}", e),
             }
-            let word = &self.index_to_word[max_index];
-            if *word != last_word && word != "-" {
-                ans = ans + word;
+        }
+
+        imgs.iter().map(|img| self.run_model(img)).collect()
+    }
+
+    /// `[1, 1, 32, width]`, the widest input for a dynamic width
+    fn input_shape(&self) -> (usize, usize, usize, usize) {
+        (1, 1, preprocess::INPUT_HEIGHT as usize, self.input_width as usize)
+    }
+
+    /// run the model on an input of `[batch, 1, 32, width]`, whose row major pixels are written by `fill` and then
+    /// clamped. the input lives in a per thread scratch, which is reused while the shape stays the same, so threads
+    /// sharing a model neither allocate an input per call nor contend on one
+    fn run_input<F: FnOnce(&mut [f32])>(&self, shape: (usize, usize, usize, usize), fill: F) -> Result<Vec<Array2<f32>>> {
+        INPUT_SCRATCH.with(|scratch| self.run_scratch(&mut scratch.borrow_mut(), shape, fill))
+    }
+
+    #[cfg(feature = "ort")]
+    fn run_scratch<F: FnOnce(&mut [f32])>(&self, scratch: &mut Array4<f32>, shape: (usize, usize, usize, usize), fill: F) -> Result<Vec<Array2<f32>>> {
+        if scratch.dim() != shape {
+            *scratch = Array4::zeros(shape);
+        }
+        let pixels = scratch.as_slice_mut().unwrap();
+        fill(pixels);
+        self.clamp_input(pixels);
+
+        let result = self.model.run(ort::inputs![scratch.view()]?)?;
+        self.output_to_probs(result[0].try_extract_tensor::<f32>()?, shape.0)
+    }
+
+    #[cfg(feature = "tract_onnx")]
+    fn run_scratch<F: FnOnce(&mut [f32])>(&self, scratch: &mut Option<Rc<Tensor>>, shape: (usize, usize, usize, usize), fill: F) -> Result<Vec<Array2<f32>>> {
+        let shape = [shape.0, shape.1, shape.2, shape.3];
+        // a new tensor if the shape changed, or if the last run still holds on to it
+        let reusable = scratch.as_mut().and_then(Rc::get_mut).is_some_and(|tensor| tensor.shape() == shape);
+        if !reusable {
+            *scratch = Some(Rc::new(Tensor::zero::<f32>(&shape)?));
+        }
+        let tensor = scratch.as_mut().unwrap();
+        let pixels = Rc::get_mut(tensor).unwrap().as_slice_mut::<f32>()?;
+        fill(pixels);
+        self.clamp_input(pixels);
+
+        let result = self.model.run(tvec!(TValue::Var(Rc::clone(tensor))))?;
+        self.output_to_probs(result[0].to_array_view::<f32>()?, shape[0])
+    }
+
+    /// one softmaxed `[time, vocab]` output per item of the `[time, batch, vocab]` output of the model
+    /// outputs which are already probabilities are kept as is
+    fn output_to_probs(&self, arr: ArrayViewD<f32>, batch: usize) -> Result<Vec<Array2<f32>>> {
+        let arr = arr.into_dimensionality::<Ix3>()?;
+        // otherwise the argmax silently ignores the labels beyond index_to_word
+        if arr.shape()[2] != self.index_to_word.len() {
+            return Err(anyhow!(
+                "the model outputs {} labels, but index to word has {}",
+                arr.shape()[2],
+                self.index_to_word.len()
+            ));
+        }
+        if arr.shape()[1] != batch {
+            return Err(anyhow!("the model outputs a batch of {}, expect {}", arr.shape()[1], batch));
+        }
+
+        let mut ans = Vec::with_capacity(arr.shape()[1]);
+        for item in arr.axis_iter(Axis(1)) {
+            let mut probs = item.to_owned();
+            // some exports already end with a softmax
+            if !postprocess::is_normalized(&probs) {
+                postprocess::softmax_rows(&mut probs);
             }
 
-            last_word.clone_from(word);
+            if let Some(histogram) = &self.emitted_label_histogram {
+                let mut histogram = histogram.lock().unwrap();
+                for index in postprocess::argmax_rows(&probs) {
+                    *histogram.entry(index).or_insert(0) += 1;
+                }
+            }
+            ans.push(probs);
         }
 
-        let time = now.elapsed()?;
-        
-        *self.invoke_count.borrow_mut() += 1;
-        *self.inference_time.borrow_mut() += time;
+        Ok(ans)
+    }
 
+    pub fn inference_string(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<String> {
+        let mut ans = String::new();
+        self.inference_string_into(img, &mut ans)?;
         Ok(ans)
     }
-}
 
-impl ImageToText<RgbImage> for YasOCRModel {
-    fn image_to_text(&self, image: &RgbImage, is_preprocessed: bool) -> Result<String> {
-        assert!(!is_preprocessed);
+    /// same as `inference_string`, but clears and writes into `out`, reusing its allocation
+    pub fn inference_string_into(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>, out: &mut String) -> Result<()> {
+        self.inference_string_checked_into(img, out)?;
+        Ok(())
+    }
+
+    /// same as `inference_string_into`, returns whether the last timestep emits a word, i.e. the text may be truncated
+    fn inference_string_checked_into(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>, out: &mut String) -> Result<bool> {
+        self.inference_string_tagged_into(img, out, DEFAULT_STATS_TAG)
+    }
 
-        let gray_image_float = preprocess::to_gray(image);
-        let (result, non_mono) = preprocess::pre_process(gray_image_float);
+    /// same as `inference_string_checked_into`, counted in the statistics of `tag`
+    fn inference_string_tagged_into(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>, out: &mut String, tag: &str) -> Result<bool> {
+        let now = SystemTime::now();
 
-        if !non_mono {
-            return Ok(String::new());
+        let truncated = self.inference_string_untracked_into(img, out)?;
+
+        self.inc_statistics_tagged(now.elapsed()?, tag);
+        if let Some(recording) = &self.recording {
+            recording.lock().unwrap().push(RecordedInference::new(img, out));
         }
 
-        let string_result = self.inference_string(&result)?;
+        Ok(truncated)
+    }
+
+    /// recognize a preprocessed image, and if the text may be truncated at the right edge, recognize it again with
+    /// the wider model (see `YasOCRModelBuilder::retry_width`) by `retry`, the longer result is returned
+    fn inference_string_or_wider<F>(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>, retry: F) -> Result<String>
+    where F: FnOnce(&YasOCRModel) -> Result<String> {
+        let mut ans = String::new();
+        let truncated = self.inference_string_checked_into(img, &mut ans)?;
+
+        if let (true, Some(wide_model)) = (truncated, &self.wide_model) {
+            let wider = retry(wide_model)?;
+            if wider.chars().count() > ans.chars().count() {
+                return Ok(wider);
+            }
+        }
 
-        Ok(string_result)
+        Ok(ans)
     }
 
-    fn get_average_inference_time(&self) -> Option<Duration> {
-        self.get_average_inference_time()
+    /// recognize preprocessed images into `out`, one entry per image
+    /// the strings already in `out` are reused, so a scan loop can keep one buffer without allocating per page
+    pub fn inference_string_batch_into(&self, imgs: &[ImageBuffer<Luma<f32>, Vec<f32>>], out: &mut Vec<String>) -> Result<()> {
+        out.truncate(imgs.len());
+        out.resize_with(imgs.len(), String::new);
+
+        for (img, text) in imgs.iter().zip(out.iter_mut()) {
+            self.inference_string_into(img, text)?;
+        }
+
+        Ok(())
     }
-}
 
-impl ImageToText<ImageBuffer<Luma<f32>, Vec<f32>>> for YasOCRModel {
-    fn image_to_text(&self, image: &ImageBuffer<Luma<f32>, Vec<f32>>, is_preprocessed: bool) -> Result<String> {
-        if is_preprocessed {
-            let string_result = self.inference_string(image)?;
-            Ok(string_result)
-        } else {
-            let im = image.clone();
-            let (preprocess_result, non_mono) = preprocess::pre_process(im);
+    /// recognize preprocessed images (e.g. all the crops of an artifact panel) with as few model invocations as the
+    /// backend allows, see `run_model_batch`
+    /// every item is counted in the statistics as one inference, taking an equal share of the batch time
+    pub fn inference_strings_batch(&self, imgs: &[ImageBuffer<Luma<f32>, Vec<f32>>]) -> Result<Vec<String>> {
+        if imgs.is_empty() {
+            return Ok(Vec::new());
+        }
 
-            if !non_mono {
-                return Ok(String::new());
-            }
+        let now = SystemTime::now();
 
-            let string_result = self.inference_string(&preprocess_result)?;
-            Ok(string_result)
+        let probs = self.run_model_batch(imgs)?;
+        let ans: Vec<String> = probs.iter()
+            .map(|p| postprocess::greedy_decode(p, &self.index_to_word, &self.decode_options))
+            .collect();
+
+        let per_item = now.elapsed()?.div_f64(imgs.len() as f64);
+        for (img, text) in imgs.iter().zip(ans.iter()) {
+            self.inc_statistics(per_item);
+            if let Some(recording) = &self.recording {
+                recording.lock().unwrap().push(RecordedInference::new(img, text));
+            }
         }
+
+        Ok(ans)
     }
 
-    fn get_average_inference_time(&self) -> Option<Duration> {
-        self.get_average_inference_time()
+    /// same as `inference_string`, and the inference is also counted in the statistics of `tag`
+    /// e.g. to tell the latency of name crops from value crops, see `get_stats_by_tag`
+    pub fn inference_string_tagged(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>, tag: &str) -> Result<String> {
+        let mut ans = String::new();
+        self.inference_string_tagged_into(img, &mut ans, tag)?;
+        Ok(ans)
     }
-}
 
-impl ImageToText<GrayImage> for YasOCRModel {
-    fn image_to_text(&self, im: &GrayImage, is_preprocessed: bool) -> Result<String> {
-        let gray_f32_image: ImageBuffer<Luma<f32>, Vec<f32>> = im.to_f32_gray_image();
-        self.image_to_text(&gray_f32_image, is_preprocessed)
+    /// same as `inference_string`, and also returns how long this inference took
+    pub fn inference_timed(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<(String, Duration)> {
+        let mut ans = String::new();
+        self.inference_string_into(img, &mut ans)?;
+        // set by `inc_statistics` of this inference
+        let time = self.get_last_inference_time().unwrap_or_default();
+        Ok((ans, time))
     }
 
-    fn get_average_inference_time(&self) -> Option<Duration> {
-        self.get_average_inference_time()
+    /// same as `inference_string`, but not counted in the statistics (nor recorded), for warmup and benchmarking
+    pub fn inference_string_untracked(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<String> {
+        let mut ans = String::new();
+        self.inference_string_untracked_into(img, &mut ans)?;
+        Ok(ans)
     }
-}
 
-pub macro yas_ocr_model($model_name:literal, $index_to_word:literal) {
+    fn inference_string_untracked_into(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>, out: &mut String) -> Result<bool> {
+        let probs = self.run_model(img)?;
+        postprocess::greedy_decode_into(&probs, &self.index_to_word, &self.decode_options, out);
+        Ok(postprocess::ends_with_emission(&probs, &self.index_to_word, &self.decode_options))
+    }
+
+    /// same as `image_to_text`, but neither the preprocessing nor the inference is counted in the statistics
+    pub fn image_to_text_untracked(&self, image: &RgbImage) -> Result<String> {
+        let gray_image_float = preprocess::to_gray_with_options(image, &self.preprocess_options);
+        let (result, non_mono) = preprocess::pre_process_with_options(gray_image_float, self.input_width, &self.preprocess_options);
+        if !non_mono {
+            return Ok(String::new());
+        }
+
+        self.inference_string_untracked(&result)
+    }
+
+    /// run the full `image_to_text` path on a real sample `iterations` times, without recording statistics
+    /// primes allocations and caches with realistic data, so later timings reflect the steady state
+    pub fn warmup_with(&self, image: &RgbImage, iterations: usize) -> Result<()> {
+        for _ in 0..iterations {
+            self.image_to_text_untracked(image)?;
+        }
+        Ok(())
+    }
+
+    /// decode an already softmaxed probability tensor of shape `[time, 1, vocab]`, the layout of the model output
+    /// this allows running the onnx model elsewhere (e.g. on an external accelerator) and only using the decoder
+    pub fn decode_probabilities(&self, probs: ArrayView3<f32>) -> Result<String> {
+        let shape = probs.shape();
+        if shape[1] != 1 || shape[2] != self.index_to_word.len() {
+            return Err(anyhow!(
+                "expect probabilities of shape [time, 1, {}], got {:?}",
+                self.index_to_word.len(),
+                shape
+            ));
+        }
+
+        let probs = probs.index_axis(Axis(1), 0).to_owned();
+        Ok(postprocess::greedy_decode(&probs, &self.index_to_word, &self.decode_options))
+    }
+
+    /// recognize a preprocessed image already stored as a `[1, 1, 32, width]` tensor, the same as `inference_string`
+    /// (input clamp, statistics and recording included)
+    pub fn inference_ndarray(&self, arr: Array4<f32>) -> Result<String> {
+        let (batch, channels, height, width) = arr.dim();
+        if (batch, channels, height) != (1, 1, preprocess::INPUT_HEIGHT as usize) || !self.accepts_width(width as u32) {
+            return Err(anyhow!("expect a tensor of shape {:?}, got {:?}", self.input_shape(), arr.shape()));
+        }
+
+        // the pixels of a standard layout tensor are already in the row major order of an image
+        let img = ImageBuffer::from_raw(width as u32, height as u32, arr.as_standard_layout().into_owned().into_raw_vec())
+            .ok_or_else(|| anyhow!("cannot convert the tensor into an image"))?;
+        self.inference_string(&img)
+    }
+
+    /// recognize a raw framebuffer with tightly packed rows, e.g. straight from a capturer
+    pub fn image_to_text_raw(&self, buf: &[u8], width: u32, height: u32, format: PixelFormat) -> Result<String> {
+        self.image_to_text_raw_strided(buf, width, height, width as usize * format.bytes_per_pixel(), format)
+    }
+
+    /// same as `image_to_text_raw`, but the rows are `stride` bytes apart
+    pub fn image_to_text_raw_strided(&self, buf: &[u8], width: u32, height: u32, stride: usize, format: PixelFormat) -> Result<String> {
+        let image = rgb_image_from_raw(buf, width, height, stride, format)?;
+        self.image_to_text(&image, false)
+    }
+
+    /// recognize vertical (top-to-bottom) text
+    /// the image is rotated 90 degrees counter-clockwise so the top of the text becomes the left of the line, then
+    /// recognized as usual, so the result is in reading order
+    pub fn image_to_text_vertical(&self, image: &RgbImage) -> Result<String> {
+        let rotated = image::imageops::rotate270(image);
+        self.image_to_text(&rotated, false)
+    }
+
+    /// stitch two crops side by side with a `gap` pixels wide gap, then recognize them as one line
+    /// the shorter crop is scaled to the height of the taller one, and the gap is filled with the background color
+    /// sampled from the bottom right corner of `left`
+    pub fn image_to_text_concat(&self, left: &RgbImage, right: &RgbImage, gap: u32) -> Result<String> {
+        if left.width() == 0 || left.height() == 0 || right.width() == 0 || right.height() == 0 {
+            return Err(anyhow!("cannot concat empty images"));
+        }
+
+        let height = std::cmp::max(left.height(), right.height());
+        let scale_to_height = |im: &RgbImage| -> RgbImage {
+            if im.height() == height {
+                im.clone()
+            } else {
+                let width = std::cmp::max((im.width() as f64 * height as f64 / im.height() as f64) as u32, 1);
+                image::imageops::resize(im, width, height, image::imageops::FilterType::Triangle)
+            }
+        };
+        let left = scale_to_height(left);
+        let right = scale_to_height(right);
+
+        let background = *left.get_pixel(left.width() - 1, left.height() - 1);
+        let mut stitched = RgbImage::from_pixel(left.width() + gap + right.width(), height, background);
+        image::imageops::overlay(&mut stitched, &left, 0, 0);
+        image::imageops::overlay(&mut stitched, &right, (left.width() + gap) as i64, 0);
+
+        self.image_to_text(&stitched, false)
+    }
+
+    /// recognize a "label: value" line, and split it on the first of `separators`
+    /// returns `None` if no separator is found
+    pub fn recognize_kv(&self, image: &RgbImage, separators: &[char]) -> Result<Option<(String, String)>> {
+        let text = self.image_to_text(image, false)?;
+        Ok(postprocess::split_kv(&text, separators))
+    }
+
+    /// recognize a line of space separated fields (e.g. "46.6% 7.8 12"), returns the non-empty fields
+    pub fn recognize_fields(&self, image: &RgbImage) -> Result<Vec<String>> {
+        let text = self.image_to_text(image, false)?;
+        Ok(text.split_whitespace().map(String::from).collect())
+    }
+
+    /// recognize many images, in order
+    pub fn image_to_text_batch<T>(&self, images: &[T]) -> Result<Vec<String>> where Self: ImageToText<T> {
+        self.image_to_text_batch_with_policy(images, ErrorPolicy::FailFast)
+    }
+
+    /// same as `image_to_text_batch`, but a failed image is handled according to `on_error`
+    /// with `ErrorPolicy::SkipToEmpty` the result always has one entry per image
+    pub fn image_to_text_batch_with_policy<T>(&self, images: &[T], on_error: ErrorPolicy) -> Result<Vec<String>> where Self: ImageToText<T> {
+        let mut results = Vec::with_capacity(images.len());
+        for (index, image) in images.iter().enumerate() {
+            match self.image_to_text(image, false) {
+                Ok(text) => results.push(text),
+                Err(e) => match on_error {
+                    ErrorPolicy::FailFast => return Err(e),
+                    ErrorPolicy::SkipToEmpty => {
+                        warn!("识别第 {} 张图片失败：{}", index, e);
+                        results.push(String::new());
+                    },
+                },
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// run both models on every image, and report `(this output, other output, agree)`, an A/B check for a new model
+    pub fn compare(&self, other: &YasOCRModel, images: &[RgbImage]) -> Result<Vec<(String, String, bool)>> {
+        let mut results = Vec::with_capacity(images.len());
+        for image in images.iter() {
+            let this_output = self.image_to_text(image, false)?;
+            let other_output = other.image_to_text(image, false)?;
+            let agree = this_output == other_output;
+            results.push((this_output, other_output, agree));
+        }
+
+        Ok(results)
+    }
+
+    /// the fraction of agreeing results of `compare`, 1.0 if there is no result
+    pub fn agreement_rate(comparison: &[(String, String, bool)]) -> f64 {
+        if comparison.is_empty() {
+            return 1.0;
+        }
+        let agreed = comparison.iter().filter(|(_, _, agree)| *agree).count();
+        agreed as f64 / comparison.len() as f64
+    }
+
+    /// same as `image_to_text_batch`, but `cancel` is checked before every image
+    /// when cancelled, returns early with the results collected so far. an `Arc<AtomicBool>` converts into a token
+    pub fn image_to_text_batch_cancellable<T>(&self, images: &[T], cancel: &CancellationToken) -> Result<ScanOutcome> where Self: ImageToText<T> {
+        let mut results = Vec::with_capacity(images.len());
+        for image in images.iter() {
+            if cancel.cancelled() {
+                return Ok(ScanOutcome {
+                    results,
+                    cancelled: true,
+                });
+            }
+
+            results.push(self.image_to_text(image, false)?);
+        }
+
+        Ok(ScanOutcome {
+            results,
+            cancelled: false,
+        })
+    }
+
+    /// recognize a regular grid of `cols * rows` cells of size `cell`, whose top left corner is at `origin`
+    /// results are in row-major order, cells which are out of the image are an error
+    pub fn image_to_text_grid(&self, image: &RgbImage, origin: (u32, u32), cell: (u32, u32), cols: usize, rows: usize) -> Result<Vec<String>> {
+        let right = origin.0 as u64 + cell.0 as u64 * cols as u64;
+        let bottom = origin.1 as u64 + cell.1 as u64 * rows as u64;
+        if right > image.width() as u64 || bottom > image.height() as u64 {
+            return Err(anyhow!(
+                "grid ({}, {}) - ({}, {}) is out of the image of size {}x{}",
+                origin.0, origin.1, right, bottom, image.width(), image.height()
+            ));
+        }
+
+        let mut cells = Vec::with_capacity(cols * rows);
+        for row in 0..rows as u32 {
+            for col in 0..cols as u32 {
+                let x = origin.0 + col * cell.0;
+                let y = origin.1 + row * cell.1;
+                cells.push(image.view(x, y, cell.0, cell.1).to_image());
+            }
+        }
+
+        self.image_to_text_batch(&cells)
+    }
+
+    /// preprocess an f32 gray image, returns `None` if the image has only one color
+    fn preprocess(&self, im: ImageBuffer<Luma<f32>, Vec<f32>>) -> Option<ImageBuffer<Luma<f32>, Vec<f32>>> {
+        self.timed_preprocess(|| preprocess::pre_process_with_options(im, self.input_width, &self.preprocess_options))
+    }
+
+    /// run a preprocess function, recording its time and whether the image is skipped as mono
+    fn timed_preprocess<F>(&self, f: F) -> Option<ImageBuffer<Luma<f32>, Vec<f32>>>
+    where F: FnOnce() -> (ImageBuffer<Luma<f32>, Vec<f32>>, bool) {
+        let now = SystemTime::now();
+        let (result, non_mono) = f();
+
+        let mut stats = self.stats.lock().unwrap();
+        stats.preprocess_count += 1;
+        stats.preprocess_time += now.elapsed().unwrap_or_default();
+
+        if !non_mono {
+            stats.mono_skip_count += 1;
+            return None;
+        }
+
+        Some(result)
+    }
+
+    pub fn inference_string_with_confidence(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>, agg: ConfidenceAgg) -> Result<TextWithConfidence> {
+        let now = SystemTime::now();
+
+        let probs = self.run_model(img)?;
+        let ans = postprocess::greedy_decode_with_confidence(&probs, &self.index_to_word, &self.decode_options, agg);
+
+        self.inc_statistics(now.elapsed()?);
+        let mut stats = self.stats.lock().unwrap();
+        stats.confidence_count += 1;
+        stats.confidence_sum += ans.confidence as f64;
+
+        Ok(ans)
+    }
+
+    /// same as `inference_string`, but decoded by prefix beam search scored with `lm`, see
+    /// `postprocess::prefix_beam_search`. slower than the greedy decode, for the noisiest lines
+    pub fn inference_string_lm(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>, lm: &dyn LanguageModel) -> Result<String> {
+        let now = SystemTime::now();
+
+        let probs = self.run_model(img)?;
+        let ans = postprocess::prefix_beam_search(&probs, &self.index_to_word, lm, &self.decode_options);
+
+        self.inc_statistics(now.elapsed()?);
+
+        Ok(ans)
+    }
+
+    /// same as `inference_string`, but one entry per emitted label
+    pub fn inference_chars(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<Vec<String>> {
+        let now = SystemTime::now();
+
+        let probs = self.run_model(img)?;
+        let ans = postprocess::greedy_decode_words(&probs, &self.index_to_word, &self.decode_options);
+
+        self.inc_statistics(now.elapsed()?);
+
+        Ok(ans)
+    }
+
+    /// same as `inference_string`, and also returns hints about the quality of the decode
+    pub fn inference_string_with_flags(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<(String, DecodeFlags)> {
+        let now = SystemTime::now();
+
+        let probs = self.run_model(img)?;
+        let ans = postprocess::greedy_decode(&probs, &self.index_to_word, &self.decode_options);
+        let flags = postprocess::decode_flags(&probs, &self.index_to_word, &self.decode_options);
+
+        self.inc_statistics(now.elapsed()?);
+
+        Ok((ans, flags))
+    }
+
+    /// max softmax probability at every output timestep (blanks included), for visualizing where the model is unsure
+    pub fn inference_timestep_confidences(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<Vec<f32>> {
+        let now = SystemTime::now();
+
+        let probs = self.run_model(img)?;
+        let confidences = postprocess::timestep_confidences(&probs);
+
+        self.inc_statistics(now.elapsed()?);
+
+        Ok(confidences)
+    }
+
+    /// render the timestep confidences as an image of the input size, brighter columns are more confident
+    /// every column shows the timestep it maps to, so low-confidence regions can be spotted along the line
+    pub fn confidence_heatmap(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<GrayImage> {
+        let confidences = self.inference_timestep_confidences(img)?;
+        if confidences.is_empty() {
+            return Err(anyhow!("the model outputs no timestep"));
+        }
+
+        let time = confidences.len();
+        let width = img.width();
+        Ok(GrayImage::from_fn(width, preprocess::INPUT_HEIGHT, |x, _| {
+            let t = std::cmp::min(x as usize * time / width as usize, time - 1);
+            Luma([(confidences[t].clamp(0.0, 1.0) * 255.0).round() as u8])
+        }))
+    }
+
+    /// the mean color of the text of a crop, e.g. to tell highlighted sub stats, see `preprocess::dominant_text_color`
+    pub fn dominant_text_color(&self, image: &RgbImage) -> [u8; 3] {
+        preprocess::dominant_text_color(image, &self.preprocess_options)
+    }
+
+    /// recognize, then reject results less confident than `min_conf`, and snap the rest to the closest entry of `dict`
+    /// `None` if the result is rejected, or no entry is similar enough (see `postprocess::DICTIONARY_MATCH_CUTOFF`)
+    pub fn recognize_validated(&self, image: &RgbImage, dict: &[String], min_conf: f32) -> Result<Option<String>> {
+        let result = match self.preprocess(preprocess::to_gray_with_options(image, &self.preprocess_options)) {
+            Some(result) => self.inference_string_with_confidence(&result, ConfidenceAgg::default())?,
+            None => return Ok(None),
+        };
+
+        if result.confidence < min_conf {
+            return Ok(None);
+        }
+
+        Ok(postprocess::match_dictionary(&result.text, dict, postprocess::DICTIONARY_MATCH_CUTOFF).map(|(entry, _)| entry))
+    }
+
+    /// recognize the image resized by each of `scales`, and return the most common result
+    /// ties are broken by the highest confidence among the tied results. a single scale of 1.0 is a plain recognition
+    pub fn image_to_text_multiscale(&self, image: &RgbImage, scales: &[f32]) -> Result<String> {
+        if scales.is_empty() {
+            return Err(anyhow!("no scale is given"));
+        }
+
+        // text -> (votes, best confidence)
+        let mut votes: HashMap<String, (usize, f32)> = HashMap::new();
+        for &scale in scales {
+            if scale.is_nan() || scale <= 0.0 {
+                return Err(anyhow!("invalid scale {}", scale));
+            }
+
+            let scaled;
+            let image = if scale == 1.0 {
+                image
+            } else {
+                let width = std::cmp::max((image.width() as f32 * scale).round() as u32, 1);
+                let height = std::cmp::max((image.height() as f32 * scale).round() as u32, 1);
+                scaled = image::imageops::resize(image, width, height, image::imageops::FilterType::Triangle);
+                &scaled
+            };
+
+            let (text, confidence) = match self.preprocess(preprocess::to_gray_with_options(image, &self.preprocess_options)) {
+                Some(result) => {
+                    let result = self.inference_string_with_confidence(&result, ConfidenceAgg::default())?;
+                    (result.text, result.confidence)
+                },
+                None => (String::new(), 0.0),
+            };
+
+            let entry = votes.entry(text).or_insert((0, 0.0));
+            entry.0 += 1;
+            entry.1 = entry.1.max(confidence);
+        }
+
+        let (text, _) = votes.into_iter()
+            .max_by(|(_, a), (_, b)| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)))
+            .unwrap();
+        Ok(text)
+    }
+
+    /// recognize an image, and also return the image the model saw (32 * 384 by default)
+    /// useful for building training datasets. if the image has only one color, the model is not invoked, and the
+    /// returned image is blank
+    pub fn image_to_text_with_input(&self, image: &RgbImage) -> Result<(String, GrayImage)> {
+        let gray_image_float = preprocess::to_gray_with_options(image, &self.preprocess_options);
+        let result = match self.preprocess(gray_image_float) {
+            Some(result) => result,
+            None => return Ok((String::new(), GrayImage::new(self.input_width, preprocess::INPUT_HEIGHT))),
+        };
+
+        let string_result = self.inference_string(&result)?;
+
+        Ok((string_result, result.to_u8_gray_image()))
+    }
+
+    /// like `image_to_text_with_input`, with the confidence of the text
+    /// the image is `None` if the image has only one color, then the model is not invoked
+    pub fn image_to_text_with_input_and_confidence(&self, image: &RgbImage) -> Result<(String, f32, Option<GrayImage>)> {
+        let gray_image_float = preprocess::to_gray_with_options(image, &self.preprocess_options);
+        let result = match self.preprocess(gray_image_float) {
+            Some(result) => result,
+            None => return Ok((String::new(), 1.0, None)),
+        };
+
+        let text = self.inference_string_with_confidence(&result, ConfidenceAgg::default())?;
+        Ok((text.text, text.confidence, Some(result.to_u8_gray_image())))
+    }
+}
+
+impl ImageToText<RgbImage> for YasOCRModel {
+    fn image_to_text(&self, image: &RgbImage, is_preprocessed: bool) -> Result<String> {
+        assert!(!is_preprocessed);
+
+        let gray_image_float = preprocess::to_gray_with_options(image, &self.preprocess_options);
+        let result = match self.preprocess(gray_image_float) {
+            Some(result) => result,
+            None => return Ok(String::new()),
+        };
+
+        self.inference_string_or_wider(&result, |wide_model| wide_model.image_to_text(image, false))
+    }
+
+    fn image_to_text_with_confidence(&self, image: &RgbImage, is_preprocessed: bool) -> Result<(String, f32)> {
+        assert!(!is_preprocessed);
+
+        let gray_image_float = preprocess::to_gray_with_options(image, &self.preprocess_options);
+        match self.preprocess(gray_image_float) {
+            Some(result) => {
+                let result = self.inference_string_with_confidence(&result, ConfidenceAgg::default())?;
+                Ok((result.text, result.confidence))
+            },
+            // nothing to doubt in a blank crop
+            None => Ok((String::new(), 1.0)),
+        }
+    }
+
+    fn get_average_inference_time(&self) -> Option<Duration> {
+        self.get_average_inference_time()
+    }
+}
+
+impl ImageToText<ImageBuffer<Luma<f32>, Vec<f32>>> for YasOCRModel {
+    fn image_to_text(&self, image: &ImageBuffer<Luma<f32>, Vec<f32>>, is_preprocessed: bool) -> Result<String> {
+        if is_preprocessed {
+            let string_result = self.inference_string(image)?;
+            Ok(string_result)
+        } else {
+            let im = image.clone();
+            let preprocess_result = match self.preprocess(im) {
+                Some(result) => result,
+                None => return Ok(String::new()),
+            };
+
+            self.inference_string_or_wider(&preprocess_result, |wide_model| wide_model.image_to_text(image, false))
+        }
+    }
+
+    fn get_average_inference_time(&self) -> Option<Duration> {
+        self.get_average_inference_time()
+    }
+}
+
+impl ImageToText<GrayImage> for YasOCRModel {
+    fn image_to_text(&self, im: &GrayImage, is_preprocessed: bool) -> Result<String> {
+        if is_preprocessed {
+            let gray_f32_image: ImageBuffer<Luma<f32>, Vec<f32>> = im.to_f32_gray_image();
+            return self.inference_string(&gray_f32_image);
+        }
+
+        let preprocess_result = match self.timed_preprocess(|| preprocess::pre_process_u8_with_options(im, self.input_width, &self.preprocess_options)) {
+            Some(result) => result,
+            None => return Ok(String::new()),
+        };
+
+        self.inference_string_or_wider(&preprocess_result, |wide_model| wide_model.image_to_text(im, false))
+    }
+
+    fn get_average_inference_time(&self) -> Option<Duration> {
+        self.get_average_inference_time()
+    }
+}
+
+pub macro yas_ocr_model($model_name:literal, $index_to_word:literal) {
     {
         let model_bytes = include_bytes!($model_name);
         let index_to_word = include_str!($index_to_word);
@@ -188,3 +1303,902 @@ pub macro yas_ocr_model($model_name:literal, $index_to_word:literal) {
         )
     }
 }
+
+/// parse an embedded index to word json without loading the model, pass the same file as to `yas_ocr_model!`
+/// e.g. to validate user input against the characters a model can output
+pub macro yas_ocr_labels($index_to_word:literal) {
+    {
+        let index_to_word = include_str!($index_to_word);
+
+        parse_index_to_word(index_to_word)
+    }
+}
+
+#[cfg(all(test, feature = "tract_onnx"))]
+mod tests {
+    use super::*;
+    use super::super::test_model::{self, LABELS};
+    use super::super::language_model::NgramModel;
+
+    #[test]
+    fn recognizes_test_model_glyphs() {
+        let model = test_model::model();
+        assert_eq!(model.image_to_text(&test_model::text_image("abc"), false).unwrap(), "abc");
+        assert_eq!(model.image_to_text(&test_model::text_image("cc a"), false).unwrap(), "cca");
+    }
+
+    #[test]
+    fn softmaxed_output_is_not_softmaxed_again() {
+        let logits_model = test_model::model();
+        let probs_model = YasOCRModel::new(&test_model::onnx(false, true), LABELS).unwrap();
+        let image = test_model::text_image("abc");
+
+        let (text, confidence) = logits_model.image_to_text_with_confidence(&image, false).unwrap();
+        let (probs_text, probs_confidence) = probs_model.image_to_text_with_confidence(&image, false).unwrap();
+        assert_eq!(text, probs_text);
+        assert!((confidence - probs_confidence).abs() < 1e-4);
+        assert!(probs_confidence > 0.9);
+    }
+
+    #[test]
+    fn save_optimized_round_trip() {
+        let model = test_model::model();
+        let path = std::env::temp_dir().join(format!("yas_save_optimized_{}.nnef.tar", std::process::id()));
+        model.save_optimized(&path).unwrap();
+        let reloaded = YasOCRModel::new_from_optimized(&path, LABELS);
+        std::fs::remove_file(&path).unwrap();
+        let reloaded = reloaded.unwrap();
+
+        for text in ["abc", "c a b", "ccc"] {
+            let image = test_model::text_image(text);
+            assert_eq!(reloaded.image_to_text(&image, false).unwrap(), model.image_to_text(&image, false).unwrap());
+        }
+        assert_eq!((reloaded.input_width, reloaded.dynamic_width), (model.input_width, model.dynamic_width));
+    }
+
+    #[test]
+    fn save_optimized_keeps_the_input_width() {
+        let path = std::env::temp_dir().join(format!("yas_save_optimized_width_{}.nnef.tar", std::process::id()));
+        let fixed = YasOCRModel::new_with_width(&test_model::onnx(false, false), LABELS, 512).unwrap();
+        fixed.save_optimized(&path).unwrap();
+        let reloaded_fixed = YasOCRModel::new_from_optimized(&path, LABELS);
+
+        // not what the graph says, so it can only come from the archive
+        let mut dynamic = YasOCRModel::new(&test_model::onnx(true, false), LABELS).unwrap();
+        dynamic.input_width = 512;
+        dynamic.save_optimized(&path).unwrap();
+        let reloaded_dynamic = YasOCRModel::new_from_optimized(&path, LABELS);
+        std::fs::remove_file(&path).unwrap();
+
+        let reloaded_fixed = reloaded_fixed.unwrap();
+        assert_eq!((reloaded_fixed.input_width, reloaded_fixed.dynamic_width), (512, false));
+        assert!(!reloaded_fixed.preprocess_options.native_width);
+
+        let reloaded_dynamic = reloaded_dynamic.unwrap();
+        assert_eq!((reloaded_dynamic.input_width, reloaded_dynamic.dynamic_width), (512, true));
+        assert!(reloaded_dynamic.preprocess_options.native_width);
+
+        let (text, input) = reloaded_dynamic.image_to_text_with_input(&test_model::text_image("abc")).unwrap();
+        assert_eq!(text, "abc");
+        assert!(input.width() < preprocess::DEFAULT_INPUT_WIDTH);
+        let image = test_model::text_image("cab");
+        assert_eq!(reloaded_fixed.image_to_text(&image, false).unwrap(), fixed.image_to_text(&image, false).unwrap());
+    }
+
+    #[test]
+    fn downscaled_path_recognizes_normal_and_oversized_crops() {
+        let options = PreprocessOptions {
+            max_long_side: Some(256),
+            ..PreprocessOptions::default()
+        };
+        let model = test_model::model();
+        let downscaled = test_model::model().with_preprocess_options(options);
+
+        let image = test_model::text_image("abc");
+        assert_eq!(downscaled.image_to_text(&image, false).unwrap(), model.image_to_text(&image, false).unwrap());
+
+        let oversized = image::imageops::resize(&image, image.width() * 8, image.height() * 8, image::imageops::FilterType::Nearest);
+        assert!(oversized.width() > 256);
+        assert_eq!(downscaled.image_to_text(&oversized, false).unwrap(), "abc");
+    }
+
+    #[test]
+    fn image_to_text_with_input_returns_model_input() {
+        let model = test_model::model();
+        let image = test_model::text_image("cab");
+
+        let (text, input) = model.image_to_text_with_input(&image).unwrap();
+        assert_eq!(input.dimensions(), (preprocess::DEFAULT_INPUT_WIDTH, preprocess::INPUT_HEIGHT));
+        assert_eq!(text, model.image_to_text(&image, false).unwrap());
+        assert_eq!(text, "cab");
+    }
+
+    #[test]
+    fn mono_images_are_counted_apart_from_inferences() {
+        let model = test_model::model();
+        let blank = RgbImage::new(64, 32);
+        let images = [blank.clone(), test_model::text_image("abc"), blank, test_model::text_image("c")];
+
+        for image in images.iter() {
+            model.image_to_text(image, false).unwrap();
+        }
+        assert_eq!(model.get_mono_skip_count(), 2);
+        assert_eq!(model.stats().invoke_count, 2);
+        assert_eq!(model.stats().preprocess_count, 4);
+    }
+
+    #[test]
+    fn decode_hand_built_probabilities() {
+        let model = test_model::model();
+        // a a - a b b c -
+        let labels = [1, 1, 0, 1, 2, 2, 3, 0];
+        let mut probs = ndarray::Array3::<f32>::zeros((labels.len(), 1, 4));
+        for (t, &label) in labels.iter().enumerate() {
+            probs[[t, 0, label]] = 1.0;
+        }
+
+        assert_eq!(model.decode_probabilities(probs.view()).unwrap(), "aabc");
+        assert!(model.decode_probabilities(ndarray::Array3::<f32>::zeros((8, 1, 3)).view()).is_err());
+    }
+
+    #[test]
+    fn blank_index_and_vocab_size() {
+        let model = test_model::model();
+        assert_eq!(model.vocab_size(), 4);
+        assert_eq!(model.blank_index(), Some(0));
+
+        let no_blank = YasOCRModel::new(&test_model::onnx(false, false), r#"{"0": "x", "1": "a", "2": "b", "3": "c"}"#).unwrap();
+        assert_eq!(no_blank.blank_index(), None);
+    }
+
+    #[test]
+    fn raw_bgra_framebuffer_matches_rgb_image() {
+        let model = test_model::model();
+        // colored, so that swapped channels would give another image
+        let mut image = test_model::text_image("cab c");
+        for p in image.pixels_mut() {
+            p.0 = if p[0] == 0 { [200, 120, 30] } else { [40, 80, 250] };
+        }
+        let (width, height) = image.dimensions();
+        let bgra = |stride: usize| {
+            let mut buf = vec![0xAB_u8; stride * height as usize];
+            for (x, y, p) in image.enumerate_pixels() {
+                let offset = y as usize * stride + x as usize * 4;
+                buf[offset..offset + 4].copy_from_slice(&[p[2], p[1], p[0], 255]);
+            }
+            buf
+        };
+        let expected = model.image_to_text(&image, false).unwrap();
+        assert_eq!(expected, "cabc");
+
+        let packed = bgra(width as usize * 4);
+        assert_eq!(rgb_image_from_raw(&packed, width, height, width as usize * 4, PixelFormat::Bgra8).unwrap(), image);
+        assert_eq!(model.image_to_text_raw(&packed, width, height, PixelFormat::Bgra8).unwrap(), expected);
+
+        // rows padded to a multiple of 64 bytes, the padding is garbage
+        let stride = (width as usize * 4).div_ceil(64) * 64 + 64;
+        let padded = bgra(stride);
+        assert_eq!(rgb_image_from_raw(&padded, width, height, stride, PixelFormat::Bgra8).unwrap(), image);
+        assert_eq!(model.image_to_text_raw_strided(&padded, width, height, stride, PixelFormat::Bgra8).unwrap(), expected);
+
+        assert!(model.image_to_text_raw(&padded[..100], width, height, PixelFormat::Bgra8).is_err());
+        assert!(model.image_to_text_raw_strided(&padded, width, height, width as usize * 3, PixelFormat::Bgra8).is_err());
+    }
+
+    #[test]
+    fn tagged_inferences_are_counted_by_tag() {
+        let model = test_model::model();
+        let (input, _) = preprocess::pre_process(preprocess::to_gray(&test_model::text_image("abc")));
+
+        assert_eq!(model.inference_string_tagged(&input, "main_stat").unwrap(), "abc");
+        model.inference_string_tagged(&input, "sub_stat").unwrap();
+        model.inference_string_tagged(&input, "sub_stat").unwrap();
+        model.inference_string(&input).unwrap();
+
+        let by_tag = model.get_stats_by_tag();
+        assert_eq!(by_tag.len(), 3);
+        assert_eq!(by_tag["main_stat"].invoke_count, 1);
+        assert_eq!(by_tag["sub_stat"].invoke_count, 2);
+        assert_eq!(by_tag[DEFAULT_STATS_TAG].invoke_count, 1);
+        assert_eq!(model.stats().invoke_count, 4);
+        let total: Duration = by_tag.values().map(|stats| stats.inference_time).sum();
+        assert_eq!(total, model.stats().inference_time);
+    }
+
+    #[test]
+    fn input_clamp_applies_before_the_model() {
+        let (clean, _) = preprocess::pre_process(preprocess::to_gray(&test_model::text_image("abc")));
+        // out of range and non finite pixels, each of which clamps back to the clean value
+        let mut corrupted = clean.clone();
+        corrupted.put_pixel(100, 2, Luma([-1000.0]));
+        corrupted.put_pixel(200, 20, Luma([f32::NAN]));
+        corrupted.put_pixel(370, 5, Luma([f32::NEG_INFINITY]));
+        corrupted.put_pixel(380, 30, Luma([-5.0]));
+        let ink = (0..clean.width()).find(|&x| clean.get_pixel(x, 0)[0] == 1.0).unwrap();
+        corrupted.put_pixel(ink, 1, Luma([f32::INFINITY]));
+        corrupted.put_pixel(ink + 1, 3, Luma([7.0]));
+
+        let plain = test_model::model();
+        // the very negative pixel in the top half reads as a bottom half glyph
+        assert_eq!(plain.inference_string(&corrupted).unwrap(), "abcb");
+        assert_ne!(plain.inference_timestep_confidences(&corrupted).unwrap(), plain.inference_timestep_confidences(&clean).unwrap());
+
+        let clamped = YasOCRModelBuilder::new(&test_model::onnx(false, false), LABELS).input_clamp(0.0, 1.0).build().unwrap();
+        assert_eq!(clamped.inference_string(&corrupted).unwrap(), "abc");
+        // the model sees exactly the clean input
+        assert_eq!(clamped.inference_timestep_confidences(&corrupted).unwrap(), clamped.inference_timestep_confidences(&clean).unwrap());
+
+        assert!(YasOCRModelBuilder::new(&test_model::onnx(false, false), LABELS).input_clamp(1.0, 0.0).build().is_err());
+    }
+
+    #[test]
+    fn vertical_text_reads_top_to_bottom() {
+        let model = test_model::model();
+        // the first glyph at the top
+        let vertical = image::imageops::rotate90(&test_model::text_image("cab"));
+        assert!(vertical.height() > vertical.width());
+
+        assert_eq!(model.image_to_text_vertical(&vertical).unwrap(), "cab");
+    }
+
+    #[test]
+    fn average_confidence_of_all_inferences() {
+        let model = test_model::model();
+        assert_eq!(model.get_average_confidence(), None);
+
+        let confidences: Vec<f32> = ["abc", "c", "ba c"].iter()
+            .map(|text| model.image_to_text_with_confidence(&test_model::text_image(text), false).unwrap().1)
+            .collect();
+        let expected = confidences.iter().map(|&c| c as f64).sum::<f64>() / confidences.len() as f64;
+        assert!((model.get_average_confidence().unwrap() - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn timestep_confidences_cover_the_time_axis() {
+        let model = test_model::model();
+        let (input, _) = preprocess::pre_process(preprocess::to_gray(&test_model::text_image("abc")));
+
+        let confidences = model.inference_timestep_confidences(&input).unwrap();
+        assert_eq!(confidences.len(), (preprocess::DEFAULT_INPUT_WIDTH / test_model::STRIDE) as usize);
+        assert!(confidences.iter().all(|c| (0.0..=1.0).contains(c)));
+    }
+
+    #[test]
+    fn concat_recognizes_two_crops_as_one_line() {
+        let labels = r#"{"0": "-", "1": "攻击力", "2": "+46.6%", "3": "c"}"#;
+        let model = YasOCRModel::new(&test_model::onnx(false, false), labels).unwrap();
+        // a top half glyph and a bottom half glyph, which only span the full height together
+        let left = test_model::text_image("a");
+        let right = image::imageops::resize(&test_model::text_image("b"), 48, 64, image::imageops::FilterType::Nearest);
+
+        assert_eq!(model.image_to_text_concat(&left, &right, 8).unwrap(), "攻击力+46.6%");
+        assert!(model.image_to_text_concat(&left, &RgbImage::new(0, 0), 8).is_err());
+    }
+
+    #[test]
+    fn deterministic_mode_is_reproducible() {
+        let model = YasOCRModelBuilder::new(&test_model::onnx(false, false), LABELS)
+            .deterministic()
+            .build()
+            .unwrap();
+        let image = test_model::text_image("cab c");
+
+        let (text, confidence) = model.image_to_text_with_confidence(&image, false).unwrap();
+        let (again, again_confidence) = model.image_to_text_with_confidence(&image, false).unwrap();
+        assert_eq!(text, again);
+        assert_eq!(confidence.to_bits(), again_confidence.to_bits());
+    }
+
+    #[test]
+    fn emitted_label_histogram_counts_timesteps() {
+        let model = test_model::model().with_emitted_label_histogram();
+        assert!(model.emitted_label_histogram().is_empty());
+
+        model.image_to_text(&test_model::text_image("abc"), false).unwrap();
+        let histogram = model.emitted_label_histogram();
+        // every glyph spans two timesteps, the rest of the 96 is blank
+        assert_eq!(histogram.get(&1), Some(&2));
+        assert_eq!(histogram.get(&2), Some(&2));
+        assert_eq!(histogram.get(&3), Some(&2));
+        assert_eq!(histogram.get(&0), Some(&90));
+
+        assert!(test_model::model().emitted_label_histogram().is_empty());
+    }
+
+    #[test]
+    fn reused_buffer_matches_inference_string() {
+        let model = test_model::model();
+        let inputs: Vec<_> = ["abc", "c"].iter()
+            .map(|text| preprocess::pre_process(preprocess::to_gray(&test_model::text_image(text))).0)
+            .collect();
+
+        let mut out = String::from("stale content which is longer");
+        model.inference_string_into(&inputs[0], &mut out).unwrap();
+        assert_eq!(out, model.inference_string(&inputs[0]).unwrap());
+
+        // a stale buffer longer than the batch is truncated, and the strings kept are overwritten
+        let mut outs = vec![String::from("stale"); 5];
+        model.inference_string_batch_into(&inputs, &mut outs).unwrap();
+        assert_eq!(outs.len(), inputs.len());
+        assert_eq!(outs, model.inference_strings_batch(&inputs).unwrap());
+
+        // and a shorter one is extended
+        let mut outs = vec![String::from("stale")];
+        model.inference_string_batch_into(&inputs, &mut outs).unwrap();
+        assert_eq!(outs, model.inference_strings_batch(&inputs).unwrap());
+    }
+
+    /// a crop which sets `flag` once it's recognized
+    struct TrippingCrop {
+        image: RgbImage,
+        flag: Option<Arc<std::sync::atomic::AtomicBool>>,
+    }
+
+    impl ImageToText<TrippingCrop> for YasOCRModel {
+        fn image_to_text(&self, crop: &TrippingCrop, is_preprocessed: bool) -> Result<String> {
+            let text = self.image_to_text(&crop.image, is_preprocessed)?;
+            if let Some(flag) = &crop.flag {
+                flag.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+            Ok(text)
+        }
+
+        fn get_average_inference_time(&self) -> Option<Duration> {
+            self.get_average_inference_time()
+        }
+    }
+
+    #[test]
+    fn cancel_mid_scan() {
+        let model = test_model::model();
+        let flag = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let crops: Vec<TrippingCrop> = (0..5)
+            .map(|index| TrippingCrop {
+                image: test_model::text_image("abc"),
+                flag: if index == 1 { Some(flag.clone()) } else { None },
+            })
+            .collect();
+
+        let outcome = model.image_to_text_batch_cancellable(&crops, &flag.into()).unwrap();
+        assert!(outcome.cancelled);
+        assert_eq!(outcome.results, ["abc", "abc"]);
+        assert_eq!(model.stats().invoke_count, 2);
+
+        let outcome = model.image_to_text_batch_cancellable(&crops[2..], &CancellationToken::new()).unwrap();
+        assert!(!outcome.cancelled);
+        assert_eq!(outcome.results.len(), 3);
+    }
+
+    #[test]
+    fn erosion_separates_outlined_glyphs() {
+        // two full height glyphs, whose 3 pixel outlines leave only 2 pixels between them
+        let image = RgbImage::from_fn(46, 32, |x, _| match x {
+            11..=18 | 27..=34 => image::Rgb([255, 255, 255]),
+            8..=10 | 19..=21 | 24..=26 | 35..=37 => image::Rgb([200, 200, 200]),
+            _ => image::Rgb([0, 0, 0]),
+        });
+
+        let model = test_model::model();
+        assert_eq!(model.image_to_text(&image, false).unwrap(), "c");
+
+        let eroded = test_model::model().with_preprocess_options(PreprocessOptions {
+            erode_kernel: Some(3),
+            ..PreprocessOptions::default()
+        });
+        assert_eq!(eroded.image_to_text(&image, false).unwrap(), "cc");
+    }
+
+    #[test]
+    fn labels_match_the_model_vocab() {
+        let model = test_model::model();
+        let labels = parse_index_to_word(LABELS).unwrap();
+        assert!(!labels.is_empty());
+        assert_eq!(labels, model.index_to_word());
+        assert_eq!(labels.len(), model.vocab_size());
+
+        // by index, not by the key string
+        let content = (0..12).map(|i| format!("\"{}\": \"w{}\"", i, i)).collect::<Vec<_>>().join(", ");
+        let labels = parse_index_to_word(&format!("{{{}}}", content)).unwrap();
+        assert_eq!(labels[2], "w2");
+        assert_eq!(labels[11], "w11");
+    }
+
+    #[test]
+    fn gray_u8_path_matches_f32_path() {
+        let model = test_model::model();
+        for text in ["abc", "c b a", "cc"] {
+            let image = test_model::text_image(text);
+            let gray = image::imageops::grayscale(&image);
+
+            let u8_result = ImageToText::<GrayImage>::image_to_text(&model, &gray, false).unwrap();
+            let f32_result = ImageToText::<ImageBuffer<Luma<f32>, Vec<f32>>>::image_to_text(&model, &gray.to_f32_gray_image(), false).unwrap();
+            assert_eq!(u8_result, f32_result);
+            assert_eq!(u8_result, text.replace(' ', ""));
+        }
+    }
+
+    #[test]
+    fn recognize_kv_splits_on_colon_and_space() {
+        let colon = YasOCRModel::new(&test_model::onnx(false, false), r#"{"0": "-", "1": "生命值", "2": "：", "3": "4780"}"#).unwrap();
+        let image = test_model::text_image("abc");
+        assert_eq!(
+            colon.recognize_kv(&image, &[':', '：']).unwrap(),
+            Some((String::from("生命值"), String::from("4780")))
+        );
+
+        let space = YasOCRModel::new(&test_model::onnx(false, false), r#"{"0": "-", "1": "生命值", "2": " ", "3": "4780"}"#).unwrap();
+        assert_eq!(
+            space.recognize_kv(&image, &[' ']).unwrap(),
+            Some((String::from("生命值"), String::from("4780")))
+        );
+        assert_eq!(space.recognize_kv(&image, &[':']).unwrap(), None);
+    }
+
+    #[test]
+    fn preprocess_time_only_recorded_when_preprocessing() {
+        let model = test_model::model();
+        let (input, _) = preprocess::pre_process(preprocess::to_gray(&test_model::text_image("abc")));
+
+        model.image_to_text(&input, true).unwrap();
+        assert_eq!(model.stats().preprocess_count, 0);
+        assert_eq!(model.get_average_preprocess_time(), None);
+
+        model.image_to_text(&test_model::text_image("abc"), false).unwrap();
+        assert_eq!(model.stats().preprocess_count, 1);
+        assert!(model.get_average_preprocess_time().is_some());
+        assert_eq!(model.stats().invoke_count, 2);
+    }
+
+    #[test]
+    fn two_models_from_one_precompiled_handle() {
+        let handle = YasOCRModel::precompile(&test_model::onnx(false, false)).unwrap();
+        let first = YasOCRModel::new_from_precompiled(handle.clone(), LABELS).unwrap();
+        let second = YasOCRModel::new_from_precompiled(handle, r#"{"0": "-", "1": "x", "2": "y", "3": "z"}"#).unwrap();
+
+        let image = test_model::text_image("abc");
+        assert_eq!(first.image_to_text(&image, false).unwrap(), "abc");
+        assert_eq!(second.image_to_text(&image, false).unwrap(), "xyz");
+        // the statistics are per model
+        assert_eq!(first.stats().invoke_count, 1);
+    }
+
+    #[test]
+    fn precompiled_model_keeps_its_dynamic_width() {
+        let handle = YasOCRModel::precompile(&test_model::onnx(true, false)).unwrap();
+        let model = YasOCRModel::new_from_precompiled(handle, LABELS).unwrap();
+
+        let (text, input) = model.image_to_text_with_input(&test_model::text_image("abc")).unwrap();
+        assert_eq!(text, "abc");
+        assert!(input.width() < preprocess::DEFAULT_INPUT_WIDTH);
+    }
+
+    #[test]
+    fn recognize_a_two_by_two_grid() {
+        let model = test_model::model();
+        let texts = ["abc", "cab", "ccc", "bac"];
+        let mut grid = RgbImage::new(10 + 56 * 2, 20 + 32 * 2);
+        for (index, text) in texts.iter().enumerate() {
+            let (col, row) = (index as i64 % 2, index as i64 / 2);
+            image::imageops::overlay(&mut grid, &test_model::text_image(text), 10 + col * 56, 20 + row * 32);
+        }
+
+        assert_eq!(model.image_to_text_grid(&grid, (10, 20), (56, 32), 2, 2).unwrap(), texts);
+        assert!(model.image_to_text_grid(&grid, (10, 20), (56, 32), 3, 2).is_err());
+    }
+
+    #[test]
+    fn label_count_mismatch_is_an_error() {
+        let model = YasOCRModel::new(&test_model::onnx(false, false), r#"{"0": "-", "1": "a", "2": "b"}"#).unwrap();
+
+        let error = model.image_to_text(&test_model::text_image("abc"), false).unwrap_err();
+        assert!(error.to_string().contains("the model outputs 4 labels, but index to word has 3"), "{}", error);
+    }
+
+    #[test]
+    fn concurrent_inference_on_a_shared_model() {
+        let model = Arc::new(test_model::model());
+        let texts = ["abc", "cab", "c", "ba cc"];
+
+        let threads: Vec<_> = texts.iter()
+            .map(|&text| {
+                let model = model.clone();
+                std::thread::spawn(move || {
+                    let image = test_model::text_image(text);
+                    (0..20).map(|_| model.image_to_text(&image, false).unwrap()).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        for (thread, text) in threads.into_iter().zip(texts) {
+            let expected = text.replace(' ', "");
+            assert!(thread.join().unwrap().iter().all(|result| *result == expected));
+        }
+        // no update of the statistics is lost
+        assert_eq!(model.stats().invoke_count, 80);
+        assert_eq!(model.stats().preprocess_count, 80);
+        assert_eq!(model.get_stats_by_tag()[DEFAULT_STATS_TAG].invoke_count, 80);
+    }
+
+    #[test]
+    fn confidence_heatmap_dimensions_and_uniform_input() {
+        let model = test_model::model();
+        let blank = ImageBuffer::new(preprocess::DEFAULT_INPUT_WIDTH, preprocess::INPUT_HEIGHT);
+
+        let heatmap = model.confidence_heatmap(&blank).unwrap();
+        assert_eq!(heatmap.dimensions(), (preprocess::DEFAULT_INPUT_WIDTH, preprocess::INPUT_HEIGHT));
+        let first = heatmap.get_pixel(0, 0);
+        assert!(heatmap.pixels().all(|p| p == first));
+    }
+
+    #[test]
+    fn recognize_space_separated_fields() {
+        let model = YasOCRModel::new(&test_model::onnx(false, false), r#"{"0": "-", "1": "46.6% ", "2": "7.8 ", "3": "12"}"#).unwrap();
+
+        assert_eq!(model.recognize_fields(&test_model::text_image("abc")).unwrap(), ["46.6%", "7.8", "12"]);
+    }
+
+    #[test]
+    fn model_with_a_wider_input() {
+        let model = YasOCRModel::new_with_width(&test_model::onnx(false, false), LABELS, 512).unwrap();
+        let gray = preprocess::to_gray(&test_model::text_image("abc"));
+
+        let (input, _) = preprocess::pre_process_with_options(gray.clone(), 512, &PreprocessOptions::default());
+        assert_eq!(input.width(), 512);
+        assert_eq!(model.inference_string(&input).unwrap(), "abc");
+        assert_eq!(model.image_to_text(&test_model::text_image("abc"), false).unwrap(), "abc");
+
+        let (narrow, _) = preprocess::pre_process(gray);
+        assert!(model.inference_string(&narrow).is_err());
+    }
+
+    #[test]
+    fn ndarray_input_matches_inference_string() {
+        let model = test_model::model().with_recording();
+        let (input, _) = preprocess::pre_process(preprocess::to_gray(&test_model::text_image("cab")));
+        let tensor = Array4::from_shape_vec((1, 1, 32, input.width() as usize), input.as_raw().clone()).unwrap();
+
+        assert_eq!(model.inference_ndarray(tensor).unwrap(), model.inference_string(&input).unwrap());
+        assert_eq!(model.stats().invoke_count, 2);
+        assert!(model.inference_ndarray(Array4::zeros((1, 1, 32, 100))).is_err());
+
+        let path = std::env::temp_dir().join(format!("yas_ndarray_recording_{}.json", std::process::id()));
+        model.record_to(&path).unwrap();
+        let recorded = recording::read_recording(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(recorded.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn recorded_inferences_replay() {
+        let model = test_model::model().with_recording();
+        for text in ["abc", "c", "ba c"] {
+            model.image_to_text(&test_model::text_image(text), false).unwrap();
+        }
+        let path = std::env::temp_dir().join(format!("yas_recording_{}.json", std::process::id()));
+        model.record_to(&path).unwrap();
+
+        let diffs = test_model::model().verify_against(&path);
+        let relabeled = YasOCRModel::new(&test_model::onnx(false, false), r#"{"0": "-", "1": "a", "2": "b", "3": "z"}"#).unwrap();
+        let relabeled_diffs = relabeled.verify_against(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(diffs.unwrap().is_empty());
+        let relabeled_diffs = relabeled_diffs.unwrap();
+        assert_eq!(relabeled_diffs.len(), 3);
+        assert_eq!((relabeled_diffs[0].expected.as_str(), relabeled_diffs[0].actual.as_str()), ("abc", "abz"));
+        assert!(test_model::model().record_to(&path).is_err());
+    }
+
+    #[test]
+    fn multiscale_voting_recovers_a_noisy_crop() {
+        let model = test_model::model();
+        // a 2 pixel wide scratch after the text, which reads as another glyph at the original scale
+        let mut image = RgbImage::new(test_model::text_image("abc").width() + 24, 32);
+        image::imageops::overlay(&mut image, &test_model::text_image("abc"), 0, 0);
+        for x in 56..58 {
+            for y in 0..32 {
+                image.put_pixel(x, y, image::Rgb([255, 255, 255]));
+            }
+        }
+
+        assert_eq!(model.image_to_text(&image, false).unwrap(), "abcc");
+        assert_eq!(model.image_to_text_multiscale(&image, &[0.75, 1.0, 1.25]).unwrap(), "abc");
+        assert_eq!(model.image_to_text_multiscale(&image, &[1.0]).unwrap(), "abcc");
+        assert!(model.image_to_text_multiscale(&image, &[]).is_err());
+    }
+
+    #[test]
+    fn untracked_inference_is_not_counted() {
+        let model = test_model::model();
+        let image = test_model::text_image("abc");
+        model.image_to_text(&image, false).unwrap();
+
+        assert_eq!(model.image_to_text_untracked(&image).unwrap(), "abc");
+        let (input, _) = preprocess::pre_process(preprocess::to_gray(&image));
+        assert_eq!(model.inference_string_untracked(&input).unwrap(), "abc");
+        assert_eq!(model.stats().invoke_count, 1);
+        assert_eq!(model.stats().preprocess_count, 1);
+    }
+
+    /// `None` is a crop which fails to be recognized
+    impl ImageToText<Option<RgbImage>> for YasOCRModel {
+        fn image_to_text(&self, crop: &Option<RgbImage>, is_preprocessed: bool) -> Result<String> {
+            match crop {
+                Some(image) => self.image_to_text(image, is_preprocessed),
+                None => Err(anyhow!("broken crop")),
+            }
+        }
+
+        fn get_average_inference_time(&self) -> Option<Duration> {
+            self.get_average_inference_time()
+        }
+    }
+
+    #[test]
+    fn skip_to_empty_keeps_one_result_per_image() {
+        let model = test_model::model();
+        let crops = [Some(test_model::text_image("abc")), None, Some(test_model::text_image("c"))];
+
+        assert_eq!(model.image_to_text_batch_with_policy(&crops, ErrorPolicy::SkipToEmpty).unwrap(), ["abc", "", "c"]);
+        assert!(model.image_to_text_batch_with_policy(&crops, ErrorPolicy::FailFast).is_err());
+        assert!(model.image_to_text_batch(&crops).is_err());
+    }
+
+    #[test]
+    fn agreement_with_itself_and_a_different_model() {
+        let model = test_model::model();
+        let relabeled = YasOCRModel::new(&test_model::onnx(false, false), r#"{"0": "-", "1": "a", "2": "b", "3": "z"}"#).unwrap();
+        let images: Vec<RgbImage> = ["ab", "abc", "ccc"].iter().map(|text| test_model::text_image(text)).collect();
+
+        let itself = model.compare(&model, &images).unwrap();
+        assert_eq!(YasOCRModel::agreement_rate(&itself), 1.0);
+
+        let different = model.compare(&relabeled, &images).unwrap();
+        assert_eq!(different[0], (String::from("ab"), String::from("ab"), true));
+        assert_eq!(different[1], (String::from("abc"), String::from("abz"), false));
+        assert!((YasOCRModel::agreement_rate(&different) - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(YasOCRModel::agreement_rate(&[]), 1.0);
+    }
+
+    #[test]
+    fn last_inference_time_is_the_last_call() {
+        let model = test_model::model();
+        assert_eq!(model.get_last_inference_time(), None);
+
+        model.image_to_text(&test_model::text_image("abc"), false).unwrap();
+        let first = model.get_last_inference_time().unwrap();
+        model.image_to_text(&test_model::text_image("cab c"), false).unwrap();
+        let last = model.get_last_inference_time().unwrap();
+
+        assert_eq!(last, model.stats().inference_time - first);
+        let average = model.get_average_inference_time().unwrap();
+        assert!(average.abs_diff((first + last) / 2) < Duration::from_micros(1));
+    }
+
+    #[test]
+    fn recognize_validated_gates_then_snaps() {
+        let model = test_model::model();
+        let dict = vec!["abc".to_string(), "bbbb".to_string()];
+
+        // confident and in the dictionary
+        let valid = model.recognize_validated(&test_model::text_image("abc"), &dict, 0.95).unwrap();
+        assert_eq!(valid.as_deref(), Some("abc"));
+
+        // confident, one letter off
+        let correctable = model.recognize_validated(&test_model::text_image("abcc"), &dict, 0.95).unwrap();
+        assert_eq!(correctable.as_deref(), Some("abc"));
+
+        // too far from every entry
+        let unknown = model.recognize_validated(&test_model::text_image("cacaca"), &dict, 0.95).unwrap();
+        assert_eq!(unknown, None);
+
+        // a full height column next to a top half one reads as `a`, with `c` close behind
+        let mut ambiguous = RgbImage::new(32, 32);
+        for y in 0..32 {
+            ambiguous.put_pixel(8, y, image::Rgb([255, 255, 255]));
+            if y < 16 {
+                ambiguous.put_pixel(9, y, image::Rgb([255, 255, 255]));
+            }
+        }
+        let dict = vec!["a".to_string()];
+        let (text, confidence) = model.image_to_text_with_confidence(&ambiguous, false).unwrap();
+        assert_eq!(text, "a");
+        assert!(confidence < 0.95);
+        assert_eq!(model.recognize_validated(&ambiguous, &dict, 0.95).unwrap(), None);
+        assert_eq!(model.recognize_validated(&ambiguous, &dict, 0.5).unwrap().as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn retry_width_recovers_a_truncated_line() {
+        // too long for 384 columns, the squeezed glyphs run up to the last timestep
+        let text = "abc".repeat(11);
+        let image = test_model::text_image(&text);
+
+        let plain = test_model::model();
+        assert_ne!(plain.image_to_text(&image, false).unwrap(), text);
+
+        let retrying = YasOCRModelBuilder::new(&test_model::onnx(false, false), LABELS).retry_width(768).build().unwrap();
+        assert_eq!(retrying.image_to_text(&image, false).unwrap(), text);
+        // a line which fits is unaffected
+        assert_eq!(retrying.image_to_text(&test_model::text_image("abc"), false).unwrap(), "abc");
+
+        assert!(YasOCRModelBuilder::new(&test_model::onnx(false, false), LABELS).retry_width(384).build().is_err());
+    }
+
+    #[test]
+    fn decode_flags_report_collapsed_repeats() {
+        let model = test_model::model();
+        // a preprocessed input with full height ink in the given timesteps
+        let input = |timesteps: &[u32]| ImageBuffer::from_fn(384, 32, |x, _| {
+            Luma([if timesteps.contains(&(x / test_model::STRIDE)) { 1.0 } else { 0.0 }])
+        });
+
+        let (text, flags) = model.inference_string_with_flags(&input(&[0, 2])).unwrap();
+        assert_eq!(text, "cc");
+        assert_eq!(flags, DecodeFlags::default());
+
+        // the same two characters with no blank between them
+        let (text, flags) = model.inference_string_with_flags(&input(&[0, 1])).unwrap();
+        assert_eq!(text, "c");
+        assert!(flags.collapsed_duplicates);
+        assert!(!flags.emitted_at_last_timestep);
+
+        let (text, flags) = model.inference_string_with_flags(&input(&[0, 95])).unwrap();
+        assert_eq!(text, "cc");
+        assert!(!flags.collapsed_duplicates);
+        assert!(flags.emitted_at_last_timestep);
+    }
+
+    #[test]
+    fn language_model_decodes_the_vocabulary_word() {
+        let words: Vec<String> = ["abc", "cab", "bca"].iter().map(|s| s.to_string()).collect();
+        let lm = NgramModel::new(&words, 2);
+        // `a`, `b`, then a bottom half glyph with a faint top half, which reads as `b` just ahead of `c`
+        let input = ImageBuffer::from_fn(384, 32, |x, y| {
+            let top = y < 16;
+            Luma([match x / test_model::STRIDE {
+                0 => if top { 1.0 } else { 0.0 },
+                2 => if top { 0.0 } else { 1.0 },
+                4 => if top { 0.435 } else { 1.0 },
+                _ => 0.0,
+            }])
+        });
+
+        let model = test_model::model();
+        assert_eq!(model.inference_string(&input).unwrap(), "abb");
+        assert_eq!(model.inference_string_lm(&input, &lm).unwrap(), "abc");
+        assert_eq!(model.stats().invoke_count, 2);
+
+        let no_lm = test_model::model().with_decode_options(DecodeOptions { lm_alpha: 0.0, ..DecodeOptions::default() });
+        assert_eq!(no_lm.inference_string_lm(&input, &lm).unwrap(), "abb");
+        let narrow = test_model::model().with_decode_options(DecodeOptions { beam_width: 1, ..DecodeOptions::default() });
+        assert_eq!(narrow.inference_string_lm(&input, &lm).unwrap(), "abb");
+    }
+
+    #[test]
+    fn inference_chars_concatenate_to_the_string() {
+        // multi byte and multi character words
+        let labels = r#"{"0": "-", "1": "生命", "2": "值", "3": "%"}"#;
+        let model = YasOCRModel::new(&test_model::onnx(false, false), labels).unwrap();
+        let (input, _) = preprocess::pre_process(preprocess::to_gray(&test_model::text_image("abcac")));
+
+        let chars = model.inference_chars(&input).unwrap();
+        assert_eq!(chars, vec!["生命", "值", "%", "生命", "%"]);
+        assert_eq!(chars.concat(), model.inference_string(&input).unwrap());
+    }
+
+    #[test]
+    fn timed_inference_returns_its_duration() {
+        let model = test_model::model();
+        let (input, _) = preprocess::pre_process(preprocess::to_gray(&test_model::text_image("bca")));
+
+        let (text, time) = model.inference_timed(&input).unwrap();
+        assert_eq!(text, model.inference_string(&input).unwrap());
+        assert!(time > Duration::ZERO);
+        assert_eq!(model.stats().invoke_count, 2);
+    }
+
+    #[test]
+    fn warmup_with_a_sample_is_not_counted() {
+        let model = test_model::model();
+        model.warmup_with(&test_model::text_image("abc"), 3).unwrap();
+        assert_eq!(model.stats().invoke_count, 0);
+        assert_eq!(model.stats().preprocess_count, 0);
+        assert_eq!(model.get_last_inference_time(), None);
+    }
+
+    #[test]
+    fn supersampling_keeps_thin_strokes() {
+        // the glyphs of `test_model::text_image` at 4 times the height, drawn with two thin anti-aliased strokes
+        let text = "abcab";
+        let (height, glyph, stroke, gap) = (128, 32, 2, 8);
+        let mut image = RgbImage::new(glyph * (2 * text.len() as u32 + 1), height);
+        for (index, c) in text.chars().enumerate() {
+            let (top, bottom) = match c {
+                'a' => (0, height / 2),
+                'b' => (height / 2, height),
+                _ => (0, height),
+            };
+            let left = glyph * (2 * index as u32 + 1);
+            for start in [left, left + gap] {
+                for x in start - 1..start + stroke + 1 {
+                    let edge = x < start || x >= start + stroke;
+                    let value = if edge { 128 } else { 255 };
+                    for y in top..bottom {
+                        image.put_pixel(x, y, image::Rgb([value, value, value]));
+                    }
+                }
+            }
+        }
+
+        let direct = test_model::model();
+        assert_ne!(direct.image_to_text(&image, false).unwrap(), text);
+
+        let supersampled = test_model::model().with_preprocess_options(PreprocessOptions {
+            supersample_height: Some(64),
+            ..PreprocessOptions::default()
+        });
+        assert_eq!(supersampled.image_to_text(&image, false).unwrap(), text);
+    }
+
+    #[test]
+    fn batch_inference_matches_single_inference() {
+        let model = test_model::model();
+        let inputs: Vec<_> = ["abc", "cab", "bb a c"].iter()
+            .map(|text| preprocess::pre_process(preprocess::to_gray(&test_model::text_image(text))).0)
+            .collect();
+
+        let batch = model.inference_strings_batch(&inputs).unwrap();
+        assert_eq!(batch, vec!["abc", "cab", "bbac"]);
+        assert_eq!(model.stats().invoke_count, 3);
+        for (input, text) in inputs.iter().zip(batch.iter()) {
+            assert_eq!(&model.inference_string(input).unwrap(), text);
+        }
+
+        assert!(model.inference_strings_batch(&[]).unwrap().is_empty());
+        assert!(model.inference_strings_batch(&[ImageBuffer::new(100, 32)]).is_err());
+    }
+
+    #[test]
+    fn load_a_model_from_paths() {
+        let dir = std::env::temp_dir().join(format!("yas_from_paths_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (model_path, labels_path, wrong_labels_path) = (dir.join("model.onnx"), dir.join("labels.json"), dir.join("wrong.json"));
+        std::fs::write(&model_path, test_model::onnx(false, false)).unwrap();
+        std::fs::write(&labels_path, LABELS).unwrap();
+        std::fs::write(&wrong_labels_path, r#"{"0": "-", "1": "a", "2": "b"}"#).unwrap();
+
+        let model = YasOCRModel::from_paths(&model_path, &labels_path);
+        let wrong_size = YasOCRModel::from_paths(&model_path, &wrong_labels_path);
+        let missing = YasOCRModel::from_paths(&dir.join("missing.onnx"), &labels_path);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(model.unwrap().image_to_text(&test_model::text_image("abc"), false).unwrap(), "abc");
+        assert!(wrong_size.err().unwrap().to_string().contains("labels"));
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn dynamic_width_model_reads_long_lines_at_their_width() {
+        // squeezed into 384 columns by the fixed width model, see `retry_width_recovers_a_truncated_line`
+        let text = "abc".repeat(11);
+        let model = YasOCRModel::new(&test_model::onnx(true, false), LABELS).unwrap();
+
+        let (recognized, input) = model.image_to_text_with_input(&test_model::text_image(&text)).unwrap();
+        assert_eq!(recognized, text);
+        assert!(input.width() > preprocess::DEFAULT_INPUT_WIDTH);
+        assert_eq!(input.width() % 8, 0);
+    }
+
+    #[test]
+    fn tract_runs_only_on_the_cpu() {
+        let onnx = test_model::onnx(false, false);
+        assert_eq!(OcrBackend::default(), OcrBackend::Cpu);
+
+        let cpu = YasOCRModelBuilder::new(&onnx, LABELS).backend(OcrBackend::Cpu).build().unwrap();
+        assert_eq!(cpu.image_to_text(&test_model::text_image("abc"), false).unwrap(), "abc");
+        for backend in [OcrBackend::Cuda, OcrBackend::DirectML] {
+            assert!(YasOCRModelBuilder::new(&onnx, LABELS).backend(backend).build().is_err());
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use super::yas_ocr_model::{OcrBackend, YasOCRModel};
+use super::preprocess::DEFAULT_INPUT_WIDTH;
+
+pub struct YasOCRModelBuilder<'a> {
+    pub(crate) model: &'a [u8],
+    pub(crate) index_to_word: &'a str,
+    pub(crate) deterministic: bool,
+    pub(crate) width: u32,
+    pub(crate) retry_width: Option<u32>,
+    pub(crate) input_clamp: Option<(f32, f32)>,
+    pub(crate) backend: OcrBackend,
+}
+
+impl<'a> YasOCRModelBuilder<'a> {
+    pub fn new(model: &'a [u8], index_to_word: &'a str) -> Self {
+        YasOCRModelBuilder {
+            model,
+            index_to_word,
+            deterministic: false,
+            width: DEFAULT_INPUT_WIDTH,
+            retry_width: None,
+            input_clamp: None,
+            backend: OcrBackend::Cpu,
+        }
+    }
+
+    /// force single-threaded execution, so the same input always produces bit-identical output
+    /// this trades speed for reproducibility. tract already runs single-threaded, for ort this limits the intra-op
+    /// thread pool to one thread
+    pub fn deterministic(&mut self) -> &mut Self {
+        self.deterministic = true;
+        self
+    }
+
+    /// the width of the model input, 384 by default
+    /// ignored by models whose onnx graph declares a dynamic width, their input is as wide as the text, see
+    /// `preprocess::MAX_NATIVE_WIDTH`
+    pub fn width(&mut self, width: u32) -> &mut Self {
+        self.width = width;
+        self
+    }
+
+    /// when the last timestep of the output is not blank, the text probably doesn't fit in the input, recognize it
+    /// again with a second model of this (larger) width and keep the longer result. this compiles the model twice
+    pub fn retry_width(&mut self, width: u32) -> &mut Self {
+        self.retry_width = Some(width);
+        self
+    }
+
+    /// clamp the pixels of the input into `[lo, hi]` (e.g. the `[0, 1]` the model is trained on) before inference
+    /// guards against out of range values from preprocessing, NaN is replaced with `lo`. off by default
+    pub fn input_clamp(&mut self, lo: f32, hi: f32) -> &mut Self {
+        self.input_clamp = Some((lo, hi));
+        self
+    }
+
+    /// run the model on a gpu with onnxruntime, the cpu by default
+    /// the gpu backends require the `ort` feature and the corresponding `cuda` or `directml` feature
+    pub fn backend(&mut self, backend: OcrBackend) -> &mut Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn build(&self) -> Result<YasOCRModel> {
+        YasOCRModel::from_builder(self)
+    }
+}
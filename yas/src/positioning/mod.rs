@@ -3,9 +3,11 @@ mod scalable;
 mod rect;
 mod pos;
 mod shape;
+mod viewport;
 
 pub use scalable::Scalable;
 pub use size::Size;
 pub use pos::Pos;
 pub use rect::Rect;
 pub use shape::Shape3D;
+pub use viewport::{letterbox, Viewport, MIN_VIEWPORT_SIZE};
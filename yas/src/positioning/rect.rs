@@ -83,6 +83,7 @@ convert_rect_type!(f64, i32);
 convert_rect_type!(f64, usize);
 convert_rect_type!(f64, u32);
 convert_rect_type!(u32, usize);
+convert_rect_type!(u32, f64);
 convert_rect_type!(i32, usize);
 convert_rect_type!(i32, f64);
 convert_rect_type!(i32, u32);
@@ -0,0 +1,103 @@
+use crate::positioning::{Pos, Rect, Size};
+
+/// the least viewport the ui is positioned in at runtime, the crops of smaller ones are too small to recognize
+pub const MIN_VIEWPORT_SIZE: Size<usize> = Size { width: 1280, height: 720 };
+
+/// the area of a window the ui of another aspect ratio is laid out in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// the area of the window the ui keeps the aspect ratio in
+    pub rect: Rect<f64>,
+    /// the width a wider window has more than `rect`. the ui is anchored to the edges of such a window,
+    /// so what is right of the middle of `rect` moves right by this
+    pub stretch: f64,
+}
+
+/// the viewport of the ui of `aspect` (width / height) in a window.
+/// a narrower window has the ui letterboxed in the middle, a wider one has it anchored to the left and right edges
+pub fn letterbox(window: Size<f64>, aspect: f64) -> Viewport {
+    if window.width / window.height > aspect {
+        let width = window.height * aspect;
+        Viewport {
+            rect: Rect::new(0.0, 0.0, width, window.height),
+            stretch: window.width - width,
+        }
+    } else {
+        let height = window.width / aspect;
+        Viewport {
+            rect: Rect::new(0.0, (window.height - height) / 2.0, window.width, height),
+            stretch: 0.0,
+        }
+    }
+}
+
+impl Viewport {
+    /// the window x of a normalized x, of which the ui is anchored to the edge `anchor` is closer to
+    fn x(&self, x: f64, anchor: f64) -> f64 {
+        let stretch = if anchor > 0.5 { self.stretch } else { 0.0 };
+        self.rect.left + stretch + x * self.rect.width
+    }
+}
+
+impl Rect<f64> {
+    /// the rect in fractions of `size`
+    pub fn normalize(&self, size: Size<f64>) -> Rect<f64> {
+        Rect::new(self.left / size.width, self.top / size.height, self.width / size.width, self.height / size.height)
+    }
+
+    /// a normalized rect placed in `viewport`, by the edge its centre is closer to
+    pub fn denormalize(&self, viewport: Viewport) -> Rect<f64> {
+        Rect::new(
+            viewport.x(self.left, self.left + self.width / 2.0),
+            viewport.rect.top + self.top * viewport.rect.height,
+            self.width * viewport.rect.width,
+            self.height * viewport.rect.height,
+        )
+    }
+}
+
+impl Pos<f64> {
+    /// the pos in fractions of `size`
+    pub fn normalize(&self, size: Size<f64>) -> Pos<f64> {
+        Pos::new(self.x / size.width, self.y / size.height)
+    }
+
+    /// a normalized pos placed in `viewport`, by the edge it is closer to
+    pub fn denormalize(&self, viewport: Viewport) -> Pos<f64> {
+        Pos::new(viewport.x(self.x, self.x), viewport.rect.top + self.y * viewport.rect.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_narrower_window_is_letterboxed_in_the_middle() {
+        let viewport = letterbox(Size::new(1600.0, 1000.0), 16.0 / 9.0);
+        assert_eq!(viewport.rect, Rect::new(0.0, 50.0, 1600.0, 900.0));
+        assert_eq!(viewport.stretch, 0.0);
+    }
+
+    #[test]
+    fn a_wider_window_is_anchored_to_the_edges() {
+        let viewport = letterbox(Size::new(2560.0, 1080.0), 16.0 / 9.0);
+        assert_eq!(viewport.rect, Rect::new(0.0, 0.0, 1920.0, 1080.0));
+        assert_eq!(viewport.stretch, 640.0);
+
+        // the list on the left stays at the left edge, the panel on the right at the right edge
+        let list = Rect::new(0.1, 0.2, 0.3, 0.5).denormalize(viewport);
+        assert_eq!(list, Rect::new(192.0, 216.0, 576.0, 540.0));
+        let panel = Rect::new(0.7, 0.1, 0.25, 0.8).denormalize(viewport);
+        assert_eq!(panel.left + panel.width, 2560.0 - 0.05 * 1920.0);
+        assert_eq!(Pos::new(0.9, 0.5).denormalize(viewport), Pos::new(1728.0 + 640.0, 540.0));
+        assert_eq!(Pos::new(0.1, 0.5).denormalize(viewport), Pos::new(192.0, 540.0));
+    }
+
+    #[test]
+    fn normalize_and_denormalize_in_the_same_window() {
+        let size = Size::new(1920.0, 1080.0);
+        let rect = Rect::new(100.0, 200.0, 300.0, 400.0);
+        assert_eq!(rect.normalize(size).denormalize(letterbox(size, 16.0 / 9.0)), rect);
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::time::{Duration, SystemTime};
 use anyhow::Result;
 
+#[derive(Default)]
 pub struct Profiler {
     scope: Vec<String>,
 
@@ -32,7 +33,7 @@ impl Profiler {
     }
 
     pub fn end(&mut self, name: &str) -> Result<()> {
-        if self.scope.len() == 0 {
+        if self.scope.is_empty() {
             panic!("Profiler called end without begin");
         }
         let len = self.scope.len();
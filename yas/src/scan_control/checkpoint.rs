@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// the items scanned so far, saved while scanning so a scan can be resumed after it's aborted or crashed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Checkpoint<T> {
+    /// how many items of the repository are handled, in scan order
+    pub position: usize,
+    pub items: Vec<T>,
+}
+
+impl<T> Default for Checkpoint<T> {
+    fn default() -> Self {
+        Checkpoint {
+            position: 0,
+            items: Vec::new(),
+        }
+    }
+}
+
+impl<T> Checkpoint<T> {
+    /// in the temp directory, e.g. `yas_genshin_artifact_checkpoint.json`
+    pub fn default_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("yas_{}_checkpoint.json", name))
+    }
+
+    pub fn remove<P: AsRef<Path>>(path: P) -> Result<()> {
+        let path = path.as_ref();
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Serialize> Checkpoint<T> {
+    /// written beside and then renamed, so a crash while saving leaves the last checkpoint
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let temp = path.with_extension("tmp");
+        std::fs::write(&temp, serde_json::to_vec(self)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+}
+
+impl<T: DeserializeOwned> Checkpoint<T> {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Checkpoint<T>> {
+        let path = path.as_ref();
+        let content = std::fs::read(path)
+            .map_err(|e| anyhow!("无法读取扫描进度 {}: {}", path.display(), e))?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+}
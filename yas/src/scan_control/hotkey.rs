@@ -0,0 +1,141 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::Result;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hotkey {
+    /// F8
+    Pause,
+    /// F9
+    Abort,
+}
+
+const HOTKEYS: [Hotkey; 2] = [Hotkey::Pause, Hotkey::Abort];
+
+impl Display for Hotkey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Hotkey::Pause => write!(f, "F8"),
+            Hotkey::Abort => write!(f, "F9"),
+        }
+    }
+}
+
+/// the hotkeys pressed since the last poll, whichever window is focused
+pub(crate) struct HotkeyPoller {
+    keyboard: Keyboard,
+    down: [bool; HOTKEYS.len()],
+}
+
+impl HotkeyPoller {
+    pub fn new() -> Result<HotkeyPoller> {
+        Ok(HotkeyPoller {
+            keyboard: Keyboard::new()?,
+            down: [false; HOTKEYS.len()],
+        })
+    }
+
+    pub fn newly_pressed(&mut self) -> Vec<Hotkey> {
+        let mut pressed = Vec::new();
+        for (i, hotkey) in HOTKEYS.iter().enumerate() {
+            let down = self.keyboard.is_down(*hotkey);
+            if down && !self.down[i] {
+                pressed.push(*hotkey);
+            }
+            self.down[i] = down;
+        }
+        pressed
+    }
+}
+
+#[cfg(windows)]
+struct Keyboard;
+
+#[cfg(windows)]
+impl Keyboard {
+    fn new() -> Result<Keyboard> {
+        Ok(Keyboard)
+    }
+
+    fn is_down(&self, hotkey: Hotkey) -> bool {
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, VK_F8, VK_F9};
+
+        let key = match hotkey {
+            Hotkey::Pause => VK_F8,
+            Hotkey::Abort => VK_F9,
+        };
+        // the most significant bit is set while the key is down
+        unsafe { GetAsyncKeyState(key as i32) < 0 }
+    }
+}
+
+/// the global key state of x11, which includes the keys of xwayland windows
+#[cfg(all(target_os = "linux", feature = "x11"))]
+struct Keyboard {
+    conn: xcb::Connection,
+    /// the keycodes of each hotkey
+    keycodes: Vec<Vec<u8>>,
+}
+
+#[cfg(all(target_os = "linux", feature = "x11"))]
+impl Keyboard {
+    fn new() -> Result<Keyboard> {
+        use xcb::x;
+
+        const XK_F8: u32 = 0xffc5;
+        const XK_F9: u32 = 0xffc6;
+
+        let (conn, _) = xcb::Connection::connect(None)?;
+        let setup = conn.get_setup();
+        let (min_keycode, max_keycode) = (setup.min_keycode(), setup.max_keycode());
+        let reply = conn.wait_for_reply(conn.send_request(&x::GetKeyboardMapping {
+            first_keycode: min_keycode,
+            count: max_keycode - min_keycode + 1,
+        }))?;
+
+        let per_keycode = reply.keysyms_per_keycode() as usize;
+        let keycodes_of = |keysym: u32| -> Vec<u8> {
+            reply.keysyms()
+                .chunks(per_keycode.max(1))
+                .enumerate()
+                .filter(|(_, keysyms)| keysyms.contains(&keysym))
+                .map(|(i, _)| min_keycode + i as u8)
+                .collect()
+        };
+        let keycodes = HOTKEYS.iter()
+            .map(|hotkey| match hotkey {
+                Hotkey::Pause => keycodes_of(XK_F8),
+                Hotkey::Abort => keycodes_of(XK_F9),
+            })
+            .collect();
+
+        Ok(Keyboard { conn, keycodes })
+    }
+
+    fn is_down(&self, hotkey: Hotkey) -> bool {
+        let index = HOTKEYS.iter().position(|h| *h == hotkey).unwrap();
+        let Ok(reply) = self.conn.wait_for_reply(self.conn.send_request(&xcb::x::QueryKeymap {})) else {
+            return false;
+        };
+        let keys = reply.keys();
+        self.keycodes[index].iter().any(|&keycode| keys[keycode as usize / 8] & (1 << (keycode % 8)) != 0)
+    }
+}
+
+#[cfg(any(target_os = "macos", all(target_os = "linux", not(feature = "x11"))))]
+struct Keyboard;
+
+#[cfg(any(target_os = "macos", all(target_os = "linux", not(feature = "x11"))))]
+impl Keyboard {
+    fn new() -> Result<Keyboard> {
+        if cfg!(target_os = "linux") {
+            Err(anyhow::anyhow!("全局快捷键需要启用 x11 特性"))
+        } else {
+            Err(anyhow::anyhow!("macOS 暂不支持全局快捷键"))
+        }
+    }
+
+    fn is_down(&self, _hotkey: Hotkey) -> bool {
+        false
+    }
+}
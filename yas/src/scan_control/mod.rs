@@ -0,0 +1,7 @@
+pub use checkpoint::Checkpoint;
+pub use hotkey::Hotkey;
+pub use scan_control::{ControlState, ScanControl};
+
+mod checkpoint;
+mod hotkey;
+mod scan_control;
@@ -0,0 +1,185 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::Duration;
+
+use log::{info, warn};
+
+use crate::scan_control::hotkey::{Hotkey, HotkeyPoller};
+use crate::utils;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlState {
+    Running,
+    Paused,
+    Aborted,
+}
+
+const RUNNING: u8 = 0;
+const PAUSED: u8 = 1;
+const ABORTED: u8 = 2;
+
+/// pauses, resumes or aborts a running scan, by the hotkeys or by the front-ends
+/// the scan asks `should_stop` before every item
+pub struct ScanControl {
+    state: AtomicU8,
+    listening: AtomicBool,
+}
+
+static GLOBAL: ScanControl = ScanControl::new();
+
+impl Default for ScanControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScanControl {
+    pub const fn new() -> ScanControl {
+        ScanControl {
+            state: AtomicU8::new(RUNNING),
+            listening: AtomicBool::new(false),
+        }
+    }
+
+    /// the one the hotkeys control
+    pub fn global() -> &'static ScanControl {
+        &GLOBAL
+    }
+
+    pub fn state(&self) -> ControlState {
+        match self.state.load(Ordering::SeqCst) {
+            PAUSED => ControlState::Paused,
+            ABORTED => ControlState::Aborted,
+            _ => ControlState::Running,
+        }
+    }
+
+    pub fn pause(&self) {
+        if self.state.compare_exchange(RUNNING, PAUSED, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+            info!("扫描已暂停，按 {} 继续", Hotkey::Pause);
+        }
+    }
+
+    pub fn resume(&self) {
+        if self.state.compare_exchange(PAUSED, RUNNING, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+            info!("扫描继续");
+        }
+    }
+
+    pub fn toggle_pause(&self) {
+        match self.state() {
+            ControlState::Running => self.pause(),
+            ControlState::Paused => self.resume(),
+            ControlState::Aborted => {},
+        }
+    }
+
+    pub fn abort(&self) {
+        if self.state.swap(ABORTED, Ordering::SeqCst) != ABORTED {
+            info!("扫描已终止");
+        }
+    }
+
+    /// running again for the next scan
+    pub fn reset(&self) {
+        self.state.store(RUNNING, Ordering::SeqCst);
+    }
+
+    /// blocks while paused, false if the scan is aborted
+    pub fn wait_while_paused(&self) -> bool {
+        loop {
+            match self.state() {
+                ControlState::Running => return true,
+                ControlState::Aborted => return false,
+                ControlState::Paused => std::thread::sleep(Duration::from_millis(50)),
+            }
+        }
+    }
+
+    /// true if the scan should stop before the next item: aborted by a hotkey, a front-end, or the right mouse
+    /// button. blocks while paused
+    pub fn should_stop(&self) -> bool {
+        if utils::is_rmb_down() {
+            self.abort();
+        }
+        !self.wait_while_paused()
+    }
+
+    /// at the start of a scan: running again, and listening to the hotkeys
+    pub fn start(&'static self) {
+        self.reset();
+        if self.listen_hotkeys() {
+            info!("按 {} 暂停/继续扫描，按 {} 或鼠标右键终止扫描", Hotkey::Pause, Hotkey::Abort);
+        } else {
+            info!("使用鼠标右键终止扫描");
+        }
+    }
+
+    /// poll the hotkeys in a thread for the rest of the process, only the first call starts it
+    /// false if the hotkeys can't be listened to
+    pub fn listen_hotkeys(&'static self) -> bool {
+        if self.listening.swap(true, Ordering::SeqCst) {
+            return true;
+        }
+
+        let mut poller = match HotkeyPoller::new() {
+            Ok(poller) => poller,
+            Err(e) => {
+                warn!("无法监听快捷键: {}", e);
+                self.listening.store(false, Ordering::SeqCst);
+                return false;
+            },
+        };
+
+        std::thread::spawn(move || loop {
+            // a hotkey is handled once when it's pressed, not while it's held
+            for hotkey in poller.newly_pressed() {
+                match hotkey {
+                    Hotkey::Pause => self.toggle_pause(),
+                    Hotkey::Abort => self.abort(),
+                }
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_new_control_is_running() {
+        let control = ScanControl::default();
+        assert_eq!(control.state(), ControlState::Running);
+        assert!(!control.should_stop());
+    }
+
+    #[test]
+    fn an_aborted_scan_stops_until_reset() {
+        let control = ScanControl::default();
+        control.pause();
+        assert_eq!(control.state(), ControlState::Paused);
+        control.abort();
+        assert!(control.should_stop());
+        control.toggle_pause();
+        assert_eq!(control.state(), ControlState::Aborted);
+
+        control.reset();
+        assert!(!control.should_stop());
+    }
+
+    #[test]
+    fn a_paused_scan_waits_for_resume() {
+        static CONTROL: ScanControl = ScanControl::new();
+        CONTROL.pause();
+
+        let resumer = std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_millis(100));
+            CONTROL.toggle_pause();
+        });
+        assert!(!CONTROL.should_stop());
+        assert_eq!(CONTROL.state(), ControlState::Running);
+        resumer.join().unwrap();
+    }
+}
@@ -0,0 +1,44 @@
+/// the delay after a scroll, lengthened when the frame is still moving after it, and shortened while it's settled
+#[derive(Debug, Clone)]
+pub struct AdaptiveDelay {
+    delay: f64,
+    min: f64,
+    max: f64,
+
+    // consecutive scrolls which were settled after the delay
+    settled_streak: usize,
+}
+
+impl AdaptiveDelay {
+    /// how many settled scrolls in a row before the delay is shortened
+    const SHRINK_AFTER: usize = 3;
+    const SHRINK: f64 = 0.9;
+    const GROW: f64 = 1.5;
+
+    pub fn new(initial: u32, min: u32, max: u32) -> AdaptiveDelay {
+        AdaptiveDelay {
+            delay: (initial.clamp(min, max)) as f64,
+            min: min as f64,
+            max: max as f64,
+            settled_streak: 0,
+        }
+    }
+
+    /// in ms
+    pub fn get(&self) -> u32 {
+        self.delay.round() as u32
+    }
+
+    pub fn settled(&mut self) {
+        self.settled_streak += 1;
+        if self.settled_streak >= Self::SHRINK_AFTER {
+            self.settled_streak = 0;
+            self.delay = (self.delay * Self::SHRINK).max(self.min);
+        }
+    }
+
+    pub fn unsettled(&mut self) {
+        self.settled_streak = 0;
+        self.delay = (self.delay * Self::GROW).min(self.max);
+    }
+}
@@ -0,0 +1,98 @@
+use image::RgbImage;
+
+/// a grayscale thumbnail of the item grid, to tell whether and how far it moved between two captures
+#[derive(Debug, Clone)]
+pub struct GridFrame {
+    width: usize,
+    height: usize,
+    scale: usize,
+    pixels: Vec<u8>,
+}
+
+/// how many rows the grid moved up between two frames
+#[derive(Debug, Clone, Copy)]
+pub struct RowShift {
+    pub rows: usize,
+    /// the mean difference of the overlap of the frames, at this shift
+    pub difference: f64,
+    /// whether another shift matches almost as well, as when the items look alike
+    pub ambiguous: bool,
+}
+
+impl GridFrame {
+    /// the mean difference below which two frames are the same
+    pub const STABLE_DIFFERENCE: f64 = 2.0;
+
+    /// downscaled by `scale` in both directions, each pixel being the mean of the block
+    pub fn new(image: &RgbImage, scale: u32) -> GridFrame {
+        let scale = scale.max(1) as usize;
+        let width = image.width() as usize / scale;
+        let height = image.height() as usize / scale;
+
+        let mut pixels = vec![0; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0_u32;
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let p = image.get_pixel((x * scale + dx) as u32, (y * scale + dy) as u32);
+                        sum += (p.0[0] as u32 * 299 + p.0[1] as u32 * 587 + p.0[2] as u32 * 114) / 1000;
+                    }
+                }
+                pixels[y * width + x] = (sum / (scale * scale) as u32) as u8;
+            }
+        }
+
+        GridFrame { width, height, scale, pixels }
+    }
+
+    /// the mean absolute difference of the frames, with `next` moved up by `offset` thumbnail rows
+    fn difference_at(&self, next: &GridFrame, offset: usize) -> Option<f64> {
+        let width = self.width.min(next.width);
+        let height = self.height.min(next.height);
+        if offset >= height || width == 0 {
+            return None;
+        }
+
+        let mut sum = 0_u64;
+        for y in 0..height - offset {
+            let a = &self.pixels[(y + offset) * self.width..][..width];
+            let b = &next.pixels[y * next.width..][..width];
+            sum += a.iter().zip(b).map(|(&a, &b)| (a as i32 - b as i32).unsigned_abs() as u64).sum::<u64>();
+        }
+        Some(sum as f64 / (width * (height - offset)) as f64)
+    }
+
+    pub fn difference(&self, other: &GridFrame) -> f64 {
+        self.difference_at(other, 0).unwrap_or(f64::MAX)
+    }
+
+    pub fn is_same(&self, other: &GridFrame) -> bool {
+        self.difference(other) < Self::STABLE_DIFFERENCE
+    }
+
+    /// the whole rows of `pitch` pixels which the grid moved up from `self` to `next`, at most `max_rows`,
+    /// none if the frames don't overlap at any of them
+    pub fn row_shift(&self, next: &GridFrame, pitch: f64, max_rows: usize) -> Option<RowShift> {
+        let pitch = pitch / self.scale as f64;
+        let mut candidates = Vec::new();
+        for rows in 0..=max_rows {
+            let offset = (pitch * rows as f64).round() as usize;
+            // a pixel of tolerance, as the rows are aligned by the flag color only
+            let difference = [offset.saturating_sub(1), offset, offset + 1]
+                .into_iter()
+                .filter_map(|offset| self.difference_at(next, offset))
+                .fold(f64::MAX, f64::min);
+            if difference < f64::MAX {
+                candidates.push((rows, difference));
+            }
+        }
+
+        candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let &(rows, difference) = candidates.first()?;
+        let ambiguous = candidates
+            .get(1)
+            .is_some_and(|&(_, second)| second < difference * 1.5 + Self::STABLE_DIFFERENCE);
+        Some(RowShift { rows, difference, ambiguous })
+    }
+}
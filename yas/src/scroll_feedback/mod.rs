@@ -0,0 +1,7 @@
+pub use adaptive_delay::AdaptiveDelay;
+pub use grid_frame::{GridFrame, RowShift};
+pub use settle::wait_until_settled;
+
+mod adaptive_delay;
+mod grid_frame;
+mod settle;
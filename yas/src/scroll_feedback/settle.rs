@@ -0,0 +1,74 @@
+use anyhow::{anyhow, Result};
+use log::info;
+
+use crate::scroll_feedback::{AdaptiveDelay, GridFrame};
+use crate::utils;
+
+// in ms
+const SETTLE_INTERVAL: u32 = 15;
+const MAX_SETTLE_CAPTURES: usize = 20;
+
+/// capture the grid until it stops moving, and lengthen the `delay` of the scroll if it's still moving after it
+pub fn wait_until_settled<F>(delay: &mut AdaptiveDelay, mut capture: F) -> Result<GridFrame>
+where
+    F: FnMut() -> Result<GridFrame>,
+{
+    let mut frame = capture()?;
+    for i in 0..MAX_SETTLE_CAPTURES {
+        utils::sleep(SETTLE_INTERVAL);
+        let next = capture()?;
+        let is_settled = frame.is_same(&next);
+        if i == 0 {
+            if is_settled {
+                delay.settled();
+            } else {
+                delay.unsettled();
+                info!("翻页停顿时间调整为 {}ms", delay.get());
+            }
+        }
+        if is_settled {
+            return Ok(next);
+        }
+        frame = next;
+    }
+
+    Err(anyhow!("翻页后画面未能稳定"))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage};
+
+    use super::*;
+
+    fn frame(gray: u8) -> GridFrame {
+        GridFrame::new(&RgbImage::from_pixel(16, 16, Rgb([gray, gray, gray])), 4)
+    }
+
+    #[test]
+    fn a_settled_grid_keeps_the_delay() {
+        let mut delay = AdaptiveDelay::new(80, 20, 400);
+        assert!(wait_until_settled(&mut delay, || Ok(frame(100))).is_ok());
+        assert_eq!(delay.get(), 80);
+    }
+
+    #[test]
+    fn a_moving_grid_lengthens_the_delay() {
+        let mut delay = AdaptiveDelay::new(80, 20, 400);
+        let mut frames = [0, 50, 100, 100].into_iter();
+        let settled = wait_until_settled(&mut delay, || Ok(frame(frames.next().unwrap()))).unwrap();
+        assert!(settled.is_same(&frame(100)));
+        assert_eq!(delay.get(), 120);
+    }
+
+    #[test]
+    fn a_grid_which_never_settles_is_an_error() {
+        let mut delay = AdaptiveDelay::new(80, 20, 400);
+        let mut gray = 0_u8;
+        let result = wait_until_settled(&mut delay, || {
+            gray = gray.wrapping_add(50);
+            Ok(frame(gray))
+        });
+        assert!(result.is_err());
+    }
+}
@@ -1,31 +1,109 @@
-use enigo::{Enigo, MouseButton, MouseControllable};
+use enigo::{Enigo, Key, KeyboardControllable, MouseButton, MouseControllable};
+#[cfg(feature = "x11")]
+use log::{info, warn};
+
+#[cfg(feature = "x11")]
+use super::uinput::UinputDevice;
+
+enum Backend {
+    /// xdo of x11, the windows of wine and proton are also controlled by it under xwayland
+    Xdo(Enigo),
+    /// uinput, for wayland sessions, where xdo may not reach the game
+    #[cfg(feature = "x11")]
+    Uinput(UinputDevice),
+}
 
 pub struct LinuxControl {
-    enigo: Enigo,
+    backend: Backend,
+}
+
+/// the size of the root window of x11, which spans all the screens
+#[cfg(feature = "x11")]
+fn screen_size() -> anyhow::Result<(i32, i32)> {
+    let (conn, screen_num) = xcb::Connection::connect(None)?;
+    let screen = conn.get_setup()
+        .roots()
+        .nth(screen_num as usize)
+        .ok_or_else(|| anyhow::anyhow!("找不到 X 屏幕"))?;
+    Ok((screen.width_in_pixels() as i32, screen.height_in_pixels() as i32))
 }
 
 impl LinuxControl {
+    #[cfg(feature = "x11")]
+    fn uinput() -> anyhow::Result<UinputDevice> {
+        let (width, height) = screen_size()?;
+        UinputDevice::new(width, height)
+    }
+
+    /// uinput on wayland with `x11`, xdo otherwise
     pub fn new() -> LinuxControl {
-        LinuxControl {
-            enigo: Enigo::new(),
+        #[cfg(feature = "x11")]
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            match Self::uinput() {
+                Ok(device) => {
+                    info!("使用 uinput 控制鼠标和键盘");
+                    return LinuxControl { backend: Backend::Uinput(device) };
+                },
+                Err(e) => warn!("无法使用 uinput（{}），使用 xdo 控制鼠标和键盘", e),
+            }
         }
+
+        LinuxControl { backend: Backend::Xdo(Enigo::new()) }
     }
 
     pub fn mouse_move_to(&mut self, x: i32, y: i32) -> anyhow::Result<()> {
-        self.enigo.mouse_move_to(x, y);
+        match &mut self.backend {
+            Backend::Xdo(enigo) => enigo.mouse_move_to(x, y),
+            #[cfg(feature = "x11")]
+            Backend::Uinput(device) => device.mouse_move_to(x, y)?,
+        }
 
         anyhow::Ok(())
     }
 
     pub fn mouse_click(&mut self) -> anyhow::Result<()> {
-        self.enigo.mouse_click(MouseButton::Left);
+        match &mut self.backend {
+            Backend::Xdo(enigo) => enigo.mouse_click(MouseButton::Left),
+            #[cfg(feature = "x11")]
+            Backend::Uinput(device) => device.mouse_click()?,
+        }
 
         anyhow::Ok(())
     }
 
     pub fn mouse_scroll(&mut self, amount: i32, _try_find: bool) -> anyhow::Result<()> {
-        self.enigo.mouse_scroll_y(amount);
+        match &mut self.backend {
+            Backend::Xdo(enigo) => enigo.mouse_scroll_y(amount),
+            #[cfg(feature = "x11")]
+            Backend::Uinput(device) => device.mouse_scroll(amount)?,
+        }
+
+        anyhow::Ok(())
+    }
+
+    pub fn key_click(&mut self, key: char) -> anyhow::Result<()> {
+        match &mut self.backend {
+            Backend::Xdo(enigo) => enigo.key_click(Key::Layout(key)),
+            #[cfg(feature = "x11")]
+            Backend::Uinput(device) => device.key_click(key)?,
+        }
+
+        anyhow::Ok(())
+    }
+
+    pub fn key_escape(&mut self) -> anyhow::Result<()> {
+        match &mut self.backend {
+            Backend::Xdo(enigo) => enigo.key_click(Key::Escape),
+            #[cfg(feature = "x11")]
+            Backend::Uinput(device) => device.key_escape()?,
+        }
 
         anyhow::Ok(())
     }
 }
+
+impl Default for LinuxControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1 +1,3 @@
 pub mod linux_control;
+#[cfg(feature = "x11")]
+mod uinput;
@@ -0,0 +1,158 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+
+use anyhow::{anyhow, Result};
+
+// see linux/uinput.h and linux/input-event-codes.h
+const UI_SET_EVBIT: u64 = 0x40045564;
+const UI_SET_KEYBIT: u64 = 0x40045565;
+const UI_SET_RELBIT: u64 = 0x40045566;
+const UI_SET_ABSBIT: u64 = 0x40045567;
+const UI_DEV_CREATE: u64 = 0x5501;
+const UI_DEV_DESTROY: u64 = 0x5502;
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+const SYN_REPORT: u16 = 0;
+const REL_WHEEL: u16 = 0x08;
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const BTN_LEFT: u16 = 0x110;
+const KEY_ESC: u16 = 1;
+const BUS_VIRTUAL: u16 = 0x06;
+
+/// the key codes of the rows of a qwerty keyboard, from the digits
+const KEY_ROWS: [(&str, u16); 4] = [
+    ("1234567890", 2),
+    ("qwertyuiop", 16),
+    ("asdfghjkl", 30),
+    ("zxcvbnm", 44),
+];
+
+fn key_code(key: char) -> Option<u16> {
+    let key = key.to_ascii_lowercase();
+    KEY_ROWS.iter().find_map(|(row, first)| {
+        row.find(key).map(|i| first + i as u16)
+    })
+}
+
+/// a virtual mouse and keyboard created by uinput, which works under any compositor of wayland.
+/// the pointer is absolute, spanning `screen_size`. it requires the permission to write /dev/uinput
+pub struct UinputDevice {
+    file: File,
+}
+
+impl UinputDevice {
+    fn ioctl(&self, request: u64, arg: libc::c_int) -> Result<()> {
+        if unsafe { libc::ioctl(self.file.as_raw_fd(), request as _, arg) } < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+
+    pub fn new(screen_width: i32, screen_height: i32) -> Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uinput")
+            .map_err(|e| anyhow!("无法打开 /dev/uinput: {}", e))?;
+        let mut device = UinputDevice { file };
+
+        for ev in [EV_SYN, EV_KEY, EV_REL, EV_ABS] {
+            device.ioctl(UI_SET_EVBIT, ev as _)?;
+        }
+        device.ioctl(UI_SET_KEYBIT, BTN_LEFT as _)?;
+        device.ioctl(UI_SET_KEYBIT, KEY_ESC as _)?;
+        for (row, first) in KEY_ROWS.iter() {
+            for i in 0..row.len() as u16 {
+                device.ioctl(UI_SET_KEYBIT, (first + i) as _)?;
+            }
+        }
+        device.ioctl(UI_SET_RELBIT, REL_WHEEL as _)?;
+        device.ioctl(UI_SET_ABSBIT, ABS_X as _)?;
+        device.ioctl(UI_SET_ABSBIT, ABS_Y as _)?;
+
+        let mut setup: libc::uinput_user_dev = unsafe { std::mem::zeroed() };
+        for (dst, src) in setup.name.iter_mut().zip(b"yas virtual input".iter()) {
+            *dst = *src as libc::c_char;
+        }
+        setup.id = libc::input_id { bustype: BUS_VIRTUAL, vendor: 0x1234, product: 0x5678, version: 1 };
+        setup.absmax[ABS_X as usize] = screen_width - 1;
+        setup.absmax[ABS_Y as usize] = screen_height - 1;
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &setup as *const libc::uinput_user_dev as *const u8,
+                std::mem::size_of::<libc::uinput_user_dev>(),
+            )
+        };
+        device.file.write_all(bytes)?;
+        device.ioctl(UI_DEV_CREATE, 0)?;
+
+        // the compositor takes a while to pick up a new device
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        Ok(device)
+    }
+
+    fn emit(&mut self, type_: u16, code: u16, value: i32) -> Result<()> {
+        let event = libc::input_event {
+            time: libc::timeval { tv_sec: 0, tv_usec: 0 },
+            type_,
+            code,
+            value,
+        };
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &event as *const libc::input_event as *const u8,
+                std::mem::size_of::<libc::input_event>(),
+            )
+        };
+        self.file.write_all(bytes)?;
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        self.emit(EV_SYN, SYN_REPORT, 0)
+    }
+
+    fn press(&mut self, code: u16) -> Result<()> {
+        self.emit(EV_KEY, code, 1)?;
+        self.sync()?;
+        self.emit(EV_KEY, code, 0)?;
+        self.sync()
+    }
+
+    pub fn mouse_move_to(&mut self, x: i32, y: i32) -> Result<()> {
+        self.emit(EV_ABS, ABS_X, x)?;
+        self.emit(EV_ABS, ABS_Y, y)?;
+        self.sync()
+    }
+
+    pub fn mouse_click(&mut self) -> Result<()> {
+        self.press(BTN_LEFT)
+    }
+
+    /// positive scrolls down, as enigo does
+    pub fn mouse_scroll(&mut self, amount: i32) -> Result<()> {
+        self.emit(EV_REL, REL_WHEEL, -amount)?;
+        self.sync()
+    }
+
+    pub fn key_click(&mut self, key: char) -> Result<()> {
+        let code = key_code(key).ok_or_else(|| anyhow!("不支持的按键: {}", key))?;
+        self.press(code)
+    }
+
+    pub fn key_escape(&mut self) -> Result<()> {
+        self.press(KEY_ESC)
+    }
+}
+
+impl Drop for UinputDevice {
+    fn drop(&mut self) {
+        let _ = self.ioctl(UI_DEV_DESTROY, 0);
+    }
+}
@@ -1,4 +1,4 @@
-use enigo::{Enigo, MouseControllable};
+use enigo::{Enigo, Key, KeyboardControllable, MouseControllable};
 
 use crate::system_control::system_control::SystemControl;
 use crate::utils;
@@ -32,6 +32,18 @@ impl MacOSControl {
         anyhow::Ok(())
     }
 
+    pub fn key_click(&mut self, key: char) -> anyhow::Result<()> {
+        self.enigo.key_click(Key::Layout(key));
+
+        anyhow::Ok(())
+    }
+
+    pub fn key_escape(&mut self) -> anyhow::Result<()> {
+        self.enigo.key_click(Key::Escape);
+
+        anyhow::Ok(())
+    }
+
     pub fn mac_scroll(&mut self, length: i32, delta: i32, times: i32) {
         let enigo = &mut self.enigo;
 
@@ -1,4 +1,4 @@
-use enigo::{Enigo, MouseControllable, MouseButton};
+use enigo::{Enigo, Key, KeyboardControllable, MouseControllable, MouseButton};
 
 pub struct WindowsSystemControl {
     enigo: Enigo,
@@ -26,4 +26,16 @@ impl WindowsSystemControl {
 
         anyhow::Ok(())
     }
+
+    pub fn key_click(&mut self, key: char) -> anyhow::Result<()> {
+        self.enigo.key_click(Key::Layout(key));
+
+        anyhow::Ok(())
+    }
+
+    pub fn key_escape(&mut self) -> anyhow::Result<()> {
+        self.enigo.key_click(Key::Escape);
+
+        anyhow::Ok(())
+    }
 }
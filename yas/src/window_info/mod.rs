@@ -6,4 +6,4 @@ mod from_window_info_repository;
 pub use from_window_info_repository::FromWindowInfoRepository;
 pub use window_info_repository::WindowInfoRepository;
 pub use window_info_type::WindowInfoType;
-pub use load_window_info::load_window_info_repo;
\ No newline at end of file
+pub use load_window_info::{load_window_info_repo, WindowInfoTemplatePerSize};
\ No newline at end of file
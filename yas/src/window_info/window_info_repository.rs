@@ -2,16 +2,19 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 use crate::game_info::{Platform, UI};
-use crate::positioning::{Pos, Scalable, Size};
+use crate::positioning::{letterbox, Pos, Scalable, Size, Viewport, MIN_VIEWPORT_SIZE};
 
 use crate::window_info::WindowInfoType;
 
+/// (window size, ui, platform) of a recorded value
+type WindowInfoKey = (Size<usize>, UI, Platform);
+
 /// Maps a window-info-key to a list of entries
 /// where entries consist of a size where the value is recorded, and accordingly a value
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct WindowInfoRepository {
     /// window info key -> (window size, ui, platform)
-    pub data: HashMap<String, HashMap<(Size<usize>, UI, Platform), WindowInfoType>>,
+    pub data: HashMap<String, HashMap<WindowInfoKey, WindowInfoType>>,
 }
 
 impl WindowInfoRepository {
@@ -24,14 +27,14 @@ impl WindowInfoRepository {
     pub fn add(&mut self, name: &str, size: Size<usize>, ui: UI, platform: Platform, value: WindowInfoType) {
         self.data
             .entry(String::from(name))
-            .or_insert(HashMap::new())
+            .or_default()
             .insert((size, ui, platform), value);
     }
 
     pub fn add_pos(&mut self, name: &str, size: Size<usize>, ui: UI, platform: Platform, value: Pos<f64>) {
         self.data
             .entry(String::from(name))
-            .or_insert(HashMap::new())
+            .or_default()
             .insert((size, ui, platform), WindowInfoType::Pos(value));
     }
 
@@ -39,7 +42,7 @@ impl WindowInfoRepository {
         for (key, data) in other.data.iter() {
             if self.data.contains_key(key) {
                 for (resolution, value) in data.iter() {
-                    self.data.get_mut(key).unwrap().insert(resolution.clone(), value.clone());
+                    self.data.get_mut(key).unwrap().insert(*resolution, *value);
                 }
             } else {
                 self.data.insert(key.clone(), data.clone());
@@ -66,6 +69,7 @@ impl WindowInfoRepository {
 
     /// Get window info by name and size
     /// if window size does not exists exactly, this function will search for the same resolution family and scale the result
+    /// failing that, the value of the closest aspect ratio is relocated into the viewport of the window, see `get_in_viewport`
     pub fn get_auto_scale<T>(&self, name: &str, window_size: Size<usize>, ui: UI, platform: Platform) -> Option<T> where WindowInfoType: TryInto<T> {
         if self.data.contains_key(name) {
             if self.data[name].contains_key(&(window_size, ui, platform)) {
@@ -82,9 +86,75 @@ impl WindowInfoRepository {
                         return value.scale(factor).try_into().ok();
                     }
                 }
+
+                return self.get_in_viewport(name, window_size, ui, platform)?.try_into().ok();
             }
         }
 
         None
     }
+
+    /// the recorded size whose aspect ratio is the closest to `window_size`, the largest one among the equally close
+    fn closest_aspect(&self, name: &str, window_size: Size<usize>, ui: UI, platform: Platform) -> Option<Size<usize>> {
+        let aspect = |size: &Size<usize>| size.width as f64 / size.height as f64;
+        let target = aspect(&window_size);
+
+        self.data.get(name)?
+            .keys()
+            .filter(|k| k.1 == ui && k.2 == platform)
+            .map(|k| k.0)
+            .min_by(|a, b| {
+                (aspect(a) - target).abs().total_cmp(&(aspect(b) - target).abs())
+                    .then(b.width.cmp(&a.width))
+            })
+    }
+
+    /// the value recorded for the closest aspect ratio, relocated into the viewport it letterboxes in the window
+    /// `None` if the viewport is smaller than `MIN_VIEWPORT_SIZE`
+    pub fn get_in_viewport(&self, name: &str, window_size: Size<usize>, ui: UI, platform: Platform) -> Option<WindowInfoType> {
+        let from = self.closest_aspect(name, window_size, ui, platform)?;
+        let viewport = self.viewport_of(from, window_size)?;
+        let value = self.data[name][&(from, ui, platform)];
+
+        Some(value.relocate(Size::new(from.width as f64, from.height as f64), viewport))
+    }
+
+    /// where the ui recorded for a window of `from` is in a window of `window_size`
+    pub fn viewport_of(&self, from: Size<usize>, window_size: Size<usize>) -> Option<Viewport> {
+        let viewport = letterbox(
+            Size::new(window_size.width as f64, window_size.height as f64),
+            from.width as f64 / from.height as f64,
+        );
+        // a rounding error is as good as the size
+        if viewport.rect.width + 1.0 < MIN_VIEWPORT_SIZE.width as f64 || viewport.rect.height + 1.0 < MIN_VIEWPORT_SIZE.height as f64 {
+            return None;
+        }
+        Some(viewport)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Size<usize> = Size { width: 1920, height: 1080 };
+
+    fn pos(x: f64) -> Pos<f64> {
+        Pos { x, y: 0.0 }
+    }
+
+    #[test]
+    fn merge_keeps_both_and_prefers_the_other() {
+        let mut a = WindowInfoRepository::default();
+        a.add_pos("a", SIZE, UI::Desktop, Platform::Windows, pos(1.0));
+        a.add_pos("b", SIZE, UI::Desktop, Platform::Windows, pos(2.0));
+        let mut b = WindowInfoRepository::default();
+        b.add_pos("b", SIZE, UI::Desktop, Platform::Windows, pos(3.0));
+
+        let merged = a.merge(&b);
+        let get = |name| merged.get_exact::<Pos<f64>>(name, SIZE, UI::Desktop, Platform::Windows).unwrap().x;
+        assert_eq!(get("a"), 1.0);
+        assert_eq!(get("b"), 3.0);
+        assert!(merged.get_exact::<Pos<f64>>("a", SIZE, UI::Mobile, Platform::Windows).is_none());
+    }
 }
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::positioning::{Pos, Rect, Scalable, Size};
+use crate::positioning::{Pos, Rect, Scalable, Size, Viewport};
 use anyhow::anyhow;
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
@@ -72,14 +72,32 @@ impl TryInto<Size<f64>> for WindowInfoType {
 
 impl Scalable for WindowInfoType {
     fn scale(&self, factor: f64) -> Self {
-        let result = match *self {
+        match *self {
             WindowInfoType::Rect(rect) => WindowInfoType::Rect(rect.scale(factor)),
             WindowInfoType::Pos(pos) => WindowInfoType::Pos(pos.scale(factor)),
             WindowInfoType::Size(size) => WindowInfoType::Size(size.scale(factor)),
             WindowInfoType::Float(v) => WindowInfoType::Float(v.scale(factor)),
             WindowInfoType::InvariantInt(v) => WindowInfoType::InvariantInt(v),
             WindowInfoType::InvariantFloat(v) => WindowInfoType::InvariantFloat(v),
-        };
-        result
+        }
+    }
+}
+
+impl WindowInfoType {
+    /// the value recorded for a window of `from`, moved into `viewport` of another window at the same place,
+    /// in proportion to the viewport
+    pub fn relocate(&self, from: Size<f64>, viewport: Viewport) -> Self {
+        let factor = viewport.rect.width / from.width;
+        match *self {
+            WindowInfoType::Rect(rect) => WindowInfoType::Rect(rect.normalize(from).denormalize(viewport)),
+            WindowInfoType::Pos(pos) => WindowInfoType::Pos(pos.normalize(from).denormalize(viewport)),
+            WindowInfoType::Size(size) => WindowInfoType::Size(Size::new(
+                size.width * factor,
+                size.height * viewport.rect.height / from.height,
+            )),
+            WindowInfoType::Float(v) => WindowInfoType::Float(v * factor),
+            WindowInfoType::InvariantInt(v) => WindowInfoType::InvariantInt(v),
+            WindowInfoType::InvariantFloat(v) => WindowInfoType::InvariantFloat(v),
+        }
     }
-}
\ No newline at end of file
+}